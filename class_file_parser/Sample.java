@@ -0,0 +1,81 @@
+import java.io.Serializable;
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.ArrayList;
+import java.util.List;
+import java.util.function.Supplier;
+
+@Sample.Marker(value = "sample", level = 3, kinds = { ElementType.TYPE, ElementType.METHOD })
+public class Sample<T extends Comparable<? super T>> implements Serializable {
+	public static final long BIG = 1234567890123L;
+	public static final double PI = 3.14159;
+	public static final String NAME = "Sample\0\u00e9\u20ac\ud83d\ude00";
+
+	private final List<T> values = new ArrayList<>();
+
+	@Retention(RetentionPolicy.RUNTIME)
+	@Target({ ElementType.TYPE, ElementType.METHOD, ElementType.PARAMETER })
+	public @interface Marker {
+		String value() default "marker";
+		int level() default 1;
+		ElementType[] kinds() default {};
+	}
+
+	public record Point(int x, int y) {}
+
+	public sealed interface Shape permits Circle, Square {}
+
+	public static final class Circle implements Shape {}
+
+	public static final class Square implements Shape {}
+
+	private class Inner {
+		int read() {
+			return values.size();
+		}
+	}
+
+	@Marker("method")
+	public <R extends T> List<R> collect(@Marker("parameter") Supplier<R> supplier, int count) throws Exception {
+		List<R> result = new ArrayList<>();
+		for (int i = 0; i < count; i++) {
+			result.add(supplier.get());
+		}
+		return result;
+	}
+
+	public static int classify(int value) {
+		switch (value) {
+			case 1: return 10;
+			case 2: return 20;
+			case 3: return 30;
+			default: return -1;
+		}
+	}
+
+	public static int lookup(int value) {
+		switch (value) {
+			case 10: return 1;
+			case 1000: return 2;
+			case 100000: return 3;
+			default: return 0;
+		}
+	}
+
+	public static long mix(long a, double b, String text) {
+		try {
+			return a + (long) b + Integer.parseInt(text);
+		} catch (NumberFormatException e) {
+			return -1L;
+		} finally {
+			System.out.println("done " + a);
+		}
+	}
+
+	public int size() {
+		Supplier<Integer> supplier = () -> values.size() + new Inner().read();
+		return supplier.get();
+	}
+}
@@ -0,0 +1,72 @@
+use std::{env, fs};
+
+use class_file_parser::{archive::JarFile, class_file::{parse_class_file, parse_class_file_ref}, lazy::LazyClassFile};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+const CLASS_FILE_PATHS: [&str; 9] = ["./Main.class", "./Sample.class", "./Sample$Circle.class", "./Sample$Inner.class", "./Sample$Marker.class", "./Sample$Point.class", "./Sample$Shape.class", "./Sample$Square.class", "./module-info.class"];
+
+/// Jar whose classes are parsed instead of the sample class files, e.g. a large application or library.
+const JAR_VARIABLE: &str = "CLASS_FILE_PARSER_BENCH_JAR";
+
+fn read_class_files() -> Vec<Vec<u8>> {
+	match env::var_os(JAR_VARIABLE) {
+		Some(path) => {
+			let jar_file = JarFile::open(path).expect("Failed to open jar file");
+			jar_file.entries().iter().filter(|entry| entry.is_class()).map(|entry| jar_file.read(entry).expect("Failed to read class file").into_owned()).collect()
+		},
+		None => CLASS_FILE_PATHS.iter().map(|path| fs::read(path).expect("Failed to read class file")).collect(),
+	}
+}
+
+/// Compares the owned parser with the borrowing and lazy ones over all sample class files.
+fn parse(c: &mut Criterion) {
+	let class_files = read_class_files();
+	let mut group = c.benchmark_group("parse");
+	group.throughput(Throughput::Bytes(class_files.iter().map(|bytes| bytes.len() as u64).sum()));
+
+	group.bench_function("owned", |b| b.iter(|| {
+		for bytes in &class_files {
+			black_box(parse_class_file(black_box(bytes)).unwrap());
+		}
+	}));
+	group.bench_function("borrowed", |b| b.iter(|| {
+		for bytes in &class_files {
+			black_box(parse_class_file_ref(black_box(bytes)).unwrap());
+		}
+	}));
+	group.bench_function("lazy", |b| b.iter(|| {
+		for bytes in &class_files {
+			black_box(LazyClassFile::parse(black_box(bytes)).unwrap());
+		}
+	}));
+	group.bench_function("borrowed_into_owned", |b| b.iter(|| {
+		for bytes in &class_files {
+			black_box(parse_class_file_ref(black_box(bytes)).unwrap().into_owned());
+		}
+	}));
+	group.finish();
+}
+
+/// Scanning for class names, which only needs a few constant pool entries of each class.
+fn class_names(c: &mut Criterion) {
+	let class_files = read_class_files();
+	let mut group = c.benchmark_group("class_names");
+	group.throughput(Throughput::Bytes(class_files.iter().map(|bytes| bytes.len() as u64).sum()));
+
+	group.bench_function("owned", |b| b.iter(|| {
+		for bytes in &class_files {
+			let class_file = parse_class_file(black_box(bytes)).unwrap();
+			black_box(class_file.constant_pool.get_class_name(class_file.this_class).unwrap().into_owned());
+		}
+	}));
+	group.bench_function("borrowed", |b| b.iter(|| {
+		for bytes in &class_files {
+			let class_file = parse_class_file_ref(black_box(bytes)).unwrap();
+			black_box(class_file.this_class_name().unwrap());
+		}
+	}));
+	group.finish();
+}
+
+criterion_group!(benches, parse, class_names);
+criterion_main!(benches);
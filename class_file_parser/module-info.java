@@ -0,0 +1,9 @@
+module sample.module {
+	requires transitive java.logging;
+	requires static java.sql;
+	exports sample.api;
+	exports sample.impl to java.base, java.logging;
+	opens sample.impl to java.logging;
+	uses sample.api.Service;
+	provides sample.api.Service with sample.impl.ServiceImpl;
+}
@@ -0,0 +1,432 @@
+use std::{fmt::{Debug, Display}, ops::{BitAnd, BitOr, BitOrAssign, Not}};
+
+use crate::U2;
+
+/// Validation helpers shared by all flag types, reporting conflicts by JVMS name.
+trait Flags: Copy + PartialEq + 'static {
+	fn bits(self) -> U2;
+
+	fn flags() -> &'static [(Self, &'static str, Option<&'static str>)];
+
+	fn contains_flag(self, flag: Self) -> bool {
+		self.bits() & flag.bits() == flag.bits()
+	}
+
+	fn name_of(flag: Self) -> &'static str {
+		Self::flags().iter().find(|(known, _, _)| *known == flag).map_or("unknown flag", |(_, name, _)| name)
+	}
+
+	fn check_conflict(self, first: Self, second: Self) -> Result<(), AccessFlagsError> {
+		if self.contains_flag(first) && self.contains_flag(second) {
+			return Err(AccessFlagsError { flags: self.bits(), kind: AccessFlagsErrorKind::Conflicting(Self::name_of(first), Self::name_of(second)) });
+		}
+		Ok(())
+	}
+
+	fn check_at_most_one(self, flags: &[Self]) -> Result<(), AccessFlagsError> {
+		for (i, first) in flags.iter().enumerate() {
+			for second in &flags[i + 1..] {
+				self.check_conflict(*first, *second)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn check_required(self, flag: Self) -> Result<(), AccessFlagsError> {
+		if !self.contains_flag(flag) {
+			return Err(AccessFlagsError { flags: self.bits(), kind: AccessFlagsErrorKind::Missing(Self::name_of(flag)) });
+		}
+		Ok(())
+	}
+
+	/// Fails on the first flag that is set in `self` but not in `allowed`.
+	fn check_allowed(self, allowed: Self) -> Result<(), AccessFlagsError> {
+		match Self::flags().iter().find(|(flag, _, _)| self.contains_flag(*flag) && !allowed.contains_flag(*flag)) {
+			Some((_, name, _)) => Err(AccessFlagsError { flags: self.bits(), kind: AccessFlagsErrorKind::NotAllowed(name) }),
+			None => Ok(()),
+		}
+	}
+}
+
+macro_rules! access_flags {
+	(@keyword) => { None };
+	(@keyword $keyword:literal) => { Some($keyword) };
+	(
+		$(#[$meta:meta])*
+		$name:ident {
+			$($flag:ident = $value:literal $(, $keyword:literal)?;)*
+		}
+	) => {
+		$(#[$meta])*
+		#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+		pub struct $name(pub U2);
+
+		impl $name {
+			$(pub const $flag: Self = Self($value);)*
+
+			/// Every flag with its JVMS name and source keyword, in the order javap prints them.
+			const FLAGS: &'static [(Self, &'static str, Option<&'static str>)] = &[
+				$((Self::$flag, concat!("ACC_", stringify!($flag)), access_flags!(@keyword $($keyword)?)),)*
+			];
+
+			pub const fn bits(self) -> U2 {
+				self.0
+			}
+
+			pub const fn is_empty(self) -> bool {
+				self.0 == 0
+			}
+
+			/// Whether all flags of `other` are set.
+			pub const fn contains(self, other: Self) -> bool {
+				self.0 & other.0 == other.0
+			}
+
+			/// Whether any flag of `other` is set.
+			pub const fn intersects(self, other: Self) -> bool {
+				self.0 & other.0 != 0
+			}
+
+			/// JVMS names of the set flags, e.g. `ACC_PUBLIC`, ignoring unassigned bits.
+			pub fn names(self) -> Vec<&'static str> {
+				Self::FLAGS.iter().filter(|(flag, _, _)| self.contains(*flag)).map(|(_, name, _)| *name).collect()
+			}
+
+			/// Source keywords of the set flags, e.g. `public`, leaving out flags like `ACC_SYNTHETIC` that have none.
+			pub fn keywords(self) -> Vec<&'static str> {
+				Self::FLAGS.iter().filter(|(flag, _, _)| self.contains(*flag)).filter_map(|(_, _, keyword)| *keyword).collect()
+			}
+
+			/// Bits that are not assigned to any flag, which the JVM ignores.
+			pub fn unknown_bits(self) -> U2 {
+				Self::FLAGS.iter().fold(self.0, |bits, (flag, _, _)| bits & !flag.0)
+			}
+		}
+
+		impl Flags for $name {
+			fn bits(self) -> U2 {
+				self.0
+			}
+
+			fn flags() -> &'static [(Self, &'static str, Option<&'static str>)] {
+				Self::FLAGS
+			}
+		}
+
+		impl BitOr for $name {
+			type Output = Self;
+
+			fn bitor(self, rhs: Self) -> Self::Output {
+				Self(self.0 | rhs.0)
+			}
+		}
+
+		impl BitOrAssign for $name {
+			fn bitor_assign(&mut self, rhs: Self) {
+				self.0 |= rhs.0;
+			}
+		}
+
+		impl BitAnd for $name {
+			type Output = Self;
+
+			fn bitand(self, rhs: Self) -> Self::Output {
+				Self(self.0 & rhs.0)
+			}
+		}
+
+		impl Not for $name {
+			type Output = Self;
+
+			fn not(self) -> Self::Output {
+				Self(!self.0)
+			}
+		}
+
+		impl From<U2> for $name {
+			fn from(value: U2) -> Self {
+				Self(value)
+			}
+		}
+
+		impl From<$name> for U2 {
+			fn from(value: $name) -> Self {
+				value.0
+			}
+		}
+
+		impl Debug for $name {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				write!(f, "{}({:#06x}", stringify!($name), self.0)?;
+				for name in self.names() {
+					write!(f, " {name}")?;
+				}
+				write!(f, ")")
+			}
+		}
+
+		/// Source keywords separated by spaces, like javap prints them, e.g. `public static final`.
+		impl Display for $name {
+			fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+				write!(f, "{}", self.keywords().join(" "))
+			}
+		}
+	};
+}
+
+access_flags! {
+	/// Flags of a class or interface (JVMS 4.1).
+	ClassAccess {
+		PUBLIC = 0x0001, "public";
+		FINAL = 0x0010, "final";
+		SUPER = 0x0020;
+		INTERFACE = 0x0200;
+		ABSTRACT = 0x0400, "abstract";
+		SYNTHETIC = 0x1000;
+		ANNOTATION = 0x2000;
+		ENUM = 0x4000;
+		MODULE = 0x8000;
+	}
+}
+
+access_flags! {
+	/// Flags of a field (JVMS 4.5).
+	FieldAccess {
+		PUBLIC = 0x0001, "public";
+		PRIVATE = 0x0002, "private";
+		PROTECTED = 0x0004, "protected";
+		STATIC = 0x0008, "static";
+		FINAL = 0x0010, "final";
+		VOLATILE = 0x0040, "volatile";
+		TRANSIENT = 0x0080, "transient";
+		SYNTHETIC = 0x1000;
+		ENUM = 0x4000;
+	}
+}
+
+access_flags! {
+	/// Flags of a method (JVMS 4.6).
+	MethodAccess {
+		PUBLIC = 0x0001, "public";
+		PRIVATE = 0x0002, "private";
+		PROTECTED = 0x0004, "protected";
+		STATIC = 0x0008, "static";
+		FINAL = 0x0010, "final";
+		SYNCHRONIZED = 0x0020, "synchronized";
+		BRIDGE = 0x0040;
+		VARARGS = 0x0080;
+		NATIVE = 0x0100, "native";
+		ABSTRACT = 0x0400, "abstract";
+		STRICT = 0x0800, "strictfp";
+		SYNTHETIC = 0x1000;
+	}
+}
+
+access_flags! {
+	/// Flags of a formal parameter, from the `MethodParameters` attribute (JVMS 4.7.24).
+	ParameterAccess {
+		FINAL = 0x0010, "final";
+		SYNTHETIC = 0x1000;
+		MANDATED = 0x8000;
+	}
+}
+
+access_flags! {
+	/// Flags of a nested class as declared in the source, from the `InnerClasses` attribute (JVMS 4.7.6).
+	InnerClassAccess {
+		PUBLIC = 0x0001, "public";
+		PRIVATE = 0x0002, "private";
+		PROTECTED = 0x0004, "protected";
+		STATIC = 0x0008, "static";
+		FINAL = 0x0010, "final";
+		INTERFACE = 0x0200;
+		ABSTRACT = 0x0400, "abstract";
+		SYNTHETIC = 0x1000;
+		ANNOTATION = 0x2000;
+		ENUM = 0x4000;
+	}
+}
+
+access_flags! {
+	/// Flags of a module and of its `exports` and `opens` entries (JVMS 4.7.25).
+	ModuleFlags {
+		OPEN = 0x0020, "open";
+		SYNTHETIC = 0x1000;
+		MANDATED = 0x8000;
+	}
+}
+
+access_flags! {
+	/// Flags of a `requires` entry of a module (JVMS 4.7.25).
+	RequiresFlags {
+		TRANSITIVE = 0x0020, "transitive";
+		STATIC_PHASE = 0x0040, "static";
+		SYNTHETIC = 0x1000;
+		MANDATED = 0x8000;
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessFlagsErrorKind {
+	/// Both flags are set although they exclude each other.
+	Conflicting(&'static str, &'static str),
+	/// The flag has to be set, given the other flags or the declaring class.
+	Missing(&'static str),
+	/// The flag may not be set, given the other flags or the declaring class.
+	NotAllowed(&'static str),
+}
+
+/// Combination of access flags that JVMS 4.1, 4.5 or 4.6 rules out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessFlagsError {
+	pub flags: U2,
+	pub kind: AccessFlagsErrorKind,
+}
+
+impl Display for AccessFlagsError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "illegal access flags {:#06x}: ", self.flags)?;
+		match self.kind {
+			AccessFlagsErrorKind::Conflicting(first, second) => write!(f, "{first} conflicts with {second}"),
+			AccessFlagsErrorKind::Missing(flag) => write!(f, "{flag} is missing"),
+			AccessFlagsErrorKind::NotAllowed(flag) => write!(f, "{flag} is not allowed"),
+		}
+	}
+}
+
+impl std::error::Error for AccessFlagsError {}
+
+impl ClassAccess {
+	/// Checks the rules of JVMS 4.1 for the flags of a class, interface or module.
+	pub fn validate(self) -> Result<(), AccessFlagsError> {
+		if self.contains(Self::MODULE) {
+			return self.check_allowed(Self::MODULE);
+		}
+		if self.contains(Self::INTERFACE) {
+			self.check_required(Self::ABSTRACT)?;
+			self.check_allowed(Self::PUBLIC | Self::INTERFACE | Self::ABSTRACT | Self::SYNTHETIC | Self::ANNOTATION)
+		} else {
+			self.check_allowed(!(Self::ANNOTATION | Self::MODULE))?;
+			self.check_conflict(Self::FINAL, Self::ABSTRACT)
+		}
+	}
+
+	pub fn is_interface(self) -> bool {
+		self.contains(Self::INTERFACE)
+	}
+}
+
+impl FieldAccess {
+	/// Checks the rules of JVMS 4.5 for a field declared in a class with `class_access`.
+	pub fn validate(self, class_access: ClassAccess) -> Result<(), AccessFlagsError> {
+		if class_access.is_interface() {
+			self.check_required(Self::PUBLIC)?;
+			self.check_required(Self::STATIC)?;
+			self.check_required(Self::FINAL)?;
+			return self.check_allowed(Self::PUBLIC | Self::STATIC | Self::FINAL | Self::SYNTHETIC);
+		}
+		self.check_at_most_one(&[Self::PUBLIC, Self::PRIVATE, Self::PROTECTED])?;
+		self.check_conflict(Self::FINAL, Self::VOLATILE)
+	}
+}
+
+impl MethodAccess {
+	/// Checks the rules of JVMS 4.6 for a method called `name` declared in a class with `class_access`.
+	///
+	/// The flags of `<clinit>` are ignored except for `ACC_STATIC`, which is required since version 51.
+	pub fn validate(self, class_access: ClassAccess, name: &str, major_version: U2) -> Result<(), AccessFlagsError> {
+		if name == "<clinit>" {
+			return match major_version {
+				51.. => self.check_required(Self::STATIC),
+				_ => Ok(()),
+			};
+		}
+		self.check_at_most_one(&[Self::PUBLIC, Self::PRIVATE, Self::PROTECTED])?;
+
+		if class_access.is_interface() {
+			if major_version < 52 {
+				self.check_required(Self::PUBLIC)?;
+				self.check_required(Self::ABSTRACT)?;
+				self.check_allowed(Self::PUBLIC | Self::ABSTRACT | Self::BRIDGE | Self::VARARGS | Self::SYNTHETIC)?;
+			} else {
+				self.check_allowed(!(Self::PROTECTED | Self::FINAL | Self::SYNCHRONIZED | Self::NATIVE))?;
+				if !self.intersects(Self::PUBLIC | Self::PRIVATE) {
+					self.check_required(Self::PUBLIC)?;
+				}
+			}
+		}
+
+		if self.contains(Self::ABSTRACT) {
+			for flag in [Self::PRIVATE, Self::STATIC, Self::FINAL, Self::SYNCHRONIZED, Self::NATIVE] {
+				self.check_conflict(Self::ABSTRACT, flag)?;
+			}
+			if (46..=60).contains(&major_version) {
+				self.check_conflict(Self::ABSTRACT, Self::STRICT)?;
+			}
+		}
+
+		if name == "<init>" {
+			self.check_allowed(Self::PUBLIC | Self::PRIVATE | Self::PROTECTED | Self::VARARGS | Self::STRICT | Self::SYNTHETIC)?;
+		}
+		Ok(())
+	}
+}
+
+impl InnerClassAccess {
+	/// Checks the flags against the rules JVMS 4.1 and the JLS impose on nested classes.
+	pub fn validate(self) -> Result<(), AccessFlagsError> {
+		self.check_at_most_one(&[Self::PUBLIC, Self::PRIVATE, Self::PROTECTED])?;
+		if self.contains(Self::INTERFACE) {
+			self.check_required(Self::ABSTRACT)?;
+			self.check_conflict(Self::INTERFACE, Self::FINAL)?;
+			self.check_conflict(Self::INTERFACE, Self::ENUM)
+		} else {
+			self.check_conflict(Self::FINAL, Self::ABSTRACT)?;
+			match self.contains(Self::ANNOTATION) {
+				true => self.check_required(Self::INTERFACE),
+				false => Ok(()),
+			}
+		}
+	}
+
+	pub fn is_interface(self) -> bool {
+		self.contains(Self::INTERFACE)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn display_keywords_in_javap_order() {
+		assert_eq!((FieldAccess::FINAL | FieldAccess::STATIC | FieldAccess::PUBLIC).to_string(), "public static final");
+		assert_eq!((MethodAccess::PUBLIC | MethodAccess::VARARGS | MethodAccess::SYNTHETIC).to_string(), "public");
+		assert_eq!(ClassAccess(0x0021).names(), ["ACC_PUBLIC", "ACC_SUPER"]);
+		assert_eq!(format!("{:?}", RequiresFlags(0x8040)), "RequiresFlags(0x8040 ACC_STATIC_PHASE ACC_MANDATED)");
+		assert_eq!(MethodAccess(0x0201).unknown_bits(), 0x0200);
+	}
+
+	#[test]
+	fn reject_illegal_combinations() {
+		let interface = ClassAccess::INTERFACE | ClassAccess::ABSTRACT;
+		assert_eq!(interface.validate(), Ok(()));
+		assert_eq!((interface | ClassAccess::FINAL).validate().unwrap_err().kind, AccessFlagsErrorKind::NotAllowed("ACC_FINAL"));
+		assert_eq!(ClassAccess::INTERFACE.validate().unwrap_err().kind, AccessFlagsErrorKind::Missing("ACC_ABSTRACT"));
+		assert_eq!((ClassAccess::FINAL | ClassAccess::ABSTRACT).validate().unwrap_err().kind, AccessFlagsErrorKind::Conflicting("ACC_FINAL", "ACC_ABSTRACT"));
+		assert_eq!((ClassAccess::MODULE | ClassAccess::PUBLIC).validate().unwrap_err().kind, AccessFlagsErrorKind::NotAllowed("ACC_PUBLIC"));
+
+		assert_eq!((FieldAccess::PUBLIC | FieldAccess::PRIVATE).validate(ClassAccess::PUBLIC).unwrap_err().kind, AccessFlagsErrorKind::Conflicting("ACC_PUBLIC", "ACC_PRIVATE"));
+		assert_eq!((FieldAccess::PUBLIC | FieldAccess::STATIC).validate(interface).unwrap_err().kind, AccessFlagsErrorKind::Missing("ACC_FINAL"));
+
+		assert_eq!((MethodAccess::ABSTRACT | MethodAccess::STATIC).validate(ClassAccess::ABSTRACT, "run", 61).unwrap_err().kind, AccessFlagsErrorKind::Conflicting("ACC_ABSTRACT", "ACC_STATIC"));
+		assert_eq!((MethodAccess::PRIVATE | MethodAccess::STATIC).validate(interface, "helper", 61), Ok(()));
+		assert_eq!((MethodAccess::PRIVATE | MethodAccess::STATIC).validate(interface, "helper", 51).unwrap_err().kind, AccessFlagsErrorKind::Missing("ACC_PUBLIC"));
+		assert_eq!(MethodAccess::STATIC.validate(interface, "run", 61).unwrap_err().kind, AccessFlagsErrorKind::Missing("ACC_PUBLIC"));
+		assert_eq!((MethodAccess::PUBLIC | MethodAccess::FINAL).validate(ClassAccess::PUBLIC, "<init>", 61).unwrap_err().kind, AccessFlagsErrorKind::NotAllowed("ACC_FINAL"));
+		assert_eq!(MethodAccess::PUBLIC.validate(ClassAccess::PUBLIC, "<clinit>", 61).unwrap_err().kind, AccessFlagsErrorKind::Missing("ACC_STATIC"));
+		assert_eq!(MethodAccess::PUBLIC.validate(ClassAccess::PUBLIC, "<clinit>", 50), Ok(()));
+
+		assert_eq!((InnerClassAccess::ANNOTATION | InnerClassAccess::ABSTRACT).validate().unwrap_err().kind, AccessFlagsErrorKind::Missing("ACC_INTERFACE"));
+	}
+}
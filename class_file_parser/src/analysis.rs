@@ -0,0 +1,278 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{attribute_info::{code::CodeAttribute, stack_map_table::Frame}, constant_pool::ConstantPool, cp_info::CPInfo, descriptor::{parse_field_descriptor, parse_method_descriptor, MethodDescriptor}, instruction::{Instruction, WideInstruction}, verifier::{infer_frames, ClassHierarchy, VerifyErrorKind}, U2};
+
+/// Reason why the code of a method could not be analyzed, at the instruction at `pc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzeError {
+	pub pc: usize,
+	pub kind: VerifyErrorKind,
+}
+
+impl Display for AnalyzeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "at pc {}: {}", self.pc, self.kind)
+	}
+}
+
+impl std::error::Error for AnalyzeError {}
+
+/// `max_stack` and `max_locals` of a `Code` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Maxs {
+	pub max_stack: U2,
+	pub max_locals: U2,
+}
+
+/// Computes the operand stack depth and number of locals the code needs, ignoring the values stored in `code`.
+///
+/// The stack depth is followed along every path through the code, so it has to be the same at every join point as the JVM requires.
+pub fn compute_maxs(code: &CodeAttribute, constant_pool: &ConstantPool, descriptor: &MethodDescriptor, is_static: bool) -> Result<Maxs, AnalyzeError> {
+	let mut instructions = HashMap::new();
+	let mut max_locals = descriptor.argument_slots() + if is_static { 0 } else { 1 };
+	let mut previous_pc = None;
+	for instruction in code.instructions() {
+		let (pc, instruction) = instruction.map_err(|error| AnalyzeError { pc: error.pc, kind: VerifyErrorKind::Decode(error) })?;
+		if let Some((index, slots)) = local_access(&instruction) {
+			max_locals = max_locals.max(index as usize + slots);
+		}
+		if let Some(previous_pc) = previous_pc.replace(pc) {
+			instructions.entry(previous_pc).and_modify(|(_, length)| *length = pc - previous_pc);
+		}
+		instructions.insert(pc, (instruction, code.code.len() - pc));
+	}
+
+	let mut depths: HashMap<usize, usize> = HashMap::new();
+	let mut pending = vec![(0, 0)];
+	for entry in &code.exception_table {
+		pending.push((entry.handler_pc as usize, 1));
+	}
+	let mut max_stack = 0;
+	while let Some((pc, depth)) = pending.pop() {
+		let error = |kind| AnalyzeError { pc, kind };
+		match depths.get(&pc) {
+			Some(&known) if known == depth => continue,
+			Some(&known) => return Err(error(VerifyErrorKind::StackHeightMismatch { target: pc, expected: known, actual: depth })),
+			None => {},
+		}
+		let (instruction, length) = match instructions.get(&pc) {
+			Some((instruction, length)) => (instruction, *length),
+			None if pc == code.code.len() => return Err(error(VerifyErrorKind::FallsOffEnd)),
+			None => return Err(error(VerifyErrorKind::InvalidBranchTarget(pc as i64))),
+		};
+		depths.insert(pc, depth);
+
+		let (pops, pushes) = stack_effect(instruction, constant_pool).map_err(error)?;
+		let depth = depth.checked_sub(pops).ok_or(error(VerifyErrorKind::StackUnderflow))? + pushes;
+		max_stack = max_stack.max(depth);
+		for (target, depth) in successors(instruction, pc, length, depth) {
+			if target < 0 {
+				return Err(error(VerifyErrorKind::InvalidBranchTarget(target)));
+			}
+			pending.push((target as usize, depth));
+		}
+	}
+
+	let max_stack = U2::try_from(max_stack).map_err(|_| AnalyzeError { pc: 0, kind: VerifyErrorKind::StackOverflow { max_stack: U2::MAX } })?;
+	let max_locals = U2::try_from(max_locals).map_err(|_| AnalyzeError { pc: 0, kind: VerifyErrorKind::LocalOutOfBounds { index: max_locals - 1, max_locals: U2::MAX } })?;
+	Ok(Maxs {
+		max_stack,
+		max_locals,
+	})
+}
+
+/// Computes the frames the type checker needs at every branch target and exception handler, ignoring any `StackMapTable` of `code`.
+///
+/// Where paths meet, locals holding different types become `Top` and references are merged to their common supertype, which `hierarchy` is asked for.
+/// `max_stack` and `max_locals` of `code` have to be large enough, e.g. computed by `compute_maxs`.
+pub fn compute_frames<H: ClassHierarchy>(code: &CodeAttribute, constant_pool: &ConstantPool, class_name: &str, method_name: &str, is_static: bool, descriptor: &MethodDescriptor, hierarchy: &H) -> Result<Vec<Frame>, AnalyzeError> {
+	infer_frames(code, constant_pool, class_name, method_name, is_static, descriptor, hierarchy).map_err(|error| AnalyzeError {
+		pc: error.pc.unwrap_or(0),
+		kind: error.kind,
+	})
+}
+
+/// Local variable an instruction loads, stores or increments, with the number of slots it takes.
+fn local_access(instruction: &Instruction) -> Option<(U2, usize)> {
+	use Instruction::*;
+
+	Some(match instruction {
+		Iload(index) | Fload(index) | Aload(index) | Istore(index) | Fstore(index) | Astore(index) | Ret(index) | Iinc(index, _) => (*index as U2, 1),
+		Lload(index) | Dload(index) | Lstore(index) | Dstore(index) => (*index as U2, 2),
+		Iload0 | Fload0 | Aload0 | Istore0 | Fstore0 | Astore0 => (0, 1),
+		Iload1 | Fload1 | Aload1 | Istore1 | Fstore1 | Astore1 => (1, 1),
+		Iload2 | Fload2 | Aload2 | Istore2 | Fstore2 | Astore2 => (2, 1),
+		Iload3 | Fload3 | Aload3 | Istore3 | Fstore3 | Astore3 => (3, 1),
+		Lload0 | Dload0 | Lstore0 | Dstore0 => (0, 2),
+		Lload1 | Dload1 | Lstore1 | Dstore1 => (1, 2),
+		Lload2 | Dload2 | Lstore2 | Dstore2 => (2, 2),
+		Lload3 | Dload3 | Lstore3 | Dstore3 => (3, 2),
+		Wide(wide) => match wide {
+			WideInstruction::Lload(index) | WideInstruction::Dload(index) | WideInstruction::Lstore(index) | WideInstruction::Dstore(index) => (*index, 2),
+			_ => (wide.opcode_and_index().1, 1),
+		},
+		_ => return None,
+	})
+}
+
+/// Instructions that can follow `instruction` at `pc`, with the stack depth they start with.
+fn successors(instruction: &Instruction, pc: usize, length: usize, depth: usize) -> Vec<(i64, usize)> {
+	use Instruction::*;
+
+	let pc = pc as i64;
+	let next = (pc + length as i64, depth);
+	match instruction {
+		Ifeq(offset) | Ifne(offset) | Iflt(offset) | Ifge(offset) | Ifgt(offset) | Ifle(offset)
+		| IfIcmpeq(offset) | IfIcmpne(offset) | IfIcmplt(offset) | IfIcmpge(offset) | IfIcmpgt(offset) | IfIcmple(offset)
+		| IfAcmpeq(offset) | IfAcmpne(offset) | Ifnull(offset) | Ifnonnull(offset) => vec![(pc + *offset as i64, depth), next],
+		Goto(offset) => vec![(pc + *offset as i64, depth)],
+		GotoW(offset) => vec![(pc + *offset as i64, depth)],
+		// The subroutine returns to the next instruction with the return address popped again.
+		Jsr(offset) => vec![(pc + *offset as i64, depth + 1), next],
+		JsrW(offset) => vec![(pc + *offset as i64, depth + 1), next],
+		Tableswitch(table_switch) => std::iter::once(table_switch.default).chain(table_switch.offsets.iter().copied()).map(|offset| (pc + offset as i64, depth)).collect(),
+		Lookupswitch(lookup_switch) => std::iter::once(lookup_switch.default).chain(lookup_switch.pairs.iter().map(|(_, offset)| *offset)).map(|offset| (pc + offset as i64, depth)).collect(),
+		Ireturn | Lreturn | Freturn | Dreturn | Areturn | Return | Athrow | Ret(_) | Wide(WideInstruction::Ret(_)) => Vec::new(),
+		_ => vec![next],
+	}
+}
+
+/// Number of stack slots `instruction` pops and pushes.
+fn stack_effect(instruction: &Instruction, constant_pool: &ConstantPool) -> Result<(usize, usize), VerifyErrorKind> {
+	use Instruction::*;
+
+	let constant_pool_error = |error| VerifyErrorKind::ClassFormat(Box::new(error));
+	let field_slots = |index: U2| -> Result<usize, VerifyErrorKind> {
+		let member_ref = constant_pool.get_member_ref(index).map_err(|e| constant_pool_error(e.into()))?;
+		Ok(parse_field_descriptor(&member_ref.descriptor).map_err(|e| constant_pool_error(e.into()))?.slots() as usize)
+	};
+	let method_descriptor = |descriptor: &str| parse_method_descriptor(descriptor).map_err(|e| constant_pool_error(e.into()));
+	let invoke = |index: U2, has_receiver: bool| -> Result<(usize, usize), VerifyErrorKind> {
+		let member_ref = constant_pool.get_member_ref(index).map_err(|e| constant_pool_error(e.into()))?;
+		let descriptor = method_descriptor(&member_ref.descriptor)?;
+		Ok((descriptor.argument_slots() + has_receiver as usize, descriptor.return_type.slots() as usize))
+	};
+
+	Ok(match instruction {
+		Nop | Iinc(..) | Goto(_) | GotoW(_) | Ret(_) | Return => (0, 0),
+		Ineg | Fneg | I2f | F2i | I2b | I2c | I2s | Checkcast(_) | Instanceof(_) | Arraylength | Newarray(_) | Anewarray(_) => (1, 1),
+		Lneg | Dneg | L2d | D2l => (2, 2),
+		Wide(WideInstruction::Iinc(..)) | Wide(WideInstruction::Ret(_)) => (0, 0),
+		AconstNull | IconstM1 | Iconst0 | Iconst1 | Iconst2 | Iconst3 | Iconst4 | Iconst5 | Fconst0 | Fconst1 | Fconst2
+		| Bipush(_) | Sipush(_) | Ldc(_) | LdcW(_) | New(_) | Jsr(_) | JsrW(_) => (0, 1),
+		Lconst0 | Lconst1 | Dconst0 | Dconst1 | Ldc2W(_) => (0, 2),
+		Iload(_) | Fload(_) | Aload(_) | Iload0 | Iload1 | Iload2 | Iload3 | Fload0 | Fload1 | Fload2 | Fload3 | Aload0 | Aload1 | Aload2 | Aload3 => (0, 1),
+		Lload(_) | Dload(_) | Lload0 | Lload1 | Lload2 | Lload3 | Dload0 | Dload1 | Dload2 | Dload3 => (0, 2),
+		Istore(_) | Fstore(_) | Astore(_) | Istore0 | Istore1 | Istore2 | Istore3 | Fstore0 | Fstore1 | Fstore2 | Fstore3 | Astore0 | Astore1 | Astore2 | Astore3 => (1, 0),
+		Lstore(_) | Dstore(_) | Lstore0 | Lstore1 | Lstore2 | Lstore3 | Dstore0 | Dstore1 | Dstore2 | Dstore3 => (2, 0),
+		Wide(WideInstruction::Iload(_) | WideInstruction::Fload(_) | WideInstruction::Aload(_)) => (0, 1),
+		Wide(WideInstruction::Lload(_) | WideInstruction::Dload(_)) => (0, 2),
+		Wide(WideInstruction::Istore(_) | WideInstruction::Fstore(_) | WideInstruction::Astore(_)) => (1, 0),
+		Wide(WideInstruction::Lstore(_) | WideInstruction::Dstore(_)) => (2, 0),
+		Iaload | Faload | Aaload | Baload | Caload | Saload => (2, 1),
+		Laload | Daload => (2, 2),
+		Iastore | Fastore | Aastore | Bastore | Castore | Sastore => (3, 0),
+		Lastore | Dastore => (4, 0),
+		Pop => (1, 0),
+		Pop2 => (2, 0),
+		Dup => (1, 2),
+		DupX1 => (2, 3),
+		DupX2 => (3, 4),
+		Dup2 => (2, 4),
+		Dup2X1 => (3, 5),
+		Dup2X2 => (4, 6),
+		Swap => (2, 2),
+		Iadd | Isub | Imul | Idiv | Irem | Ishl | Ishr | Iushr | Iand | Ior | Ixor | Fadd | Fsub | Fmul | Fdiv | Frem | Fcmpl | Fcmpg => (2, 1),
+		Ladd | Lsub | Lmul | Ldiv | Lrem | Land | Lor | Lxor | Dadd | Dsub | Dmul | Ddiv | Drem => (4, 2),
+		Lshl | Lshr | Lushr => (3, 2),
+		Lcmp | Dcmpl | Dcmpg => (4, 1),
+		I2l | I2d | F2l | F2d => (1, 2),
+		L2i | L2f | D2i | D2f => (2, 1),
+		Ifeq(_) | Ifne(_) | Iflt(_) | Ifge(_) | Ifgt(_) | Ifle(_) | Ifnull(_) | Ifnonnull(_) => (1, 0),
+		IfIcmpeq(_) | IfIcmpne(_) | IfIcmplt(_) | IfIcmpge(_) | IfIcmpgt(_) | IfIcmple(_) | IfAcmpeq(_) | IfAcmpne(_) => (2, 0),
+		Tableswitch(_) | Lookupswitch(_) => (1, 0),
+		Ireturn | Freturn | Areturn | Athrow | Monitorenter | Monitorexit => (1, 0),
+		Lreturn | Dreturn => (2, 0),
+		Getstatic(index) => (0, field_slots(*index)?),
+		Putstatic(index) => (field_slots(*index)?, 0),
+		Getfield(index) => (1, field_slots(*index)?),
+		Putfield(index) => (1 + field_slots(*index)?, 0),
+		Invokevirtual(index) | Invokespecial(index) | Invokeinterface(index, _) => invoke(*index, true)?,
+		Invokestatic(index) => invoke(*index, false)?,
+		Invokedynamic(index) => {
+			let (_, descriptor) = match constant_pool.get(*index).map_err(|e| constant_pool_error(e.into()))? {
+				CPInfo::InvokeDynamic(invoke_dynamic) => constant_pool.get_name_and_type(invoke_dynamic.name_and_type_index).map_err(|e| constant_pool_error(e.into()))?,
+				_ => return Err(VerifyErrorKind::InvalidConstant(*index)),
+			};
+			let descriptor = method_descriptor(&descriptor)?;
+			(descriptor.argument_slots(), descriptor.return_type.slots() as usize)
+		},
+		Multianewarray(_, dimensions) => (*dimensions as usize, 1),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::{access_flags::MethodAccess, class_file::parse_class_file};
+
+	use super::*;
+
+	#[test]
+	fn compute_maxs_of_javac_code() {
+		for path in ["./Sample.class", "./Sample$Point.class", "./module-info.class"] {
+			let class_file_raw = fs::read(path).expect("Failed to read class file");
+			let class_file = parse_class_file(&class_file_raw).expect("Failed to parse class file");
+			let constant_pool = &class_file.constant_pool;
+			for method in &class_file.methods {
+				let Some(code) = method.code(constant_pool).unwrap() else {
+					continue;
+				};
+				let descriptor = method.descriptor(constant_pool).unwrap();
+				let maxs = compute_maxs(&code, constant_pool, &descriptor, method.access().contains(MethodAccess::STATIC)).unwrap();
+				let name = constant_pool.get_utf8_str(method.name_index).unwrap();
+				assert_eq!((maxs.max_stack, maxs.max_locals), (code.max_stack, code.max_locals), "{path} {name}");
+			}
+		}
+	}
+
+	#[test]
+	fn compute_frames_of_javac_code() {
+		for path in ["./Sample.class", "./Sample$Point.class", "./Sample$Inner.class"] {
+			let class_file_raw = fs::read(path).expect("Failed to read class file");
+			let class_file = parse_class_file(&class_file_raw).expect("Failed to parse class file");
+			let constant_pool = &class_file.constant_pool;
+			let class_name = constant_pool.get_class_name(class_file.this_class).unwrap();
+			let mut hierarchy = crate::tests::sample_hierarchy();
+			hierarchy.insert_class_file(&class_file).unwrap();
+			for method in &class_file.methods {
+				let Some(code) = method.code(constant_pool).unwrap() else {
+					continue;
+				};
+				let name = constant_pool.get_utf8_str(method.name_index).unwrap();
+				let descriptor = method.descriptor(constant_pool).unwrap();
+				let frames = compute_frames(&code, constant_pool, &class_name, &name, method.access().contains(MethodAccess::STATIC), &descriptor, &hierarchy).unwrap_or_else(|e| panic!("{path} {name}: {e}"));
+				// javac declares locals with their source types and drops them out of scope, so only the positions and stacks agree.
+				let javac_frames = method.stack_map_frames(&class_file).unwrap();
+				let shape = |frames: &[Frame]| frames.iter().map(|frame| (frame.pc, frame.stack.len())).collect::<Vec<_>>();
+				assert_eq!(shape(&frames), shape(&javac_frames), "{path} {name}");
+			}
+		}
+	}
+
+	#[test]
+	fn report_inconsistent_stack_height() {
+		let class_file_raw = fs::read("./Sample.class").expect("Failed to read class file");
+		let class_file = parse_class_file(&class_file_raw).expect("Failed to parse class file");
+		let constant_pool = &class_file.constant_pool;
+		let classify = class_file.methods.iter().find(|method| constant_pool.get_utf8_str(method.name_index).unwrap() == "classify").unwrap();
+		let mut code = classify.code(constant_pool).unwrap().unwrap();
+		// bipush 10; ireturn -> bipush 10; nop, which falls through to the next case with one more value
+		code.code[30] = 0x00;
+
+		let error = compute_maxs(&code, constant_pool, &classify.descriptor(constant_pool).unwrap(), true).expect_err("Computed maxs of inconsistent code");
+		assert_eq!(error.pc, 31);
+		assert!(matches!(error.kind, VerifyErrorKind::StackHeightMismatch { target: 31, .. }), "{error}");
+	}
+}
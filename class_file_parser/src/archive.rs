@@ -0,0 +1,529 @@
+use std::{borrow::Cow, collections::{BTreeSet, HashMap}, fmt::Display, fs, io, path::Path};
+
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+use nom::{bytes::complete::take, combinator::verify, error::context, number::complete::{le_u16, le_u32}, IResult};
+
+use crate::{class_file::{parse_class_file, ClassFile}, class_format_error::{indexed, ClassFormatError, ClassFormatErrorKind, ClassParseError}, U2, U4};
+
+const LOCAL_FILE_HEADER_SIGNATURE: U4 = 0x04034b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: U4 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: U4 = 0x06054b50;
+/// Size of the end of central directory record without its comment.
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+const FLAG_ENCRYPTED: U2 = 0x0001;
+
+pub const MANIFEST_NAME: &str = "META-INF/MANIFEST.MF";
+/// Directory holding the classes of a multi-release jar that replace the base ones from a Java version on.
+pub const VERSIONS_DIRECTORY: &str = "META-INF/versions/";
+/// First Java version that reads the versioned directories of multi-release jars (JEP 238).
+pub const FIRST_VERSIONED_RELEASE: U2 = 9;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+	Io(io::Error),
+	MissingEndOfCentralDirectory,
+	/// The central directory would start after the end of central directory record.
+	InvalidCentralDirectoryOffset(U4),
+	/// A zip structure could not be parsed, the error locates it in the archive.
+	Malformed(ClassFormatError),
+	Unsupported(&'static str),
+	UnsupportedCompressionMethod {
+		name: String,
+		method: U2,
+	},
+	CorruptEntry(String),
+	ChecksumMismatch(String),
+	EntryNotFound(String),
+	InvalidClass {
+		name: String,
+		error: ClassFormatError,
+	},
+}
+
+impl Display for ArchiveError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io(error) => write!(f, "{error}"),
+			Self::MissingEndOfCentralDirectory => write!(f, "not a zip archive, end of central directory not found"),
+			Self::InvalidCentralDirectoryOffset(offset) => write!(f, "central directory offset {offset} is past the end of central directory"),
+			Self::Malformed(error) => write!(f, "malformed zip archive at offset {} in '{}': {}", error.offset(), error.path(), error.kind()),
+			Self::Unsupported(feature) => write!(f, "{feature} is not supported"),
+			Self::UnsupportedCompressionMethod { name, method } => write!(f, "entry '{name}' uses unsupported compression method {method}"),
+			Self::CorruptEntry(name) => write!(f, "entry '{name}' cannot be decompressed"),
+			Self::ChecksumMismatch(name) => write!(f, "entry '{name}' does not match its CRC-32"),
+			Self::EntryNotFound(name) => write!(f, "entry '{name}' does not exist"),
+			Self::InvalidClass { name, error } => write!(f, "entry '{name}': {error}"),
+		}
+	}
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+	fn from(value: io::Error) -> Self {
+		Self::Io(value)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionMethod {
+	Stored,
+	Deflated,
+}
+
+impl CompressionMethod {
+	pub fn from_u2(method: U2) -> Option<Self> {
+		match method {
+			0 => Some(Self::Stored),
+			8 => Some(Self::Deflated),
+			_ => None,
+		}
+	}
+}
+
+/// File header from the central directory of a zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CentralDirectoryHeader {
+	pub flags: U2,
+	pub compression_method: U2,
+	pub crc32: U4,
+	pub compressed_size: U4,
+	pub uncompressed_size: U4,
+	pub local_header_offset: U4,
+	pub name: String,
+}
+
+/// Entry of a jar file with the sizes and checksum from the central directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+	pub name: String,
+	pub compression_method: CompressionMethod,
+	pub crc32: U4,
+	pub compressed_size: U4,
+	pub uncompressed_size: U4,
+	local_header_offset: U4,
+}
+
+impl ArchiveEntry {
+	pub fn is_directory(&self) -> bool {
+		self.name.ends_with('/')
+	}
+
+	pub fn is_class(&self) -> bool {
+		self.name.ends_with(".class")
+	}
+}
+
+/// Main section of `META-INF/MANIFEST.MF`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+	pub attributes: Vec<(String, String)>,
+}
+
+impl Manifest {
+	/// Reads the main attributes, joining continuation lines and stopping at the first blank line.
+	pub fn parse(text: &str) -> Self {
+		let mut attributes: Vec<(String, String)> = Vec::new();
+		for line in text.lines() {
+			if line.is_empty() {
+				break;
+			}
+			if let Some(continuation) = line.strip_prefix(' ') {
+				if let Some((_, value)) = attributes.last_mut() {
+					value.push_str(continuation);
+				}
+			} else if let Some((name, value)) = line.split_once(':') {
+				attributes.push((name.to_string(), value.strip_prefix(' ').unwrap_or(value).to_string()));
+			}
+		}
+		Self {
+			attributes,
+		}
+	}
+
+	/// Value of the attribute `name`, which is matched ignoring case.
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.attributes.iter().find(|(attribute, _)| attribute.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+	}
+
+	/// `Main-Class` in binary form, e.g. `demo.App`.
+	pub fn main_class(&self) -> Option<&str> {
+		self.get("Main-Class")
+	}
+
+	/// Relative URLs of the jars listed in `Class-Path`.
+	pub fn class_path(&self) -> Vec<&str> {
+		self.get("Class-Path").map(|class_path| class_path.split_whitespace().collect()).unwrap_or_default()
+	}
+
+	pub fn is_multi_release(&self) -> bool {
+		self.get("Multi-Release").is_some_and(|value| value.eq_ignore_ascii_case("true"))
+	}
+}
+
+/// Jar or zip archive held in memory, with entries decompressed when they are read.
+#[derive(Debug, Clone)]
+pub struct JarFile {
+	data: Vec<u8>,
+	entries: Vec<ArchiveEntry>,
+	index: HashMap<String, usize>,
+	/// Versions with a directory under `META-INF/versions/`.
+	versions: BTreeSet<U2>,
+	manifest: Option<Manifest>,
+}
+
+impl JarFile {
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+		Self::from_bytes(fs::read(path)?)
+	}
+
+	pub fn from_bytes(data: Vec<u8>) -> Result<Self, ArchiveError> {
+		let headers = central_directory(&data)?;
+		let mut entries = Vec::with_capacity(headers.len());
+		for header in headers {
+			if header.flags & FLAG_ENCRYPTED != 0 {
+				return Err(ArchiveError::Unsupported("encryption"));
+			}
+			let compression_method = CompressionMethod::from_u2(header.compression_method).ok_or_else(|| ArchiveError::UnsupportedCompressionMethod {
+				name: header.name.clone(),
+				method: header.compression_method,
+			})?;
+			if header.compressed_size == U4::MAX || header.uncompressed_size == U4::MAX || header.local_header_offset == U4::MAX {
+				return Err(ArchiveError::Unsupported("zip64"));
+			}
+			entries.push(ArchiveEntry {
+				name: header.name,
+				compression_method,
+				crc32: header.crc32,
+				compressed_size: header.compressed_size,
+				uncompressed_size: header.uncompressed_size,
+				local_header_offset: header.local_header_offset,
+			});
+		}
+		// Of several entries with the same name the first one is used.
+		let mut index = HashMap::with_capacity(entries.len());
+		for (i, entry) in entries.iter().enumerate() {
+			index.entry(entry.name.clone()).or_insert(i);
+		}
+		let versions = entries.iter()
+			.filter_map(|entry| entry.name.strip_prefix(VERSIONS_DIRECTORY)?.split_once('/')?.0.parse::<U2>().ok())
+			.filter(|&version| version >= FIRST_VERSIONED_RELEASE)
+			.collect();
+
+		let mut jar_file = Self {
+			data,
+			entries,
+			index,
+			versions,
+			manifest: None,
+		};
+		if let Some(entry) = jar_file.entry(MANIFEST_NAME) {
+			let manifest = Manifest::parse(&String::from_utf8_lossy(&jar_file.read(entry)?));
+			jar_file.manifest = Some(manifest);
+		}
+		Ok(jar_file)
+	}
+
+	pub fn entries(&self) -> &[ArchiveEntry] {
+		&self.entries
+	}
+
+	pub fn entry(&self, name: &str) -> Option<&ArchiveEntry> {
+		self.index.get(name).map(|&i| &self.entries[i])
+	}
+
+	pub fn manifest(&self) -> Option<&Manifest> {
+		self.manifest.as_ref()
+	}
+
+	pub fn is_multi_release(&self) -> bool {
+		self.manifest.as_ref().is_some_and(Manifest::is_multi_release)
+	}
+
+	/// Contents of `entry`, borrowed from the archive if it is stored uncompressed.
+	pub fn read(&self, entry: &ArchiveEntry) -> Result<Cow<'_, [u8]>, ArchiveError> {
+		let offset = entry.local_header_offset as usize;
+		let input = self.data.get(offset..).unwrap_or_default();
+		let (_, compressed) = local_file_data_parser::<ClassFormatError>(input, entry.compressed_size).map_err(malformed(&self.data))?;
+		let contents = match entry.compression_method {
+			CompressionMethod::Stored => Cow::Borrowed(compressed),
+			CompressionMethod::Deflated => Cow::Owned(decompress_to_vec_with_limit(compressed, entry.uncompressed_size as usize).map_err(|_| ArchiveError::CorruptEntry(entry.name.clone()))?),
+		};
+		if contents.len() != entry.uncompressed_size as usize {
+			return Err(ArchiveError::CorruptEntry(entry.name.clone()));
+		}
+		if crc32(&contents) != entry.crc32 {
+			return Err(ArchiveError::ChecksumMismatch(entry.name.clone()));
+		}
+		Ok(contents)
+	}
+
+	pub fn read_entry(&self, name: &str) -> Result<Cow<'_, [u8]>, ArchiveError> {
+		let entry = self.entry(name).ok_or_else(|| ArchiveError::EntryNotFound(name.to_string()))?;
+		self.read(entry)
+	}
+
+	/// Entry holding the class `class_name`, given in internal form such as `demo/App`.
+	///
+	/// For multi-release jars and a `release` the highest `META-INF/versions/N/` with N up to `release` that contains the class is used.
+	/// Without a `release` only the base entries are considered.
+	pub fn class_entry(&self, class_name: &str, release: Option<U2>) -> Option<&ArchiveEntry> {
+		if let Some(release) = release.filter(|_| self.is_multi_release()) {
+			for version in self.versions.range(..=release).rev() {
+				if let Some(entry) = self.entry(&format!("{VERSIONS_DIRECTORY}{version}/{class_name}.class")) {
+					return Some(entry);
+				}
+			}
+		}
+		self.entry(&format!("{class_name}.class"))
+	}
+
+	/// Internal names of the classes visible to `release`, sorted.
+	pub fn class_names(&self, release: Option<U2>) -> Vec<&str> {
+		let release = release.filter(|_| self.is_multi_release());
+		let mut class_names = BTreeSet::new();
+		for entry in self.entries.iter().filter(|entry| entry.is_class()) {
+			let name = match entry.name.strip_prefix(VERSIONS_DIRECTORY) {
+				Some(versioned) => match (versioned.split_once('/'), release) {
+					(Some((version, name)), Some(release)) if version.parse::<U2>().is_ok_and(|version| (FIRST_VERSIONED_RELEASE..=release).contains(&version)) => name,
+					_ => continue,
+				},
+				None if entry.name.starts_with("META-INF/") => continue,
+				None => entry.name.as_str(),
+			};
+			class_names.insert(name.strip_suffix(".class").unwrap_or(name));
+		}
+		class_names.into_iter().collect()
+	}
+
+	/// Parses the class `class_name` as resolved by `class_entry`, `None` if the archive does not contain it.
+	pub fn parse_class(&self, class_name: &str, release: Option<U2>) -> Result<Option<ClassFile>, ArchiveError> {
+		let Some(entry) = self.class_entry(class_name, release) else {
+			return Ok(None);
+		};
+		let contents = self.read(entry)?;
+		parse_class_file(&contents).map(Some).map_err(|error| ArchiveError::InvalidClass {
+			name: entry.name.clone(),
+			error,
+		})
+	}
+}
+
+/// Reads the headers of the central directory, located through the end of central directory record.
+pub fn central_directory(data: &[u8]) -> Result<Vec<CentralDirectoryHeader>, ArchiveError> {
+	let end = end_of_central_directory_offset(data).ok_or(ArchiveError::MissingEndOfCentralDirectory)?;
+	let (_, (entry_count, offset)) = end_of_central_directory_parser::<ClassFormatError>(&data[end..]).map_err(malformed(data))?;
+	if entry_count == U2::MAX || offset == U4::MAX {
+		return Err(ArchiveError::Unsupported("zip64"));
+	}
+
+	if offset as usize > end {
+		return Err(ArchiveError::InvalidCentralDirectoryOffset(offset));
+	}
+
+	let mut input = &data[offset as usize..end];
+	let mut headers = Vec::with_capacity(entry_count as usize);
+	for i in 0..entry_count as usize {
+		let (rest, header) = context("central_directory", indexed(i, central_directory_header_parser::<ClassFormatError>))(input).map_err(malformed(&data[..end]))?;
+		input = rest;
+		headers.push(header);
+	}
+	Ok(headers)
+}
+
+/// Locates errors of the zip parsers in `data`, which has to end where the input of the parser ends.
+fn malformed(data: &[u8]) -> impl Fn(nom::Err<ClassFormatError>) -> ArchiveError + '_ {
+	move |error| match error {
+		nom::Err::Error(error) | nom::Err::Failure(error) => ArchiveError::Malformed(error.locate(data)),
+		nom::Err::Incomplete(_) => ArchiveError::Malformed(ClassFormatError::new(&[], ClassFormatErrorKind::UnexpectedEof).locate(data)),
+	}
+}
+
+/// Offset of the last end of central directory record, which is followed by a comment of at most 65535 bytes.
+fn end_of_central_directory_offset(data: &[u8]) -> Option<usize> {
+	let last = data.len().checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE)?;
+	let first = last.saturating_sub(U2::MAX as usize);
+	(first..=last).rev().find(|&offset| data[offset..offset + 4] == END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes())
+}
+
+fn signature<'a, E: ClassParseError<&'a [u8]>>(expected: U4) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], U4, E> {
+	context("signature", verify(le_u32, move |signature| *signature == expected))
+}
+
+/// Number of entries and offset of the central directory.
+fn end_of_central_directory_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], (U2, U4), E> {
+	let (input, _) = context("end_of_central_directory", signature(END_OF_CENTRAL_DIRECTORY_SIGNATURE))(input)?;
+	let (input, _disk_number) = context("disk_number", le_u16)(input)?;
+	let (input, _central_directory_disk) = context("central_directory_disk", le_u16)(input)?;
+	let (input, _disk_entries) = context("disk_entries", le_u16)(input)?;
+	let (input, total_entries) = context("total_entries", le_u16)(input)?;
+	let (input, _central_directory_size) = context("central_directory_size", le_u32)(input)?;
+	let (input, central_directory_offset) = context("central_directory_offset", le_u32)(input)?;
+
+	Ok((input, (total_entries, central_directory_offset)))
+}
+
+pub fn central_directory_header_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], CentralDirectoryHeader, E> {
+	let (input, _) = signature(CENTRAL_DIRECTORY_HEADER_SIGNATURE)(input)?;
+	let (input, _version_made_by) = context("version_made_by", le_u16)(input)?;
+	let (input, _version_needed) = context("version_needed", le_u16)(input)?;
+	let (input, flags) = context("flags", le_u16)(input)?;
+	let (input, compression_method) = context("compression_method", le_u16)(input)?;
+	let (input, _last_modified_time) = context("last_modified_time", le_u16)(input)?;
+	let (input, _last_modified_date) = context("last_modified_date", le_u16)(input)?;
+	let (input, crc32) = context("crc32", le_u32)(input)?;
+	let (input, compressed_size) = context("compressed_size", le_u32)(input)?;
+	let (input, uncompressed_size) = context("uncompressed_size", le_u32)(input)?;
+	let (input, name_length) = context("name_length", le_u16)(input)?;
+	let (input, extra_length) = context("extra_length", le_u16)(input)?;
+	let (input, comment_length) = context("comment_length", le_u16)(input)?;
+	let (input, _disk_number) = context("disk_number", le_u16)(input)?;
+	let (input, _internal_attributes) = context("internal_attributes", le_u16)(input)?;
+	let (input, _external_attributes) = context("external_attributes", le_u32)(input)?;
+	let (input, local_header_offset) = context("local_header_offset", le_u32)(input)?;
+	let (input, name) = context("name", take(name_length))(input)?;
+	let (input, _extra) = context("extra", take(extra_length))(input)?;
+	let (input, _comment) = context("comment", take(comment_length))(input)?;
+
+	Ok((input, CentralDirectoryHeader {
+		flags,
+		compression_method,
+		crc32,
+		compressed_size,
+		uncompressed_size,
+		local_header_offset,
+		// Jar tools write UTF-8 names whether or not they set the language encoding flag.
+		name: String::from_utf8_lossy(name).into_owned(),
+	}))
+}
+
+/// Skips a local file header, returning the `compressed_size` bytes of data that follow it.
+///
+/// Sizes come from the central directory since the local header leaves them zero when a data descriptor follows the data.
+fn local_file_data_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8], compressed_size: U4) -> IResult<&'a [u8], &'a [u8], E> {
+	let (input, _) = context("local_file_header", signature(LOCAL_FILE_HEADER_SIGNATURE))(input)?;
+	let (input, _) = context("local_file_header", take(22usize))(input)?;
+	let (input, name_length) = context("name_length", le_u16)(input)?;
+	let (input, extra_length) = context("extra_length", le_u16)(input)?;
+	let (input, _name) = context("name", take(name_length))(input)?;
+	let (input, _extra) = context("extra", take(extra_length))(input)?;
+	context("data", take(compressed_size))(input)
+}
+
+const CRC32_TABLE: [U4; 256] = crc32_table();
+
+const fn crc32_table() -> [U4; 256] {
+	let mut table = [0; 256];
+	let mut i = 0;
+	while i < 256 {
+		let mut crc = i as U4;
+		let mut bit = 0;
+		while bit < 8 {
+			crc = if crc & 1 != 0 { 0xEDB88320 ^ (crc >> 1) } else { crc >> 1 };
+			bit += 1;
+		}
+		table[i] = crc;
+		i += 1;
+	}
+	table
+}
+
+/// CRC-32 as used by zip archives.
+pub fn crc32(data: &[u8]) -> U4 {
+	!data.iter().fold(U4::MAX, |crc, &byte| CRC32_TABLE[((crc ^ byte as U4) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const JAR_FILE_PATH: &str = "./Sample.jar";
+
+	fn open_sample() -> JarFile {
+		JarFile::open(JAR_FILE_PATH).expect("Failed to open jar file")
+	}
+
+	#[test]
+	fn read_manifest() {
+		let jar_file = open_sample();
+		let manifest = jar_file.manifest().unwrap();
+		assert_eq!(manifest.main_class(), Some("demo.App"));
+		assert_eq!(manifest.class_path(), vec!["lib/util.jar", "lib/extra.jar"]);
+		assert_eq!(manifest.get("manifest-version"), Some("1.0"));
+		assert!(jar_file.is_multi_release());
+	}
+
+	#[test]
+	fn read_stored_and_deflated_entries() {
+		let jar_file = open_sample();
+		let deflated = jar_file.entry("demo/App.class").unwrap();
+		assert_eq!(deflated.compression_method, CompressionMethod::Deflated);
+		let stored = jar_file.entry("META-INF/versions/11/demo/Greeter.class").unwrap();
+		assert_eq!(stored.compression_method, CompressionMethod::Stored);
+		assert!(matches!(jar_file.read(stored).unwrap(), Cow::Borrowed(_)));
+
+		assert_eq!(jar_file.read(deflated).unwrap().len(), deflated.uncompressed_size as usize);
+		assert!(jar_file.entry("demo/").unwrap().is_directory());
+		assert!(matches!(jar_file.read_entry("demo/Missing.class"), Err(ArchiveError::EntryNotFound(_))));
+	}
+
+	#[test]
+	fn resolve_multi_release_classes() {
+		let jar_file = open_sample();
+		assert_eq!(jar_file.class_names(None), vec!["demo/App", "demo/Greeter"]);
+		assert_eq!(jar_file.class_names(Some(17)), vec!["demo/App", "demo/Greeter"]);
+
+		assert_eq!(jar_file.class_entry("demo/Greeter", None).unwrap().name, "demo/Greeter.class");
+		assert_eq!(jar_file.class_entry("demo/Greeter", Some(10)).unwrap().name, "demo/Greeter.class");
+		assert_eq!(jar_file.class_entry("demo/Greeter", Some(11)).unwrap().name, "META-INF/versions/11/demo/Greeter.class");
+		assert_eq!(jar_file.class_entry("demo/App", Some(17)).unwrap().name, "demo/App.class");
+
+		let base = jar_file.parse_class("demo/Greeter", Some(8)).unwrap().unwrap();
+		assert_eq!(base.major_version, 52);
+		let versioned = jar_file.parse_class("demo/Greeter", Some(21)).unwrap().unwrap();
+		assert_eq!(versioned.major_version, 55);
+		assert!(jar_file.parse_class("demo/Missing", None).unwrap().is_none());
+	}
+
+	#[test]
+	fn reject_corrupt_archives() {
+		let mut data = fs::read(JAR_FILE_PATH).unwrap();
+		assert!(matches!(JarFile::from_bytes(data[..data.len() - 30].to_vec()), Err(ArchiveError::MissingEndOfCentralDirectory)));
+
+		let jar_file = JarFile::from_bytes(data.clone()).unwrap();
+		let entry = jar_file.entry("META-INF/versions/11/demo/Greeter.class").unwrap().clone();
+		let last = entry.local_header_offset as usize + 30 + entry.name.len() + entry.compressed_size as usize - 1;
+		data[last] ^= 0xFF;
+		let jar_file = JarFile::from_bytes(data).unwrap();
+		assert!(matches!(jar_file.read(&entry), Err(ArchiveError::ChecksumMismatch(_))));
+	}
+
+	#[test]
+	fn locate_central_directory_errors() {
+		let data = fs::read(JAR_FILE_PATH).unwrap();
+		let end = end_of_central_directory_offset(&data).unwrap();
+		let offset = U4::from_le_bytes(data[end + 16..end + 20].try_into().unwrap());
+		let lengths = &data[offset as usize + 28..offset as usize + 34];
+		let header_length = 46 + lengths.chunks(2).map(|length| U2::from_le_bytes([length[0], length[1]]) as usize).sum::<usize>();
+
+		let mut corrupt = data.clone();
+		corrupt[offset as usize + header_length] ^= 0xFF;
+		match central_directory(&corrupt) {
+			Err(ArchiveError::Malformed(error)) => {
+				assert_eq!(error.offset(), offset as usize + header_length);
+				assert_eq!(error.path(), "central_directory[1].signature");
+			},
+			result => panic!("unexpected result {result:?}"),
+		}
+
+		let mut corrupt = data;
+		corrupt[end + 16..end + 20].copy_from_slice(&(end as U4 + 1).to_le_bytes());
+		assert!(matches!(central_directory(&corrupt), Err(ArchiveError::InvalidCentralDirectoryOffset(_))));
+	}
+
+	#[test]
+	fn compute_crc32() {
+		assert_eq!(crc32(b""), 0);
+		assert_eq!(crc32(b"123456789"), 0xCBF43926);
+	}
+}
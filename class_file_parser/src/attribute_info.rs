@@ -1,6 +1,22 @@
-use nom::{error::ParseError, multi::length_count, number::complete::{be_u16, be_u32, be_u8}, IResult};
+use std::{borrow::Cow, io::{self, Write}};
 
-use crate::{U1, U2, U4};
+use nom::{bytes::complete::take, combinator::flat_map, error::context, number::complete::{be_u16, be_u32}, IResult};
+
+use crate::{class_format_error::{parse_all, ClassFormatError, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError, ConstantPoolRef}, writer::{write_length_u2, write_length_u4, write_u2}, U1, U2, U4};
+
+pub mod annotations;
+pub mod bootstrap_methods;
+pub mod code;
+pub mod constant_value;
+pub mod debug_info;
+pub mod exceptions;
+pub mod inner_classes;
+pub mod method_parameters;
+pub mod module;
+pub mod nest;
+pub mod record;
+pub mod signature;
+pub mod stack_map_table;
 
 #[derive(Debug)]
 pub struct AttributeInfo {
@@ -9,13 +25,104 @@ pub struct AttributeInfo {
 	pub info: Vec<U1>,
 }
 
-pub fn attribute_info_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], AttributeInfo> {
-	let (input, attribute_name_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'attribute_name_index'");
+impl AttributeInfo {
+	pub fn name<'c>(&self, constant_pool: &'c ConstantPool) -> Result<Cow<'c, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.attribute_name_index)
+	}
+
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.attribute_name_index)?;
+		write_length_u4(writer, self.info.len(), "info")?;
+		writer.write_all(&self.info)
+	}
+
+	/// Decodes `info` with `parser`, which has to consume it completely.
+	///
+	/// Errors are reported relative to the start of `info`, with `name` as the outermost path segment.
+	pub fn parse<'a, T, F>(&'a self, name: &'static str, parser: F) -> Result<T, ClassFormatError>
+	where
+		F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, ClassFormatError>,
+	{
+		parse_all(&self.info, context(name, parser))
+	}
+}
+
+/// `AttributeInfo` whose `info` is borrowed from the class file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeInfoRef<'a> {
+	pub attribute_name_index: U2,
+	pub attribute_length: U4,
+	pub info: &'a [U1],
+}
+
+impl<'a> AttributeInfoRef<'a> {
+	pub fn name(&self, constant_pool: &ConstantPoolRef<'a>) -> Result<Cow<'a, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.attribute_name_index)
+	}
+
+	/// Like `AttributeInfo::parse`, but the result may borrow from the class file rather than from `self`.
+	pub fn parse<T, F>(&self, name: &'static str, parser: F) -> Result<T, ClassFormatError>
+	where
+		F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, ClassFormatError>,
+	{
+		parse_all(self.info, context(name, parser))
+	}
+
+	pub fn into_owned(self) -> AttributeInfo {
+		AttributeInfo {
+			attribute_name_index: self.attribute_name_index,
+			attribute_length: self.attribute_length,
+			info: self.info.to_vec(),
+		}
+	}
+}
+
+/// Decoded attribute that can be encoded again.
+pub trait AttributeBody {
+	/// Writes the contents of the attribute, i.e. what `AttributeInfo.info` holds.
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+}
+
+pub(crate) fn write_attributes<W: Write>(writer: &mut W, attributes: &[AttributeInfo]) -> io::Result<()> {
+	write_length_u2(writer, attributes.len(), "attributes")?;
+	for attribute in attributes {
+		attribute.write_to(writer)?;
+	}
+	Ok(())
+}
+
+/// Finds the first attribute called `name`.
+pub fn find_attribute<'a>(attributes: &'a [AttributeInfo], constant_pool: &ConstantPool, name: &str) -> Result<Option<&'a AttributeInfo>, ConstantPoolError> {
+	for attribute in attributes {
+		if attribute.name(constant_pool)? == name {
+			return Ok(Some(attribute));
+		}
+	}
+	Ok(None)
+}
+
+/// Finds the first attribute called `name` among borrowed attributes.
+pub fn find_attribute_ref<'a>(attributes: &[AttributeInfoRef<'a>], constant_pool: &ConstantPoolRef<'a>, name: &str) -> Result<Option<AttributeInfoRef<'a>>, ConstantPoolError> {
+	for attribute in attributes {
+		if attribute.name(constant_pool)? == name {
+			return Ok(Some(*attribute));
+		}
+	}
+	Ok(None)
+}
+
+pub fn attribute_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], AttributeInfo, E> {
+	let (input, attribute) = attribute_info_ref_parser(input)?;
+	Ok((input, attribute.into_owned()))
+}
+
+pub fn attribute_info_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], AttributeInfoRef<'a>, E> {
+	let (input, attribute_name_index) = context("attribute_name_index", be_u16)(input)?;
 	
-	let (input, info) = length_count(be_u32::<&[u8], E>, be_u8::<&[u8], E>)(input).expect("Failed to read 'attribute_length' or 'info'");
+	let (input, info) = context("info", flat_map(be_u32, take))(input)?;
 	let attribute_length = info.len() as U4;
 
-	Ok((input, AttributeInfo {
+	Ok((input, AttributeInfoRef {
 		attribute_name_index,
 		attribute_length,
 		info
@@ -0,0 +1,850 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::{be_u16, be_u8}, IResult};
+
+use crate::{attribute_info::{find_attribute, AttributeBody, AttributeInfo}, class_format_error::{fail_with, indexed_length_count, ClassFormatError, ClassFormatErrorKind, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, mutf8::JavaString, writer::{write_length_u1, write_length_u2, write_u1, write_u2}, U1, U2};
+
+const TAG_BYTE: U1 = b'B';
+const TAG_CHAR: U1 = b'C';
+const TAG_DOUBLE: U1 = b'D';
+const TAG_FLOAT: U1 = b'F';
+const TAG_INT: U1 = b'I';
+const TAG_LONG: U1 = b'J';
+const TAG_SHORT: U1 = b'S';
+const TAG_BOOLEAN: U1 = b'Z';
+const TAG_STRING: U1 = b's';
+const TAG_ENUM: U1 = b'e';
+const TAG_CLASS: U1 = b'c';
+const TAG_ANNOTATION: U1 = b'@';
+const TAG_ARRAY: U1 = b'[';
+
+/// Whether annotations are stored in the `RuntimeVisible*` or the `RuntimeInvisible*` attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+	Visible,
+	Invisible,
+}
+
+impl Visibility {
+	pub fn annotations_attribute(&self) -> &'static str {
+		match self {
+			Self::Visible => "RuntimeVisibleAnnotations",
+			Self::Invisible => "RuntimeInvisibleAnnotations",
+		}
+	}
+
+	pub fn parameter_annotations_attribute(&self) -> &'static str {
+		match self {
+			Self::Visible => "RuntimeVisibleParameterAnnotations",
+			Self::Invisible => "RuntimeInvisibleParameterAnnotations",
+		}
+	}
+
+	pub fn type_annotations_attribute(&self) -> &'static str {
+		match self {
+			Self::Visible => "RuntimeVisibleTypeAnnotations",
+			Self::Invisible => "RuntimeInvisibleTypeAnnotations",
+		}
+	}
+}
+
+/// `element_value` as stored in the class file (JVMS 4.7.16.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementValueInfo {
+	/// Primitive or `String` constant, `tag` being one of `BCDFIJSZs`.
+	Const {
+		tag: U1,
+		const_value_index: U2,
+	},
+	Enum {
+		type_name_index: U2,
+		const_name_index: U2,
+	},
+	Class {
+		class_info_index: U2,
+	},
+	Annotation(AnnotationInfo),
+	Array {
+		num_values: U2,
+		values: Vec<ElementValueInfo>,
+	},
+}
+
+impl ElementValueInfo {
+	pub fn tag(&self) -> U1 {
+		match self {
+			Self::Const { tag, .. } => *tag,
+			Self::Enum { .. } => TAG_ENUM,
+			Self::Class { .. } => TAG_CLASS,
+			Self::Annotation(_) => TAG_ANNOTATION,
+			Self::Array { .. } => TAG_ARRAY,
+		}
+	}
+
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u1(writer, self.tag())?;
+		match self {
+			Self::Const { const_value_index, .. } => write_u2(writer, *const_value_index),
+			Self::Enum { type_name_index, const_name_index } => {
+				write_u2(writer, *type_name_index)?;
+				write_u2(writer, *const_name_index)
+			},
+			Self::Class { class_info_index } => write_u2(writer, *class_info_index),
+			Self::Annotation(annotation) => annotation.write_to(writer),
+			Self::Array { values, .. } => {
+				write_length_u2(writer, values.len(), "values")?;
+				for value in values {
+					value.write_to(writer)?;
+				}
+				Ok(())
+			},
+		}
+	}
+
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<ElementValue, ConstantPoolError> {
+		Ok(match self {
+			Self::Const { tag, const_value_index } => {
+				let index = *const_value_index;
+				match *tag {
+					TAG_BYTE => ElementValue::Byte(constant_pool.get_integer(index)? as i8),
+					TAG_CHAR => ElementValue::Char(constant_pool.get_integer(index)? as u16),
+					TAG_DOUBLE => ElementValue::Double(constant_pool.get_double(index)?),
+					TAG_FLOAT => ElementValue::Float(constant_pool.get_float(index)?),
+					TAG_INT => ElementValue::Int(constant_pool.get_integer(index)?),
+					TAG_LONG => ElementValue::Long(constant_pool.get_long(index)?),
+					TAG_SHORT => ElementValue::Short(constant_pool.get_integer(index)? as i16),
+					TAG_BOOLEAN => ElementValue::Boolean(constant_pool.get_integer(index)? != 0),
+					_ => ElementValue::String(constant_pool.get_java_string(index)?),
+				}
+			},
+			Self::Enum { type_name_index, const_name_index } => ElementValue::Enum {
+				type_name: constant_pool.get_utf8_str(*type_name_index)?.into_owned(),
+				const_name: constant_pool.get_utf8_str(*const_name_index)?.into_owned(),
+			},
+			Self::Class { class_info_index } => ElementValue::Class(constant_pool.get_utf8_str(*class_info_index)?.into_owned()),
+			Self::Annotation(annotation) => ElementValue::Annotation(annotation.resolve(constant_pool)?),
+			Self::Array { values, .. } => ElementValue::Array(values.iter().map(|value| value.resolve(constant_pool)).collect::<Result<_, _>>()?),
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementValuePairInfo {
+	pub element_name_index: U2,
+	pub value: ElementValueInfo,
+}
+
+/// `annotation` as stored in the class file (JVMS 4.7.16).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationInfo {
+	pub type_index: U2,
+	pub num_element_value_pairs: U2,
+	pub element_value_pairs: Vec<ElementValuePairInfo>,
+}
+
+impl AnnotationInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.type_index)?;
+		write_length_u2(writer, self.element_value_pairs.len(), "element_value_pairs")?;
+		for pair in &self.element_value_pairs {
+			write_u2(writer, pair.element_name_index)?;
+			pair.value.write_to(writer)?;
+		}
+		Ok(())
+	}
+
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Annotation, ConstantPoolError> {
+		let mut elements = Vec::with_capacity(self.element_value_pairs.len());
+		for pair in &self.element_value_pairs {
+			elements.push((constant_pool.get_utf8_str(pair.element_name_index)?.into_owned(), pair.value.resolve(constant_pool)?));
+		}
+		Ok(Annotation {
+			type_name: constant_pool.get_utf8_str(self.type_index)?.into_owned(),
+			elements,
+		})
+	}
+}
+
+/// Element value with constants resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+	Byte(i8),
+	Char(u16),
+	Double(f64),
+	Float(f32),
+	Int(i32),
+	Long(i64),
+	Short(i16),
+	Boolean(bool),
+	String(JavaString),
+	/// Enum constant, with `type_name` being a field descriptor such as `Ljava/lang/annotation/ElementType;`.
+	Enum {
+		type_name: String,
+		const_name: String,
+	},
+	/// Class literal as a return descriptor, e.g. `Ljava/lang/String;` or `V`.
+	Class(String),
+	Annotation(Annotation),
+	Array(Vec<ElementValue>),
+}
+
+/// Annotation with constants resolved, `type_name` being a field descriptor such as `Ljava/lang/Deprecated;`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+	pub type_name: String,
+	pub elements: Vec<(String, ElementValue)>,
+}
+
+impl Annotation {
+	/// Value of the element called `name`, elements left at their default are not stored.
+	pub fn element(&self, name: &str) -> Option<&ElementValue> {
+		self.elements.iter().find(|(element_name, _)| element_name == name).map(|(_, value)| value)
+	}
+}
+
+/// `RuntimeVisibleAnnotations` and `RuntimeInvisibleAnnotations`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationsAttribute {
+	pub num_annotations: U2,
+	pub annotations: Vec<AnnotationInfo>,
+}
+
+impl AnnotationsAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Vec<Annotation>, ConstantPoolError> {
+		self.annotations.iter().map(|annotation| annotation.resolve(constant_pool)).collect()
+	}
+}
+
+impl AttributeBody for AnnotationsAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.annotations.len(), "annotations")?;
+		for annotation in &self.annotations {
+			annotation.write_to(writer)?;
+		}
+		Ok(())
+	}
+}
+
+/// `RuntimeVisibleParameterAnnotations` and `RuntimeInvisibleParameterAnnotations`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterAnnotationsAttribute {
+	pub num_parameters: U1,
+	pub parameter_annotations: Vec<AnnotationsAttribute>,
+}
+
+impl AttributeBody for ParameterAnnotationsAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u1(writer, self.parameter_annotations.len(), "parameter_annotations")?;
+		for annotations in &self.parameter_annotations {
+			annotations.write_to(writer)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalVarTargetEntry {
+	pub start_pc: U2,
+	pub length: U2,
+	pub index: U2,
+}
+
+/// `target_info` of a type annotation (JVMS 4.7.20.1), which kind is used depends on `target_type`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetInfo {
+	TypeParameter {
+		type_parameter_index: U1,
+	},
+	Supertype {
+		supertype_index: U2,
+	},
+	TypeParameterBound {
+		type_parameter_index: U1,
+		bound_index: U1,
+	},
+	Empty,
+	FormalParameter {
+		formal_parameter_index: U1,
+	},
+	Throws {
+		throws_type_index: U2,
+	},
+	LocalVar {
+		table_length: U2,
+		table: Vec<LocalVarTargetEntry>,
+	},
+	Catch {
+		exception_table_index: U2,
+	},
+	Offset {
+		offset: U2,
+	},
+	TypeArgument {
+		offset: U2,
+		type_argument_index: U1,
+	},
+}
+
+impl TargetInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		match self {
+			Self::TypeParameter { type_parameter_index } => write_u1(writer, *type_parameter_index),
+			Self::Supertype { supertype_index } => write_u2(writer, *supertype_index),
+			Self::TypeParameterBound { type_parameter_index, bound_index } => {
+				write_u1(writer, *type_parameter_index)?;
+				write_u1(writer, *bound_index)
+			},
+			Self::Empty => Ok(()),
+			Self::FormalParameter { formal_parameter_index } => write_u1(writer, *formal_parameter_index),
+			Self::Throws { throws_type_index } => write_u2(writer, *throws_type_index),
+			Self::LocalVar { table, .. } => {
+				write_length_u2(writer, table.len(), "table")?;
+				for entry in table {
+					write_u2(writer, entry.start_pc)?;
+					write_u2(writer, entry.length)?;
+					write_u2(writer, entry.index)?;
+				}
+				Ok(())
+			},
+			Self::Catch { exception_table_index } => write_u2(writer, *exception_table_index),
+			Self::Offset { offset } => write_u2(writer, *offset),
+			Self::TypeArgument { offset, type_argument_index } => {
+				write_u2(writer, *offset)?;
+				write_u1(writer, *type_argument_index)
+			},
+		}
+	}
+}
+
+/// Step of a `type_path` (JVMS 4.7.20.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypePathEntry {
+	/// 0 for an array element, 1 for a nested type, 2 for a wildcard bound and 3 for a type argument.
+	pub type_path_kind: U1,
+	pub type_argument_index: U1,
+}
+
+/// `type_annotation` as stored in the class file (JVMS 4.7.20).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeAnnotationInfo {
+	pub target_type: U1,
+	pub target_info: TargetInfo,
+	pub path_length: U1,
+	pub target_path: Vec<TypePathEntry>,
+	pub annotation: AnnotationInfo,
+}
+
+impl TypeAnnotationInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u1(writer, self.target_type)?;
+		self.target_info.write_to(writer)?;
+		write_length_u1(writer, self.target_path.len(), "path")?;
+		for entry in &self.target_path {
+			write_u1(writer, entry.type_path_kind)?;
+			write_u1(writer, entry.type_argument_index)?;
+		}
+		self.annotation.write_to(writer)
+	}
+
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<TypeAnnotation, ConstantPoolError> {
+		Ok(TypeAnnotation {
+			target_type: self.target_type,
+			target_info: self.target_info.clone(),
+			target_path: self.target_path.clone(),
+			annotation: self.annotation.resolve(constant_pool)?,
+		})
+	}
+}
+
+/// Type annotation with constants resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAnnotation {
+	pub target_type: U1,
+	pub target_info: TargetInfo,
+	pub target_path: Vec<TypePathEntry>,
+	pub annotation: Annotation,
+}
+
+/// `RuntimeVisibleTypeAnnotations` and `RuntimeInvisibleTypeAnnotations`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeAnnotationsAttribute {
+	pub num_annotations: U2,
+	pub annotations: Vec<TypeAnnotationInfo>,
+}
+
+impl AttributeBody for TypeAnnotationsAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.annotations.len(), "annotations")?;
+		for annotation in &self.annotations {
+			annotation.write_to(writer)?;
+		}
+		Ok(())
+	}
+}
+
+/// `AnnotationDefault` of an annotation interface element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationDefaultAttribute {
+	pub default_value: ElementValueInfo,
+}
+
+impl AttributeBody for AnnotationDefaultAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		self.default_value.write_to(writer)
+	}
+}
+
+/// Resolved annotations of the `Runtime*Annotations` attribute in `attributes`, empty if there is none.
+pub fn annotations(attributes: &[AttributeInfo], constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<Annotation>, ClassFormatError> {
+	let name = visibility.annotations_attribute();
+	match find_attribute(attributes, constant_pool, name)? {
+		Some(attribute) => Ok(attribute.parse(name, annotations_attribute_parser)?.resolve(constant_pool)?),
+		None => Ok(Vec::new()),
+	}
+}
+
+/// Resolved annotations per parameter, empty if there is no `Runtime*ParameterAnnotations` attribute.
+pub fn parameter_annotations(attributes: &[AttributeInfo], constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<Vec<Annotation>>, ClassFormatError> {
+	let name = visibility.parameter_annotations_attribute();
+	match find_attribute(attributes, constant_pool, name)? {
+		Some(attribute) => {
+			let parameter_annotations = attribute.parse(name, parameter_annotations_attribute_parser)?;
+			Ok(parameter_annotations.parameter_annotations.iter().map(|annotations| annotations.resolve(constant_pool)).collect::<Result<_, _>>()?)
+		},
+		None => Ok(Vec::new()),
+	}
+}
+
+/// Resolved type annotations of the `Runtime*TypeAnnotations` attribute in `attributes`, empty if there is none.
+pub fn type_annotations(attributes: &[AttributeInfo], constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<TypeAnnotation>, ClassFormatError> {
+	let name = visibility.type_annotations_attribute();
+	match find_attribute(attributes, constant_pool, name)? {
+		Some(attribute) => {
+			let type_annotations = attribute.parse(name, type_annotations_attribute_parser)?;
+			Ok(type_annotations.annotations.iter().map(|annotation| annotation.resolve(constant_pool)).collect::<Result<_, _>>()?)
+		},
+		None => Ok(Vec::new()),
+	}
+}
+
+/// Deepest nesting of arrays and annotations in an element value.
+///
+/// Parsing keeps the values being nested on the heap, but writing, resolving and dropping element values recurse once per level.
+/// The limit bounds that recursion the way the 255 array dimensions of a descriptor bound nested array types.
+pub const MAX_ELEMENT_VALUE_DEPTH: usize = 255;
+
+/// Array or annotation of which `element_value_parser` is parsing the values.
+enum PendingValue {
+	Array {
+		num_values: U2,
+		values: Vec<ElementValueInfo>,
+	},
+	Annotation {
+		type_index: U2,
+		num_element_value_pairs: U2,
+		element_value_pairs: Vec<ElementValuePairInfo>,
+		/// Name of the pair whose value is being parsed.
+		element_name_index: U2,
+	},
+}
+
+impl PendingValue {
+	fn is_complete(&self) -> bool {
+		match self {
+			Self::Array { num_values, values } => values.len() == *num_values as usize,
+			Self::Annotation { num_element_value_pairs, element_value_pairs, .. } => element_value_pairs.len() == *num_element_value_pairs as usize,
+		}
+	}
+
+	fn push(&mut self, value: ElementValueInfo) {
+		match self {
+			Self::Array { values, .. } => values.push(value),
+			Self::Annotation { element_value_pairs, element_name_index, .. } => element_value_pairs.push(ElementValuePairInfo {
+				element_name_index: *element_name_index,
+				value,
+			}),
+		}
+	}
+
+	fn into_value(self) -> ElementValueInfo {
+		match self {
+			Self::Array { num_values, values } => ElementValueInfo::Array {
+				num_values,
+				values,
+			},
+			Self::Annotation { type_index, num_element_value_pairs, element_value_pairs, .. } => ElementValueInfo::Annotation(AnnotationInfo {
+				type_index,
+				num_element_value_pairs,
+				element_value_pairs,
+			}),
+		}
+	}
+
+	/// Adds the path from this value to the value being parsed in it, `field` names the part of an annotation pair the error is in.
+	fn locate<'a, E: ClassParseError<&'a [u8]>>(&self, input: &'a [u8], field: &'static str, error: E) -> E {
+		match self {
+			Self::Array { values, .. } => E::add_context(input, "values", E::add_index(input, values.len(), error)),
+			Self::Annotation { element_value_pairs, .. } => {
+				let error = E::add_index(input, element_value_pairs.len(), E::add_context(input, field, error));
+				E::add_context(input, "annotation_value", E::add_context(input, "element_value_pairs", error))
+			},
+		}
+	}
+}
+
+/// Adds the path through the `pending` values to an error of the innermost one.
+fn locate_pending<'a, E: ClassParseError<&'a [u8]>>(pending: &[PendingValue], input: &'a [u8], field: &'static str, error: E) -> E {
+	let mut error = error;
+	let mut field = field;
+	for value in pending.iter().rev() {
+		error = value.locate(input, field, error);
+		field = "value";
+	}
+	error
+}
+
+enum ElementValueStart {
+	Value(ElementValueInfo),
+	/// Array or annotation whose values follow.
+	Nested(PendingValue),
+}
+
+/// Parses an element value nested in `depth` arrays and annotations, up to the values of an array or annotation.
+fn element_value_start_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8], depth: usize) -> IResult<&'a [u8], ElementValueStart, E> {
+	let (rest, tag) = context("tag", be_u8)(input)?;
+	match tag {
+		TAG_BYTE | TAG_CHAR | TAG_DOUBLE | TAG_FLOAT | TAG_INT | TAG_LONG | TAG_SHORT | TAG_BOOLEAN | TAG_STRING => {
+			let (rest, const_value_index) = context("const_value_index", be_u16)(rest)?;
+			Ok((rest, ElementValueStart::Value(ElementValueInfo::Const {
+				tag,
+				const_value_index,
+			})))
+		},
+		TAG_ENUM => {
+			let (rest, type_name_index) = context("type_name_index", be_u16)(rest)?;
+			let (rest, const_name_index) = context("const_name_index", be_u16)(rest)?;
+			Ok((rest, ElementValueStart::Value(ElementValueInfo::Enum {
+				type_name_index,
+				const_name_index,
+			})))
+		},
+		TAG_CLASS => {
+			let (rest, class_info_index) = context("class_info_index", be_u16)(rest)?;
+			Ok((rest, ElementValueStart::Value(ElementValueInfo::Class {
+				class_info_index,
+			})))
+		},
+		TAG_ANNOTATION | TAG_ARRAY if depth >= MAX_ELEMENT_VALUE_DEPTH => fail_with(input, ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH)),
+		TAG_ANNOTATION => {
+			let (rest, type_index) = context("annotation_value", context("type_index", be_u16))(rest)?;
+			let (rest, num_element_value_pairs) = context("annotation_value", context("element_value_pairs", be_u16))(rest)?;
+			Ok((rest, ElementValueStart::Nested(PendingValue::Annotation {
+				type_index,
+				num_element_value_pairs,
+				element_value_pairs: Vec::with_capacity((num_element_value_pairs as usize).min(rest.len())),
+				element_name_index: 0,
+			})))
+		},
+		TAG_ARRAY => {
+			let (rest, num_values) = context("values", be_u16)(rest)?;
+			Ok((rest, ElementValueStart::Nested(PendingValue::Array {
+				num_values,
+				values: Vec::with_capacity((num_values as usize).min(rest.len())),
+			})))
+		},
+		tag => fail_with(input, ClassFormatErrorKind::InvalidTag(tag)),
+	}
+}
+
+/// Parses an element value, keeping nested arrays and annotations on a stack instead of recursing into them.
+pub fn element_value_parser<'a, E: ClassParseError<&'a [u8]>>(mut input: &'a [u8]) -> IResult<&'a [u8], ElementValueInfo, E> {
+	let mut pending = Vec::new();
+	loop {
+		let (rest, start) = element_value_start_parser(input, pending.len()).map_err(|e| e.map(|e| locate_pending(&pending, input, "value", e)))?;
+		input = rest;
+		let mut value = match start {
+			ElementValueStart::Value(value) => Some(value),
+			ElementValueStart::Nested(nested) => {
+				pending.push(nested);
+				None
+			},
+		};
+		// Adds the value to its parent, which in turn is complete if that was its last value.
+		loop {
+			if let Some(value) = value.take() {
+				match pending.last_mut() {
+					Some(parent) => parent.push(value),
+					None => return Ok((input, value)),
+				}
+			}
+			match pending.last() {
+				Some(parent) if parent.is_complete() => value = pending.pop().map(PendingValue::into_value),
+				_ => break,
+			}
+		}
+		if let Some(PendingValue::Annotation { .. }) = pending.last() {
+			let (rest, name_index) = be_u16(input).map_err(|e: nom::Err<E>| e.map(|e| locate_pending(&pending, input, "element_name_index", e)))?;
+			input = rest;
+			if let Some(PendingValue::Annotation { element_name_index, .. }) = pending.last_mut() {
+				*element_name_index = name_index;
+			}
+		}
+	}
+}
+
+pub fn element_value_pair_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ElementValuePairInfo, E> {
+	let (input, element_name_index) = context("element_name_index", be_u16)(input)?;
+	let (input, value) = context("value", element_value_parser)(input)?;
+
+	Ok((input, ElementValuePairInfo {
+		element_name_index,
+		value,
+	}))
+}
+
+pub fn annotation_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], AnnotationInfo, E> {
+	let (input, type_index) = context("type_index", be_u16)(input)?;
+	let (input, element_value_pairs) = context("element_value_pairs", indexed_length_count(be_u16, element_value_pair_parser::<'a, E>))(input)?;
+	let num_element_value_pairs = element_value_pairs.len() as U2;
+
+	Ok((input, AnnotationInfo {
+		type_index,
+		num_element_value_pairs,
+		element_value_pairs,
+	}))
+}
+
+pub fn annotations_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], AnnotationsAttribute, E> {
+	let (input, annotations) = context("annotations", indexed_length_count(be_u16, annotation_parser::<'a, E>))(input)?;
+	let num_annotations = annotations.len() as U2;
+
+	Ok((input, AnnotationsAttribute {
+		num_annotations,
+		annotations,
+	}))
+}
+
+pub fn parameter_annotations_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ParameterAnnotationsAttribute, E> {
+	let (input, parameter_annotations) = context("parameter_annotations", indexed_length_count(be_u8, annotations_attribute_parser::<'a, E>))(input)?;
+	let num_parameters = parameter_annotations.len() as U1;
+
+	Ok((input, ParameterAnnotationsAttribute {
+		num_parameters,
+		parameter_annotations,
+	}))
+}
+
+fn local_var_target_entry_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], LocalVarTargetEntry, E> {
+	let (input, start_pc) = context("start_pc", be_u16)(input)?;
+	let (input, length) = context("length", be_u16)(input)?;
+	let (input, index) = context("index", be_u16)(input)?;
+
+	Ok((input, LocalVarTargetEntry {
+		start_pc,
+		length,
+		index,
+	}))
+}
+
+pub fn target_info_parser<'a, E: ClassParseError<&'a [u8]>>(target_type: U1) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], TargetInfo, E> {
+	move |input: &'a [u8]| match target_type {
+		0x00 | 0x01 => {
+			let (input, type_parameter_index) = context("type_parameter_index", be_u8)(input)?;
+			Ok((input, TargetInfo::TypeParameter {
+				type_parameter_index,
+			}))
+		},
+		0x10 => {
+			let (input, supertype_index) = context("supertype_index", be_u16)(input)?;
+			Ok((input, TargetInfo::Supertype {
+				supertype_index,
+			}))
+		},
+		0x11 | 0x12 => {
+			let (input, type_parameter_index) = context("type_parameter_index", be_u8)(input)?;
+			let (input, bound_index) = context("bound_index", be_u8)(input)?;
+			Ok((input, TargetInfo::TypeParameterBound {
+				type_parameter_index,
+				bound_index,
+			}))
+		},
+		0x13..=0x15 => Ok((input, TargetInfo::Empty)),
+		0x16 => {
+			let (input, formal_parameter_index) = context("formal_parameter_index", be_u8)(input)?;
+			Ok((input, TargetInfo::FormalParameter {
+				formal_parameter_index,
+			}))
+		},
+		0x17 => {
+			let (input, throws_type_index) = context("throws_type_index", be_u16)(input)?;
+			Ok((input, TargetInfo::Throws {
+				throws_type_index,
+			}))
+		},
+		0x40 | 0x41 => {
+			let (input, table) = context("table", indexed_length_count(be_u16, local_var_target_entry_parser::<'a, E>))(input)?;
+			Ok((input, TargetInfo::LocalVar {
+				table_length: table.len() as U2,
+				table,
+			}))
+		},
+		0x42 => {
+			let (input, exception_table_index) = context("exception_table_index", be_u16)(input)?;
+			Ok((input, TargetInfo::Catch {
+				exception_table_index,
+			}))
+		},
+		0x43..=0x46 => {
+			let (input, offset) = context("offset", be_u16)(input)?;
+			Ok((input, TargetInfo::Offset {
+				offset,
+			}))
+		},
+		0x47..=0x4B => {
+			let (input, offset) = context("offset", be_u16)(input)?;
+			let (input, type_argument_index) = context("type_argument_index", be_u8)(input)?;
+			Ok((input, TargetInfo::TypeArgument {
+				offset,
+				type_argument_index,
+			}))
+		},
+		target_type => fail_with(input, ClassFormatErrorKind::InvalidTag(target_type)),
+	}
+}
+
+fn type_path_entry_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], TypePathEntry, E> {
+	let (input, type_path_kind) = context("type_path_kind", be_u8)(input)?;
+	let (input, type_argument_index) = context("type_argument_index", be_u8)(input)?;
+
+	Ok((input, TypePathEntry {
+		type_path_kind,
+		type_argument_index,
+	}))
+}
+
+pub fn type_annotation_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], TypeAnnotationInfo, E> {
+	let (input, target_type) = context("target_type", be_u8)(input)?;
+	let (input, target_info) = context("target_info", target_info_parser(target_type))(input)?;
+	let (input, target_path) = context("target_path", indexed_length_count(be_u8, type_path_entry_parser::<'a, E>))(input)?;
+	let path_length = target_path.len() as U1;
+	let (input, annotation) = annotation_parser(input)?;
+
+	Ok((input, TypeAnnotationInfo {
+		target_type,
+		target_info,
+		path_length,
+		target_path,
+		annotation,
+	}))
+}
+
+pub fn type_annotations_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], TypeAnnotationsAttribute, E> {
+	let (input, annotations) = context("annotations", indexed_length_count(be_u16, type_annotation_parser::<'a, E>))(input)?;
+	let num_annotations = annotations.len() as U2;
+
+	Ok((input, TypeAnnotationsAttribute {
+		num_annotations,
+		annotations,
+	}))
+}
+
+pub fn annotation_default_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], AnnotationDefaultAttribute, E> {
+	let (input, default_value) = context("default_value", element_value_parser)(input)?;
+
+	Ok((input, AnnotationDefaultAttribute {
+		default_value,
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::class_format_error::parse_all;
+
+	use super::*;
+
+	#[test]
+	fn type_annotations_round_trip() {
+		let input = [
+			0x00, 0x02,
+			// local variable target with one entry and a type argument path
+			0x40, 0x00, 0x01, 0x00, 0x02, 0x00, 0x05, 0x00, 0x01,
+			0x01, 0x03, 0x00,
+			0x00, 0x07, 0x00, 0x00,
+			// method receiver with a nested annotation element
+			0x15,
+			0x00,
+			0x00, 0x07, 0x00, 0x01, 0x00, 0x08, b'@', 0x00, 0x09, 0x00, 0x00,
+		];
+
+		let attribute = parse_all(&input, type_annotations_attribute_parser).expect("Failed to parse type annotations");
+		assert_eq!(attribute.annotations[0].target_info, TargetInfo::LocalVar {
+			table_length: 1,
+			table: vec![LocalVarTargetEntry { start_pc: 2, length: 5, index: 1 }],
+		});
+		assert_eq!(attribute.annotations[0].target_path, [TypePathEntry { type_path_kind: 3, type_argument_index: 0 }]);
+		assert_eq!(attribute.annotations[1].target_info, TargetInfo::Empty);
+		assert!(matches!(attribute.annotations[1].annotation.element_value_pairs[0].value, ElementValueInfo::Annotation(AnnotationInfo { type_index: 9, .. })));
+
+		let mut written = Vec::new();
+		attribute.write_to(&mut written).unwrap();
+		assert_eq!(written, input);
+	}
+
+	#[test]
+	fn unknown_target_type_is_rejected() {
+		let input = [0x00, 0x01, 0x30];
+
+		let error = parse_all(&input, type_annotations_attribute_parser).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::InvalidTag(0x30));
+		assert_eq!(error.path(), "annotations[0].target_info");
+	}
+
+	#[test]
+	fn deeply_nested_element_values_are_rejected() {
+		// Default value nested in `depth` arrays of one element each.
+		let nested_arrays = |depth: usize| {
+			let mut input = [b'[', 0x00, 0x01].repeat(depth);
+			input.extend([b'I', 0x00, 0x01]);
+			input
+		};
+
+		let attribute = parse_all(&nested_arrays(MAX_ELEMENT_VALUE_DEPTH), annotation_default_attribute_parser).expect("Failed to parse nested arrays");
+		let mut written = Vec::new();
+		attribute.write_to(&mut written).unwrap();
+		assert_eq!(written, nested_arrays(MAX_ELEMENT_VALUE_DEPTH));
+
+		let error = parse_all(&nested_arrays(20000), annotation_default_attribute_parser).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH));
+		assert_eq!(error.offset(), MAX_ELEMENT_VALUE_DEPTH * 3);
+
+		// Default value nested in `depth` annotations with one element each.
+		let nested_annotations = |depth: usize| {
+			let mut input = [b'@', 0x00, 0x01, 0x00, 0x01, 0x00, 0x02].repeat(depth);
+			input.extend([b'I', 0x00, 0x01]);
+			input
+		};
+
+		let attribute = parse_all(&nested_annotations(MAX_ELEMENT_VALUE_DEPTH), annotation_default_attribute_parser).expect("Failed to parse nested annotations");
+		let mut written = Vec::new();
+		attribute.write_to(&mut written).unwrap();
+		assert_eq!(written, nested_annotations(MAX_ELEMENT_VALUE_DEPTH));
+
+		let error = parse_all(&nested_annotations(MAX_ELEMENT_VALUE_DEPTH + 1), annotation_default_attribute_parser).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::NestingTooDeep(MAX_ELEMENT_VALUE_DEPTH));
+	}
+
+	#[test]
+	fn nested_element_value_errors_are_located() {
+		// { @A(x = { 1, @B(y = ?) }) } with an invalid tag as the value of y.
+		let input = [b'[', 0x00, 0x01, b'@', 0x00, 0x01, 0x00, 0x01, 0x00, 0x02, b'[', 0x00, 0x02, b'I', 0x00, 0x03, b'@', 0x00, 0x04, 0x00, 0x01, 0x00, 0x05, b'?'];
+
+		let error = parse_all(&input, annotation_default_attribute_parser).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::InvalidTag(b'?'));
+		assert_eq!(error.offset(), input.len() - 1);
+		assert_eq!(error.path(), "default_value.values[0].annotation_value.element_value_pairs[0].value.values[1].annotation_value.element_value_pairs[0].value");
+
+		let error = parse_all(&input[..input.len() - 2], annotation_default_attribute_parser).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::UnexpectedEof);
+		assert_eq!(error.path(), "default_value.values[0].annotation_value.element_value_pairs[0].value.values[1].annotation_value.element_value_pairs[0].element_name_index");
+	}
+}
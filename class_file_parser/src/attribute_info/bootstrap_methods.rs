@@ -0,0 +1,246 @@
+use std::{collections::HashMap, io::{self, Write}, sync::Arc};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, class_format_error::{indexed_length_count, ClassFormatError, ClassFormatErrorKind, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError, MethodHandleRef}, cp_info::CPInfo, mutf8::JavaString, writer::{write_length_u2, write_u2}, U2};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapMethod {
+	pub bootstrap_method_ref: U2,
+	pub num_bootstrap_arguments: U2,
+	pub bootstrap_arguments: Vec<U2>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BootstrapMethodsAttribute {
+	pub num_bootstrap_methods: U2,
+	pub bootstrap_methods: Vec<BootstrapMethod>,
+}
+
+/// Loadable constant (JVMS 4.4) passed as a static argument to a bootstrap method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+	Integer(i32),
+	Float(f32),
+	Long(i64),
+	Double(f64),
+	String(JavaString),
+	/// Class or array type in internal form.
+	Class(String),
+	MethodType(String),
+	MethodHandle(MethodHandleRef<'static>),
+	/// Dynamically-computed constant, with `descriptor` being a field descriptor.
+	///
+	/// Shared, as the same dynamic constant may be passed to several bootstrap methods.
+	Dynamic(Arc<CallSite>),
+}
+
+/// Resolved `invokedynamic` call site or dynamically-computed constant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallSite {
+	pub bootstrap_method: MethodHandleRef<'static>,
+	pub name: String,
+	/// Method descriptor for call sites, field descriptor for dynamic constants.
+	pub descriptor: String,
+	pub static_arguments: Vec<Constant>,
+}
+
+/// Deepest nesting of dynamic constants passed as static arguments to each other.
+///
+/// Every level recurses, the limit keeps resolving untrusted class files off the end of the stack,
+/// while the class files javac and other compilers write nest dynamic constants a few levels at most.
+pub const MAX_DYNAMIC_CONSTANT_DEPTH: usize = 64;
+
+/// Dynamic constants being resolved, innermost last, and those already resolved.
+#[derive(Default)]
+struct Resolution {
+	pending: Vec<U2>,
+	resolved: HashMap<U2, Constant>,
+}
+
+impl BootstrapMethodsAttribute {
+	/// Resolves the `InvokeDynamic` or `Dynamic` entry at `index`, including its bootstrap method and static arguments.
+	pub fn resolve_call_site(&self, constant_pool: &ConstantPool, index: U2) -> Result<CallSite, ClassFormatError> {
+		self.resolve_dynamic(constant_pool, index, &mut Resolution::default())
+	}
+
+	/// Resolves the loadable constant at `index`, as loaded by `ldc` or passed as a static argument.
+	pub fn resolve_constant(&self, constant_pool: &ConstantPool, index: U2) -> Result<Constant, ClassFormatError> {
+		self.resolve_constant_inner(constant_pool, index, &mut Resolution::default())
+	}
+
+	fn resolve_dynamic(&self, constant_pool: &ConstantPool, index: U2, resolution: &mut Resolution) -> Result<CallSite, ClassFormatError> {
+		let (bootstrap_method_attr_index, name_and_type_index) = match constant_pool.get(index)? {
+			CPInfo::InvokeDynamic(value) => (value.bootstrap_method_attr_index, value.name_and_type_index),
+			CPInfo::Dynamic(value) => (value.bootstrap_method_attr_index, value.name_and_type_index),
+			entry => return Err(ConstantPoolError::UnexpectedEntry {
+				index,
+				expected: "InvokeDynamic or Dynamic",
+				found: entry.name(),
+			}.into()),
+		};
+		let bootstrap_method = self.bootstrap_methods.get(bootstrap_method_attr_index as usize)
+			.ok_or_else(|| ClassFormatError::new(&[], ClassFormatErrorKind::InvalidBootstrapMethodIndex(bootstrap_method_attr_index)))?;
+		let (name, descriptor) = constant_pool.get_name_and_type(name_and_type_index)?;
+
+		if resolution.pending.contains(&index) {
+			return Err(ClassFormatError::new(&[], ClassFormatErrorKind::CyclicDynamicConstant(index)));
+		}
+		if resolution.pending.len() >= MAX_DYNAMIC_CONSTANT_DEPTH {
+			return Err(ClassFormatError::new(&[], ClassFormatErrorKind::NestingTooDeep(MAX_DYNAMIC_CONSTANT_DEPTH)));
+		}
+		resolution.pending.push(index);
+		let static_arguments = bootstrap_method.bootstrap_arguments.iter()
+			.map(|argument| self.resolve_constant_inner(constant_pool, *argument, resolution))
+			.collect::<Result<_, _>>()?;
+		resolution.pending.pop();
+
+		Ok(CallSite {
+			bootstrap_method: constant_pool.get_method_handle(bootstrap_method.bootstrap_method_ref)?.into_owned(),
+			name: name.into_owned(),
+			descriptor: descriptor.into_owned(),
+			static_arguments,
+		})
+	}
+
+	fn resolve_constant_inner(&self, constant_pool: &ConstantPool, index: U2, resolution: &mut Resolution) -> Result<Constant, ClassFormatError> {
+		Ok(match constant_pool.get(index)? {
+			CPInfo::Integer(_) => Constant::Integer(constant_pool.get_integer(index)?),
+			CPInfo::Float(_) => Constant::Float(constant_pool.get_float(index)?),
+			CPInfo::Long(_) => Constant::Long(constant_pool.get_long(index)?),
+			CPInfo::Double(_) => Constant::Double(constant_pool.get_double(index)?),
+			CPInfo::String(_) => Constant::String(constant_pool.get_string(index)?),
+			CPInfo::Class(_) => Constant::Class(constant_pool.get_class_name(index)?.into_owned()),
+			CPInfo::MethodType(value) => Constant::MethodType(constant_pool.get_utf8_str(value.descriptor_index)?.into_owned()),
+			CPInfo::MethodHandle(_) => Constant::MethodHandle(constant_pool.get_method_handle(index)?.into_owned()),
+			CPInfo::Dynamic(_) => match resolution.resolved.get(&index) {
+				Some(constant) => constant.clone(),
+				None => {
+					let constant = Constant::Dynamic(Arc::new(self.resolve_dynamic(constant_pool, index, resolution)?));
+					resolution.resolved.insert(index, constant.clone());
+					constant
+				},
+			},
+			entry => return Err(ConstantPoolError::UnexpectedEntry {
+				index,
+				expected: "loadable constant",
+				found: entry.name(),
+			}.into()),
+		})
+	}
+}
+
+impl AttributeBody for BootstrapMethodsAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.bootstrap_methods.len(), "bootstrap_methods")?;
+		for bootstrap_method in &self.bootstrap_methods {
+			write_u2(writer, bootstrap_method.bootstrap_method_ref)?;
+			write_length_u2(writer, bootstrap_method.bootstrap_arguments.len(), "bootstrap_arguments")?;
+			for argument in &bootstrap_method.bootstrap_arguments {
+				write_u2(writer, *argument)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+pub fn bootstrap_method_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], BootstrapMethod, E> {
+	let (input, bootstrap_method_ref) = context("bootstrap_method_ref", be_u16)(input)?;
+	let (input, bootstrap_arguments) = context("bootstrap_arguments", indexed_length_count(be_u16, be_u16))(input)?;
+	let num_bootstrap_arguments = bootstrap_arguments.len() as U2;
+
+	Ok((input, BootstrapMethod {
+		bootstrap_method_ref,
+		num_bootstrap_arguments,
+		bootstrap_arguments,
+	}))
+}
+
+pub fn bootstrap_methods_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], BootstrapMethodsAttribute, E> {
+	let (input, bootstrap_methods) = context("bootstrap_methods", indexed_length_count(be_u16, bootstrap_method_parser::<'a, E>))(input)?;
+	let num_bootstrap_methods = bootstrap_methods.len() as U2;
+
+	Ok((input, BootstrapMethodsAttribute {
+		num_bootstrap_methods,
+		bootstrap_methods,
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::borrow::Cow;
+
+	use crate::{constant_pool::{constant_pool_parser, ConstantPoolBuilder}, cp_info::method_handle::ReferenceKind};
+
+	use super::*;
+
+	#[test]
+	fn cyclic_dynamic_constants_are_rejected() {
+		let input = [
+			0x00, 0x05,
+			0x01, 0x00, 0x01, b'x',
+			0x01, 0x00, 0x01, b'I',
+			0x0C, 0x00, 0x01, 0x00, 0x02,
+			0x11, 0x00, 0x00, 0x00, 0x03,
+		];
+		let (_, constant_pool) = constant_pool_parser::<ClassFormatError>(&input).expect("Failed to parse constant pool");
+		let bootstrap_methods = BootstrapMethodsAttribute {
+			num_bootstrap_methods: 1,
+			bootstrap_methods: vec![BootstrapMethod {
+				bootstrap_method_ref: 0,
+				num_bootstrap_arguments: 1,
+				bootstrap_arguments: vec![4],
+			}],
+		};
+
+		let error = bootstrap_methods.resolve_call_site(&constant_pool, 4).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::CyclicDynamicConstant(4));
+		let error = BootstrapMethodsAttribute::default().resolve_call_site(&constant_pool, 4).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::InvalidBootstrapMethodIndex(0));
+	}
+
+	/// Chain of `length` dynamic constants, each passing the next one twice to its bootstrap method.
+	fn dynamic_chain(length: usize) -> (ConstantPoolBuilder, BootstrapMethodsAttribute, U2) {
+		let mut constant_pool = ConstantPoolBuilder::new();
+		let bootstrap_method_ref = constant_pool.method_handle(&MethodHandleRef {
+			kind: ReferenceKind::InvokeStatic,
+			owner: Cow::Borrowed("Boot"),
+			name: Cow::Borrowed("bootstrap"),
+			descriptor: Cow::Borrowed("(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/Class;[Ljava/lang/Object;)Ljava/lang/Object;"),
+			is_interface: false,
+		}).unwrap();
+		let mut bootstrap_methods = Vec::new();
+		let mut index = None;
+		for level in 0..length {
+			let bootstrap_arguments = index.map(|index| vec![index, index]).unwrap_or_default();
+			bootstrap_methods.push(BootstrapMethod {
+				bootstrap_method_ref,
+				num_bootstrap_arguments: bootstrap_arguments.len() as U2,
+				bootstrap_arguments,
+			});
+			index = Some(constant_pool.dynamic(level as U2, "x", "Ljava/lang/Object;").unwrap());
+		}
+		let bootstrap_methods = BootstrapMethodsAttribute {
+			num_bootstrap_methods: bootstrap_methods.len() as U2,
+			bootstrap_methods,
+		};
+		(constant_pool, bootstrap_methods, index.unwrap())
+	}
+
+	#[test]
+	fn shared_dynamic_constants_are_resolved_once() {
+		let (constant_pool, bootstrap_methods, index) = dynamic_chain(MAX_DYNAMIC_CONSTANT_DEPTH);
+		let mut constant = bootstrap_methods.resolve_constant(constant_pool.constant_pool(), index).unwrap();
+		for _ in 1..MAX_DYNAMIC_CONSTANT_DEPTH {
+			let Constant::Dynamic(call_site) = constant else {
+				panic!("Expected a dynamic constant, got: {constant:?}");
+			};
+			assert!(matches!(&call_site.static_arguments[..], [Constant::Dynamic(a), Constant::Dynamic(b)] if Arc::ptr_eq(a, b)));
+			constant = call_site.static_arguments[0].clone();
+		}
+
+		let (constant_pool, bootstrap_methods, index) = dynamic_chain(MAX_DYNAMIC_CONSTANT_DEPTH + 1);
+		let error = bootstrap_methods.resolve_constant(constant_pool.constant_pool(), index).unwrap_err();
+		assert_eq!(error.kind(), &ClassFormatErrorKind::NestingTooDeep(MAX_DYNAMIC_CONSTANT_DEPTH));
+	}
+}
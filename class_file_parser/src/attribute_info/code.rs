@@ -0,0 +1,166 @@
+use nom::{bytes::complete::take, error::context, number::complete::{be_u16, be_u32}, IResult};
+
+use std::io::{self, Write};
+
+use crate::{attribute_info::{annotations::{self, TypeAnnotation, Visibility}, attribute_info_parser, debug_info::{line_number_table_attribute_parser, local_variable_table_attribute_parser, LineNumberTableEntry, LocalVariable, LocalVariableTableEntry}, find_attribute, stack_map_table::{stack_map_table_parser, StackMapTable}, write_attributes, AttributeBody, AttributeInfo}, class_format_error::{indexed_length_count, ClassFormatError, ClassParseError}, constant_pool::ConstantPool, instruction::{decode, Decoder}, writer::{write_length_u2, write_length_u4, write_u2}, U1, U2, U4};
+
+#[derive(Debug)]
+pub struct ExceptionTableEntry {
+	pub start_pc: U2,
+	pub end_pc: U2,
+	pub handler_pc: U2,
+	pub catch_type: U2,
+}
+
+#[derive(Debug)]
+pub struct CodeAttribute {
+	pub max_stack: U2,
+	pub max_locals: U2,
+	pub code_length: U4,
+	pub code: Vec<U1>,
+	pub exception_table_length: U2,
+	pub exception_table: Vec<ExceptionTableEntry>,
+	pub attributes_count: U2,
+	pub attributes: Vec<AttributeInfo>,
+}
+
+impl CodeAttribute {
+	pub fn instructions(&self) -> Decoder<'_> {
+		decode(&self.code)
+	}
+
+	pub fn stack_map_table(&self, constant_pool: &ConstantPool) -> Result<Option<StackMapTable>, ClassFormatError> {
+		match find_attribute(&self.attributes, constant_pool, "StackMapTable")? {
+			Some(attribute) => Ok(Some(attribute.parse("StackMapTable", stack_map_table_parser)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Decodes every attribute called `name`, as some attributes may appear more than once.
+	fn parse_all_named<'a, T, F>(&'a self, constant_pool: &ConstantPool, name: &'static str, parser: F) -> Result<Vec<T>, ClassFormatError>
+	where
+		F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, ClassFormatError> + Copy,
+	{
+		let mut values = Vec::new();
+		for attribute in &self.attributes {
+			if attribute.name(constant_pool)? == name {
+				values.push(attribute.parse(name, parser)?);
+			}
+		}
+		Ok(values)
+	}
+
+	/// Entries of all `LineNumberTable` attributes, empty if the code has none.
+	pub fn line_numbers(&self, constant_pool: &ConstantPool) -> Result<Vec<LineNumberTableEntry>, ClassFormatError> {
+		let tables = self.parse_all_named(constant_pool, "LineNumberTable", line_number_table_attribute_parser)?;
+		Ok(tables.into_iter().flat_map(|table| table.line_number_table).collect())
+	}
+
+	/// Source line of the instruction at `pc`.
+	pub fn line_for_pc(&self, constant_pool: &ConstantPool, pc: U2) -> Result<Option<U2>, ClassFormatError> {
+		let line_numbers = self.line_numbers(constant_pool)?;
+		Ok(line_numbers.iter().filter(|entry| entry.start_pc <= pc).max_by_key(|entry| entry.start_pc).map(|entry| entry.line_number))
+	}
+
+	/// Start of every range of instructions belonging to `line`, in ascending order.
+	pub fn pcs_for_line(&self, constant_pool: &ConstantPool, line: U2) -> Result<Vec<U2>, ClassFormatError> {
+		let mut pcs = self.line_numbers(constant_pool)?.into_iter().filter(|entry| entry.line_number == line).map(|entry| entry.start_pc).collect::<Vec<_>>();
+		pcs.sort_unstable();
+		pcs.dedup();
+		Ok(pcs)
+	}
+
+	/// Entries of all `LocalVariableTable` attributes, with signatures taken from the `LocalVariableTypeTable`.
+	pub fn local_variables(&self, constant_pool: &ConstantPool) -> Result<Vec<LocalVariable>, ClassFormatError> {
+		let tables = self.parse_all_named(constant_pool, "LocalVariableTable", local_variable_table_attribute_parser)?;
+		let type_tables = self.parse_all_named(constant_pool, "LocalVariableTypeTable", local_variable_table_attribute_parser)?;
+		let type_entries = type_tables.iter().flat_map(|table| &table.local_variable_table).collect::<Vec<_>>();
+
+		let mut local_variables = Vec::new();
+		for entry in tables.iter().flat_map(|table| &table.local_variable_table) {
+			let same_variable = |type_entry: &&&LocalVariableTableEntry| type_entry.index == entry.index && type_entry.start_pc == entry.start_pc && type_entry.length == entry.length;
+			let signature = match type_entries.iter().find(same_variable) {
+				Some(type_entry) => Some(constant_pool.get_utf8_str(type_entry.descriptor_index)?.into_owned()),
+				None => None,
+			};
+			local_variables.push(LocalVariable {
+				start_pc: entry.start_pc,
+				length: entry.length,
+				name: constant_pool.get_utf8_str(entry.name_index)?.into_owned(),
+				descriptor: constant_pool.get_utf8_str(entry.descriptor_index)?.into_owned(),
+				signature,
+				index: entry.index,
+			});
+		}
+		Ok(local_variables)
+	}
+
+	/// Local variables that have a value at `pc`, ordered by slot.
+	pub fn locals_live_at(&self, constant_pool: &ConstantPool, pc: U2) -> Result<Vec<LocalVariable>, ClassFormatError> {
+		let mut local_variables = self.local_variables(constant_pool)?;
+		local_variables.retain(|local_variable| local_variable.is_live_at(pc));
+		local_variables.sort_by_key(|local_variable| local_variable.index);
+		Ok(local_variables)
+	}
+
+	/// Type annotations on types used within the code, such as local variables, casts and `new` expressions.
+	pub fn type_annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<TypeAnnotation>, ClassFormatError> {
+		annotations::type_annotations(&self.attributes, constant_pool, visibility)
+	}
+}
+
+impl AttributeBody for CodeAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.max_stack)?;
+		write_u2(writer, self.max_locals)?;
+		write_length_u4(writer, self.code.len(), "code")?;
+		writer.write_all(&self.code)?;
+		write_length_u2(writer, self.exception_table.len(), "exception_table")?;
+		for entry in &self.exception_table {
+			write_u2(writer, entry.start_pc)?;
+			write_u2(writer, entry.end_pc)?;
+			write_u2(writer, entry.handler_pc)?;
+			write_u2(writer, entry.catch_type)?;
+		}
+		write_attributes(writer, &self.attributes)
+	}
+}
+
+pub fn exception_table_entry_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ExceptionTableEntry, E> {
+	let (input, start_pc) = context("start_pc", be_u16)(input)?;
+	let (input, end_pc) = context("end_pc", be_u16)(input)?;
+	let (input, handler_pc) = context("handler_pc", be_u16)(input)?;
+	let (input, catch_type) = context("catch_type", be_u16)(input)?;
+
+	Ok((input, ExceptionTableEntry {
+		start_pc,
+		end_pc,
+		handler_pc,
+		catch_type,
+	}))
+}
+
+pub fn code_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], CodeAttribute, E> {
+	let (input, max_stack) = context("max_stack", be_u16)(input)?;
+	let (input, max_locals) = context("max_locals", be_u16)(input)?;
+
+	let (input, code_length) = context("code_length", be_u32)(input)?;
+	let (input, code) = context("code", take(code_length))(input)?;
+	let code = code.to_vec();
+
+	let (input, exception_table) = context("exception_table", indexed_length_count(be_u16, exception_table_entry_parser::<'a, E>))(input)?;
+	let exception_table_length = exception_table.len() as U2;
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_parser::<'a, E>))(input)?;
+	let attributes_count = attributes.len() as U2;
+
+	Ok((input, CodeAttribute {
+		max_stack,
+		max_locals,
+		code_length,
+		code,
+		exception_table_length,
+		exception_table,
+		attributes_count,
+		attributes,
+	}))
+}
@@ -0,0 +1,25 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, class_format_error::ClassParseError, writer::write_u2, U2};
+
+/// `ConstantValue` of a static field, referring to an `Integer`, `Float`, `Long`, `Double` or `String` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantValueAttribute {
+	pub constantvalue_index: U2,
+}
+
+impl AttributeBody for ConstantValueAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.constantvalue_index)
+	}
+}
+
+pub fn constant_value_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ConstantValueAttribute, E> {
+	let (input, constantvalue_index) = context("constantvalue_index", be_u16)(input)?;
+
+	Ok((input, ConstantValueAttribute {
+		constantvalue_index,
+	}))
+}
@@ -0,0 +1,163 @@
+use std::io::{self, Write};
+
+use nom::{combinator::rest, error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, class_format_error::{indexed_length_count, ClassParseError}, writer::{write_length_u2, write_u2}, U1, U2};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFileAttribute {
+	pub sourcefile_index: U2,
+}
+
+impl AttributeBody for SourceFileAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.sourcefile_index)
+	}
+}
+
+/// `SourceDebugExtension`, holding modified UTF-8 without a length prefix, e.g. an SMAP (JSR 45).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDebugExtensionAttribute {
+	pub debug_extension: Vec<U1>,
+}
+
+impl AttributeBody for SourceDebugExtensionAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		writer.write_all(&self.debug_extension)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineNumberTableEntry {
+	pub start_pc: U2,
+	pub line_number: U2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineNumberTableAttribute {
+	pub line_number_table_length: U2,
+	pub line_number_table: Vec<LineNumberTableEntry>,
+}
+
+impl AttributeBody for LineNumberTableAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.line_number_table.len(), "line_number_table")?;
+		for entry in &self.line_number_table {
+			write_u2(writer, entry.start_pc)?;
+			write_u2(writer, entry.line_number)?;
+		}
+		Ok(())
+	}
+}
+
+/// Entry of a `LocalVariableTable`, or of a `LocalVariableTypeTable` with `descriptor_index` referring to a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalVariableTableEntry {
+	pub start_pc: U2,
+	pub length: U2,
+	pub name_index: U2,
+	pub descriptor_index: U2,
+	pub index: U2,
+}
+
+/// `LocalVariableTable` and `LocalVariableTypeTable`, which share the same layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalVariableTableAttribute {
+	pub local_variable_table_length: U2,
+	pub local_variable_table: Vec<LocalVariableTableEntry>,
+}
+
+impl AttributeBody for LocalVariableTableAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.local_variable_table.len(), "local_variable_table")?;
+		for entry in &self.local_variable_table {
+			write_u2(writer, entry.start_pc)?;
+			write_u2(writer, entry.length)?;
+			write_u2(writer, entry.name_index)?;
+			write_u2(writer, entry.descriptor_index)?;
+			write_u2(writer, entry.index)?;
+		}
+		Ok(())
+	}
+}
+
+/// Local variable with its name and types resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalVariable {
+	pub start_pc: U2,
+	pub length: U2,
+	pub name: String,
+	pub descriptor: String,
+	/// Generic signature from the `LocalVariableTypeTable`, if the variable has a generic type.
+	pub signature: Option<String>,
+	pub index: U2,
+}
+
+impl LocalVariable {
+	/// Whether the variable has a value at `pc`.
+	pub fn is_live_at(&self, pc: U2) -> bool {
+		(self.start_pc as u32..self.start_pc as u32 + self.length as u32).contains(&(pc as u32))
+	}
+}
+
+pub fn source_file_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], SourceFileAttribute, E> {
+	let (input, sourcefile_index) = context("sourcefile_index", be_u16)(input)?;
+
+	Ok((input, SourceFileAttribute {
+		sourcefile_index,
+	}))
+}
+
+pub fn source_debug_extension_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], SourceDebugExtensionAttribute, E> {
+	let (input, debug_extension) = context("debug_extension", rest)(input)?;
+
+	Ok((input, SourceDebugExtensionAttribute {
+		debug_extension: debug_extension.to_vec(),
+	}))
+}
+
+fn line_number_table_entry_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], LineNumberTableEntry, E> {
+	let (input, start_pc) = context("start_pc", be_u16)(input)?;
+	let (input, line_number) = context("line_number", be_u16)(input)?;
+
+	Ok((input, LineNumberTableEntry {
+		start_pc,
+		line_number,
+	}))
+}
+
+pub fn line_number_table_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], LineNumberTableAttribute, E> {
+	let (input, line_number_table) = context("line_number_table", indexed_length_count(be_u16, line_number_table_entry_parser::<'a, E>))(input)?;
+	let line_number_table_length = line_number_table.len() as U2;
+
+	Ok((input, LineNumberTableAttribute {
+		line_number_table_length,
+		line_number_table,
+	}))
+}
+
+fn local_variable_table_entry_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], LocalVariableTableEntry, E> {
+	let (input, start_pc) = context("start_pc", be_u16)(input)?;
+	let (input, length) = context("length", be_u16)(input)?;
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
+	let (input, index) = context("index", be_u16)(input)?;
+
+	Ok((input, LocalVariableTableEntry {
+		start_pc,
+		length,
+		name_index,
+		descriptor_index,
+		index,
+	}))
+}
+
+pub fn local_variable_table_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], LocalVariableTableAttribute, E> {
+	let (input, local_variable_table) = context("local_variable_table", indexed_length_count(be_u16, local_variable_table_entry_parser::<'a, E>))(input)?;
+	let local_variable_table_length = local_variable_table.len() as U2;
+
+	Ok((input, LocalVariableTableAttribute {
+		local_variable_table_length,
+		local_variable_table,
+	}))
+}
@@ -0,0 +1,38 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, class_format_error::{indexed_length_count, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, writer::{write_length_u2, write_u2}, U2};
+
+/// `Exceptions`, listing the checked exceptions a method declares with `throws`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExceptionsAttribute {
+	pub number_of_exceptions: U2,
+	pub exception_index_table: Vec<U2>,
+}
+
+impl ExceptionsAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Vec<String>, ConstantPoolError> {
+		self.exception_index_table.iter().map(|index| Ok(constant_pool.get_class_name(*index)?.into_owned())).collect()
+	}
+}
+
+impl AttributeBody for ExceptionsAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.exception_index_table.len(), "exception_index_table")?;
+		for index in &self.exception_index_table {
+			write_u2(writer, *index)?;
+		}
+		Ok(())
+	}
+}
+
+pub fn exceptions_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ExceptionsAttribute, E> {
+	let (input, exception_index_table) = context("exception_index_table", indexed_length_count(be_u16, be_u16))(input)?;
+	let number_of_exceptions = exception_index_table.len() as U2;
+
+	Ok((input, ExceptionsAttribute {
+		number_of_exceptions,
+		exception_index_table,
+	}))
+}
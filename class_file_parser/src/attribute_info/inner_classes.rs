@@ -0,0 +1,167 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, access_flags::InnerClassAccess, class_format_error::{indexed_length_count, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, writer::{write_length_u2, write_u2}, U2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InnerClassInfo {
+	pub inner_class_info_index: U2,
+	/// 0 for top-level, local and anonymous classes.
+	pub outer_class_info_index: U2,
+	/// 0 for anonymous classes.
+	pub inner_name_index: U2,
+	pub inner_class_access_flags: U2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerClassesAttribute {
+	pub number_of_classes: U2,
+	pub classes: Vec<InnerClassInfo>,
+}
+
+/// Entry of the `InnerClasses` attribute with its names resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerClass {
+	pub inner_class: String,
+	/// Declaring class, `None` for local and anonymous classes.
+	pub outer_class: Option<String>,
+	/// Simple name as written in the source, `None` for anonymous classes.
+	pub inner_name: Option<String>,
+	pub access_flags: InnerClassAccess,
+}
+
+impl InnerClassesAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Vec<InnerClass>, ConstantPoolError> {
+		self.classes.iter().map(|class| Ok(InnerClass {
+			inner_class: constant_pool.get_class_name(class.inner_class_info_index)?.into_owned(),
+			outer_class: match class.outer_class_info_index {
+				0 => None,
+				index => Some(constant_pool.get_class_name(index)?.into_owned()),
+			},
+			inner_name: match class.inner_name_index {
+				0 => None,
+				index => Some(constant_pool.get_utf8_str(index)?.into_owned()),
+			},
+			access_flags: InnerClassAccess(class.inner_class_access_flags),
+		})).collect()
+	}
+}
+
+impl AttributeBody for InnerClassesAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.classes.len(), "classes")?;
+		for class in &self.classes {
+			write_u2(writer, class.inner_class_info_index)?;
+			write_u2(writer, class.outer_class_info_index)?;
+			write_u2(writer, class.inner_name_index)?;
+			write_u2(writer, class.inner_class_access_flags)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnclosingMethodAttribute {
+	pub class_index: U2,
+	/// 0 if the class is not enclosed by a method or constructor, e.g. in an initializer.
+	pub method_index: U2,
+}
+
+/// Innermost class and method enclosing a local or anonymous class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnclosingMethod {
+	pub class: String,
+	/// Name and descriptor of the method.
+	pub method: Option<(String, String)>,
+}
+
+impl EnclosingMethodAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<EnclosingMethod, ConstantPoolError> {
+		let method = match self.method_index {
+			0 => None,
+			index => {
+				let (name, descriptor) = constant_pool.get_name_and_type(index)?;
+				Some((name.into_owned(), descriptor.into_owned()))
+			},
+		};
+
+		Ok(EnclosingMethod {
+			class: constant_pool.get_class_name(self.class_index)?.into_owned(),
+			method,
+		})
+	}
+}
+
+impl AttributeBody for EnclosingMethodAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.class_index)?;
+		write_u2(writer, self.method_index)
+	}
+}
+
+fn inner_class_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], InnerClassInfo, E> {
+	let (input, inner_class_info_index) = context("inner_class_info_index", be_u16)(input)?;
+	let (input, outer_class_info_index) = context("outer_class_info_index", be_u16)(input)?;
+	let (input, inner_name_index) = context("inner_name_index", be_u16)(input)?;
+	let (input, inner_class_access_flags) = context("inner_class_access_flags", be_u16)(input)?;
+
+	Ok((input, InnerClassInfo {
+		inner_class_info_index,
+		outer_class_info_index,
+		inner_name_index,
+		inner_class_access_flags,
+	}))
+}
+
+pub fn inner_classes_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], InnerClassesAttribute, E> {
+	let (input, classes) = context("classes", indexed_length_count(be_u16, inner_class_info_parser::<'a, E>))(input)?;
+	let number_of_classes = classes.len() as U2;
+
+	Ok((input, InnerClassesAttribute {
+		number_of_classes,
+		classes,
+	}))
+}
+
+pub fn enclosing_method_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], EnclosingMethodAttribute, E> {
+	let (input, class_index) = context("class_index", be_u16)(input)?;
+	let (input, method_index) = context("method_index", be_u16)(input)?;
+
+	Ok((input, EnclosingMethodAttribute {
+		class_index,
+		method_index,
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{class_format_error::ClassFormatError, constant_pool::constant_pool_parser};
+
+	use super::*;
+
+	#[test]
+	fn resolve_enclosing_method() {
+		let input = [
+			0x00, 0x06,
+			0x01, 0x00, 0x04, b'M', b'a', b'i', b'n',
+			0x07, 0x00, 0x01,
+			0x01, 0x00, 0x03, b'r', b'u', b'n',
+			0x01, 0x00, 0x03, b'(', b')', b'V',
+			0x0C, 0x00, 0x03, 0x00, 0x04,
+		];
+		let (_, constant_pool) = constant_pool_parser::<ClassFormatError>(&input).expect("Failed to parse constant pool");
+
+		let (_, attribute) = enclosing_method_attribute_parser::<ClassFormatError>(&[0x00, 0x02, 0x00, 0x05]).expect("Failed to parse attribute");
+		assert_eq!(attribute.resolve(&constant_pool).unwrap(), EnclosingMethod {
+			class: "Main".to_string(),
+			method: Some(("run".to_string(), "()V".to_string())),
+		});
+		let mut written = Vec::new();
+		attribute.write_to(&mut written).unwrap();
+		assert_eq!(written, [0x00, 0x02, 0x00, 0x05]);
+
+		let attribute = EnclosingMethodAttribute { class_index: 2, method_index: 0 };
+		assert_eq!(attribute.resolve(&constant_pool).unwrap().method, None);
+	}
+}
@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::{be_u16, be_u8}, IResult};
+
+use crate::{attribute_info::AttributeBody, access_flags::ParameterAccess, class_format_error::{indexed_length_count, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, writer::{write_length_u1, write_u2}, U1, U2};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodParameterInfo {
+	/// Zero for a parameter without a name.
+	pub name_index: U2,
+	pub access_flags: U2,
+}
+
+/// `MethodParameters`, naming the formal parameters of a method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodParametersAttribute {
+	pub parameters_count: U1,
+	pub parameters: Vec<MethodParameterInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodParameter {
+	pub name: Option<String>,
+	pub access_flags: ParameterAccess,
+}
+
+impl MethodParametersAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Vec<MethodParameter>, ConstantPoolError> {
+		self.parameters.iter().map(|parameter| Ok(MethodParameter {
+			name: match parameter.name_index {
+				0 => None,
+				index => Some(constant_pool.get_utf8_str(index)?.into_owned()),
+			},
+			access_flags: ParameterAccess(parameter.access_flags),
+		})).collect()
+	}
+}
+
+impl AttributeBody for MethodParametersAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u1(writer, self.parameters.len(), "parameters")?;
+		for parameter in &self.parameters {
+			write_u2(writer, parameter.name_index)?;
+			write_u2(writer, parameter.access_flags)?;
+		}
+		Ok(())
+	}
+}
+
+pub fn method_parameter_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], MethodParameterInfo, E> {
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+
+	Ok((input, MethodParameterInfo {
+		name_index,
+		access_flags,
+	}))
+}
+
+pub fn method_parameters_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], MethodParametersAttribute, E> {
+	let (input, parameters) = context("parameters", indexed_length_count(be_u8, method_parameter_info_parser::<'a, E>))(input)?;
+	let parameters_count = parameters.len() as U1;
+
+	Ok((input, MethodParametersAttribute {
+		parameters_count,
+		parameters,
+	}))
+}
@@ -0,0 +1,311 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, access_flags::{ModuleFlags, RequiresFlags}, class_format_error::{indexed_length_count, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, writer::{write_length_u2, write_u2}, U2};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiresInfo {
+	pub requires_index: U2,
+	pub requires_flags: U2,
+	/// 0 if no version was recorded.
+	pub requires_version_index: U2,
+}
+
+/// `exports` and `opens` entries, which share the same layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageTargetsInfo {
+	pub package_index: U2,
+	pub flags: U2,
+	pub to_count: U2,
+	pub to_index: Vec<U2>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvidesInfo {
+	pub provides_index: U2,
+	pub provides_with_count: U2,
+	pub provides_with_index: Vec<U2>,
+}
+
+/// `Module` attribute of a `module-info.class` (JVMS 4.7.25).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleAttribute {
+	pub module_name_index: U2,
+	pub module_flags: U2,
+	pub module_version_index: U2,
+	pub requires_count: U2,
+	pub requires: Vec<RequiresInfo>,
+	pub exports_count: U2,
+	pub exports: Vec<PackageTargetsInfo>,
+	pub opens_count: U2,
+	pub opens: Vec<PackageTargetsInfo>,
+	pub uses_count: U2,
+	pub uses_index: Vec<U2>,
+	pub provides_count: U2,
+	pub provides: Vec<ProvidesInfo>,
+}
+
+fn write_indices<W: Write>(writer: &mut W, indices: &[U2], name: &str) -> io::Result<()> {
+	write_length_u2(writer, indices.len(), name)?;
+	for index in indices {
+		write_u2(writer, *index)?;
+	}
+	Ok(())
+}
+
+fn write_package_targets<W: Write>(writer: &mut W, entries: &[PackageTargetsInfo], name: &str) -> io::Result<()> {
+	write_length_u2(writer, entries.len(), name)?;
+	for entry in entries {
+		write_u2(writer, entry.package_index)?;
+		write_u2(writer, entry.flags)?;
+		write_indices(writer, &entry.to_index, "to_index")?;
+	}
+	Ok(())
+}
+
+fn optional_utf8(constant_pool: &ConstantPool, index: U2) -> Result<Option<String>, ConstantPoolError> {
+	match index {
+		0 => Ok(None),
+		index => Ok(Some(constant_pool.get_utf8_str(index)?.into_owned())),
+	}
+}
+
+fn resolve_package_targets(constant_pool: &ConstantPool, entries: &[PackageTargetsInfo]) -> Result<Vec<PackageTargets>, ConstantPoolError> {
+	entries.iter().map(|entry| Ok(PackageTargets {
+		package: constant_pool.get_package_name(entry.package_index)?.into_owned(),
+		flags: ModuleFlags(entry.flags),
+		to: entry.to_index.iter().map(|index| Ok(constant_pool.get_module_name(*index)?.into_owned())).collect::<Result<_, ConstantPoolError>>()?,
+	})).collect()
+}
+
+impl ModuleAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<ModuleDescriptor, ConstantPoolError> {
+		Ok(ModuleDescriptor {
+			name: constant_pool.get_module_name(self.module_name_index)?.into_owned(),
+			flags: ModuleFlags(self.module_flags),
+			version: optional_utf8(constant_pool, self.module_version_index)?,
+			requires: self.requires.iter().map(|requires| Ok(Requires {
+				module: constant_pool.get_module_name(requires.requires_index)?.into_owned(),
+				flags: RequiresFlags(requires.requires_flags),
+				version: optional_utf8(constant_pool, requires.requires_version_index)?,
+			})).collect::<Result<_, ConstantPoolError>>()?,
+			exports: resolve_package_targets(constant_pool, &self.exports)?,
+			opens: resolve_package_targets(constant_pool, &self.opens)?,
+			uses: self.uses_index.iter().map(|index| Ok(constant_pool.get_class_name(*index)?.into_owned())).collect::<Result<_, ConstantPoolError>>()?,
+			provides: self.provides.iter().map(|provides| Ok(Provides {
+				service: constant_pool.get_class_name(provides.provides_index)?.into_owned(),
+				with: provides.provides_with_index.iter().map(|index| Ok(constant_pool.get_class_name(*index)?.into_owned())).collect::<Result<_, ConstantPoolError>>()?,
+			})).collect::<Result<_, ConstantPoolError>>()?,
+		})
+	}
+}
+
+impl AttributeBody for ModuleAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.module_name_index)?;
+		write_u2(writer, self.module_flags)?;
+		write_u2(writer, self.module_version_index)?;
+		write_length_u2(writer, self.requires.len(), "requires")?;
+		for requires in &self.requires {
+			write_u2(writer, requires.requires_index)?;
+			write_u2(writer, requires.requires_flags)?;
+			write_u2(writer, requires.requires_version_index)?;
+		}
+		write_package_targets(writer, &self.exports, "exports")?;
+		write_package_targets(writer, &self.opens, "opens")?;
+		write_indices(writer, &self.uses_index, "uses_index")?;
+		write_length_u2(writer, self.provides.len(), "provides")?;
+		for provides in &self.provides {
+			write_u2(writer, provides.provides_index)?;
+			write_indices(writer, &provides.provides_with_index, "provides_with_index")?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requires {
+	pub module: String,
+	pub flags: RequiresFlags,
+	pub version: Option<String>,
+}
+
+/// Package exported or opened to the modules in `to`, or to every module if `to` is empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageTargets {
+	/// Package name in internal form, e.g. `java/lang`.
+	pub package: String,
+	pub flags: ModuleFlags,
+	pub to: Vec<String>,
+}
+
+impl PackageTargets {
+	pub fn is_qualified(&self) -> bool {
+		!self.to.is_empty()
+	}
+
+	pub fn includes(&self, module: &str) -> bool {
+		self.to.is_empty() || self.to.iter().any(|target| target == module)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provides {
+	pub service: String,
+	pub with: Vec<String>,
+}
+
+/// `Module` attribute with all constant pool references resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleDescriptor {
+	pub name: String,
+	pub flags: ModuleFlags,
+	pub version: Option<String>,
+	pub requires: Vec<Requires>,
+	pub exports: Vec<PackageTargets>,
+	pub opens: Vec<PackageTargets>,
+	/// Service interfaces in internal form.
+	pub uses: Vec<String>,
+	pub provides: Vec<Provides>,
+}
+
+impl ModuleDescriptor {
+	pub fn is_open(&self) -> bool {
+		self.flags.contains(ModuleFlags::OPEN)
+	}
+
+	pub fn requires(&self, module: &str) -> Option<&Requires> {
+		self.requires.iter().find(|requires| requires.module == module)
+	}
+
+	/// Whether public types of `package` are accessible to code in `module`.
+	pub fn exports_to(&self, package: &str, module: &str) -> bool {
+		self.exports.iter().any(|exports| exports.package == package && exports.includes(module))
+	}
+
+	/// Whether all of `package` is accessible to deep reflection from code in `module`.
+	pub fn opens_to(&self, package: &str, module: &str) -> bool {
+		self.is_open() || self.opens.iter().any(|opens| opens.package == package && opens.includes(module))
+	}
+}
+
+/// `ModulePackages`, listing every package of the module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModulePackagesAttribute {
+	pub package_count: U2,
+	pub package_index: Vec<U2>,
+}
+
+impl ModulePackagesAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Vec<String>, ConstantPoolError> {
+		self.package_index.iter().map(|index| Ok(constant_pool.get_package_name(*index)?.into_owned())).collect()
+	}
+}
+
+impl AttributeBody for ModulePackagesAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_indices(writer, &self.package_index, "package_index")
+	}
+}
+
+/// `ModuleMainClass`, referring to the `Class` entry of the main class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleMainClassAttribute {
+	pub main_class_index: U2,
+}
+
+impl AttributeBody for ModuleMainClassAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.main_class_index)
+	}
+}
+
+fn requires_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], RequiresInfo, E> {
+	let (input, requires_index) = context("requires_index", be_u16)(input)?;
+	let (input, requires_flags) = context("requires_flags", be_u16)(input)?;
+	let (input, requires_version_index) = context("requires_version_index", be_u16)(input)?;
+
+	Ok((input, RequiresInfo {
+		requires_index,
+		requires_flags,
+		requires_version_index,
+	}))
+}
+
+fn package_targets_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], PackageTargetsInfo, E> {
+	let (input, package_index) = context("package_index", be_u16)(input)?;
+	let (input, flags) = context("flags", be_u16)(input)?;
+	let (input, to_index) = context("to_index", indexed_length_count(be_u16, be_u16))(input)?;
+	let to_count = to_index.len() as U2;
+
+	Ok((input, PackageTargetsInfo {
+		package_index,
+		flags,
+		to_count,
+		to_index,
+	}))
+}
+
+fn provides_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ProvidesInfo, E> {
+	let (input, provides_index) = context("provides_index", be_u16)(input)?;
+	let (input, provides_with_index) = context("provides_with_index", indexed_length_count(be_u16, be_u16))(input)?;
+	let provides_with_count = provides_with_index.len() as U2;
+
+	Ok((input, ProvidesInfo {
+		provides_index,
+		provides_with_count,
+		provides_with_index,
+	}))
+}
+
+pub fn module_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ModuleAttribute, E> {
+	let (input, module_name_index) = context("module_name_index", be_u16)(input)?;
+	let (input, module_flags) = context("module_flags", be_u16)(input)?;
+	let (input, module_version_index) = context("module_version_index", be_u16)(input)?;
+
+	let (input, requires) = context("requires", indexed_length_count(be_u16, requires_parser::<'a, E>))(input)?;
+	let requires_count = requires.len() as U2;
+	let (input, exports) = context("exports", indexed_length_count(be_u16, package_targets_parser::<'a, E>))(input)?;
+	let exports_count = exports.len() as U2;
+	let (input, opens) = context("opens", indexed_length_count(be_u16, package_targets_parser::<'a, E>))(input)?;
+	let opens_count = opens.len() as U2;
+	let (input, uses_index) = context("uses_index", indexed_length_count(be_u16, be_u16))(input)?;
+	let uses_count = uses_index.len() as U2;
+	let (input, provides) = context("provides", indexed_length_count(be_u16, provides_parser::<'a, E>))(input)?;
+	let provides_count = provides.len() as U2;
+
+	Ok((input, ModuleAttribute {
+		module_name_index,
+		module_flags,
+		module_version_index,
+		requires_count,
+		requires,
+		exports_count,
+		exports,
+		opens_count,
+		opens,
+		uses_count,
+		uses_index,
+		provides_count,
+		provides,
+	}))
+}
+
+pub fn module_packages_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ModulePackagesAttribute, E> {
+	let (input, package_index) = context("package_index", indexed_length_count(be_u16, be_u16))(input)?;
+	let package_count = package_index.len() as U2;
+
+	Ok((input, ModulePackagesAttribute {
+		package_count,
+		package_index,
+	}))
+}
+
+pub fn module_main_class_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ModuleMainClassAttribute, E> {
+	let (input, main_class_index) = context("main_class_index", be_u16)(input)?;
+
+	Ok((input, ModuleMainClassAttribute {
+		main_class_index,
+	}))
+}
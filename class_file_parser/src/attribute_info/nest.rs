@@ -0,0 +1,58 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::AttributeBody, class_format_error::{indexed_length_count, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, writer::{write_length_u2, write_u2}, U2};
+
+/// `NestHost`, referring to the `Class` entry of the nest host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestHostAttribute {
+	pub host_class_index: U2,
+}
+
+impl AttributeBody for NestHostAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.host_class_index)
+	}
+}
+
+/// `NestMembers` and `PermittedSubclasses`, which share the same layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassListAttribute {
+	pub number_of_classes: U2,
+	pub classes: Vec<U2>,
+}
+
+impl ClassListAttribute {
+	pub fn resolve(&self, constant_pool: &ConstantPool) -> Result<Vec<String>, ConstantPoolError> {
+		self.classes.iter().map(|index| Ok(constant_pool.get_class_name(*index)?.into_owned())).collect()
+	}
+}
+
+impl AttributeBody for ClassListAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.classes.len(), "classes")?;
+		for class in &self.classes {
+			write_u2(writer, *class)?;
+		}
+		Ok(())
+	}
+}
+
+pub fn nest_host_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], NestHostAttribute, E> {
+	let (input, host_class_index) = context("host_class_index", be_u16)(input)?;
+
+	Ok((input, NestHostAttribute {
+		host_class_index,
+	}))
+}
+
+pub fn class_list_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ClassListAttribute, E> {
+	let (input, classes) = context("classes", indexed_length_count(be_u16, be_u16))(input)?;
+	let number_of_classes = classes.len() as U2;
+
+	Ok((input, ClassListAttribute {
+		number_of_classes,
+		classes,
+	}))
+}
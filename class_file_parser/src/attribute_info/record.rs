@@ -0,0 +1,86 @@
+use std::{borrow::Cow, io::{self, Write}};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::{annotations::{self, Annotation, TypeAnnotation, Visibility}, attribute_info_parser, signature, write_attributes, AttributeBody, AttributeInfo}, class_format_error::{indexed_length_count, ClassFormatError, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError}, descriptor::{parse_field_descriptor, FieldType}, signature::{parse_field_signature, ReferenceTypeSignature}, writer::{write_length_u2, write_u2}, U2};
+
+/// Component of a record class, carrying its own `Signature` and annotation attributes.
+#[derive(Debug)]
+pub struct RecordComponentInfo {
+	pub name_index: U2,
+	pub descriptor_index: U2,
+	pub attributes_count: U2,
+	pub attributes: Vec<AttributeInfo>,
+}
+
+impl RecordComponentInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.name_index)?;
+		write_u2(writer, self.descriptor_index)?;
+		write_attributes(writer, &self.attributes)
+	}
+
+	pub fn name<'c>(&self, constant_pool: &'c ConstantPool) -> Result<Cow<'c, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.name_index)
+	}
+
+	pub fn descriptor(&self, constant_pool: &ConstantPool) -> Result<FieldType, ClassFormatError> {
+		Ok(parse_field_descriptor(&constant_pool.get_utf8_str(self.descriptor_index)?)?)
+	}
+
+	/// Generic signature, which only components of a generic type have.
+	pub fn signature(&self, constant_pool: &ConstantPool) -> Result<Option<ReferenceTypeSignature>, ClassFormatError> {
+		match signature::signature(&self.attributes, constant_pool)? {
+			Some(signature) => Ok(Some(parse_field_signature(&signature)?)),
+			None => Ok(None),
+		}
+	}
+
+	pub fn annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<Annotation>, ClassFormatError> {
+		annotations::annotations(&self.attributes, constant_pool, visibility)
+	}
+
+	pub fn type_annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<TypeAnnotation>, ClassFormatError> {
+		annotations::type_annotations(&self.attributes, constant_pool, visibility)
+	}
+}
+
+#[derive(Debug)]
+pub struct RecordAttribute {
+	pub components_count: U2,
+	pub components: Vec<RecordComponentInfo>,
+}
+
+impl AttributeBody for RecordAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.components.len(), "components")?;
+		for component in &self.components {
+			component.write_to(writer)?;
+		}
+		Ok(())
+	}
+}
+
+pub fn record_component_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], RecordComponentInfo, E> {
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_parser::<'a, E>))(input)?;
+	let attributes_count = attributes.len() as U2;
+
+	Ok((input, RecordComponentInfo {
+		name_index,
+		descriptor_index,
+		attributes_count,
+		attributes,
+	}))
+}
+
+pub fn record_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], RecordAttribute, E> {
+	let (input, components) = context("components", indexed_length_count(be_u16, record_component_info_parser::<'a, E>))(input)?;
+	let components_count = components.len() as U2;
+
+	Ok((input, RecordAttribute {
+		components_count,
+		components,
+	}))
+}
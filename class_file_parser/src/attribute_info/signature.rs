@@ -0,0 +1,35 @@
+use std::io::{self, Write};
+
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{attribute_info::{find_attribute, AttributeBody, AttributeInfo}, class_format_error::{ClassFormatError, ClassParseError}, constant_pool::ConstantPool, writer::write_u2, U2};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureAttribute {
+	pub signature_index: U2,
+}
+
+impl AttributeBody for SignatureAttribute {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.signature_index)
+	}
+}
+
+/// Unparsed generic signature from the `Signature` attribute among `attributes`.
+pub fn signature(attributes: &[AttributeInfo], constant_pool: &ConstantPool) -> Result<Option<String>, ClassFormatError> {
+	match find_attribute(attributes, constant_pool, "Signature")? {
+		Some(attribute) => {
+			let signature = attribute.parse("Signature", signature_attribute_parser)?;
+			Ok(Some(constant_pool.get_utf8_str(signature.signature_index)?.into_owned()))
+		},
+		None => Ok(None),
+	}
+}
+
+pub fn signature_attribute_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], SignatureAttribute, E> {
+	let (input, signature_index) = context("signature_index", be_u16)(input)?;
+
+	Ok((input, SignatureAttribute {
+		signature_index,
+	}))
+}
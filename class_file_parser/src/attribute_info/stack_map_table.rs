@@ -0,0 +1,463 @@
+use std::{fmt::Display, io::{self, Write}};
+
+use nom::{error::context, number::complete::{be_u16, be_u8}, IResult};
+
+use crate::{attribute_info::AttributeBody, class_format_error::{fail_with, indexed, indexed_length_count, ClassFormatError, ClassFormatErrorKind, ClassParseError}, constant_pool::ConstantPool, descriptor::{BaseType, FieldType, MethodDescriptor}, writer::{write_length_u2, write_u1, write_u2}, U1, U2};
+
+const ITEM_TOP: U1 = 0;
+const ITEM_INTEGER: U1 = 1;
+const ITEM_FLOAT: U1 = 2;
+const ITEM_DOUBLE: U1 = 3;
+const ITEM_LONG: U1 = 4;
+const ITEM_NULL: U1 = 5;
+const ITEM_UNINITIALIZED_THIS: U1 = 6;
+const ITEM_OBJECT: U1 = 7;
+const ITEM_UNINITIALIZED: U1 = 8;
+
+const SAME_LOCALS_1_STACK_ITEM_EXTENDED: U1 = 247;
+const SAME_FRAME_EXTENDED: U1 = 251;
+const FULL_FRAME: U1 = 255;
+
+/// `verification_type_info` as stored in the class file (JVMS 4.7.4).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
+	Top,
+	Integer,
+	Float,
+	Long,
+	Double,
+	Null,
+	UninitializedThis,
+	/// Constant pool index of a `Class` entry.
+	Object(U2),
+	/// Offset of the `new` instruction that created the object.
+	Uninitialized(U2),
+}
+
+impl VerificationTypeInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		match self {
+			Self::Top => write_u1(writer, ITEM_TOP),
+			Self::Integer => write_u1(writer, ITEM_INTEGER),
+			Self::Float => write_u1(writer, ITEM_FLOAT),
+			Self::Long => write_u1(writer, ITEM_LONG),
+			Self::Double => write_u1(writer, ITEM_DOUBLE),
+			Self::Null => write_u1(writer, ITEM_NULL),
+			Self::UninitializedThis => write_u1(writer, ITEM_UNINITIALIZED_THIS),
+			Self::Object(cpool_index) => {
+				write_u1(writer, ITEM_OBJECT)?;
+				write_u2(writer, *cpool_index)
+			},
+			Self::Uninitialized(offset) => {
+				write_u1(writer, ITEM_UNINITIALIZED)?;
+				write_u2(writer, *offset)
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackMapFrame {
+	SameFrame {
+		offset_delta: U2,
+	},
+	SameLocals1StackItemFrame {
+		offset_delta: U2,
+		stack: VerificationTypeInfo,
+	},
+	SameLocals1StackItemFrameExtended {
+		offset_delta: U2,
+		stack: VerificationTypeInfo,
+	},
+	ChopFrame {
+		chopped: U1,
+		offset_delta: U2,
+	},
+	SameFrameExtended {
+		offset_delta: U2,
+	},
+	AppendFrame {
+		offset_delta: U2,
+		locals: Vec<VerificationTypeInfo>,
+	},
+	FullFrame {
+		offset_delta: U2,
+		locals: Vec<VerificationTypeInfo>,
+		stack: Vec<VerificationTypeInfo>,
+	},
+}
+
+impl StackMapFrame {
+	pub fn offset_delta(&self) -> U2 {
+		match self {
+			Self::SameFrame { offset_delta }
+			| Self::SameLocals1StackItemFrame { offset_delta, .. }
+			| Self::SameLocals1StackItemFrameExtended { offset_delta, .. }
+			| Self::ChopFrame { offset_delta, .. }
+			| Self::SameFrameExtended { offset_delta }
+			| Self::AppendFrame { offset_delta, .. }
+			| Self::FullFrame { offset_delta, .. } => *offset_delta,
+		}
+	}
+
+	pub fn frame_type(&self) -> U1 {
+		match self {
+			Self::SameFrame { offset_delta } => *offset_delta as U1,
+			Self::SameLocals1StackItemFrame { offset_delta, .. } => 64 + *offset_delta as U1,
+			Self::SameLocals1StackItemFrameExtended { .. } => SAME_LOCALS_1_STACK_ITEM_EXTENDED,
+			Self::ChopFrame { chopped, .. } => SAME_FRAME_EXTENDED - chopped,
+			Self::SameFrameExtended { .. } => SAME_FRAME_EXTENDED,
+			Self::AppendFrame { locals, .. } => SAME_FRAME_EXTENDED + locals.len() as U1,
+			Self::FullFrame { .. } => FULL_FRAME,
+		}
+	}
+
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u1(writer, self.frame_type())?;
+		match self {
+			Self::SameFrame { .. } => Ok(()),
+			Self::SameLocals1StackItemFrame { stack, .. } => stack.write_to(writer),
+			Self::SameLocals1StackItemFrameExtended { offset_delta, stack } => {
+				write_u2(writer, *offset_delta)?;
+				stack.write_to(writer)
+			},
+			Self::ChopFrame { offset_delta, .. } | Self::SameFrameExtended { offset_delta } => write_u2(writer, *offset_delta),
+			Self::AppendFrame { offset_delta, locals } => {
+				write_u2(writer, *offset_delta)?;
+				for local in locals {
+					local.write_to(writer)?;
+				}
+				Ok(())
+			},
+			Self::FullFrame { offset_delta, locals, stack } => {
+				write_u2(writer, *offset_delta)?;
+				write_length_u2(writer, locals.len(), "locals")?;
+				for local in locals {
+					local.write_to(writer)?;
+				}
+				write_length_u2(writer, stack.len(), "stack")?;
+				for item in stack {
+					item.write_to(writer)?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackMapTable {
+	pub number_of_entries: U2,
+	pub entries: Vec<StackMapFrame>,
+}
+
+/// Verification type with class references resolved, as used by the type checker (JVMS 4.10.1.2).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VerificationType {
+	Top,
+	Integer,
+	Float,
+	Long,
+	Double,
+	Null,
+	UninitializedThis,
+	/// Class, interface or array type in internal form, e.g. `java/lang/String` or `[I`.
+	Object(String),
+	Uninitialized(U2),
+}
+
+impl VerificationType {
+	/// Type of a local variable or stack entry holding a value of `field_type`.
+	pub fn from_field_type(field_type: &FieldType) -> Self {
+		match field_type {
+			FieldType::Base(BaseType::Float) => Self::Float,
+			FieldType::Base(BaseType::Long) => Self::Long,
+			FieldType::Base(BaseType::Double) => Self::Double,
+			FieldType::Base(_) => Self::Integer,
+			FieldType::Object(class_name) => Self::Object(class_name.clone()),
+			array => Self::Object(array.to_string()),
+		}
+	}
+
+	pub fn is_wide(&self) -> bool {
+		matches!(self, Self::Long | Self::Double)
+	}
+
+	fn resolve(info: &VerificationTypeInfo, constant_pool: &ConstantPool) -> Result<Self, ClassFormatError> {
+		Ok(match info {
+			VerificationTypeInfo::Top => Self::Top,
+			VerificationTypeInfo::Integer => Self::Integer,
+			VerificationTypeInfo::Float => Self::Float,
+			VerificationTypeInfo::Long => Self::Long,
+			VerificationTypeInfo::Double => Self::Double,
+			VerificationTypeInfo::Null => Self::Null,
+			VerificationTypeInfo::UninitializedThis => Self::UninitializedThis,
+			VerificationTypeInfo::Object(cpool_index) => Self::Object(constant_pool.get_class_name(*cpool_index)?.into_owned()),
+			VerificationTypeInfo::Uninitialized(offset) => Self::Uninitialized(*offset),
+		})
+	}
+}
+
+impl Display for VerificationType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Top => write!(f, "top"),
+			Self::Integer => write!(f, "int"),
+			Self::Float => write!(f, "float"),
+			Self::Long => write!(f, "long"),
+			Self::Double => write!(f, "double"),
+			Self::Null => write!(f, "null"),
+			Self::UninitializedThis => write!(f, "uninitializedThis"),
+			Self::Object(class_name) => write!(f, "class {class_name}"),
+			Self::Uninitialized(offset) => write!(f, "uninitialized({offset})"),
+		}
+	}
+}
+
+/// Absolute state of locals and operand stack at `pc`.
+///
+/// Long and double values take two entries, the type itself followed by `Top`, in both the locals and the stack.
+/// The stack is listed from bottom to top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+	pub pc: U2,
+	pub locals: Vec<VerificationType>,
+	pub stack: Vec<VerificationType>,
+}
+
+fn expand_types(types: &[VerificationType]) -> Vec<VerificationType> {
+	let mut expanded = Vec::with_capacity(types.len());
+	for verification_type in types {
+		expanded.push(verification_type.clone());
+		if verification_type.is_wide() {
+			expanded.push(VerificationType::Top);
+		}
+	}
+	expanded
+}
+
+/// Locals at method entry (JVMS 4.10.1.6), with `this` first for instance methods.
+///
+/// The result is not expanded, i.e. long and double parameters take a single entry.
+pub fn initial_locals(class_name: &str, method_name: &str, is_static: bool, descriptor: &MethodDescriptor) -> Vec<VerificationType> {
+	let mut locals = Vec::with_capacity(descriptor.parameters.len() + 1);
+	if !is_static {
+		if method_name == "<init>" && class_name != "java/lang/Object" {
+			locals.push(VerificationType::UninitializedThis);
+		} else {
+			locals.push(VerificationType::Object(class_name.to_string()));
+		}
+	}
+	locals.extend(descriptor.parameters.iter().map(VerificationType::from_field_type));
+	locals
+}
+
+/// Frame at the absolute offset `pc` with unexpanded locals and stack, as passed to `StackMapTable::compress`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameInfo {
+	pub pc: U2,
+	pub locals: Vec<VerificationTypeInfo>,
+	pub stack: Vec<VerificationTypeInfo>,
+}
+
+impl StackMapTable {
+	/// Encodes `frames` with the smallest frame types, each relative to the frame before it.
+	///
+	/// `frames` have to be ordered by `pc` without duplicates, `initial_locals` are the unexpanded locals at method entry.
+	/// Other frames are a bug of the caller, debug builds panic on them and release builds encode wrapped offsets.
+	pub fn compress(initial_locals: &[VerificationTypeInfo], frames: &[FrameInfo]) -> Self {
+		let mut entries = Vec::with_capacity(frames.len());
+		let mut locals = initial_locals;
+		let mut previous_pc: Option<U2> = None;
+		for frame in frames {
+			let offset_delta = match previous_pc {
+				Some(pc) => {
+					debug_assert!(frame.pc > pc, "frame at {} follows the frame at {pc}", frame.pc);
+					frame.pc.wrapping_sub(pc).wrapping_sub(1)
+				},
+				None => frame.pc,
+			};
+			previous_pc = Some(frame.pc);
+
+			let same_locals = frame.locals == locals;
+			let entry = match frame.stack.as_slice() {
+				[] if same_locals && offset_delta < 64 => StackMapFrame::SameFrame { offset_delta },
+				[] if same_locals => StackMapFrame::SameFrameExtended { offset_delta },
+				[stack] if same_locals && offset_delta < 64 => StackMapFrame::SameLocals1StackItemFrame { offset_delta, stack: *stack },
+				[stack] if same_locals => StackMapFrame::SameLocals1StackItemFrameExtended { offset_delta, stack: *stack },
+				[] if frame.locals.len() < locals.len() && locals.len() - frame.locals.len() <= 3 && locals.starts_with(&frame.locals) => StackMapFrame::ChopFrame {
+					chopped: (locals.len() - frame.locals.len()) as U1,
+					offset_delta,
+				},
+				[] if frame.locals.len() > locals.len() && frame.locals.len() - locals.len() <= 3 && frame.locals.starts_with(locals) => StackMapFrame::AppendFrame {
+					offset_delta,
+					locals: frame.locals[locals.len()..].to_vec(),
+				},
+				_ => StackMapFrame::FullFrame {
+					offset_delta,
+					locals: frame.locals.clone(),
+					stack: frame.stack.clone(),
+				},
+			};
+			entries.push(entry);
+			locals = &frame.locals;
+		}
+		Self {
+			number_of_entries: entries.len() as U2,
+			entries,
+		}
+	}
+
+	/// Turns the delta encoded frames into absolute snapshots, starting from the unexpanded `initial_locals`.
+	pub fn expand(&self, constant_pool: &ConstantPool, initial_locals: &[VerificationType]) -> Result<Vec<Frame>, ClassFormatError> {
+		let resolve_all = |infos: &[VerificationTypeInfo]| infos.iter().map(|info| VerificationType::resolve(info, constant_pool)).collect::<Result<Vec<_>, _>>();
+
+		let mut frames = Vec::with_capacity(self.entries.len());
+		let mut locals = initial_locals.to_vec();
+		let mut pc: Option<U2> = None;
+		for (index, entry) in self.entries.iter().enumerate() {
+			let invalid = || ClassFormatError::new(&[], ClassFormatErrorKind::InvalidStackMapFrame(index));
+			let offset = match pc {
+				Some(pc) => pc.checked_add(entry.offset_delta()).and_then(|pc| pc.checked_add(1)),
+				None => Some(entry.offset_delta()),
+			};
+			let offset = offset.ok_or_else(invalid)?;
+			pc = Some(offset);
+
+			let stack = match entry {
+				StackMapFrame::SameFrame { .. } | StackMapFrame::SameFrameExtended { .. } => Vec::new(),
+				StackMapFrame::SameLocals1StackItemFrame { stack, .. } | StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => vec![VerificationType::resolve(stack, constant_pool)?],
+				StackMapFrame::ChopFrame { chopped, .. } => {
+					let remaining = locals.len().checked_sub(*chopped as usize).ok_or_else(invalid)?;
+					locals.truncate(remaining);
+					Vec::new()
+				},
+				StackMapFrame::AppendFrame { locals: appended, .. } => {
+					locals.extend(resolve_all(appended)?);
+					Vec::new()
+				},
+				StackMapFrame::FullFrame { locals: full_locals, stack, .. } => {
+					locals = resolve_all(full_locals)?;
+					resolve_all(stack)?
+				},
+			};
+
+			frames.push(Frame {
+				pc: offset,
+				locals: expand_types(&locals),
+				stack: expand_types(&stack),
+			});
+		}
+		Ok(frames)
+	}
+}
+
+impl AttributeBody for StackMapTable {
+	fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.entries.len(), "entries")?;
+		for entry in &self.entries {
+			entry.write_to(writer)?;
+		}
+		Ok(())
+	}
+}
+
+pub fn verification_type_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], VerificationTypeInfo, E> {
+	let (rest, tag) = context("tag", be_u8)(input)?;
+	match tag {
+		ITEM_TOP => Ok((rest, VerificationTypeInfo::Top)),
+		ITEM_INTEGER => Ok((rest, VerificationTypeInfo::Integer)),
+		ITEM_FLOAT => Ok((rest, VerificationTypeInfo::Float)),
+		ITEM_DOUBLE => Ok((rest, VerificationTypeInfo::Double)),
+		ITEM_LONG => Ok((rest, VerificationTypeInfo::Long)),
+		ITEM_NULL => Ok((rest, VerificationTypeInfo::Null)),
+		ITEM_UNINITIALIZED_THIS => Ok((rest, VerificationTypeInfo::UninitializedThis)),
+		ITEM_OBJECT => {
+			let (rest, cpool_index) = context("cpool_index", be_u16)(rest)?;
+			Ok((rest, VerificationTypeInfo::Object(cpool_index)))
+		},
+		ITEM_UNINITIALIZED => {
+			let (rest, offset) = context("offset", be_u16)(rest)?;
+			Ok((rest, VerificationTypeInfo::Uninitialized(offset)))
+		},
+		tag => fail_with(input, ClassFormatErrorKind::InvalidTag(tag)),
+	}
+}
+
+fn verification_type_infos_parser<'a, E: ClassParseError<&'a [u8]>>(count: usize) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Vec<VerificationTypeInfo>, E> {
+	move |mut input: &'a [u8]| {
+		let mut infos = Vec::with_capacity(count);
+		for index in 0..count {
+			let (rest, info) = indexed(index, verification_type_info_parser::<'a, E>)(input)?;
+			input = rest;
+			infos.push(info);
+		}
+		Ok((input, infos))
+	}
+}
+
+pub fn stack_map_frame_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], StackMapFrame, E> {
+	let (rest, frame_type) = context("frame_type", be_u8)(input)?;
+	match frame_type {
+		0..=63 => Ok((rest, StackMapFrame::SameFrame {
+			offset_delta: frame_type as U2,
+		})),
+		64..=127 => {
+			let (rest, stack) = context("stack", verification_type_info_parser)(rest)?;
+			Ok((rest, StackMapFrame::SameLocals1StackItemFrame {
+				offset_delta: (frame_type - 64) as U2,
+				stack,
+			}))
+		},
+		SAME_LOCALS_1_STACK_ITEM_EXTENDED => {
+			let (rest, offset_delta) = context("offset_delta", be_u16)(rest)?;
+			let (rest, stack) = context("stack", verification_type_info_parser)(rest)?;
+			Ok((rest, StackMapFrame::SameLocals1StackItemFrameExtended {
+				offset_delta,
+				stack,
+			}))
+		},
+		248..=250 => {
+			let (rest, offset_delta) = context("offset_delta", be_u16)(rest)?;
+			Ok((rest, StackMapFrame::ChopFrame {
+				chopped: SAME_FRAME_EXTENDED - frame_type,
+				offset_delta,
+			}))
+		},
+		SAME_FRAME_EXTENDED => {
+			let (rest, offset_delta) = context("offset_delta", be_u16)(rest)?;
+			Ok((rest, StackMapFrame::SameFrameExtended {
+				offset_delta,
+			}))
+		},
+		252..=254 => {
+			let (rest, offset_delta) = context("offset_delta", be_u16)(rest)?;
+			let (rest, locals) = context("locals", verification_type_infos_parser((frame_type - SAME_FRAME_EXTENDED) as usize))(rest)?;
+			Ok((rest, StackMapFrame::AppendFrame {
+				offset_delta,
+				locals,
+			}))
+		},
+		FULL_FRAME => {
+			let (rest, offset_delta) = context("offset_delta", be_u16)(rest)?;
+			let (rest, locals) = context("locals", indexed_length_count(be_u16, verification_type_info_parser))(rest)?;
+			let (rest, stack) = context("stack", indexed_length_count(be_u16, verification_type_info_parser))(rest)?;
+			Ok((rest, StackMapFrame::FullFrame {
+				offset_delta,
+				locals,
+				stack,
+			}))
+		},
+		frame_type => fail_with(input, ClassFormatErrorKind::InvalidTag(frame_type)),
+	}
+}
+
+pub fn stack_map_table_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], StackMapTable, E> {
+	let (input, entries) = context("entries", indexed_length_count(be_u16, stack_map_frame_parser::<'a, E>))(input)?;
+	let number_of_entries = entries.len() as U2;
+
+	Ok((input, StackMapTable {
+		number_of_entries,
+		entries,
+	}))
+}
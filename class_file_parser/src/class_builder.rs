@@ -0,0 +1,551 @@
+use crate::{access_flags::{ClassAccess, FieldAccess, MethodAccess}, attribute_info::bootstrap_methods::{CallSite, Constant}, class_file::ClassFile, class_writer::{ClassWriter, Compute, WriteError}, instruction::*, verifier::ClassHierarchy, visitor::{ClassVersion, ClassVisitor, FrameValue, Label, MethodVisitor}, U1, U2};
+
+/// Assembles a class from Rust, with `max_stack`, `max_locals` and the frames of every method computed.
+///
+/// Fields and methods are written in the order they are finished with `end`.
+pub struct ClassBuilder {
+	writer: ClassWriter,
+}
+
+impl ClassBuilder {
+	pub fn new(version: ClassVersion, access: ClassAccess, name: &str, super_name: Option<&str>, interfaces: &[&str]) -> Self {
+		let mut writer = ClassWriter::new();
+		writer.set_compute(Compute::Frames);
+		writer.visit(version, access.bits(), name, None, super_name, interfaces);
+		Self {
+			writer,
+		}
+	}
+
+	/// What is computed instead of given, e.g. `Compute::Maxs` to give frames with `CodeBuilder::frame`.
+	pub fn compute(&mut self, compute: Compute) -> &mut Self {
+		self.writer.set_compute(compute);
+		self
+	}
+
+	/// Class hierarchy for merging class types in computed frames, which has to be set before adding methods.
+	pub fn class_hierarchy(&mut self, hierarchy: impl ClassHierarchy + 'static) -> &mut Self {
+		self.writer.set_class_hierarchy(hierarchy);
+		self
+	}
+
+	pub fn source_file(&mut self, source_file: &str) -> &mut Self {
+		self.writer.visit_source(source_file);
+		self
+	}
+
+	/// Adds an attribute the builder has no method for, already encoded.
+	pub fn attribute(&mut self, name: &str, info: &[U1]) -> &mut Self {
+		self.writer.visit_attribute(name, info);
+		self
+	}
+
+	pub fn field(&mut self, access: FieldAccess, name: &str, descriptor: &str) -> FieldBuilder<'_> {
+		FieldBuilder {
+			writer: &mut self.writer,
+			access,
+			name: name.to_string(),
+			descriptor: descriptor.to_string(),
+			signature: None,
+			value: None,
+			attributes: Vec::new(),
+		}
+	}
+
+	pub fn method(&mut self, access: MethodAccess, name: &str, descriptor: &str) -> MethodBuilder<'_> {
+		MethodBuilder {
+			writer: &mut self.writer,
+			access,
+			name: name.to_string(),
+			descriptor: descriptor.to_string(),
+			signature: None,
+			exceptions: Vec::new(),
+			attributes: Vec::new(),
+		}
+	}
+
+	/// The assembled class, or the first error that occurred while assembling it.
+	pub fn build(mut self) -> Result<ClassFile, WriteError> {
+		self.writer.visit_end();
+		self.writer.into_class_file()
+	}
+
+	pub fn to_bytes(mut self) -> Result<Vec<u8>, WriteError> {
+		self.writer.visit_end();
+		self.writer.into_bytes()
+	}
+}
+
+pub struct FieldBuilder<'c> {
+	writer: &'c mut ClassWriter,
+	access: FieldAccess,
+	name: String,
+	descriptor: String,
+	signature: Option<String>,
+	value: Option<Constant>,
+	attributes: Vec<(String, Vec<U1>)>,
+}
+
+impl FieldBuilder<'_> {
+	pub fn signature(mut self, signature: &str) -> Self {
+		self.signature = Some(signature.to_string());
+		self
+	}
+
+	/// Initial value of a static field, written as `ConstantValue`.
+	pub fn constant_value(mut self, value: Constant) -> Self {
+		self.value = Some(value);
+		self
+	}
+
+	pub fn attribute(mut self, name: &str, info: &[U1]) -> Self {
+		self.attributes.push((name.to_string(), info.to_vec()));
+		self
+	}
+
+	/// Adds the field to the class.
+	pub fn end(self) {
+		if let Some(mut field) = self.writer.visit_field(self.access.bits(), &self.name, &self.descriptor, self.signature.as_deref(), self.value.as_ref()) {
+			for (name, info) in &self.attributes {
+				field.visit_attribute(name, info);
+			}
+			field.visit_end();
+		}
+	}
+}
+
+pub struct MethodBuilder<'c> {
+	writer: &'c mut ClassWriter,
+	access: MethodAccess,
+	name: String,
+	descriptor: String,
+	signature: Option<String>,
+	exceptions: Vec<String>,
+	attributes: Vec<(String, Vec<U1>)>,
+}
+
+impl<'c> MethodBuilder<'c> {
+	pub fn signature(mut self, signature: &str) -> Self {
+		self.signature = Some(signature.to_string());
+		self
+	}
+
+	/// Adds a checked exception to the `throws` clause.
+	pub fn exception(mut self, class_name: &str) -> Self {
+		self.exceptions.push(class_name.to_string());
+		self
+	}
+
+	pub fn attribute(mut self, name: &str, info: &[U1]) -> Self {
+		self.attributes.push((name.to_string(), info.to_vec()));
+		self
+	}
+
+	fn visit(self) -> Option<Box<dyn MethodVisitor + 'c>> {
+		let exceptions = self.exceptions.iter().map(String::as_str).collect::<Vec<_>>();
+		let mut method = self.writer.visit_method(self.access.bits(), &self.name, &self.descriptor, self.signature.as_deref(), &exceptions)?;
+		for (name, info) in &self.attributes {
+			method.visit_attribute(name, info);
+		}
+		Some(method)
+	}
+
+	/// Adds the method without code, as abstract and native methods have none.
+	pub fn end(self) {
+		if let Some(mut method) = self.visit() {
+			method.visit_end();
+		}
+	}
+
+	/// Starts the code of the method, which is added to the class by `CodeBuilder::end`.
+	pub fn code(self) -> CodeBuilder<'c> {
+		let method = self.visit();
+		let mut code = CodeBuilder {
+			method,
+		};
+		code.visit(|method| method.visit_code());
+		code
+	}
+}
+
+/// Bytecode assembler, branches refer to labels that may be placed before or after them.
+///
+/// Branch offsets are resolved when the method ends, branches that cannot reach their label are widened to `goto_w`.
+/// Frames for version 50 and later classes are computed from the assembled code, so they also cover widened conditional branches.
+/// Without computed frames, widening a conditional branch of a class that has frames fails with `WriteErrorKind::UnframedWideBranch`.
+pub struct CodeBuilder<'c> {
+	/// `None` once writing the class failed, the error is reported by `ClassBuilder::build`.
+	method: Option<Box<dyn MethodVisitor + 'c>>,
+}
+
+impl CodeBuilder<'_> {
+	fn visit(&mut self, event: impl FnOnce(&mut dyn MethodVisitor)) -> &mut Self {
+		if let Some(method) = &mut self.method {
+			event(method.as_mut());
+		}
+		self
+	}
+
+	pub fn new_label(&self) -> Label {
+		Label::new()
+	}
+
+	/// Places `label` at the next instruction.
+	pub fn label(&mut self, label: Label) -> &mut Self {
+		self.visit(|method| method.visit_label(label))
+	}
+
+	/// Instruction without operands, e.g. `iadd` or `return`.
+	pub fn insn(&mut self, opcode: U1) -> &mut Self {
+		self.visit(|method| method.visit_insn(opcode))
+	}
+
+	/// Pushes `value` with the shortest instruction for it.
+	pub fn push_int(&mut self, value: i32) -> &mut Self {
+		match value {
+			-1..=5 => self.insn((ICONST_0 as i32 + value) as U1),
+			value if i8::try_from(value).is_ok() => self.int_insn(BIPUSH, value),
+			value if i16::try_from(value).is_ok() => self.int_insn(SIPUSH, value),
+			value => self.ldc(Constant::Integer(value)),
+		}
+	}
+
+	/// `bipush`, `sipush` or `newarray`.
+	pub fn int_insn(&mut self, opcode: U1, operand: i32) -> &mut Self {
+		self.visit(|method| method.visit_int_insn(opcode, operand))
+	}
+
+	/// Load, store or `ret` as `iload` or `astore`, the shortest encoding for `var` is chosen.
+	pub fn var_insn(&mut self, opcode: U1, var: U2) -> &mut Self {
+		self.visit(|method| method.visit_var_insn(opcode, var))
+	}
+
+	/// `new`, `anewarray`, `checkcast` or `instanceof`.
+	pub fn type_insn(&mut self, opcode: U1, type_name: &str) -> &mut Self {
+		self.visit(|method| method.visit_type_insn(opcode, type_name))
+	}
+
+	pub fn field_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str) -> &mut Self {
+		self.visit(|method| method.visit_field_insn(opcode, owner, name, descriptor))
+	}
+
+	/// Invokes a method of a class, or of an interface for `invokeinterface`.
+	pub fn method_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str) -> &mut Self {
+		self.visit(|method| method.visit_method_insn(opcode, owner, name, descriptor, opcode == INVOKEINTERFACE))
+	}
+
+	/// Invokes a static or private method of an interface.
+	pub fn interface_method_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str) -> &mut Self {
+		self.visit(|method| method.visit_method_insn(opcode, owner, name, descriptor, true))
+	}
+
+	pub fn invoke_dynamic(&mut self, call_site: &CallSite) -> &mut Self {
+		self.visit(|method| method.visit_invoke_dynamic_insn(call_site))
+	}
+
+	pub fn jump(&mut self, opcode: U1, label: Label) -> &mut Self {
+		self.visit(|method| method.visit_jump_insn(opcode, label))
+	}
+
+	/// Loads a constant with `ldc`, `ldc_w` or `ldc2_w`.
+	pub fn ldc(&mut self, constant: Constant) -> &mut Self {
+		self.visit(|method| method.visit_ldc_insn(&constant))
+	}
+
+	pub fn iinc(&mut self, var: U2, increment: i16) -> &mut Self {
+		self.visit(|method| method.visit_iinc_insn(var, increment))
+	}
+
+	/// `tableswitch` over `low..low + labels.len()`.
+	///
+	/// Fails with `WriteErrorKind::InvalidOperand` if `labels` is empty or the last case is beyond `i32::MAX`.
+	pub fn table_switch(&mut self, low: i32, default: Label, labels: &[Label]) -> &mut Self {
+		// a `high` that does not match the number of labels is rejected by the writer
+		let high = i32::try_from(labels.len()).ok().and_then(|count| low.checked_add(count - 1)).unwrap_or(low.wrapping_sub(1));
+		self.visit(|method| method.visit_table_switch_insn(low, high, default, labels))
+	}
+
+	/// `lookupswitch`, the cases do not have to be sorted.
+	pub fn lookup_switch(&mut self, default: Label, cases: &[(i32, Label)]) -> &mut Self {
+		let (keys, labels): (Vec<_>, Vec<_>) = cases.iter().copied().unzip();
+		self.visit(|method| method.visit_lookup_switch_insn(default, &keys, &labels))
+	}
+
+	pub fn multi_anewarray(&mut self, descriptor: &str, dimensions: U1) -> &mut Self {
+		self.visit(|method| method.visit_multi_anewarray_insn(descriptor, dimensions))
+	}
+
+	/// Exception handler at `handler` for the code from `start` up to `end`, catching everything if `catch_type` is `None`.
+	///
+	/// Handlers are tried in the order they are added.
+	pub fn try_catch(&mut self, start: Label, end: Label, handler: Label, catch_type: Option<&str>) -> &mut Self {
+		self.visit(|method| method.visit_try_catch(start, end, handler, catch_type))
+	}
+
+	pub fn line_number(&mut self, line: U2, start: Label) -> &mut Self {
+		self.visit(|method| method.visit_line_number(line, start))
+	}
+
+	pub fn local_variable(&mut self, name: &str, descriptor: &str, start: Label, end: Label, index: U2) -> &mut Self {
+		self.visit(|method| method.visit_local_variable(name, descriptor, None, start, end, index))
+	}
+
+	/// Frame at the next instruction, with long and double values as a single entry.
+	///
+	/// Ignored unless `ClassBuilder::compute` turned off computing frames.
+	pub fn frame(&mut self, locals: &[FrameValue], stack: &[FrameValue]) -> &mut Self {
+		self.visit(|method| method.visit_frame(locals, stack))
+	}
+
+	/// Assembles the code and adds the method to the class.
+	pub fn end(mut self) {
+		self.visit(|method| method.visit_end());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{analysis::Maxs, attribute_info::stack_map_table::VerificationType, class_file::parse_class_file, class_writer::WriteErrorKind, verifier::{verify_class, ClassHierarchyMap}};
+
+	use super::*;
+
+	/// Class file of `Main.java`, which adds two numbers and prints the sum.
+	fn main_class() -> Vec<u8> {
+		let mut class = ClassBuilder::new(ClassVersion { major: 63, minor: 0 }, ClassAccess::PUBLIC | ClassAccess::SUPER, "Main", Some("java/lang/Object"), &[]);
+		class.source_file("Main.java");
+
+		let mut init = class.method(MethodAccess::PUBLIC, "<init>", "()V").code();
+		let start = init.new_label();
+		init.label(start)
+			.line_number(1, start)
+			.var_insn(ALOAD, 0)
+			.method_insn(INVOKESPECIAL, "java/lang/Object", "<init>", "()V")
+			.insn(RETURN);
+		init.end();
+
+		let mut main = class.method(MethodAccess::PUBLIC | MethodAccess::STATIC, "main", "([Ljava/lang/String;)V").code();
+		let lines = [main.new_label(), main.new_label(), main.new_label(), main.new_label(), main.new_label()];
+		for (line, label) in (3..).zip(lines) {
+			main.line_number(line, label);
+		}
+		main.label(lines[0]).push_int(1).var_insn(ISTORE, 1)
+			.label(lines[1]).push_int(2).var_insn(ISTORE, 2)
+			.label(lines[2]).var_insn(ILOAD, 1).var_insn(ILOAD, 2).insn(IADD).var_insn(ISTORE, 3)
+			.label(lines[3]).field_insn(GETSTATIC, "java/lang/System", "out", "Ljava/io/PrintStream;").var_insn(ILOAD, 3).method_insn(INVOKEVIRTUAL, "java/io/PrintStream", "println", "(I)V")
+			.label(lines[4]).insn(RETURN);
+		main.end();
+
+		class.to_bytes().expect("Failed to build Main")
+	}
+
+	fn maxs(class_file: &ClassFile, index: usize) -> Maxs {
+		let code = class_file.methods[index].code(&class_file.constant_pool).unwrap().expect("Missing Code");
+		Maxs {
+			max_stack: code.max_stack,
+			max_locals: code.max_locals,
+		}
+	}
+
+	#[test]
+	fn build_main() {
+		let class_file = parse_class_file(&main_class()).expect("Failed to parse built class");
+		assert_eq!(class_file.source_file().unwrap().as_deref(), Some("Main.java"));
+		assert_eq!(maxs(&class_file, 0), Maxs { max_stack: 1, max_locals: 1 });
+		assert_eq!(maxs(&class_file, 1), Maxs { max_stack: 2, max_locals: 4 });
+		let code = class_file.methods[1].code(&class_file.constant_pool).unwrap().unwrap();
+		assert_eq!(code.line_for_pc(&class_file.constant_pool, 8).unwrap(), Some(6));
+
+		let mut hierarchy = ClassHierarchyMap::new();
+		hierarchy.insert_class_file(&class_file).unwrap();
+		verify_class(&class_file, &hierarchy).unwrap_or_else(|e| panic!("{e}"));
+
+		// same code as javac, with the members it refers to resolved as the constant pools differ
+		let javac = parse_class_file(&std::fs::read("./Main.class").expect("Failed to read class file")).expect("Failed to parse class file");
+		let instructions = |class_file: &ClassFile, index: usize| {
+			let code = class_file.methods[index].code(&class_file.constant_pool).unwrap().expect("Missing Code");
+			decode(&code.code).map(|instruction| match instruction.expect("Failed to decode code") {
+				(pc, Instruction::Getstatic(index) | Instruction::Invokevirtual(index) | Instruction::Invokespecial(index)) => {
+					let member = class_file.constant_pool.get_member_ref(index).unwrap();
+					format!("{pc}: {} {}.{}:{}", mnemonic(code.code[pc]).unwrap(), member.class_name, member.name, member.descriptor)
+				},
+				(pc, instruction) => format!("{pc}: {instruction}"),
+			}).collect::<Vec<_>>()
+		};
+		for index in 0..2 {
+			assert_eq!(instructions(&class_file, index), instructions(&javac, index));
+			assert_eq!(maxs(&class_file, index), maxs(&javac, index));
+		}
+	}
+
+	#[test]
+	fn deduplicate_constants() {
+		let mut class = ClassBuilder::new(ClassVersion { major: 49, minor: 0 }, ClassAccess::PUBLIC, "Constants", Some("java/lang/Object"), &[]);
+		class.field(FieldAccess::STATIC | FieldAccess::FINAL, "A", "J").constant_value(Constant::Long(7)).end();
+		class.field(FieldAccess::STATIC | FieldAccess::FINAL, "B", "J").constant_value(Constant::Long(7)).end();
+		let mut code = class.method(MethodAccess::STATIC, "b", "()J").code();
+		code.ldc(Constant::Long(7)).insn(LRETURN);
+		code.end();
+		let class_file = class.build().unwrap();
+
+		let constant_value = |index: usize| class_file.fields[index].constant_value(&class_file.constant_pool).unwrap().unwrap().constantvalue_index;
+		assert_eq!(constant_value(0), constant_value(1));
+		let code = class_file.methods[0].code(&class_file.constant_pool).unwrap().unwrap();
+		assert_eq!(code.code[1..3], constant_value(0).to_be_bytes());
+		assert_eq!((code.max_stack, code.max_locals), (2, 0));
+	}
+
+	#[test]
+	fn widen_branches_out_of_range() {
+		let mut class = ClassBuilder::new(ClassVersion { major: 49, minor: 0 }, ClassAccess::PUBLIC, "Far", Some("java/lang/Object"), &[]);
+		let mut code = class.method(MethodAccess::STATIC, "far", "(I)I").code();
+		let (far, end) = (code.new_label(), code.new_label());
+		code.var_insn(ILOAD, 0).jump(IFEQ, far).jump(GOTO, end);
+		for _ in 0..40000 {
+			code.insn(NOP);
+		}
+		code.label(far).push_int(1).insn(IRETURN).label(end).push_int(2).insn(IRETURN);
+		code.end();
+		let class_file = class.build().unwrap();
+
+		let code = class_file.methods[0].code(&class_file.constant_pool).unwrap().unwrap();
+		// ifne over goto_w to far, then goto_w to end
+		assert_eq!(code.code[..14], [ILOAD_0, IFNE, 0, 8, GOTO_W, 0, 0, 0x9C, 0x4A, GOTO_W, 0, 0, 0x9C, 0x47]);
+		assert_eq!(code.code.len(), 14 + 40000 + 4);
+		assert_eq!(maxs(&class_file, 0), Maxs { max_stack: 1, max_locals: 1 });
+	}
+
+	#[test]
+	fn compute_frames_at_branches_and_handlers() {
+		let mut hierarchy = ClassHierarchyMap::new();
+		hierarchy.insert("java/lang/Number", Some("java/lang/Object"), false);
+		hierarchy.insert("java/lang/Integer", Some("java/lang/Number"), false);
+		hierarchy.insert("java/lang/Long", Some("java/lang/Number"), false);
+		hierarchy.insert("java/lang/NumberFormatException", Some("java/lang/Throwable"), false);
+		let mut class = ClassBuilder::new(ClassVersion { major: 61, minor: 0 }, ClassAccess::PUBLIC, "Frames", Some("java/lang/Object"), &[]);
+		class.class_hierarchy(hierarchy.clone());
+
+		// long sum = 0; for (; n > 0; n--) sum += n; return n == 0 ? Integer.valueOf(0) : Long.valueOf(sum);
+		let mut code = class.method(MethodAccess::STATIC, "sum", "(I)Ljava/lang/Number;").code();
+		let (head, done, other, join) = (code.new_label(), code.new_label(), code.new_label(), code.new_label());
+		code.insn(LCONST_0).var_insn(LSTORE, 1)
+			.label(head).var_insn(ILOAD, 0).jump(IFLE, done)
+			.var_insn(LLOAD, 1).var_insn(ILOAD, 0).insn(I2L).insn(LADD).var_insn(LSTORE, 1).iinc(0, -1).jump(GOTO, head)
+			.label(done).var_insn(ILOAD, 0).jump(IFNE, other)
+			.push_int(0).method_insn(INVOKESTATIC, "java/lang/Integer", "valueOf", "(I)Ljava/lang/Integer;").jump(GOTO, join)
+			.label(other).var_insn(LLOAD, 1).method_insn(INVOKESTATIC, "java/lang/Long", "valueOf", "(J)Ljava/lang/Long;")
+			.label(join).insn(ARETURN);
+		code.end();
+
+		let mut code = class.method(MethodAccess::STATIC, "parse", "(Ljava/lang/String;)I").code();
+		let (start, end, handler) = (code.new_label(), code.new_label(), code.new_label());
+		code.try_catch(start, end, handler, Some("java/lang/NumberFormatException"))
+			.label(start).var_insn(ALOAD, 0).method_insn(INVOKESTATIC, "java/lang/Integer", "parseInt", "(Ljava/lang/String;)I").label(end).insn(IRETURN)
+			.label(handler).var_insn(ASTORE, 1).push_int(-1).insn(IRETURN);
+		code.end();
+		let class_file = class.build().unwrap();
+
+		let frames = class_file.methods[0].stack_map_frames(&class_file).unwrap();
+		let summary = frames.iter().map(|frame| (frame.pc, frame.locals.len(), frame.stack.clone())).collect::<Vec<_>>();
+		assert_eq!(summary, [(2, 3, vec![]), (17, 3, vec![]), (28, 3, vec![]), (32, 3, vec![VerificationType::Object("java/lang/Number".to_string())])]);
+		let frames = class_file.methods[1].stack_map_frames(&class_file).unwrap();
+		assert_eq!(frames.iter().map(|frame| (frame.pc, frame.stack.clone())).collect::<Vec<_>>(), [(5, vec![VerificationType::Object("java/lang/NumberFormatException".to_string())])]);
+
+		hierarchy.insert_class_file(&class_file).unwrap();
+		verify_class(&class_file, &hierarchy).unwrap_or_else(|e| panic!("{e}"));
+	}
+
+	#[test]
+	fn compute_frame_after_widened_branch() {
+		let mut class = ClassBuilder::new(ClassVersion { major: 61, minor: 0 }, ClassAccess::PUBLIC, "Far", Some("java/lang/Object"), &[]);
+		let mut code = class.method(MethodAccess::STATIC, "far", "(I)I").code();
+		let far = code.new_label();
+		code.var_insn(ILOAD, 0).jump(IFEQ, far);
+		for _ in 0..40000 {
+			code.insn(NOP);
+		}
+		code.push_int(2).insn(IRETURN).label(far).push_int(1).insn(IRETURN);
+		code.end();
+		let class_file = class.build().unwrap();
+
+		// ifne over goto_w to the code the ifeq fell through to
+		let frames = class_file.methods[0].stack_map_frames(&class_file).unwrap();
+		assert_eq!(frames.iter().map(|frame| frame.pc).collect::<Vec<_>>(), [9, 40011]);
+		let mut hierarchy = ClassHierarchyMap::new();
+		hierarchy.insert_class_file(&class_file).unwrap();
+		verify_class(&class_file, &hierarchy).unwrap_or_else(|e| panic!("{e}"));
+	}
+
+	#[test]
+	fn reject_widened_branch_without_computed_frames() {
+		let mut class = ClassBuilder::new(ClassVersion { major: 61, minor: 0 }, ClassAccess::PUBLIC, "Far", Some("java/lang/Object"), &[]);
+		class.compute(Compute::Maxs);
+		let mut code = class.method(MethodAccess::STATIC, "far", "(I)I").code();
+		let far = code.new_label();
+		code.var_insn(ILOAD, 0).jump(IFEQ, far);
+		for _ in 0..40000 {
+			code.insn(NOP);
+		}
+		code.push_int(2).insn(IRETURN).label(far).frame(&[FrameValue::Integer], &[]).push_int(1).insn(IRETURN);
+		code.end();
+
+		assert_eq!(class.build().unwrap_err().kind, WriteErrorKind::UnframedWideBranch(1));
+	}
+
+	#[test]
+	fn resolve_handlers_and_switches() {
+		let mut class = ClassBuilder::new(ClassVersion { major: 49, minor: 0 }, ClassAccess::PUBLIC, "Handlers", Some("java/lang/Object"), &[]);
+		let mut code = class.method(MethodAccess::STATIC, "parse", "(Ljava/lang/String;)J").exception("java/lang/Exception").code();
+		let (start, end, handler, zero, other) = (code.new_label(), code.new_label(), code.new_label(), code.new_label(), code.new_label());
+		code.try_catch(start, end, handler, Some("java/lang/NumberFormatException"))
+			.label(start)
+			.var_insn(ALOAD, 0)
+			.method_insn(INVOKESTATIC, "java/lang/Integer", "parseInt", "(Ljava/lang/String;)I")
+			.lookup_switch(other, &[(100, other), (0, zero)])
+			.label(zero)
+			.insn(LCONST_0)
+			.label(end)
+			.insn(LRETURN)
+			.label(other)
+			.ldc(Constant::Long(-1))
+			.insn(LRETURN)
+			.label(handler)
+			.var_insn(ASTORE, 300)
+			.insn(LCONST_1)
+			.insn(LRETURN);
+		code.end();
+		let class_file = class.build().unwrap();
+
+		let method = &class_file.methods[0];
+		assert_eq!(method.exceptions(&class_file.constant_pool).unwrap(), ["java/lang/Exception"]);
+		let code = method.code(&class_file.constant_pool).unwrap().unwrap();
+		// aload_0, invokestatic, lookupswitch padded to pc 8 with keys sorted
+		assert_eq!(code.code[4..8], [LOOKUPSWITCH, 0, 0, 0]);
+		assert_eq!(code.code[16..20], 0i32.to_be_bytes());
+		let entry = &code.exception_table[0];
+		assert_eq!((entry.start_pc, entry.end_pc, entry.handler_pc), (0, 33, 38));
+		assert_eq!(class_file.constant_pool.get_class_name(entry.catch_type).unwrap(), "java/lang/NumberFormatException");
+		// wide astore to local 300
+		assert_eq!(code.code[38..42], [WIDE, ASTORE, 1, 44]);
+		assert_eq!(maxs(&class_file, 0), Maxs { max_stack: 2, max_locals: 301 });
+	}
+
+	#[test]
+	fn table_switch_up_to_the_last_key() {
+		let build = |low: i32, cases: usize| {
+			let mut class = ClassBuilder::new(ClassVersion { major: 49, minor: 0 }, ClassAccess::PUBLIC, "Switch", Some("java/lang/Object"), &[]);
+			let mut code = class.method(MethodAccess::STATIC, "switch", "(I)I").code();
+			let default = code.new_label();
+			code.var_insn(ILOAD, 0).table_switch(low, default, &vec![default; cases]).label(default).push_int(0).insn(IRETURN);
+			code.end();
+			class.build()
+		};
+
+		let class_file = build(i32::MAX, 1).unwrap();
+		let code = class_file.methods[0].code(&class_file.constant_pool).unwrap().unwrap();
+		// iload_0, tableswitch padded to pc 4, default, low and high
+		assert_eq!(code.code[8..16], [i32::MAX.to_be_bytes(), i32::MAX.to_be_bytes()].concat());
+		for (low, cases) in [(i32::MAX, 2), (0, 0), (i32::MIN, 0)] {
+			assert!(matches!(build(low, cases).unwrap_err().kind, WriteErrorKind::InvalidOperand { opcode: TABLESWITCH, .. }), "{low} with {cases} cases");
+		}
+	}
+}
@@ -1,6 +1,8 @@
-use nom::{combinator::map, error::ParseError, multi::length_count, number::complete::{be_u16, be_u32}, IResult};
+use std::{borrow::Cow, io::{self, Write}};
 
-use crate::{attribute_info::{attribute_info_parser, AttributeInfo}, cp_info::{cp_info_parser, CPInfo}, field_info::{field_info_parser, FieldInfo}, method_info::{method_info_parser, MethodInfo}, U2, U4};
+use nom::{error::context, number::complete::{be_u16, be_u32}, IResult};
+
+use crate::{access_flags::ClassAccess, attribute_info::{annotations::{self, Annotation, TypeAnnotation, Visibility}, attribute_info_parser, attribute_info_ref_parser, bootstrap_methods::{bootstrap_methods_attribute_parser, BootstrapMethodsAttribute, CallSite}, debug_info::{source_debug_extension_attribute_parser, source_file_attribute_parser}, find_attribute, inner_classes::{enclosing_method_attribute_parser, inner_classes_attribute_parser, EnclosingMethod, InnerClass}, module::{module_attribute_parser, module_main_class_attribute_parser, module_packages_attribute_parser, ModuleDescriptor}, nest::{class_list_attribute_parser, nest_host_attribute_parser}, record::{record_attribute_parser, RecordComponentInfo}, signature, write_attributes, AttributeInfo, AttributeInfoRef}, class_format_error::{indexed_length_count, parse_all, ClassFormatError, ClassFormatErrorKind, ClassParseError}, constant_pool::{constant_pool_parser, constant_pool_ref_parser, ConstantPool, ConstantPoolError, ConstantPoolRef}, field_info::{field_info_parser, field_info_ref_parser, FieldInfo, FieldInfoRef}, method_info::{method_info_parser, method_info_ref_parser, MethodInfo, MethodInfoRef}, mutf8::{self, JavaString}, signature::{parse_class_signature, ClassSignature}, visitor::{self, ClassVisitor}, writer::{write_length_u2, write_u2, write_u4}, U2, U4};
 
 #[derive(Debug)]
 pub struct ClassFile {
@@ -8,7 +10,7 @@ pub struct ClassFile {
     pub minor_version: U2,
     pub major_version: U2,
     pub constant_pool_count: U2,
-    pub constant_pool: Vec<CPInfo>,
+    pub constant_pool: ConstantPool,
     pub access_flags: U2,
     pub this_class: U2,
     pub super_class: U2,
@@ -22,27 +24,263 @@ pub struct ClassFile {
     pub attributes: Vec<AttributeInfo>,
 }
 
-pub fn class_file_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], ClassFile> {
-	let (input, magic) = be_u32::<&[u8], E>(input).expect("Failed to read 'magic'");
-	let (input, minor_version) = be_u16::<&[u8], E>(input).expect("Failed to read 'minor_version'");
-	let (input, major_version) = be_u16::<&[u8], E>(input).expect("Failed to read 'major_version'");
+impl ClassFile {
+	/// Writes the class file, taking all counts and lengths from the vectors rather than the stored `*_count` fields.
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u4(writer, self.magic)?;
+		write_u2(writer, self.minor_version)?;
+		write_u2(writer, self.major_version)?;
+		self.constant_pool.write_to(writer)?;
+		write_u2(writer, self.access_flags)?;
+		write_u2(writer, self.this_class)?;
+		write_u2(writer, self.super_class)?;
+		write_length_u2(writer, self.interfaces.len(), "interfaces")?;
+		for interface in &self.interfaces {
+			write_u2(writer, *interface)?;
+		}
+		write_length_u2(writer, self.fields.len(), "fields")?;
+		for field in &self.fields {
+			field.write_to(writer)?;
+		}
+		write_length_u2(writer, self.methods.len(), "methods")?;
+		for method in &self.methods {
+			method.write_to(writer)?;
+		}
+		write_attributes(writer, &self.attributes)
+	}
+
+	pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		self.write_to(&mut bytes)?;
+		Ok(bytes)
+	}
+
+	pub fn access(&self) -> ClassAccess {
+		ClassAccess(self.access_flags)
+	}
+
+	pub fn annotations(&self, visibility: Visibility) -> Result<Vec<Annotation>, ClassFormatError> {
+		annotations::annotations(&self.attributes, &self.constant_pool, visibility)
+	}
+
+	pub fn type_annotations(&self, visibility: Visibility) -> Result<Vec<TypeAnnotation>, ClassFormatError> {
+		annotations::type_annotations(&self.attributes, &self.constant_pool, visibility)
+	}
+
+	/// Generic signature, which only classes with type parameters or generic supertypes have.
+	pub fn signature(&self) -> Result<Option<ClassSignature>, ClassFormatError> {
+		match signature::signature(&self.attributes, &self.constant_pool)? {
+			Some(signature) => Ok(Some(parse_class_signature(&signature)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Name of the source file without any directories, e.g. `Main.java`.
+	pub fn source_file(&self) -> Result<Option<String>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "SourceFile")? {
+			Some(attribute) => {
+				let source_file = attribute.parse("SourceFile", source_file_attribute_parser)?;
+				Ok(Some(self.constant_pool.get_utf8_str(source_file.sourcefile_index)?.into_owned()))
+			},
+			None => Ok(None),
+		}
+	}
+
+	pub fn source_debug_extension(&self) -> Result<Option<JavaString>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "SourceDebugExtension")? {
+			Some(attribute) => {
+				let source_debug_extension = attribute.parse("SourceDebugExtension", source_debug_extension_attribute_parser)?;
+				let debug_extension = mutf8::decode(&source_debug_extension.debug_extension).map_err(|error| ClassFormatError::new(&source_debug_extension.debug_extension[error.position..], ClassFormatErrorKind::MalformedUtf8(error)).locate(&source_debug_extension.debug_extension))?;
+				Ok(Some(debug_extension))
+			},
+			None => Ok(None),
+		}
+	}
+
+	pub fn bootstrap_methods(&self) -> Result<Option<BootstrapMethodsAttribute>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "BootstrapMethods")? {
+			Some(attribute) => Ok(Some(attribute.parse("BootstrapMethods", bootstrap_methods_attribute_parser)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Resolves the `InvokeDynamic` or `Dynamic` entry at `index`.
+	///
+	/// This decodes `BootstrapMethods` on every call, use `BootstrapMethodsAttribute::resolve_call_site` to resolve many call sites.
+	pub fn call_site(&self, index: U2) -> Result<CallSite, ClassFormatError> {
+		self.bootstrap_methods()?.unwrap_or_default().resolve_call_site(&self.constant_pool, index)
+	}
+
+	/// Drives `visitor` with the contents of this class, see `visitor::accept`.
+	pub fn accept(&self, visitor: &mut dyn ClassVisitor) -> Result<(), ClassFormatError> {
+		visitor::accept(self, visitor)
+	}
+
+	/// Decodes the `Module` attribute, which only `module-info.class` has.
+	pub fn module(&self) -> Result<Option<ModuleDescriptor>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "Module")? {
+			Some(attribute) => Ok(Some(attribute.parse("Module", module_attribute_parser)?.resolve(&self.constant_pool)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Packages of the module in internal form, `None` if the `ModulePackages` attribute is missing.
+	pub fn module_packages(&self) -> Result<Option<Vec<String>>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "ModulePackages")? {
+			Some(attribute) => Ok(Some(attribute.parse("ModulePackages", module_packages_attribute_parser)?.resolve(&self.constant_pool)?)),
+			None => Ok(None),
+		}
+	}
+
+	pub fn module_main_class(&self) -> Result<Option<String>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "ModuleMainClass")? {
+			Some(attribute) => {
+				let main_class = attribute.parse("ModuleMainClass", module_main_class_attribute_parser)?;
+				Ok(Some(self.constant_pool.get_class_name(main_class.main_class_index)?.into_owned()))
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Host of the nest this class belongs to, `None` if the class is its own nest host.
+	pub fn nest_host(&self) -> Result<Option<String>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "NestHost")? {
+			Some(attribute) => {
+				let nest_host = attribute.parse("NestHost", nest_host_attribute_parser)?;
+				Ok(Some(self.constant_pool.get_class_name(nest_host.host_class_index)?.into_owned()))
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Other members of the nest hosted by this class, empty for nest members and classes without nestmates.
+	pub fn nest_members(&self) -> Result<Vec<String>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "NestMembers")? {
+			Some(attribute) => Ok(attribute.parse("NestMembers", class_list_attribute_parser)?.resolve(&self.constant_pool)?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Classes allowed to extend or implement this class, `None` unless it is sealed.
+	pub fn permitted_subclasses(&self) -> Result<Option<Vec<String>>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "PermittedSubclasses")? {
+			Some(attribute) => Ok(Some(attribute.parse("PermittedSubclasses", class_list_attribute_parser)?.resolve(&self.constant_pool)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Whether the class has a `Record` attribute.
+	pub fn is_record(&self) -> Result<bool, ConstantPoolError> {
+		Ok(find_attribute(&self.attributes, &self.constant_pool, "Record")?.is_some())
+	}
+
+	/// Components of a record class in declaration order, empty if it is not a record.
+	pub fn record_components(&self) -> Result<Vec<RecordComponentInfo>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "Record")? {
+			Some(attribute) => Ok(attribute.parse("Record", record_attribute_parser)?.components),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Entries of the `InnerClasses` attribute, covering the nested classes of this class and every nested class it refers to.
+	pub fn inner_classes(&self) -> Result<Vec<InnerClass>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "InnerClasses")? {
+			Some(attribute) => Ok(attribute.parse("InnerClasses", inner_classes_attribute_parser)?.resolve(&self.constant_pool)?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Enclosing class and method, which only local and anonymous classes have.
+	pub fn enclosing_method(&self) -> Result<Option<EnclosingMethod>, ClassFormatError> {
+		match find_attribute(&self.attributes, &self.constant_pool, "EnclosingMethod")? {
+			Some(attribute) => Ok(Some(attribute.parse("EnclosingMethod", enclosing_method_attribute_parser)?.resolve(&self.constant_pool)?)),
+			None => Ok(None),
+		}
+	}
+}
+
+/// Class file that borrows its `Utf8` constants and attribute contents from the input.
+///
+/// Parsing this way avoids most allocations when only part of many class files is of interest, e.g. their names.
+#[derive(Debug)]
+pub struct ClassFileRef<'a> {
+	pub magic: U4,
+	pub minor_version: U2,
+	pub major_version: U2,
+	pub constant_pool_count: U2,
+	pub constant_pool: ConstantPoolRef<'a>,
+	pub access_flags: U2,
+	pub this_class: U2,
+	pub super_class: U2,
+	pub interfaces_count: U2,
+	pub interfaces: Vec<U2>,
+	pub fields_count: U2,
+	pub fields: Vec<FieldInfoRef<'a>>,
+	pub methods_count: U2,
+	pub methods: Vec<MethodInfoRef<'a>>,
+	pub attributes_count: U2,
+	pub attributes: Vec<AttributeInfoRef<'a>>,
+}
+
+impl<'a> ClassFileRef<'a> {
+	pub fn access(&self) -> ClassAccess {
+		ClassAccess(self.access_flags)
+	}
+
+	pub fn this_class_name(&self) -> Result<Cow<'a, str>, ConstantPoolError> {
+		self.constant_pool.get_class_name(self.this_class)
+	}
+
+	/// Name of the superclass, `None` for `java/lang/Object` and modules.
+	pub fn super_class_name(&self) -> Result<Option<Cow<'a, str>>, ConstantPoolError> {
+		match self.super_class {
+			0 => Ok(None),
+			index => self.constant_pool.get_class_name(index).map(Some),
+		}
+	}
+
+	/// Copies everything borrowed from the input, giving the same `ClassFile` as `parse_class_file` would.
+	pub fn into_owned(self) -> ClassFile {
+		ClassFile {
+			magic: self.magic,
+			minor_version: self.minor_version,
+			major_version: self.major_version,
+			constant_pool_count: self.constant_pool_count,
+			constant_pool: self.constant_pool.into_owned(),
+			access_flags: self.access_flags,
+			this_class: self.this_class,
+			super_class: self.super_class,
+			interfaces_count: self.interfaces_count,
+			interfaces: self.interfaces,
+			fields_count: self.fields_count,
+			fields: self.fields.into_iter().map(FieldInfoRef::into_owned).collect(),
+			methods_count: self.methods_count,
+			methods: self.methods.into_iter().map(MethodInfoRef::into_owned).collect(),
+			attributes_count: self.attributes_count,
+			attributes: self.attributes.into_iter().map(AttributeInfoRef::into_owned).collect(),
+		}
+	}
+}
+
+pub fn class_file_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ClassFile, E> {
+	let (input, magic) = context("magic", be_u32)(input)?;
+	let (input, minor_version) = context("minor_version", be_u16)(input)?;
+	let (input, major_version) = context("major_version", be_u16)(input)?;
 	
-	let (input, constant_pool) = length_count(map(be_u16, |i| {
-		i - 1
-	}), cp_info_parser::<'a, E>)(input).expect("Failed to read 'constant_pool_count' or 'constant_pool'");
+	let (input, constant_pool) = context("constant_pool", constant_pool_parser::<'a, E>)(input)?;
 	let constant_pool_count = constant_pool.len() as U2;
 
-	let (input, access_flags) = be_u16::<&[u8], E>(input).expect("Failed to read 'access_flags'");
-	let (input, this_class) = be_u16::<&[u8], E>(input).expect("Failed to read 'this_class'");
-	let (input, super_class) = be_u16::<&[u8], E>(input).expect("Failed to read 'super_class'");
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+	let (input, this_class) = context("this_class", be_u16)(input)?;
+	let (input, super_class) = context("super_class", be_u16)(input)?;
 
-	let (input, interfaces) = length_count(be_u16::<&[u8], E>, be_u16::<&[u8], E>)(input).expect("Failed to read 'interfaces_count' or 'interfaces'");
+	let (input, interfaces) = context("interfaces", indexed_length_count(be_u16, be_u16))(input)?;
 	let interfaces_count = interfaces.len() as U2;
-	let (input, fields) = length_count(be_u16, field_info_parser::<'a, E>)(input).expect("Failed to read 'fields_count' or 'fields'");
+	let (input, fields) = context("fields", indexed_length_count(be_u16, field_info_parser::<'a, E>))(input)?;
 	let fields_count = fields.len() as U2;
-	let (input, methods) = length_count(be_u16, method_info_parser::<'a, E>)(input).expect("Failed to read 'methods_count' or 'methods'");
+	let (input, methods) = context("methods", indexed_length_count(be_u16, method_info_parser::<'a, E>))(input)?;
 	let methods_count = methods.len() as U2;
-	let (input, attributes) = length_count(be_u16, attribute_info_parser::<'a, E>)(input).expect("Failed to read 'attributes_count' or 'attributes'");
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_parser::<'a, E>))(input)?;
 	let attributes_count = attributes.len() as U2;
 
 	Ok((input, ClassFile {
@@ -63,4 +301,55 @@ pub fn class_file_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'
 		attributes_count,
 		attributes,
 	}))
+}
+
+/// Parses a complete class file, reporting failures with their byte offset into `input`.
+pub fn parse_class_file(input: &[u8]) -> Result<ClassFile, ClassFormatError> {
+	parse_all(input, class_file_parser::<ClassFormatError>)
+}
+
+pub fn class_file_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ClassFileRef<'a>, E> {
+	let (input, magic) = context("magic", be_u32)(input)?;
+	let (input, minor_version) = context("minor_version", be_u16)(input)?;
+	let (input, major_version) = context("major_version", be_u16)(input)?;
+	
+	let (input, constant_pool) = context("constant_pool", constant_pool_ref_parser::<'a, E>)(input)?;
+	let constant_pool_count = constant_pool.len() as U2;
+
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+	let (input, this_class) = context("this_class", be_u16)(input)?;
+	let (input, super_class) = context("super_class", be_u16)(input)?;
+
+	let (input, interfaces) = context("interfaces", indexed_length_count(be_u16, be_u16))(input)?;
+	let interfaces_count = interfaces.len() as U2;
+	let (input, fields) = context("fields", indexed_length_count(be_u16, field_info_ref_parser::<'a, E>))(input)?;
+	let fields_count = fields.len() as U2;
+	let (input, methods) = context("methods", indexed_length_count(be_u16, method_info_ref_parser::<'a, E>))(input)?;
+	let methods_count = methods.len() as U2;
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_ref_parser::<'a, E>))(input)?;
+	let attributes_count = attributes.len() as U2;
+
+	Ok((input, ClassFileRef {
+		magic,
+		minor_version,
+		major_version,
+		constant_pool_count,
+		constant_pool,
+		access_flags,
+		this_class,
+		super_class,
+		interfaces_count,
+		interfaces,
+		fields_count,
+		fields,
+		methods_count,
+		methods,
+		attributes_count,
+		attributes,
+	}))
+}
+
+/// Parses a complete class file without copying its strings and attributes.
+pub fn parse_class_file_ref(input: &[u8]) -> Result<ClassFileRef<'_>, ClassFormatError> {
+	parse_all(input, class_file_ref_parser::<ClassFormatError>)
 }
\ No newline at end of file
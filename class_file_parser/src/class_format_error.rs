@@ -0,0 +1,254 @@
+use std::fmt::Display;
+
+use nom::{error::{ContextError, ErrorKind, ParseError, VerboseError}, IResult, InputLength, Parser};
+
+use crate::{access_flags::AccessFlagsError, constant_pool::ConstantPoolError, descriptor::DescriptorError, instruction::DecodeError, mutf8::MUtf8Error, signature::SignatureError, U1, U2};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassFormatErrorKind {
+	UnexpectedEof,
+	UnknownConstantTag(U1),
+	TrailingBytes(usize),
+	ConstantPool(ConstantPoolError),
+	MalformedUtf8(MUtf8Error),
+	InvalidTag(U1),
+	InvalidDescriptor(DescriptorError),
+	InvalidSignature(SignatureError),
+	InvalidAccessFlags(AccessFlagsError),
+	InvalidStackMapFrame(usize),
+	InvalidBootstrapMethodIndex(U2),
+	CyclicDynamicConstant(U2),
+	InvalidInstruction(DecodeError),
+	/// Offset into the code of a method that is not the start of an instruction.
+	InvalidCodeOffset(i64),
+	/// Values nest deeper than the given limit, e.g. `MAX_ELEMENT_VALUE_DEPTH` for element values.
+	NestingTooDeep(usize),
+	Nom(ErrorKind),
+}
+
+impl Display for ClassFormatErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnexpectedEof => write!(f, "unexpected end of input"),
+			Self::UnknownConstantTag(tag) => write!(f, "unknown constant tag '{tag}'"),
+			Self::TrailingBytes(count) => write!(f, "{count} trailing bytes after end of class file"),
+			Self::ConstantPool(error) => write!(f, "{error}"),
+			Self::MalformedUtf8(error) => write!(f, "{error}"),
+			Self::InvalidTag(tag) => write!(f, "invalid tag '{tag}'"),
+			Self::InvalidDescriptor(error) => write!(f, "{error}"),
+			Self::InvalidSignature(error) => write!(f, "{error}"),
+			Self::InvalidAccessFlags(error) => write!(f, "{error}"),
+			Self::InvalidStackMapFrame(index) => write!(f, "stack map frame {index} does not fit the preceding frames"),
+			Self::InvalidBootstrapMethodIndex(index) => write!(f, "bootstrap method {index} does not exist"),
+			Self::CyclicDynamicConstant(index) => write!(f, "dynamic constant {index} depends on itself"),
+			Self::InvalidInstruction(error) => write!(f, "{error}"),
+			Self::InvalidCodeOffset(offset) => write!(f, "code offset {offset} is not the start of an instruction"),
+			Self::NestingTooDeep(limit) => write!(f, "values are nested more than {limit} levels deep"),
+			Self::Nom(kind) => write!(f, "parser error '{}'", kind.description()),
+		}
+	}
+}
+
+impl From<ConstantPoolError> for ClassFormatErrorKind {
+	fn from(value: ConstantPoolError) -> Self {
+		Self::ConstantPool(value)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+	Field(&'static str),
+	Index(usize),
+}
+
+/// Error describing why a class file could not be parsed.
+///
+/// Parsers report where they failed as the number of bytes that were left in their input.
+/// Call `locate` with the complete input to turn that into a byte offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassFormatError {
+	remaining: usize,
+	offset: usize,
+	path: Vec<PathSegment>,
+	kind: ClassFormatErrorKind,
+}
+
+impl ClassFormatError {
+	pub fn new(input: &[u8], kind: ClassFormatErrorKind) -> Self {
+		Self {
+			remaining: input.len(),
+			offset: 0,
+			path: Vec::new(),
+			kind,
+		}
+	}
+
+	pub fn locate(mut self, input: &[u8]) -> Self {
+		self.offset = input.len().saturating_sub(self.remaining);
+		self
+	}
+
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	pub fn kind(&self) -> &ClassFormatErrorKind {
+		&self.kind
+	}
+
+	/// Structure that was being parsed, e.g. `constant_pool[17].Utf8.bytes`.
+	pub fn path(&self) -> String {
+		let mut path = String::new();
+		for segment in self.path.iter().rev() {
+			match segment {
+				PathSegment::Field(name) => {
+					if !path.is_empty() {
+						path.push('.');
+					}
+					path.push_str(name);
+				},
+				PathSegment::Index(index) => path.push_str(&format!("[{index}]")),
+			}
+		}
+		path
+	}
+}
+
+impl Display for ClassFormatError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let path = self.path();
+		if path.is_empty() {
+			write!(f, "ClassFormatError at offset {}: {}", self.offset, self.kind)
+		} else {
+			write!(f, "ClassFormatError at offset {} in '{path}': {}", self.offset, self.kind)
+		}
+	}
+}
+
+impl std::error::Error for ClassFormatError {}
+
+impl From<DescriptorError> for ClassFormatError {
+	fn from(value: DescriptorError) -> Self {
+		Self::new(&[], ClassFormatErrorKind::InvalidDescriptor(value))
+	}
+}
+
+impl From<SignatureError> for ClassFormatError {
+	fn from(value: SignatureError) -> Self {
+		Self::new(&[], ClassFormatErrorKind::InvalidSignature(value))
+	}
+}
+
+impl From<AccessFlagsError> for ClassFormatError {
+	fn from(value: AccessFlagsError) -> Self {
+		Self::new(&[], ClassFormatErrorKind::InvalidAccessFlags(value))
+	}
+}
+
+impl From<ConstantPoolError> for ClassFormatError {
+	fn from(value: ConstantPoolError) -> Self {
+		Self::new(&[], ClassFormatErrorKind::ConstantPool(value))
+	}
+}
+
+impl ParseError<&[u8]> for ClassFormatError {
+	fn from_error_kind(input: &[u8], kind: ErrorKind) -> Self {
+		let kind = match kind {
+			ErrorKind::Eof => ClassFormatErrorKind::UnexpectedEof,
+			kind => ClassFormatErrorKind::Nom(kind),
+		};
+		Self::new(input, kind)
+	}
+
+	fn append(_input: &[u8], _kind: ErrorKind, other: Self) -> Self {
+		other
+	}
+}
+
+impl ContextError<&[u8]> for ClassFormatError {
+	fn add_context(_input: &[u8], ctx: &'static str, mut other: Self) -> Self {
+		other.path.push(PathSegment::Field(ctx));
+		other
+	}
+}
+
+/// Error types the class file parsers can report into.
+///
+/// Besides nom's `context` this allows attaching table indices and class file specific failures.
+/// Error types that cannot carry this information fall back to the plain nom error kinds.
+pub trait ClassParseError<I>: ParseError<I> + ContextError<I> {
+	fn add_index(_input: I, _index: usize, other: Self) -> Self {
+		other
+	}
+
+	fn from_class_format_error(input: I, _kind: ClassFormatErrorKind) -> Self {
+		Self::from_error_kind(input, ErrorKind::Verify)
+	}
+}
+
+impl ClassParseError<&[u8]> for ClassFormatError {
+	fn add_index(_input: &[u8], index: usize, mut other: Self) -> Self {
+		other.path.push(PathSegment::Index(index));
+		other
+	}
+
+	fn from_class_format_error(input: &[u8], kind: ClassFormatErrorKind) -> Self {
+		Self::new(input, kind)
+	}
+}
+
+impl<I> ClassParseError<I> for () {}
+
+impl<I> ClassParseError<I> for nom::error::Error<I> {}
+
+impl<I> ClassParseError<I> for VerboseError<I> {}
+
+/// Attaches `index` to errors produced by `parser`, like nom's `context` does for names.
+pub fn indexed<I: Clone, O, E: ClassParseError<I>, F>(index: usize, mut parser: F) -> impl FnMut(I) -> IResult<I, O, E>
+where
+	F: Parser<I, O, E>,
+{
+	move |input: I| match parser.parse(input.clone()) {
+		Ok(v) => Ok(v),
+		Err(nom::Err::Error(e)) => Err(nom::Err::Error(E::add_index(input, index, e))),
+		Err(nom::Err::Failure(e)) => Err(nom::Err::Failure(E::add_index(input, index, e))),
+		Err(e) => Err(e),
+	}
+}
+
+/// Like nom's `length_count`, but tags errors of each element with its index.
+pub fn indexed_length_count<I: Clone + InputLength, O, N: Into<usize>, E: ClassParseError<I>, F, G>(mut count: F, mut parser: G) -> impl FnMut(I) -> IResult<I, Vec<O>, E>
+where
+	F: Parser<I, N, E>,
+	G: Parser<I, O, E>,
+{
+	move |input: I| {
+		let (mut input, count) = count.parse(input)?;
+		let count = count.into();
+		let mut result = Vec::with_capacity(count.min(input.input_len()));
+		for index in 0..count {
+			let (rest, value) = indexed(index, |i| parser.parse(i))(input)?;
+			input = rest;
+			result.push(value);
+		}
+		Ok((input, result))
+	}
+}
+
+/// Runs `parser` over all of `input`, reporting errors with their offset into `input`.
+pub fn parse_all<'a, T, F>(input: &'a [u8], mut parser: F) -> Result<T, ClassFormatError>
+where
+	F: Parser<&'a [u8], T, ClassFormatError>,
+{
+	match parser.parse(input) {
+		Ok((rest, _)) if !rest.is_empty() => Err(ClassFormatError::new(rest, ClassFormatErrorKind::TrailingBytes(rest.len())).locate(input)),
+		Ok((_, value)) => Ok(value),
+		Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(e.locate(input)),
+		Err(nom::Err::Incomplete(_)) => Err(ClassFormatError::new(&[], ClassFormatErrorKind::UnexpectedEof).locate(input)),
+	}
+}
+
+/// Fails with `kind` without consuming any input.
+pub fn fail_with<I, O, E: ClassParseError<I>>(input: I, kind: ClassFormatErrorKind) -> IResult<I, O, E> {
+	Err(nom::Err::Error(E::from_class_format_error(input, kind)))
+}
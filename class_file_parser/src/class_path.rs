@@ -0,0 +1,351 @@
+use std::{borrow::Cow, collections::{BTreeMap, HashMap}, env, ffi::OsStr, fmt::Display, fs, io, path::{Path, PathBuf}, sync::{Arc, Mutex, PoisonError}};
+
+use crate::{access_flags::ClassAccess, archive::{ArchiveError, JarFile}, class_file::{parse_class_file, ClassFile}, class_format_error::ClassFormatError, verifier::ClassHierarchy, U2};
+
+#[derive(Debug)]
+pub enum ClassPathError {
+	Io {
+		path: PathBuf,
+		error: io::Error,
+	},
+	Archive {
+		path: PathBuf,
+		error: Box<ArchiveError>,
+	},
+	InvalidClass {
+		class_name: String,
+		entry: String,
+		error: Box<ClassFormatError>,
+	},
+}
+
+impl Display for ClassPathError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io { path, error } => write!(f, "{}: {error}", path.display()),
+			Self::Archive { path, error } => write!(f, "{}: {error}", path.display()),
+			Self::InvalidClass { class_name, entry, error } => write!(f, "class '{class_name}' in {entry}: {error}"),
+		}
+	}
+}
+
+impl std::error::Error for ClassPathError {}
+
+/// Location classes are loaded from.
+#[derive(Debug)]
+pub enum ClassPathEntry {
+	/// Directory holding class files in subdirectories of their packages.
+	Directory(PathBuf),
+	Jar {
+		path: PathBuf,
+		jar_file: JarFile,
+	},
+	/// Class files by internal name, e.g. for classes generated by tests.
+	Memory(HashMap<String, Vec<u8>>),
+}
+
+impl ClassPathEntry {
+	/// Opens `path` as a jar file if it is a file, otherwise uses it as a directory.
+	pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self, ClassPathError> {
+		let path = path.into();
+		if path.is_file() {
+			match JarFile::open(&path) {
+				Ok(jar_file) => Ok(Self::Jar {
+					path,
+					jar_file,
+				}),
+				Err(error) => Err(ClassPathError::Archive {
+					path,
+					error: Box::new(error),
+				}),
+			}
+		} else {
+			Ok(Self::Directory(path))
+		}
+	}
+
+	pub fn contains_class(&self, class_name: &str, release: Option<U2>) -> bool {
+		if !is_valid_class_name(class_name) {
+			return false;
+		}
+		match self {
+			Self::Directory(directory) => directory.join(format!("{class_name}.class")).is_file(),
+			Self::Jar { jar_file, .. } => jar_file.class_entry(class_name, release).is_some(),
+			Self::Memory(classes) => classes.contains_key(class_name),
+		}
+	}
+
+	/// Contents of the class file for `class_name`, `None` if this entry does not contain it.
+	pub fn read_class(&self, class_name: &str, release: Option<U2>) -> Result<Option<Cow<'_, [u8]>>, ClassPathError> {
+		if !is_valid_class_name(class_name) {
+			return Ok(None);
+		}
+		match self {
+			Self::Directory(directory) => {
+				let path = directory.join(format!("{class_name}.class"));
+				match fs::read(&path) {
+					Ok(bytes) => Ok(Some(Cow::Owned(bytes))),
+					Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+					Err(error) => Err(ClassPathError::Io {
+						path,
+						error,
+					}),
+				}
+			},
+			Self::Jar { path, jar_file } => match jar_file.class_entry(class_name, release) {
+				Some(entry) => jar_file.read(entry).map(Some).map_err(|error| ClassPathError::Archive {
+					path: path.clone(),
+					error: Box::new(error),
+				}),
+				None => Ok(None),
+			},
+			Self::Memory(classes) => Ok(classes.get(class_name).map(|bytes| Cow::Borrowed(bytes.as_slice()))),
+		}
+	}
+
+	/// Internal names of all classes in this entry, sorted.
+	pub fn class_names(&self, release: Option<U2>) -> Result<Vec<String>, ClassPathError> {
+		match self {
+			Self::Directory(directory) => {
+				let mut class_names = Vec::new();
+				collect_class_names(directory, "", &mut class_names)?;
+				class_names.sort();
+				Ok(class_names)
+			},
+			Self::Jar { jar_file, .. } => Ok(jar_file.class_names(release).into_iter().map(str::to_string).collect()),
+			Self::Memory(classes) => {
+				let mut class_names: Vec<String> = classes.keys().cloned().collect();
+				class_names.sort();
+				Ok(class_names)
+			},
+		}
+	}
+}
+
+impl Display for ClassPathEntry {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Directory(path) | Self::Jar { path, .. } => write!(f, "{}", path.display()),
+			Self::Memory(_) => write!(f, "<memory>"),
+		}
+	}
+}
+
+/// Rejects names that could leave a directory entry, such as `../Secret`.
+fn is_valid_class_name(class_name: &str) -> bool {
+	class_name.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != ".." && !segment.contains('\\'))
+}
+
+fn collect_class_names(directory: &Path, package: &str, class_names: &mut Vec<String>) -> Result<(), ClassPathError> {
+	let io_error = |error| ClassPathError::Io {
+		path: directory.to_path_buf(),
+		error,
+	};
+	for dir_entry in fs::read_dir(directory).map_err(io_error)? {
+		let dir_entry = dir_entry.map_err(io_error)?;
+		let path = dir_entry.path();
+		let Some(name) = path.file_name().and_then(OsStr::to_str) else {
+			continue;
+		};
+		if dir_entry.file_type().map_err(io_error)?.is_dir() {
+			collect_class_names(&path, &format!("{package}{name}/"), class_names)?;
+		} else if let Some(simple_name) = name.strip_suffix(".class") {
+			class_names.push(format!("{package}{simple_name}"));
+		}
+	}
+	Ok(())
+}
+
+/// Class that is found in more than one entry of a class path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateClass {
+	pub class_name: String,
+	/// Indices of the entries containing the class, the first one shadows the others.
+	pub entries: Vec<usize>,
+}
+
+/// Class file contents and the index of the entry they were read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassBytes<'a> {
+	pub entry: usize,
+	pub bytes: Cow<'a, [u8]>,
+}
+
+/// Ordered entries classes are looked up in, where the first entry containing a class wins.
+///
+/// Parsed classes are cached, including the classes that could not be found.
+#[derive(Debug, Default)]
+pub struct ClassPath {
+	entries: Vec<ClassPathEntry>,
+	release: Option<U2>,
+	cache: Mutex<HashMap<String, Option<Arc<ClassFile>>>>,
+}
+
+impl ClassPath {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Opens each path of a class path string such as `lib/a.jar:classes`, separated like `PATH` on this platform.
+	pub fn parse(class_path: &str) -> Result<Self, ClassPathError> {
+		let mut result = Self::new();
+		for path in env::split_paths(class_path).filter(|path| !path.as_os_str().is_empty()) {
+			result.push(ClassPathEntry::open(path)?);
+		}
+		Ok(result)
+	}
+
+	/// Java version multi-release jars are resolved for, `None` uses their base entries only.
+	pub fn with_release(mut self, release: U2) -> Self {
+		self.release = Some(release);
+		self.clear_cache();
+		self
+	}
+
+	pub fn push(&mut self, entry: ClassPathEntry) {
+		self.entries.push(entry);
+		self.clear_cache();
+	}
+
+	pub fn entries(&self) -> &[ClassPathEntry] {
+		&self.entries
+	}
+
+	pub fn release(&self) -> Option<U2> {
+		self.release
+	}
+
+	pub fn clear_cache(&mut self) {
+		self.cache.get_mut().unwrap_or_else(PoisonError::into_inner).clear();
+	}
+
+	/// Index of the entry providing `class_name`, given in internal form such as `java/util/HashMap`.
+	pub fn locate(&self, class_name: &str) -> Option<usize> {
+		for (i, entry) in self.entries.iter().enumerate() {
+			if entry.contains_class(class_name, self.release) {
+				return Some(i);
+			}
+		}
+		None
+	}
+
+	/// Contents of the class file for `class_name` from the first entry containing it.
+	pub fn find_class_bytes(&self, class_name: &str) -> Result<Option<ClassBytes<'_>>, ClassPathError> {
+		for (i, entry) in self.entries.iter().enumerate() {
+			if let Some(bytes) = entry.read_class(class_name, self.release)? {
+				return Ok(Some(ClassBytes {
+					entry: i,
+					bytes,
+				}));
+			}
+		}
+		Ok(None)
+	}
+
+	/// Parses `class_name` from the first entry containing it, or returns the cached result of an earlier lookup.
+	pub fn find_class(&self, class_name: &str) -> Result<Option<Arc<ClassFile>>, ClassPathError> {
+		if let Some(cached) = self.cache.lock().unwrap_or_else(PoisonError::into_inner).get(class_name) {
+			return Ok(cached.clone());
+		}
+		let class_file = match self.find_class_bytes(class_name)? {
+			Some(found) => Some(Arc::new(parse_class_file(&found.bytes).map_err(|error| ClassPathError::InvalidClass {
+				class_name: class_name.to_string(),
+				entry: self.entries[found.entry].to_string(),
+				error: Box::new(error),
+			})?)),
+			None => None,
+		};
+		// Another thread may have parsed the class in the meantime, keep its result so all callers share one instance.
+		let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+		Ok(cache.entry(class_name.to_string()).or_insert(class_file).clone())
+	}
+
+	/// Classes found in several entries, sorted by name.
+	pub fn duplicates(&self) -> Result<Vec<DuplicateClass>, ClassPathError> {
+		let mut locations: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+		for (i, entry) in self.entries.iter().enumerate() {
+			for class_name in entry.class_names(self.release)? {
+				locations.entry(class_name).or_default().push(i);
+			}
+		}
+		Ok(locations.into_iter().filter(|(_, entries)| entries.len() > 1).map(|(class_name, entries)| DuplicateClass {
+			class_name,
+			entries,
+		}).collect())
+	}
+}
+
+/// Answers hierarchy queries from the classes on the class path, treating classes that cannot be loaded as unknown.
+impl ClassHierarchy for ClassPath {
+	fn is_interface(&self, class_name: &str) -> bool {
+		matches!(self.find_class(class_name), Ok(Some(class_file)) if class_file.access().contains(ClassAccess::INTERFACE))
+	}
+
+	fn super_class(&self, class_name: &str) -> Option<String> {
+		let class_file = self.find_class(class_name).ok()??;
+		if class_file.super_class == 0 {
+			return None;
+		}
+		class_file.constant_pool.get_class_name(class_file.super_class).ok().map(Cow::into_owned)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample_class_path() -> ClassPath {
+		let main = fs::read("./Main.class").unwrap();
+		let mut class_path = ClassPath::new().with_release(17);
+		class_path.push(ClassPathEntry::Memory(HashMap::from([("Sample".to_string(), main)])));
+		class_path.push(ClassPathEntry::open(".").unwrap());
+		class_path.push(ClassPathEntry::open("./Sample.jar").unwrap());
+		class_path
+	}
+
+	#[test]
+	fn find_classes_in_order() {
+		let class_path = sample_class_path();
+		assert!(matches!(class_path.entries()[2], ClassPathEntry::Jar { .. }));
+
+		let shadowed = class_path.find_class("Sample").unwrap().unwrap();
+		assert_eq!(shadowed.constant_pool.get_class_name(shadowed.this_class).unwrap(), "Main");
+		assert_eq!(class_path.locate("Sample$Point"), Some(1));
+		assert_eq!(class_path.locate("demo/App"), Some(2));
+		assert_eq!(class_path.find_class("demo/Greeter").unwrap().unwrap().major_version, 55);
+		assert!(class_path.find_class("demo/Missing").unwrap().is_none());
+		assert!(class_path.find_class("../class_file_parser/Main").unwrap().is_none());
+
+		let found = class_path.find_class_bytes("Main").unwrap().unwrap();
+		assert_eq!(found.entry, 1);
+		assert_eq!(found.bytes, fs::read("./Main.class").unwrap());
+	}
+
+	#[test]
+	fn cache_parsed_classes() {
+		let class_path = sample_class_path();
+		let first = class_path.find_class("Sample$Point").unwrap().unwrap();
+		let second = class_path.find_class("Sample$Point").unwrap().unwrap();
+		assert!(Arc::ptr_eq(&first, &second));
+		assert_eq!(class_path.super_class("Sample$Point").as_deref(), Some("java/lang/Record"));
+		assert!(class_path.is_interface("Sample$Shape"));
+		assert!(!class_path.is_interface("demo/App"));
+	}
+
+	#[test]
+	fn report_duplicates() {
+		let class_path = sample_class_path();
+		assert_eq!(class_path.duplicates().unwrap(), vec![DuplicateClass {
+			class_name: "Sample".to_string(),
+			entries: vec![0, 1],
+		}]);
+	}
+
+	#[test]
+	fn report_invalid_classes() {
+		let mut class_path = ClassPath::new();
+		class_path.push(ClassPathEntry::Memory(HashMap::from([("Broken".to_string(), vec![0xCA, 0xFE])])));
+		let error = class_path.find_class("Broken").unwrap_err();
+		assert!(error.to_string().starts_with("class 'Broken' in <memory>: ClassFormatError at offset 0 in 'magic'"), "{error}");
+	}
+}
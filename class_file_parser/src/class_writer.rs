@@ -0,0 +1,1409 @@
+use std::{collections::HashMap, fmt::Display, io};
+
+use crate::{access_flags::{ClassAccess, MethodAccess}, analysis::{compute_frames, compute_maxs, AnalyzeError}, attribute_info::{bootstrap_methods::{BootstrapMethod, BootstrapMethodsAttribute, CallSite, Constant}, code::{CodeAttribute, ExceptionTableEntry}, debug_info::{LineNumberTableAttribute, LineNumberTableEntry, LocalVariableTableAttribute, LocalVariableTableEntry}, stack_map_table::{initial_locals, FrameInfo, StackMapFrame, StackMapTable, VerificationType, VerificationTypeInfo}, AttributeBody, AttributeInfo}, class_file::ClassFile, class_format_error::ClassFormatError, constant_pool::{ConstantPoolBuilder, ConstantPoolError, MethodHandleRef}, descriptor::{parse_method_descriptor, DescriptorError}, field_info::FieldInfo, instruction::*, method_info::MethodInfo, verifier::{common_super_class, ClassHierarchy, ClassHierarchyMap, TYPE_CHECKING_MAJOR_VERSION}, visitor::{ClassVersion, ClassVisitor, FieldVisitor, FrameValue, Label, MethodVisitor}, U1, U2, U4};
+
+const MAGIC: U4 = 0xCAFEBABE;
+
+/// Largest `code_length` a method can have (JVMS 4.7.3).
+pub const MAX_CODE_LENGTH: usize = 65535;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteErrorKind {
+	ConstantPool(ConstantPoolError),
+	InvalidDescriptor(DescriptorError),
+	/// `opcode` was passed to the visitor method of a different kind of instruction.
+	InvalidOpcode {
+		opcode: U1,
+		event: &'static str,
+	},
+	InvalidOperand {
+		opcode: U1,
+		operand: i64,
+	},
+	/// A label was used but never passed to `visit_label`.
+	UnboundLabel,
+	CodeTooLarge(usize),
+	DuplicateFrame(usize),
+	/// The conditional branch at this pc was widened, which needs a frame after it that only `Compute::Frames` adds.
+	UnframedWideBranch(usize),
+	/// The code is not consistent enough to compute what `Compute` asks for.
+	Analyze(AnalyzeError),
+	/// A table of the class file has more entries than its count can hold.
+	TooLarge(String),
+}
+
+impl Display for WriteErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ConstantPool(error) => write!(f, "{error}"),
+			Self::InvalidDescriptor(error) => write!(f, "{error}"),
+			Self::InvalidOpcode { opcode, event } => write!(f, "opcode 0x{opcode:02X} cannot be passed to {event}"),
+			Self::InvalidOperand { opcode, operand } => write!(f, "operand {operand} is invalid for opcode 0x{opcode:02X}"),
+			Self::UnboundLabel => write!(f, "label is used but never visited"),
+			Self::CodeTooLarge(length) => write!(f, "code has {length} bytes, more than the maximum of {MAX_CODE_LENGTH}"),
+			Self::DuplicateFrame(pc) => write!(f, "more than one frame at pc {pc}"),
+			Self::UnframedWideBranch(pc) => write!(f, "branch at pc {pc} is widened and needs a frame at pc {}, which is only computed with Compute::Frames", pc + 8),
+			Self::Analyze(error) => write!(f, "{error}"),
+			Self::TooLarge(message) => write!(f, "{message}"),
+		}
+	}
+}
+
+impl From<ConstantPoolError> for WriteErrorKind {
+	fn from(value: ConstantPoolError) -> Self {
+		Self::ConstantPool(value)
+	}
+}
+
+impl From<AnalyzeError> for WriteErrorKind {
+	fn from(value: AnalyzeError) -> Self {
+		Self::Analyze(value)
+	}
+}
+
+impl From<DescriptorError> for WriteErrorKind {
+	fn from(value: DescriptorError) -> Self {
+		Self::InvalidDescriptor(value)
+	}
+}
+
+impl From<io::Error> for WriteErrorKind {
+	fn from(value: io::Error) -> Self {
+		Self::TooLarge(value.to_string())
+	}
+}
+
+/// Failure to write a class, with `method` naming the method it occurred in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteError {
+	pub method: Option<String>,
+	pub kind: WriteErrorKind,
+}
+
+impl Display for WriteError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.method {
+			Some(method) => write!(f, "WriteError in method '{method}': {}", self.kind),
+			None => write!(f, "WriteError: {}", self.kind),
+		}
+	}
+}
+
+impl std::error::Error for WriteError {}
+
+impl From<WriteErrorKind> for WriteError {
+	fn from(kind: WriteErrorKind) -> Self {
+		Self {
+			method: None,
+			kind,
+		}
+	}
+}
+
+/// What a `ClassWriter` computes itself instead of taking it from the events it receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compute {
+	#[default]
+	Nothing,
+	/// `max_stack` and `max_locals` of every method, `visit_maxs` is ignored.
+	Maxs,
+	/// Like `Maxs`, and the `StackMapTable` of every method in version 50 and later classes, `visit_frame` is ignored.
+	///
+	/// Types meeting at a branch target are merged with the help of the class hierarchy given to `ClassWriter::set_class_hierarchy`.
+	Frames,
+}
+
+/// Class hierarchy of a `ClassWriter`, which the class being written is added to.
+struct WriterHierarchy<'a> {
+	hierarchy: &'a dyn ClassHierarchy,
+	class_name: &'a str,
+	super_name: Option<String>,
+	is_interface: bool,
+}
+
+impl ClassHierarchy for WriterHierarchy<'_> {
+	fn is_interface(&self, class_name: &str) -> bool {
+		if class_name == self.class_name {
+			self.is_interface
+		} else {
+			self.hierarchy.is_interface(class_name)
+		}
+	}
+
+	fn super_class(&self, class_name: &str) -> Option<String> {
+		if class_name == self.class_name {
+			self.super_name.clone()
+		} else {
+			self.hierarchy.super_class(class_name)
+		}
+	}
+
+	fn common_super_class(&self, a: &str, b: &str) -> String {
+		if a == self.class_name || b == self.class_name {
+			common_super_class(self, a, b)
+		} else {
+			self.hierarchy.common_super_class(a, b)
+		}
+	}
+}
+
+/// `ClassVisitor` that builds a class file from the events it receives, e.g. at the end of a chain of adapters.
+///
+/// Errors are recorded when they occur and reported by `into_class_file`, later events are ignored.
+#[derive(Default)]
+pub struct ClassWriter {
+	constant_pool: ConstantPoolBuilder,
+	bootstrap_methods: Vec<BootstrapMethod>,
+	bootstrap_method_indices: HashMap<(U2, Vec<U2>), U2>,
+	compute: Compute,
+	hierarchy: Option<Box<dyn ClassHierarchy>>,
+	version: Option<ClassVersion>,
+	access_flags: U2,
+	class_name: String,
+	this_class: U2,
+	super_class: U2,
+	interfaces: Vec<U2>,
+	fields: Vec<FieldInfo>,
+	methods: Vec<MethodInfo>,
+	attributes: Vec<AttributeInfo>,
+	error: Option<WriteError>,
+}
+
+impl std::fmt::Debug for ClassWriter {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ClassWriter")
+			.field("compute", &self.compute)
+			.field("version", &self.version)
+			.field("class_name", &self.class_name)
+			.field("fields", &self.fields)
+			.field("methods", &self.methods)
+			.field("error", &self.error)
+			.finish_non_exhaustive()
+	}
+}
+
+impl ClassWriter {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Starts with the constant pool and bootstrap methods of `class_file`, for transforming it.
+	///
+	/// Attributes passed through `visit_attribute` then keep referring to the right constants, and unchanged constants keep their index.
+	pub fn from_class_file(class_file: &ClassFile) -> Result<Self, ClassFormatError> {
+		let bootstrap_methods = class_file.bootstrap_methods()?.unwrap_or_default().bootstrap_methods;
+		let mut bootstrap_method_indices = HashMap::new();
+		for (index, bootstrap_method) in bootstrap_methods.iter().enumerate() {
+			bootstrap_method_indices.entry((bootstrap_method.bootstrap_method_ref, bootstrap_method.bootstrap_arguments.clone())).or_insert(index as U2);
+		}
+		Ok(Self {
+			constant_pool: ConstantPoolBuilder::from_constant_pool(&class_file.constant_pool),
+			bootstrap_methods,
+			bootstrap_method_indices,
+			..Self::default()
+		})
+	}
+
+	pub fn set_compute(&mut self, compute: Compute) {
+		self.compute = compute;
+	}
+
+	/// Class hierarchy for computing frames, without it only the class being written and `java/lang/Object` are known.
+	pub fn set_class_hierarchy(&mut self, hierarchy: impl ClassHierarchy + 'static) {
+		self.hierarchy = Some(Box::new(hierarchy));
+	}
+
+	/// Constant pool of the class being written, e.g. to add constants for attributes passed to `visit_attribute`.
+	pub fn constant_pool(&mut self) -> &mut ConstantPoolBuilder {
+		&mut self.constant_pool
+	}
+
+	/// The written class, or the first error that occurred while writing it.
+	pub fn into_class_file(mut self) -> Result<ClassFile, WriteError> {
+		if let Some(error) = self.error {
+			return Err(error);
+		}
+		if !self.bootstrap_methods.is_empty() {
+			let bootstrap_methods = BootstrapMethodsAttribute {
+				num_bootstrap_methods: self.bootstrap_methods.len() as U2,
+				bootstrap_methods: std::mem::take(&mut self.bootstrap_methods),
+			};
+			let mut info = Vec::new();
+			bootstrap_methods.write_to(&mut info).map_err(WriteErrorKind::from)?;
+			let attribute = self.attribute("BootstrapMethods", info)?;
+			self.attributes.push(attribute);
+		}
+
+		let version = self.version.unwrap_or(ClassVersion {
+			major: 0,
+			minor: 0,
+		});
+		let constant_pool = self.constant_pool.build();
+		Ok(ClassFile {
+			magic: MAGIC,
+			minor_version: version.minor,
+			major_version: version.major,
+			constant_pool_count: constant_pool.len() as U2,
+			constant_pool,
+			access_flags: self.access_flags,
+			this_class: self.this_class,
+			super_class: self.super_class,
+			interfaces_count: self.interfaces.len() as U2,
+			interfaces: self.interfaces,
+			fields_count: self.fields.len() as U2,
+			fields: self.fields,
+			methods_count: self.methods.len() as U2,
+			methods: self.methods,
+			attributes_count: self.attributes.len() as U2,
+			attributes: self.attributes,
+		})
+	}
+
+	pub fn into_bytes(self) -> Result<Vec<u8>, WriteError> {
+		Ok(self.into_class_file()?.to_bytes().map_err(WriteErrorKind::from)?)
+	}
+
+	fn fail(&mut self, method: Option<String>, kind: WriteErrorKind) {
+		if self.error.is_none() {
+			self.error = Some(WriteError {
+				method,
+				kind,
+			});
+		}
+	}
+
+	fn record<T>(&mut self, result: Result<T, WriteErrorKind>) -> Option<T> {
+		result.map_err(|kind| self.fail(None, kind)).ok()
+	}
+
+	fn attribute(&mut self, name: &str, info: Vec<U1>) -> Result<AttributeInfo, WriteErrorKind> {
+		Ok(AttributeInfo {
+			attribute_name_index: self.constant_pool.utf8(name)?,
+			attribute_length: info.len() as U4,
+			info,
+		})
+	}
+
+	/// Attribute holding a single constant pool index, such as `Signature` or `SourceFile`.
+	fn index_attribute(&mut self, name: &str, index: U2) -> Result<AttributeInfo, WriteErrorKind> {
+		self.attribute(name, index.to_be_bytes().to_vec())
+	}
+
+	/// Adds `constant` as it is loaded by `ldc` or passed to a bootstrap method.
+	pub fn constant(&mut self, constant: &Constant) -> Result<U2, ConstantPoolError> {
+		match constant {
+			Constant::Integer(value) => self.constant_pool.integer(*value),
+			Constant::Float(value) => self.constant_pool.float(*value),
+			Constant::Long(value) => self.constant_pool.long(*value),
+			Constant::Double(value) => self.constant_pool.double(*value),
+			Constant::String(value) => self.constant_pool.java_string(value),
+			Constant::Class(class_name) => self.constant_pool.class(class_name),
+			Constant::MethodType(descriptor) => self.constant_pool.method_type(descriptor),
+			Constant::MethodHandle(method_handle) => self.constant_pool.method_handle(method_handle),
+			Constant::Dynamic(call_site) => {
+				let bootstrap_method_attr_index = self.bootstrap_method(&call_site.bootstrap_method, &call_site.static_arguments)?;
+				self.constant_pool.dynamic(bootstrap_method_attr_index, &call_site.name, &call_site.descriptor)
+			},
+		}
+	}
+
+	/// Adds an entry to the `BootstrapMethods` attribute unless an equal one exists, returning its index.
+	pub fn bootstrap_method(&mut self, method_handle: &MethodHandleRef, static_arguments: &[Constant]) -> Result<U2, ConstantPoolError> {
+		let bootstrap_method_ref = self.constant_pool.method_handle(method_handle)?;
+		let bootstrap_arguments = static_arguments.iter().map(|argument| self.constant(argument)).collect::<Result<Vec<_>, _>>()?;
+		let key = (bootstrap_method_ref, bootstrap_arguments);
+		if let Some(index) = self.bootstrap_method_indices.get(&key) {
+			return Ok(*index);
+		}
+		let index = self.bootstrap_methods.len() as U2;
+		self.bootstrap_methods.push(BootstrapMethod {
+			bootstrap_method_ref,
+			num_bootstrap_arguments: key.1.len() as U2,
+			bootstrap_arguments: key.1.clone(),
+		});
+		self.bootstrap_method_indices.insert(key, index);
+		Ok(index)
+	}
+}
+
+impl ClassVisitor for ClassWriter {
+	fn visit(&mut self, version: ClassVersion, access: U2, name: &str, signature: Option<&str>, super_name: Option<&str>, interfaces: &[&str]) {
+		let result = (|| {
+			self.this_class = self.constant_pool.class(name)?;
+			self.super_class = match super_name {
+				Some(super_name) => self.constant_pool.class(super_name)?,
+				None => 0,
+			};
+			self.interfaces = interfaces.iter().map(|interface| self.constant_pool.class(interface)).collect::<Result<_, _>>()?;
+			if let Some(signature) = signature {
+				let signature_index = self.constant_pool.utf8(signature)?;
+				let attribute = self.index_attribute("Signature", signature_index)?;
+				self.attributes.push(attribute);
+			}
+			Ok(())
+		})();
+		self.version = Some(version);
+		self.access_flags = access;
+		self.class_name = name.to_string();
+		self.record(result);
+	}
+
+	fn visit_source(&mut self, source: &str) {
+		let result = self.constant_pool.utf8(source).map_err(WriteErrorKind::from).and_then(|index| self.index_attribute("SourceFile", index));
+		if let Some(attribute) = self.record(result) {
+			self.attributes.push(attribute);
+		}
+	}
+
+	fn visit_attribute(&mut self, name: &str, info: &[U1]) {
+		let result = self.attribute(name, info.to_vec());
+		if let Some(attribute) = self.record(result) {
+			self.attributes.push(attribute);
+		}
+	}
+
+	fn visit_field(&mut self, access: U2, name: &str, descriptor: &str, signature: Option<&str>, value: Option<&Constant>) -> Option<Box<dyn FieldVisitor + '_>> {
+		let result = (|| {
+			let name_index = self.constant_pool.utf8(name)?;
+			let descriptor_index = self.constant_pool.utf8(descriptor)?;
+			let mut attributes = Vec::new();
+			if let Some(value) = value {
+				let value_index = self.constant(value)?;
+				attributes.push(self.index_attribute("ConstantValue", value_index)?);
+			}
+			if let Some(signature) = signature {
+				let signature_index = self.constant_pool.utf8(signature)?;
+				attributes.push(self.index_attribute("Signature", signature_index)?);
+			}
+			Ok(FieldInfo {
+				access_flags: access,
+				name_index,
+				descriptor_index,
+				attributes_count: 0,
+				attributes,
+			})
+		})();
+		let field = self.record(result)?;
+		Some(Box::new(FieldWriter {
+			class: self,
+			field,
+		}))
+	}
+
+	fn visit_method(&mut self, access: U2, name: &str, descriptor: &str, signature: Option<&str>, exceptions: &[&str]) -> Option<Box<dyn MethodVisitor + '_>> {
+		let result = (|| {
+			let name_index = self.constant_pool.utf8(name)?;
+			let descriptor_index = self.constant_pool.utf8(descriptor)?;
+			let mut attributes = Vec::new();
+			if !exceptions.is_empty() {
+				let mut info = Vec::with_capacity(2 + 2 * exceptions.len());
+				info.extend((exceptions.len() as U2).to_be_bytes());
+				for exception in exceptions {
+					info.extend(self.constant_pool.class(exception)?.to_be_bytes());
+				}
+				attributes.push(self.attribute("Exceptions", info)?);
+			}
+			if let Some(signature) = signature {
+				let signature_index = self.constant_pool.utf8(signature)?;
+				attributes.push(self.index_attribute("Signature", signature_index)?);
+			}
+			Ok(MethodInfo {
+				access_flags: access,
+				name_index,
+				descriptor_index,
+				attributes_count: 0,
+				attributes,
+			})
+		})();
+		let method = self.record(result)?;
+		Some(Box::new(MethodWriter {
+			class: self,
+			name: name.to_string(),
+			descriptor: descriptor.to_string(),
+			method,
+			code: None,
+		}))
+	}
+}
+
+struct FieldWriter<'w> {
+	class: &'w mut ClassWriter,
+	field: FieldInfo,
+}
+
+impl FieldVisitor for FieldWriter<'_> {
+	fn visit_attribute(&mut self, name: &str, info: &[U1]) {
+		let result = self.class.attribute(name, info.to_vec());
+		if let Some(attribute) = self.class.record(result) {
+			self.field.attributes.push(attribute);
+		}
+	}
+
+	fn visit_end(&mut self) {
+		let field = FieldInfo {
+			attributes_count: self.field.attributes.len() as U2,
+			attributes: std::mem::take(&mut self.field.attributes),
+			..self.field
+		};
+		self.class.fields.push(field);
+	}
+}
+
+/// Part of the code of a method, with everything but labels and branches already encoded.
+#[derive(Debug)]
+enum CodeItem {
+	Bytes(Vec<U1>),
+	Label(Label),
+	/// Branch that is widened to `goto_w` or `jsr_w` if its target is out of reach of a 16-bit offset.
+	Jump {
+		opcode: U1,
+		label: Label,
+		wide: bool,
+	},
+	TableSwitch {
+		low: i32,
+		high: i32,
+		default: Label,
+		labels: Vec<Label>,
+	},
+	LookupSwitch {
+		default: Label,
+		pairs: Vec<(i32, Label)>,
+	},
+	Frame {
+		locals: Vec<FrameValue>,
+		stack: Vec<FrameValue>,
+	},
+}
+
+#[derive(Debug)]
+struct LocalVariableItem {
+	name_index: U2,
+	descriptor_index: U2,
+	signature_index: Option<U2>,
+	start: Label,
+	end: Label,
+	index: U2,
+}
+
+#[derive(Debug, Default)]
+struct CodeBuffer {
+	items: Vec<CodeItem>,
+	try_catches: Vec<(Label, Label, Label, U2)>,
+	line_numbers: Vec<(U2, Label)>,
+	local_variables: Vec<LocalVariableItem>,
+	max_stack: U2,
+	max_locals: U2,
+}
+
+impl CodeBuffer {
+	fn emit(&mut self, bytes: &[U1]) {
+		match self.items.last_mut() {
+			Some(CodeItem::Bytes(code)) => code.extend_from_slice(bytes),
+			_ => self.items.push(CodeItem::Bytes(bytes.to_vec())),
+		}
+	}
+}
+
+/// Padding that aligns the operands of a switch at `pc` to a multiple of four bytes.
+fn switch_padding(pc: usize) -> usize {
+	3 - pc % 4
+}
+
+/// Opcode of the conditional branch taken exactly when `opcode` is not.
+fn invert_condition(opcode: U1) -> U1 {
+	match opcode {
+		IFNULL => IFNONNULL,
+		IFNONNULL => IFNULL,
+		// Conditions come in pairs, starting with `ifeq` and `ifne`.
+		opcode => ((opcode - IFEQ) ^ 1) + IFEQ,
+	}
+}
+
+/// Offsets of all labels and the length of the code.
+fn layout(items: &[CodeItem]) -> (HashMap<Label, usize>, usize) {
+	let mut offsets = HashMap::new();
+	let mut pc = 0;
+	for item in items {
+		if let CodeItem::Label(label) = item {
+			offsets.insert(*label, pc);
+		}
+		pc += item_length(item, pc);
+	}
+	(offsets, pc)
+}
+
+fn item_length(item: &CodeItem, pc: usize) -> usize {
+	match item {
+		CodeItem::Bytes(bytes) => bytes.len(),
+		CodeItem::Label(_) | CodeItem::Frame { .. } => 0,
+		CodeItem::Jump { wide: false, .. } => 3,
+		CodeItem::Jump { opcode: GOTO | JSR, .. } => 5,
+		// Inverted condition jumping over a `goto_w`
+		CodeItem::Jump { .. } => 8,
+		CodeItem::TableSwitch { labels, .. } => 1 + switch_padding(pc) + 12 + 4 * labels.len(),
+		CodeItem::LookupSwitch { pairs, .. } => 1 + switch_padding(pc) + 8 + 8 * pairs.len(),
+	}
+}
+
+struct MethodWriter<'w> {
+	class: &'w mut ClassWriter,
+	name: String,
+	descriptor: String,
+	method: MethodInfo,
+	code: Option<CodeBuffer>,
+}
+
+impl MethodWriter<'_> {
+	fn fail(&mut self, kind: WriteErrorKind) {
+		let method = format!("{}{}", self.name, self.descriptor);
+		self.class.fail(Some(method), kind);
+	}
+
+	fn record<T>(&mut self, result: Result<T, WriteErrorKind>) -> Option<T> {
+		result.map_err(|kind| self.fail(kind)).ok()
+	}
+
+	fn code(&mut self) -> &mut CodeBuffer {
+		self.code.get_or_insert_with(CodeBuffer::default)
+	}
+
+	fn emit(&mut self, bytes: &[U1]) {
+		self.code().emit(bytes);
+	}
+
+	/// Emits an instruction with a constant pool index as operand, e.g. `getfield`.
+	fn emit_indexed(&mut self, opcode: U1, result: Result<U2, ConstantPoolError>, operands: &[U1]) {
+		if let Some(index) = self.record(result.map_err(WriteErrorKind::from)) {
+			let [high, low] = index.to_be_bytes();
+			self.emit(&[&[opcode, high, low], operands].concat());
+		}
+	}
+
+	fn invalid_opcode(&mut self, opcode: U1, event: &'static str) {
+		self.fail(WriteErrorKind::InvalidOpcode {
+			opcode,
+			event,
+		});
+	}
+
+	/// Unexpanded locals at method entry, which the first frame is encoded relative to.
+	fn initial_frame_locals(&self, classes: &mut FrameClasses) -> Result<Vec<VerificationTypeInfo>, WriteErrorKind> {
+		let descriptor = parse_method_descriptor(&self.descriptor)?;
+		let is_static = MethodAccess(self.method.access_flags).contains(MethodAccess::STATIC);
+		let locals = initial_locals(&self.class.class_name, &self.name, is_static, &descriptor);
+		Ok(locals.iter().map(|local| classes.verification_type(local)).collect())
+	}
+
+	/// Frames of the assembled `code`, computed from its instructions.
+	fn compute_frames(&self, code: &CodeAttribute, classes: &mut FrameClasses) -> Result<Vec<FrameInfo>, WriteErrorKind> {
+		let descriptor = parse_method_descriptor(&self.descriptor)?;
+		let is_static = MethodAccess(self.method.access_flags).contains(MethodAccess::STATIC);
+		let constant_pool = self.class.constant_pool.constant_pool();
+		let unknown = ClassHierarchyMap::new();
+		let hierarchy = WriterHierarchy {
+			hierarchy: self.class.hierarchy.as_deref().unwrap_or(&unknown),
+			class_name: &self.class.class_name,
+			super_name: match self.class.super_class {
+				0 => None,
+				index => Some(constant_pool.get_class_name(index)?.into_owned()),
+			},
+			is_interface: ClassAccess(self.class.access_flags).is_interface(),
+		};
+		let frames = compute_frames(code, constant_pool, &self.class.class_name, &self.name, is_static, &descriptor, &hierarchy)?;
+		Ok(frames.iter().map(|frame| FrameInfo {
+			pc: frame.pc,
+			locals: classes.verification_types(&frame.locals),
+			stack: classes.verification_types(&frame.stack),
+		}).collect())
+	}
+
+	/// Replaces the ids of `classes` in the compressed frames with constant pool indices.
+	///
+	/// Only classes the frames still mention after compression are added, e.g. not the parameter types that every frame keeps.
+	fn resolve_frame_classes(&mut self, stack_map_table: &mut StackMapTable, classes: &FrameClasses) -> Result<(), WriteErrorKind> {
+		for entry in &mut stack_map_table.entries {
+			let types = match entry {
+				StackMapFrame::SameLocals1StackItemFrame { stack, .. } | StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => std::slice::from_mut(stack),
+				StackMapFrame::AppendFrame { locals, .. } => locals.as_mut_slice(),
+				StackMapFrame::FullFrame { locals, stack, .. } => {
+					for local in locals {
+						self.resolve_frame_class(local, classes)?;
+					}
+					stack.as_mut_slice()
+				},
+				_ => &mut [],
+			};
+			for verification_type in types {
+				self.resolve_frame_class(verification_type, classes)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn resolve_frame_class(&mut self, verification_type: &mut VerificationTypeInfo, classes: &FrameClasses) -> Result<(), WriteErrorKind> {
+		if let VerificationTypeInfo::Object(id) = verification_type {
+			*id = self.class.constant_pool.class(&classes.names[*id as usize])?;
+		}
+		Ok(())
+	}
+
+	/// Lays out the code, resolving labels to offsets, and encodes the `Code` attribute.
+	///
+	/// Branches are widened until all targets are in reach, widening only makes code longer so this ends.
+	fn assemble(&mut self, mut code: CodeBuffer) -> Result<AttributeInfo, WriteErrorKind> {
+		let (offsets, pc) = loop {
+			let (offsets, code_length) = layout(&code.items);
+			let mut widened = false;
+			let mut pc = 0;
+			for item in &mut code.items {
+				if let CodeItem::Jump { label, wide: wide @ false, .. } = item {
+					if i16::try_from(resolve(&offsets, *label)? as i64 - pc as i64).is_err() {
+						*wide = true;
+						widened = true;
+					}
+				}
+				pc += item_length(item, pc);
+			}
+			if !widened {
+				break (offsets, code_length);
+			}
+		};
+		if pc > MAX_CODE_LENGTH {
+			return Err(WriteErrorKind::CodeTooLarge(pc));
+		}
+
+		let computes_frames = self.class.compute == Compute::Frames && self.class.version.is_some_and(|version| version.major >= TYPE_CHECKING_MAJOR_VERSION);
+		let needs_frames = self.class.version.is_some_and(|version| version.major >= TYPE_CHECKING_MAJOR_VERSION)
+			|| code.items.iter().any(|item| matches!(item, CodeItem::Frame { .. }));
+		let mut bytes = Vec::with_capacity(pc);
+		let mut frames: Vec<FrameInfo> = Vec::new();
+		let mut frame_classes = FrameClasses::default();
+		for item in &code.items {
+			let pc = bytes.len();
+			match item {
+				CodeItem::Bytes(code) => bytes.extend_from_slice(code),
+				CodeItem::Label(_) => {},
+				CodeItem::Jump { opcode, label, wide } => {
+					let offset = resolve(&offsets, *label)? as i64 - pc as i64;
+					match (*opcode, wide) {
+						(_, false) => {
+							bytes.push(*opcode);
+							bytes.extend((offset as i16).to_be_bytes());
+						},
+						(GOTO | JSR, true) => {
+							bytes.push(if *opcode == GOTO { GOTO_W } else { JSR_W });
+							bytes.extend((offset as i32).to_be_bytes());
+						},
+						(_, true) => {
+							if needs_frames && !computes_frames {
+								return Err(WriteErrorKind::UnframedWideBranch(pc));
+							}
+							bytes.extend([invert_condition(*opcode), 0, 8, GOTO_W]);
+							bytes.extend((offset as i32 - 3).to_be_bytes());
+						},
+					}
+				},
+				CodeItem::TableSwitch { low, high, default, labels } => {
+					bytes.push(TABLESWITCH);
+					bytes.resize(bytes.len() + switch_padding(pc), 0);
+					bytes.extend(switch_offset(&offsets, pc, *default)?.to_be_bytes());
+					bytes.extend(low.to_be_bytes());
+					bytes.extend(high.to_be_bytes());
+					for label in labels {
+						bytes.extend(switch_offset(&offsets, pc, *label)?.to_be_bytes());
+					}
+				},
+				CodeItem::LookupSwitch { default, pairs } => {
+					bytes.push(LOOKUPSWITCH);
+					bytes.resize(bytes.len() + switch_padding(pc), 0);
+					bytes.extend(switch_offset(&offsets, pc, *default)?.to_be_bytes());
+					bytes.extend((pairs.len() as i32).to_be_bytes());
+					for (key, label) in pairs {
+						bytes.extend(key.to_be_bytes());
+						bytes.extend(switch_offset(&offsets, pc, *label)?.to_be_bytes());
+					}
+				},
+				CodeItem::Frame { locals, stack } => {
+					if frames.last().is_some_and(|frame| frame.pc as usize == pc) {
+						return Err(WriteErrorKind::DuplicateFrame(pc));
+					}
+					frames.push(FrameInfo {
+						pc: pc as U2,
+						locals: locals.iter().map(|value| frame_classes.frame_value(value, &offsets)).collect::<Result<_, _>>()?,
+						stack: stack.iter().map(|value| frame_classes.frame_value(value, &offsets)).collect::<Result<_, _>>()?,
+					});
+				},
+			}
+		}
+
+		let mut exception_table = Vec::with_capacity(code.try_catches.len());
+		for (start, end, handler, catch_type) in &code.try_catches {
+			exception_table.push(ExceptionTableEntry {
+				start_pc: resolve(&offsets, *start)? as U2,
+				end_pc: resolve(&offsets, *end)? as U2,
+				handler_pc: resolve(&offsets, *handler)? as U2,
+				catch_type: *catch_type,
+			});
+		}
+
+		let mut attributes = Vec::new();
+		if !code.line_numbers.is_empty() {
+			let line_number_table = code.line_numbers.iter().map(|(line_number, start)| Ok(LineNumberTableEntry {
+				start_pc: resolve(&offsets, *start)? as U2,
+				line_number: *line_number,
+			})).collect::<Result<Vec<_>, WriteErrorKind>>()?;
+			let mut info = Vec::new();
+			LineNumberTableAttribute {
+				line_number_table_length: line_number_table.len() as U2,
+				line_number_table,
+			}.write_to(&mut info)?;
+			attributes.push(self.class.attribute("LineNumberTable", info)?);
+		}
+		if !code.local_variables.is_empty() {
+			let mut local_variable_table = Vec::with_capacity(code.local_variables.len());
+			let mut local_variable_type_table = Vec::new();
+			for variable in &code.local_variables {
+				let start_pc = resolve(&offsets, variable.start)?;
+				let length = resolve(&offsets, variable.end)?.saturating_sub(start_pc) as U2;
+				let entry = |descriptor_index| LocalVariableTableEntry {
+					start_pc: start_pc as U2,
+					length,
+					name_index: variable.name_index,
+					descriptor_index,
+					index: variable.index,
+				};
+				local_variable_table.push(entry(variable.descriptor_index));
+				if let Some(signature_index) = variable.signature_index {
+					local_variable_type_table.push(entry(signature_index));
+				}
+			}
+			for (name, table) in [("LocalVariableTable", local_variable_table), ("LocalVariableTypeTable", local_variable_type_table)] {
+				if !table.is_empty() {
+					let mut info = Vec::new();
+					LocalVariableTableAttribute {
+						local_variable_table_length: table.len() as U2,
+						local_variable_table: table,
+					}.write_to(&mut info)?;
+					attributes.push(self.class.attribute(name, info)?);
+				}
+			}
+		}
+
+		let mut code_attribute = CodeAttribute {
+			max_stack: code.max_stack,
+			max_locals: code.max_locals,
+			code_length: bytes.len() as U4,
+			code: bytes,
+			exception_table_length: exception_table.len() as U2,
+			exception_table,
+			attributes_count: attributes.len() as U2,
+			attributes,
+		};
+		if self.class.compute != Compute::Nothing {
+			let descriptor = parse_method_descriptor(&self.descriptor)?;
+			let is_static = MethodAccess(self.method.access_flags).contains(MethodAccess::STATIC);
+			let maxs = compute_maxs(&code_attribute, self.class.constant_pool.constant_pool(), &descriptor, is_static)?;
+			code_attribute.max_stack = maxs.max_stack;
+			code_attribute.max_locals = maxs.max_locals;
+		}
+		if computes_frames {
+			frames = self.compute_frames(&code_attribute, &mut frame_classes)?;
+		}
+		if !frames.is_empty() {
+			let initial_locals = self.initial_frame_locals(&mut frame_classes)?;
+			let mut stack_map_table = StackMapTable::compress(&initial_locals, &frames);
+			self.resolve_frame_classes(&mut stack_map_table, &frame_classes)?;
+			let mut info = Vec::new();
+			stack_map_table.write_to(&mut info)?;
+			code_attribute.attributes.push(self.class.attribute("StackMapTable", info)?);
+			code_attribute.attributes_count += 1;
+		}
+		let mut info = Vec::new();
+		code_attribute.write_to(&mut info)?;
+		self.class.attribute("Code", info)
+	}
+}
+
+/// Classes of the frames of a method, numbered in `Object` types until it is known which ones need a constant.
+#[derive(Debug, Default)]
+struct FrameClasses {
+	names: Vec<String>,
+	ids: HashMap<String, U2>,
+}
+
+impl FrameClasses {
+	fn id(&mut self, class_name: &str) -> U2 {
+		if let Some(id) = self.ids.get(class_name) {
+			return *id;
+		}
+		let id = self.names.len() as U2;
+		self.names.push(class_name.to_string());
+		self.ids.insert(class_name.to_string(), id);
+		id
+	}
+
+	fn verification_type(&mut self, verification_type: &VerificationType) -> VerificationTypeInfo {
+		match verification_type {
+			VerificationType::Top => VerificationTypeInfo::Top,
+			VerificationType::Integer => VerificationTypeInfo::Integer,
+			VerificationType::Float => VerificationTypeInfo::Float,
+			VerificationType::Long => VerificationTypeInfo::Long,
+			VerificationType::Double => VerificationTypeInfo::Double,
+			VerificationType::Null => VerificationTypeInfo::Null,
+			VerificationType::UninitializedThis => VerificationTypeInfo::UninitializedThis,
+			VerificationType::Object(class_name) => VerificationTypeInfo::Object(self.id(class_name)),
+			VerificationType::Uninitialized(offset) => VerificationTypeInfo::Uninitialized(*offset),
+		}
+	}
+
+	/// Unexpanded types of expanded frame contents, i.e. without the `Top` after long and double values.
+	fn verification_types(&mut self, types: &[VerificationType]) -> Vec<VerificationTypeInfo> {
+		let mut infos = Vec::with_capacity(types.len());
+		let mut types = types.iter();
+		while let Some(verification_type) = types.next() {
+			if verification_type.is_wide() {
+				types.next();
+			}
+			infos.push(self.verification_type(verification_type));
+		}
+		infos
+	}
+
+	fn frame_value(&mut self, value: &FrameValue, offsets: &HashMap<Label, usize>) -> Result<VerificationTypeInfo, WriteErrorKind> {
+		Ok(match value {
+			FrameValue::Top => VerificationTypeInfo::Top,
+			FrameValue::Integer => VerificationTypeInfo::Integer,
+			FrameValue::Float => VerificationTypeInfo::Float,
+			FrameValue::Long => VerificationTypeInfo::Long,
+			FrameValue::Double => VerificationTypeInfo::Double,
+			FrameValue::Null => VerificationTypeInfo::Null,
+			FrameValue::UninitializedThis => VerificationTypeInfo::UninitializedThis,
+			FrameValue::Object(class_name) => VerificationTypeInfo::Object(self.id(class_name)),
+			FrameValue::Uninitialized(label) => VerificationTypeInfo::Uninitialized(resolve(offsets, *label)? as U2),
+		})
+	}
+}
+
+fn resolve(offsets: &HashMap<Label, usize>, label: Label) -> Result<usize, WriteErrorKind> {
+	offsets.get(&label).copied().ok_or(WriteErrorKind::UnboundLabel)
+}
+
+fn switch_offset(offsets: &HashMap<Label, usize>, pc: usize, label: Label) -> Result<i32, WriteErrorKind> {
+	Ok((resolve(offsets, label)? as i64 - pc as i64) as i32)
+}
+
+/// Whether `opcode` is an instruction that consists of the opcode alone.
+fn is_simple_instruction(opcode: U1) -> bool {
+	matches!(decode_instruction(&[opcode], 0), Ok((_, 1)))
+}
+
+impl MethodVisitor for MethodWriter<'_> {
+	fn visit_attribute(&mut self, name: &str, info: &[U1]) {
+		let result = self.class.attribute(name, info.to_vec());
+		if let Some(attribute) = self.record(result) {
+			self.method.attributes.push(attribute);
+		}
+	}
+
+	fn visit_code(&mut self) {
+		self.code();
+	}
+
+	fn visit_insn(&mut self, opcode: U1) {
+		if is_simple_instruction(opcode) {
+			self.emit(&[opcode]);
+		} else {
+			self.invalid_opcode(opcode, "visit_insn");
+		}
+	}
+
+	fn visit_int_insn(&mut self, opcode: U1, operand: i32) {
+		let invalid_operand = WriteErrorKind::InvalidOperand {
+			opcode,
+			operand: operand as i64,
+		};
+		match opcode {
+			BIPUSH => match i8::try_from(operand) {
+				Ok(value) => self.emit(&[BIPUSH, value as U1]),
+				Err(_) => self.fail(invalid_operand),
+			},
+			SIPUSH => match i16::try_from(operand) {
+				Ok(value) => self.emit(&[&[SIPUSH], &value.to_be_bytes()[..]].concat()),
+				Err(_) => self.fail(invalid_operand),
+			},
+			NEWARRAY if (4..=11).contains(&operand) => self.emit(&[NEWARRAY, operand as U1]),
+			NEWARRAY => self.fail(invalid_operand),
+			opcode => self.invalid_opcode(opcode, "visit_int_insn"),
+		}
+	}
+
+	fn visit_var_insn(&mut self, opcode: U1, var: U2) {
+		let short_opcode = match opcode {
+			ILOAD..=ALOAD => ILOAD_0 + (opcode - ILOAD) * 4,
+			ISTORE..=ASTORE => ISTORE_0 + (opcode - ISTORE) * 4,
+			RET => RET,
+			opcode => return self.invalid_opcode(opcode, "visit_var_insn"),
+		};
+		match U1::try_from(var) {
+			Ok(var) if var < 4 && opcode != RET => self.emit(&[short_opcode + var]),
+			Ok(var) => self.emit(&[opcode, var]),
+			Err(_) => self.emit(&[&[WIDE, opcode], &var.to_be_bytes()[..]].concat()),
+		}
+	}
+
+	fn visit_type_insn(&mut self, opcode: U1, type_name: &str) {
+		match opcode {
+			NEW | ANEWARRAY | CHECKCAST | INSTANCEOF => {
+				let index = self.class.constant_pool.class(type_name);
+				self.emit_indexed(opcode, index, &[]);
+			},
+			opcode => self.invalid_opcode(opcode, "visit_type_insn"),
+		}
+	}
+
+	fn visit_field_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str) {
+		match opcode {
+			GETSTATIC | PUTSTATIC | GETFIELD | PUTFIELD => {
+				let index = self.class.constant_pool.fieldref(owner, name, descriptor);
+				self.emit_indexed(opcode, index, &[]);
+			},
+			opcode => self.invalid_opcode(opcode, "visit_field_insn"),
+		}
+	}
+
+	fn visit_method_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str, is_interface: bool) {
+		match opcode {
+			INVOKEVIRTUAL | INVOKESPECIAL | INVOKESTATIC => {
+				let index = self.class.constant_pool.methodref(owner, name, descriptor, is_interface);
+				self.emit_indexed(opcode, index, &[]);
+			},
+			INVOKEINTERFACE => {
+				let Some(method_descriptor) = self.record(parse_method_descriptor(descriptor).map_err(WriteErrorKind::from)) else {
+					return;
+				};
+				let count = method_descriptor.argument_slots() + 1;
+				let Ok(count) = U1::try_from(count) else {
+					return self.fail(WriteErrorKind::InvalidOperand {
+						opcode,
+						operand: count as i64,
+					});
+				};
+				let index = self.class.constant_pool.methodref(owner, name, descriptor, true);
+				self.emit_indexed(opcode, index, &[count, 0]);
+			},
+			opcode => self.invalid_opcode(opcode, "visit_method_insn"),
+		}
+	}
+
+	fn visit_invoke_dynamic_insn(&mut self, call_site: &CallSite) {
+		let index = self.class.bootstrap_method(&call_site.bootstrap_method, &call_site.static_arguments)
+			.and_then(|bootstrap_method_attr_index| self.class.constant_pool.invoke_dynamic(bootstrap_method_attr_index, &call_site.name, &call_site.descriptor));
+		self.emit_indexed(INVOKEDYNAMIC, index, &[0, 0]);
+	}
+
+	fn visit_jump_insn(&mut self, opcode: U1, label: Label) {
+		let opcode = match opcode {
+			IFEQ..=JSR | IFNULL | IFNONNULL => opcode,
+			GOTO_W => GOTO,
+			JSR_W => JSR,
+			opcode => return self.invalid_opcode(opcode, "visit_jump_insn"),
+		};
+		self.code().items.push(CodeItem::Jump {
+			opcode,
+			label,
+			wide: false,
+		});
+	}
+
+	fn visit_label(&mut self, label: Label) {
+		self.code().items.push(CodeItem::Label(label));
+	}
+
+	fn visit_ldc_insn(&mut self, constant: &Constant) {
+		let is_wide = match constant {
+			Constant::Long(_) | Constant::Double(_) => true,
+			Constant::Dynamic(call_site) => call_site.descriptor == "J" || call_site.descriptor == "D",
+			_ => false,
+		};
+		let index = self.class.constant(constant).map_err(WriteErrorKind::from);
+		let Some(index) = self.record(index) else {
+			return;
+		};
+		let [high, low] = index.to_be_bytes();
+		match (is_wide, high) {
+			(true, _) => self.emit(&[LDC2_W, high, low]),
+			(false, 0) => self.emit(&[LDC, low]),
+			(false, _) => self.emit(&[LDC_W, high, low]),
+		}
+	}
+
+	fn visit_iinc_insn(&mut self, var: U2, increment: i16) {
+		match (U1::try_from(var), i8::try_from(increment)) {
+			(Ok(var), Ok(increment)) => self.emit(&[IINC, var, increment as U1]),
+			_ => self.emit(&[&[WIDE, IINC], &var.to_be_bytes()[..], &increment.to_be_bytes()[..]].concat()),
+		}
+	}
+
+	fn visit_table_switch_insn(&mut self, low: i32, high: i32, default: Label, labels: &[Label]) {
+		if high < low || (high as i64 - low as i64 + 1) as usize != labels.len() {
+			return self.fail(WriteErrorKind::InvalidOperand {
+				opcode: TABLESWITCH,
+				operand: high as i64,
+			});
+		}
+		self.code().items.push(CodeItem::TableSwitch {
+			low,
+			high,
+			default,
+			labels: labels.to_vec(),
+		});
+	}
+
+	fn visit_lookup_switch_insn(&mut self, default: Label, keys: &[i32], labels: &[Label]) {
+		if keys.len() != labels.len() {
+			return self.fail(WriteErrorKind::InvalidOperand {
+				opcode: LOOKUPSWITCH,
+				operand: labels.len() as i64,
+			});
+		}
+		let mut pairs = keys.iter().copied().zip(labels.iter().copied()).collect::<Vec<_>>();
+		pairs.sort_by_key(|(key, _)| *key);
+		if let Some(pair) = pairs.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+			return self.fail(WriteErrorKind::InvalidOperand {
+				opcode: LOOKUPSWITCH,
+				operand: pair[0].0 as i64,
+			});
+		}
+		self.code().items.push(CodeItem::LookupSwitch {
+			default,
+			pairs,
+		});
+	}
+
+	fn visit_multi_anewarray_insn(&mut self, descriptor: &str, dimensions: U1) {
+		if dimensions == 0 {
+			return self.fail(WriteErrorKind::InvalidOperand {
+				opcode: MULTIANEWARRAY,
+				operand: 0,
+			});
+		}
+		let index = self.class.constant_pool.class(descriptor);
+		self.emit_indexed(MULTIANEWARRAY, index, &[dimensions]);
+	}
+
+	fn visit_try_catch(&mut self, start: Label, end: Label, handler: Label, catch_type: Option<&str>) {
+		let catch_type = match catch_type {
+			Some(catch_type) => self.class.constant_pool.class(catch_type),
+			None => Ok(0),
+		};
+		if let Some(catch_type) = self.record(catch_type.map_err(WriteErrorKind::from)) {
+			self.code().try_catches.push((start, end, handler, catch_type));
+		}
+	}
+
+	fn visit_line_number(&mut self, line: U2, start: Label) {
+		self.code().line_numbers.push((line, start));
+	}
+
+	fn visit_local_variable(&mut self, name: &str, descriptor: &str, signature: Option<&str>, start: Label, end: Label, index: U2) {
+		let result = (|| {
+			let constant_pool = &mut self.class.constant_pool;
+			Ok(LocalVariableItem {
+				name_index: constant_pool.utf8(name)?,
+				descriptor_index: constant_pool.utf8(descriptor)?,
+				signature_index: signature.map(|signature| constant_pool.utf8(signature)).transpose()?,
+				start,
+				end,
+				index,
+			})
+		})();
+		if let Some(local_variable) = self.record(result) {
+			self.code().local_variables.push(local_variable);
+		}
+	}
+
+	fn visit_frame(&mut self, locals: &[FrameValue], stack: &[FrameValue]) {
+		if self.class.compute == Compute::Frames {
+			return;
+		}
+		self.code().items.push(CodeItem::Frame {
+			locals: locals.to_vec(),
+			stack: stack.to_vec(),
+		});
+	}
+
+	fn visit_maxs(&mut self, max_stack: U2, max_locals: U2) {
+		let code = self.code();
+		code.max_stack = max_stack;
+		code.max_locals = max_locals;
+	}
+
+	fn visit_end(&mut self) {
+		if self.class.error.is_some() {
+			return;
+		}
+		if let Some(code) = self.code.take() {
+			let result = self.assemble(code);
+			let Some(attribute) = self.record(result) else {
+				return;
+			};
+			self.method.attributes.insert(0, attribute);
+		}
+		let method = MethodInfo {
+			attributes_count: self.method.attributes.len() as U2,
+			attributes: std::mem::take(&mut self.method.attributes),
+			..self.method
+		};
+		self.class.methods.push(method);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::{attribute_info::{find_attribute, stack_map_table::Frame}, class_file::parse_class_file, verifier::{verify_class, ClassHierarchyMap}};
+
+	use super::*;
+
+	fn read_sample() -> ClassFile {
+		let class_file_raw = fs::read("./Sample.class").expect("Failed to read class file");
+		parse_class_file(&class_file_raw).expect("Failed to parse class file")
+	}
+
+	fn hierarchy(class_file: &ClassFile) -> ClassHierarchyMap {
+		let mut hierarchy = ClassHierarchyMap::new();
+		for (class_name, super_class) in [
+			("java/lang/NumberFormatException", "java/lang/IllegalArgumentException"),
+			("java/lang/IllegalArgumentException", "java/lang/RuntimeException"),
+			("java/lang/RuntimeException", "java/lang/Exception"),
+			("java/lang/Exception", "java/lang/Throwable"),
+			("java/lang/Throwable", "java/lang/Object"),
+		] {
+			hierarchy.insert(class_name, Some(super_class), false);
+		}
+		for interface in ["java/util/List", "java/util/function/Supplier", "java/lang/Comparable", "java/io/Serializable"] {
+			hierarchy.insert(interface, Some("java/lang/Object"), true);
+		}
+		hierarchy.insert_class_file(class_file).unwrap();
+		hierarchy
+	}
+
+	fn code_attribute(class_file: &ClassFile, name: &str) -> CodeAttribute {
+		let constant_pool = &class_file.constant_pool;
+		let method = class_file.methods.iter()
+			.find(|method| constant_pool.get_utf8_str(method.name_index).unwrap() == name)
+			.unwrap_or_else(|| panic!("Missing method '{name}'"));
+		method.code(constant_pool).unwrap().expect("Missing Code")
+	}
+
+	#[test]
+	fn round_trip_keeps_code() {
+		let class_file = read_sample();
+		let mut writer = ClassWriter::from_class_file(&class_file).unwrap();
+		class_file.accept(&mut writer).unwrap();
+		let bytes = writer.into_bytes().unwrap();
+		let written = parse_class_file(&bytes).expect("Failed to parse written class file");
+
+		assert_eq!(written.constant_pool.len(), class_file.constant_pool.len());
+		assert_eq!(written.fields.len(), class_file.fields.len());
+		assert_eq!(written.methods.len(), class_file.methods.len());
+		for (method, written_method) in class_file.methods.iter().zip(&written.methods) {
+			assert_eq!(written_method.name_index, method.name_index);
+			assert_eq!(written_method.exceptions(&written.constant_pool).unwrap(), method.exceptions(&class_file.constant_pool).unwrap());
+			let (Some(code), Some(written_code)) = (method.code(&class_file.constant_pool).unwrap(), written_method.code(&written.constant_pool).unwrap()) else {
+				panic!("Missing Code");
+			};
+			assert_eq!(written_code.code, code.code);
+			assert_eq!((written_code.max_stack, written_code.max_locals), (code.max_stack, code.max_locals));
+			let entries = |code: &CodeAttribute| code.exception_table.iter().map(|entry| (entry.start_pc, entry.end_pc, entry.handler_pc, entry.catch_type)).collect::<Vec<_>>();
+			assert_eq!(entries(&written_code), entries(&code));
+			assert_eq!(written_code.line_numbers(&written.constant_pool).unwrap(), code.line_numbers(&class_file.constant_pool).unwrap());
+			assert_eq!(written_method.stack_map_frames(&written).unwrap(), method.stack_map_frames(&class_file).unwrap());
+			let stack_map_table = |code: &CodeAttribute, class_file: &ClassFile| find_attribute(&code.attributes, &class_file.constant_pool, "StackMapTable").unwrap().map(|attribute| attribute.info.clone());
+			assert_eq!(stack_map_table(&written_code, &written), stack_map_table(&code, &class_file));
+		}
+		assert_eq!(written.bootstrap_methods().unwrap().unwrap().bootstrap_methods.len(), class_file.bootstrap_methods().unwrap().unwrap().bootstrap_methods.len());
+		verify_class(&written, &hierarchy(&written)).unwrap_or_else(|e| panic!("{e}"));
+	}
+
+	#[test]
+	fn recompute_frames() {
+		let class_file = read_sample();
+		let mut writer = ClassWriter::from_class_file(&class_file).unwrap();
+		writer.set_compute(Compute::Frames);
+		writer.set_class_hierarchy(hierarchy(&class_file));
+		class_file.accept(&mut writer).unwrap();
+		let written = parse_class_file(&writer.into_bytes().unwrap()).expect("Failed to parse written class file");
+
+		for (method, written_method) in class_file.methods.iter().zip(&written.methods) {
+			let pcs = |frames: Vec<Frame>| frames.iter().map(|frame| frame.pc).collect::<Vec<_>>();
+			assert_eq!(pcs(written_method.stack_map_frames(&written).unwrap()), pcs(method.stack_map_frames(&class_file).unwrap()));
+		}
+		verify_class(&written, &hierarchy(&written)).unwrap_or_else(|e| panic!("{e}"));
+	}
+
+	/// Doubles the operand of every `bipush`.
+	struct DoublePushes<'a> {
+		delegate: Box<dyn MethodVisitor + 'a>,
+	}
+
+	impl MethodVisitor for DoublePushes<'_> {
+		fn delegate(&mut self) -> Option<&mut dyn MethodVisitor> {
+			Some(&mut *self.delegate)
+		}
+
+		fn visit_int_insn(&mut self, opcode: U1, operand: i32) {
+			let operand = if opcode == BIPUSH { operand * 2 } else { operand };
+			self.delegate.visit_int_insn(opcode, operand);
+		}
+	}
+
+	struct DoublePushesInClass<'a> {
+		delegate: &'a mut dyn ClassVisitor,
+	}
+
+	impl ClassVisitor for DoublePushesInClass<'_> {
+		fn delegate(&mut self) -> Option<&mut dyn ClassVisitor> {
+			Some(self.delegate)
+		}
+
+		fn visit_method(&mut self, access: U2, name: &str, descriptor: &str, signature: Option<&str>, exceptions: &[&str]) -> Option<Box<dyn MethodVisitor + '_>> {
+			let delegate = self.delegate.visit_method(access, name, descriptor, signature, exceptions)?;
+			Some(Box::new(DoublePushes {
+				delegate,
+			}))
+		}
+	}
+
+	#[test]
+	fn adapters_transform_code() {
+		let class_file = read_sample();
+		let mut writer = ClassWriter::from_class_file(&class_file).unwrap();
+		class_file.accept(&mut DoublePushesInClass {
+			delegate: &mut writer,
+		}).unwrap();
+		let written = writer.into_class_file().unwrap();
+
+		let code = code_attribute(&written, "classify");
+		assert_eq!(&code.code[28..37], [BIPUSH, 20, IRETURN, BIPUSH, 40, IRETURN, BIPUSH, 60, IRETURN]);
+		assert_eq!(code_attribute(&written, "mix").code, code_attribute(&class_file, "mix").code);
+	}
+
+	/// `static int max(int a, int b) { try { return a > b ? a : b; } catch (RuntimeException e) { return 0; } }`
+	fn write_max(class: &mut dyn ClassVisitor) {
+		let mut method = class.visit_method((MethodAccess::PUBLIC | MethodAccess::STATIC).0, "max", "(II)I", None, &[]).unwrap();
+		let (start, else_branch, end, handler) = (Label::new(), Label::new(), Label::new(), Label::new());
+		method.visit_code();
+		method.visit_try_catch(start, end, handler, Some("java/lang/RuntimeException"));
+		method.visit_label(start);
+		method.visit_var_insn(ILOAD, 0);
+		method.visit_var_insn(ILOAD, 1);
+		method.visit_jump_insn(IF_ICMPLE, else_branch);
+		method.visit_var_insn(ILOAD, 0);
+		method.visit_insn(IRETURN);
+		method.visit_label(else_branch);
+		method.visit_frame(&[FrameValue::Integer, FrameValue::Integer], &[]);
+		method.visit_var_insn(ILOAD, 1);
+		method.visit_label(end);
+		method.visit_insn(IRETURN);
+		method.visit_label(handler);
+		method.visit_frame(&[FrameValue::Integer, FrameValue::Integer], &[FrameValue::Object("java/lang/RuntimeException".to_string())]);
+		method.visit_var_insn(ASTORE, 2);
+		method.visit_insn(ICONST_0);
+		method.visit_insn(IRETURN);
+		method.visit_maxs(2, 3);
+		method.visit_end();
+	}
+
+	#[test]
+	fn write_class_from_events() {
+		let mut writer = ClassWriter::new();
+		writer.visit(ClassVersion { major: 61, minor: 0 }, 0x0021, "Generated", None, Some("java/lang/Object"), &[]);
+		writer.visit_source("Generated.java");
+		let field = writer.visit_field(0x0019, "LIMIT", "J", None, Some(&Constant::Long(1 << 40)));
+		field.unwrap().visit_end();
+		write_max(&mut writer);
+		writer.visit_end();
+		let bytes = writer.into_bytes().unwrap();
+		let class_file = parse_class_file(&bytes).expect("Failed to parse written class file");
+
+		assert_eq!(class_file.source_file().unwrap().as_deref(), Some("Generated.java"));
+		let code = code_attribute(&class_file, "max");
+		assert_eq!(code.code, [ILOAD_0, ILOAD_1, IF_ICMPLE, 0, 5, ILOAD_0, IRETURN, ILOAD_1, IRETURN, ASTORE_2, ICONST_0, IRETURN]);
+		assert_eq!(code.exception_table.len(), 1);
+		assert_eq!((code.exception_table[0].start_pc, code.exception_table[0].end_pc, code.exception_table[0].handler_pc), (0, 8, 9));
+		let frames = class_file.methods[0].stack_map_frames(&class_file).unwrap();
+		assert_eq!(frames.iter().map(|frame| frame.pc).collect::<Vec<_>>(), [7, 9]);
+		verify_class(&class_file, &hierarchy(&class_file)).unwrap_or_else(|e| panic!("{e}"));
+	}
+
+	#[test]
+	fn report_first_error() {
+		let mut writer = ClassWriter::new();
+		writer.visit(ClassVersion { major: 61, minor: 0 }, 0x0021, "Broken", None, Some("java/lang/Object"), &[]);
+		let mut method = writer.visit_method(MethodAccess::STATIC.0, "run", "()V", None, &[]).unwrap();
+		method.visit_code();
+		method.visit_jump_insn(GOTO, Label::new());
+		method.visit_insn(BIPUSH);
+		method.visit_end();
+		drop(method);
+
+		let error = writer.into_class_file().expect_err("Wrote class with invalid code");
+		assert_eq!(error.method.as_deref(), Some("run()V"));
+		assert_eq!(error.kind, WriteErrorKind::InvalidOpcode {
+			opcode: BIPUSH,
+			event: "visit_insn",
+		});
+	}
+
+	#[test]
+	fn reject_invokeinterface_count_overflow() {
+		let mut writer = ClassWriter::new();
+		writer.visit(ClassVersion { major: 61, minor: 0 }, 0x0021, "Broken", None, Some("java/lang/Object"), &[]);
+		let mut method = writer.visit_method(MethodAccess::STATIC.0, "run", "()V", None, &[]).unwrap();
+		method.visit_code();
+		// 127 longs and the receiver fit in the count, one more int does not
+		let descriptor = format!("({}I)V", "J".repeat(127));
+		method.visit_method_insn(INVOKEINTERFACE, "Wide", "run", &descriptor, true);
+		method.visit_end();
+		drop(method);
+
+		let error = writer.into_class_file().expect_err("Wrote invokeinterface with a wrapped count");
+		assert_eq!(error.kind, WriteErrorKind::InvalidOperand {
+			opcode: INVOKEINTERFACE,
+			operand: 256,
+		});
+	}
+
+	#[test]
+	fn reject_duplicate_switch_keys_and_zero_dimensions() {
+		let invalid_operand = |visit: &dyn Fn(&mut dyn MethodVisitor)| {
+			let mut writer = ClassWriter::new();
+			writer.visit(ClassVersion { major: 61, minor: 0 }, 0x0021, "Broken", None, Some("java/lang/Object"), &[]);
+			let mut method = writer.visit_method(MethodAccess::STATIC.0, "run", "(I)V", None, &[]).unwrap();
+			method.visit_code();
+			visit(method.as_mut());
+			method.visit_end();
+			drop(method);
+			writer.into_class_file().expect_err("Wrote invalid operand").kind
+		};
+
+		let error = invalid_operand(&|method| {
+			let (default, first, second) = (Label::new(), Label::new(), Label::new());
+			method.visit_var_insn(ILOAD, 0);
+			method.visit_lookup_switch_insn(default, &[3, 1, 3], &[first, second, default]);
+		});
+		assert_eq!(error, WriteErrorKind::InvalidOperand {
+			opcode: LOOKUPSWITCH,
+			operand: 3,
+		});
+
+		let error = invalid_operand(&|method| method.visit_multi_anewarray_insn("[[I", 0));
+		assert_eq!(error, WriteErrorKind::InvalidOperand {
+			opcode: MULTIANEWARRAY,
+			operand: 0,
+		});
+	}
+}
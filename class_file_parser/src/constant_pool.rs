@@ -0,0 +1,719 @@
+use std::{borrow::Cow, collections::HashMap, fmt::Display, io::{self, Write}};
+
+use nom::{number::complete::be_u16, IResult};
+
+use crate::{class_format_error::{fail_with, indexed, ClassFormatErrorKind, ClassParseError}, cp_info::{self, class::Class, cp_info_parser, cp_info_ref_parser, double::Double, dynamic::Dynamic, fieldref::Fieldref, float::Float, integer::Integer, interface_methodref::InterfaceMethodref, invoke_dynamic::InvokeDynamic, long::Long, method_handle::{MethodHandle, ReferenceKind}, method_type::MethodType, methodref::Methodref, module::Module, name_and_type::NameAndType, package::Package, utf8::{Utf8, Utf8Ref}, CPInfo, CPInfoRef}, mutf8::{self, JavaString, MUtf8Error}, writer::write_length_u2, U1, U2, U4};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantPoolError {
+	IndexOutOfBounds(U2),
+	UnusableSlot(U2),
+	MalformedUtf8 {
+		index: U2,
+		error: MUtf8Error,
+	},
+	UnexpectedEntry {
+		index: U2,
+		expected: &'static str,
+		found: &'static str,
+	},
+	InvalidReferenceKind {
+		index: U2,
+		reference_kind: U1,
+	},
+	/// Adding an entry would exceed the 65535 slots a constant pool can have.
+	TooManyEntries,
+}
+
+impl Display for ConstantPoolError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::IndexOutOfBounds(index) => write!(f, "constant pool index {index} is out of bounds"),
+			Self::UnusableSlot(index) => write!(f, "constant pool index {index} refers to an unusable slot"),
+			Self::MalformedUtf8 { index, error } => write!(f, "constant pool index {index}: {error}"),
+			Self::UnexpectedEntry { index, expected, found } => write!(f, "constant pool index {index} should be {expected}, but is {found}"),
+			Self::InvalidReferenceKind { index, reference_kind } => write!(f, "constant pool index {index} has invalid reference kind {reference_kind}"),
+			Self::TooManyEntries => write!(f, "constant pool has no room for more entries"),
+		}
+	}
+}
+
+impl std::error::Error for ConstantPoolError {}
+
+/// `Fieldref`, `Methodref` or `InterfaceMethodref` with all indices resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberRef<'a> {
+	pub class_name: Cow<'a, str>,
+	pub name: Cow<'a, str>,
+	pub descriptor: Cow<'a, str>,
+}
+
+/// `MethodHandle` with the member it refers to resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodHandleRef<'a> {
+	pub kind: ReferenceKind,
+	pub owner: Cow<'a, str>,
+	pub name: Cow<'a, str>,
+	pub descriptor: Cow<'a, str>,
+	/// Whether the member is referenced through an `InterfaceMethodref`.
+	pub is_interface: bool,
+}
+
+impl MethodHandleRef<'_> {
+	pub fn into_owned(self) -> MethodHandleRef<'static> {
+		MethodHandleRef {
+			kind: self.kind,
+			owner: Cow::Owned(self.owner.into_owned()),
+			name: Cow::Owned(self.name.into_owned()),
+			descriptor: Cow::Owned(self.descriptor.into_owned()),
+			is_interface: self.is_interface,
+		}
+	}
+}
+
+impl Display for MethodHandleRef<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}.{}:{}", self.kind, self.owner, self.name, self.descriptor)
+	}
+}
+
+/// Constant pool indexed by the 1-based slot numbers used throughout the class file.
+///
+/// Slot 0 and the slot following every `Long` and `Double` entry are unusable and stored as `None`.
+#[derive(Debug)]
+pub struct ConstantPool {
+	slots: Vec<Option<CPInfo>>,
+}
+
+impl ConstantPool {
+	pub fn new() -> Self {
+		Self {
+			slots: vec![None],
+		}
+	}
+
+	/// Number of slots including the unusable slot 0, i.e. the `constant_pool_count` of the class file.
+	pub fn len(&self) -> usize {
+		self.slots.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.slots.len() <= 1
+	}
+
+	/// Appends `entry` and returns its index, unless the pool has no room for the slots it takes.
+	pub fn push(&mut self, entry: CPInfo) -> Result<U2, ConstantPoolError> {
+		let is_wide = entry.is_wide();
+		let slots = if is_wide { 2 } else { 1 };
+		if self.slots.len() + slots > U2::MAX as usize {
+			return Err(ConstantPoolError::TooManyEntries);
+		}
+		let index = self.slots.len() as U2;
+		self.slots.push(Some(entry));
+		if is_wide {
+			self.slots.push(None);
+		}
+		Ok(index)
+	}
+
+	pub fn get(&self, index: U2) -> Result<&CPInfo, ConstantPoolError> {
+		match self.slots.get(index as usize) {
+			Some(Some(entry)) => Ok(entry),
+			Some(None) => Err(ConstantPoolError::UnusableSlot(index)),
+			None => Err(ConstantPoolError::IndexOutOfBounds(index)),
+		}
+	}
+
+	/// Usable entries together with their index.
+	pub fn iter(&self) -> impl Iterator<Item = (U2, &CPInfo)> {
+		self.slots.iter().enumerate().filter_map(|(index, slot)| slot.as_ref().map(|entry| (index as U2, entry)))
+	}
+
+	pub fn get_utf8(&self, index: U2) -> Result<&Utf8, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Utf8(value) => Ok(value),
+			entry => Err(unexpected(index, "Utf8", entry)),
+		}
+	}
+
+	pub fn get_utf8_str(&self, index: U2) -> Result<Cow<'_, str>, ConstantPoolError> {
+		self.get_utf8(index)?.to_str().map_err(|error| ConstantPoolError::MalformedUtf8 {
+			index,
+			error,
+		})
+	}
+
+	/// Resolves a `String` entry into the literal it refers to.
+	pub fn get_string(&self, index: U2) -> Result<JavaString, ConstantPoolError> {
+		let string_index = match self.get(index)? {
+			CPInfo::String(value) => value.string_index,
+			entry => return Err(unexpected(index, "String", entry)),
+		};
+		self.get_java_string(string_index)
+	}
+
+	pub fn get_integer(&self, index: U2) -> Result<i32, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Integer(value) => Ok(value.bytes as i32),
+			entry => Err(unexpected(index, "Integer", entry)),
+		}
+	}
+
+	pub fn get_float(&self, index: U2) -> Result<f32, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Float(value) => Ok(f32::from_bits(value.bytes)),
+			entry => Err(unexpected(index, "Float", entry)),
+		}
+	}
+
+	pub fn get_long(&self, index: U2) -> Result<i64, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Long(value) => Ok(((value.high_bytes as u64) << 32 | value.low_bytes as u64) as i64),
+			entry => Err(unexpected(index, "Long", entry)),
+		}
+	}
+
+	pub fn get_double(&self, index: U2) -> Result<f64, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Double(value) => Ok(f64::from_bits((value.high_bytes as u64) << 32 | value.low_bytes as u64)),
+			entry => Err(unexpected(index, "Double", entry)),
+		}
+	}
+
+	/// Decodes a `Utf8` entry losslessly, keeping unpaired surrogates.
+	pub fn get_java_string(&self, index: U2) -> Result<JavaString, ConstantPoolError> {
+		self.get_utf8(index)?.to_java_string().map_err(|error| ConstantPoolError::MalformedUtf8 {
+			index,
+			error,
+		})
+	}
+
+	pub fn get_class(&self, index: U2) -> Result<&Class, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Class(value) => Ok(value),
+			entry => Err(unexpected(index, "Class", entry)),
+		}
+	}
+
+	pub fn get_class_name(&self, index: U2) -> Result<Cow<'_, str>, ConstantPoolError> {
+		self.get_utf8_str(self.get_class(index)?.name_index)
+	}
+
+	pub fn get_module_name(&self, index: U2) -> Result<Cow<'_, str>, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Module(value) => self.get_utf8_str(value.name_index),
+			entry => Err(unexpected(index, "Module", entry)),
+		}
+	}
+
+	/// Package name in internal form, e.g. `java/lang`.
+	pub fn get_package_name(&self, index: U2) -> Result<Cow<'_, str>, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfo::Package(value) => self.get_utf8_str(value.name_index),
+			entry => Err(unexpected(index, "Package", entry)),
+		}
+	}
+
+	/// Resolves a `NameAndType` entry into its name and descriptor.
+	pub fn get_name_and_type(&self, index: U2) -> Result<(Cow<'_, str>, Cow<'_, str>), ConstantPoolError> {
+		let name_and_type: &NameAndType = match self.get(index)? {
+			CPInfo::NameAndType(value) => value,
+			entry => return Err(unexpected(index, "NameAndType", entry)),
+		};
+		Ok((self.get_utf8_str(name_and_type.name_index)?, self.get_utf8_str(name_and_type.descriptor_index)?))
+	}
+
+	/// Resolves a `Fieldref`, `Methodref` or `InterfaceMethodref` entry.
+	pub fn get_member_ref(&self, index: U2) -> Result<MemberRef<'_>, ConstantPoolError> {
+		let (class_index, name_and_type_index) = match self.get(index)? {
+			CPInfo::Fieldref(value) => (value.class_index, value.name_and_type_index),
+			CPInfo::Methodref(value) => (value.class_index, value.name_and_type_index),
+			CPInfo::InterfaceMethodref(value) => (value.class_index, value.name_and_type_index),
+			entry => return Err(unexpected(index, "Fieldref, Methodref or InterfaceMethodref", entry)),
+		};
+		let (name, descriptor) = self.get_name_and_type(name_and_type_index)?;
+		Ok(MemberRef {
+			class_name: self.get_class_name(class_index)?,
+			name,
+			descriptor,
+		})
+	}
+
+	/// Resolves a `MethodHandle` entry, checking that its reference kind fits the referenced entry.
+	pub fn get_method_handle(&self, index: U2) -> Result<MethodHandleRef<'_>, ConstantPoolError> {
+		let method_handle = match self.get(index)? {
+			CPInfo::MethodHandle(value) => value,
+			entry => return Err(unexpected(index, "MethodHandle", entry)),
+		};
+		let kind = ReferenceKind::from_u1(method_handle.reference_kind).ok_or(ConstantPoolError::InvalidReferenceKind {
+			index,
+			reference_kind: method_handle.reference_kind,
+		})?;
+		let reference_index = method_handle.reference_index;
+		let is_interface = match (kind, self.get(reference_index)?) {
+			(kind, CPInfo::Fieldref(_)) if kind.is_field() => false,
+			(ReferenceKind::InvokeVirtual | ReferenceKind::NewInvokeSpecial | ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial, CPInfo::Methodref(_)) => false,
+			(ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial | ReferenceKind::InvokeInterface, CPInfo::InterfaceMethodref(_)) => true,
+			(kind, entry) => {
+				let expected = match kind {
+					kind if kind.is_field() => "Fieldref",
+					ReferenceKind::InvokeVirtual | ReferenceKind::NewInvokeSpecial => "Methodref",
+					ReferenceKind::InvokeInterface => "InterfaceMethodref",
+					_ => "Methodref or InterfaceMethodref",
+				};
+				return Err(unexpected(reference_index, expected, entry));
+			},
+		};
+		let member_ref = self.get_member_ref(reference_index)?;
+		Ok(MethodHandleRef {
+			kind,
+			owner: member_ref.class_name,
+			name: member_ref.name,
+			descriptor: member_ref.descriptor,
+			is_interface,
+		})
+	}
+
+	/// Writes `constant_pool_count` followed by all usable entries.
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_length_u2(writer, self.slots.len(), "constant_pool")?;
+		for (_, entry) in self.iter() {
+			entry.write_to(writer)?;
+		}
+		Ok(())
+	}
+}
+
+impl Default for ConstantPool {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+fn unexpected(index: U2, expected: &'static str, found: &CPInfo) -> ConstantPoolError {
+	ConstantPoolError::UnexpectedEntry {
+		index,
+		expected,
+		found: found.name(),
+	}
+}
+
+/// Constant pool whose `Utf8` entries borrow from the class file, so names resolve without copying.
+#[derive(Debug)]
+pub struct ConstantPoolRef<'a> {
+	slots: Vec<Option<CPInfoRef<'a>>>,
+}
+
+impl<'a> ConstantPoolRef<'a> {
+	/// Number of slots including the unusable slot 0, i.e. the `constant_pool_count` of the class file.
+	pub fn len(&self) -> usize {
+		self.slots.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.slots.len() <= 1
+	}
+
+	pub fn get(&self, index: U2) -> Result<&CPInfoRef<'a>, ConstantPoolError> {
+		match self.slots.get(index as usize) {
+			Some(Some(entry)) => Ok(entry),
+			Some(None) => Err(ConstantPoolError::UnusableSlot(index)),
+			None => Err(ConstantPoolError::IndexOutOfBounds(index)),
+		}
+	}
+
+	/// Usable entries together with their index.
+	pub fn iter(&self) -> impl Iterator<Item = (U2, &CPInfoRef<'a>)> {
+		self.slots.iter().enumerate().filter_map(|(index, slot)| slot.as_ref().map(|entry| (index as U2, entry)))
+	}
+
+	fn get_other(&self, index: U2, expected: &'static str) -> Result<&CPInfo, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfoRef::Other(entry) => Ok(entry),
+			entry => Err(ConstantPoolError::UnexpectedEntry {
+				index,
+				expected,
+				found: entry.name(),
+			}),
+		}
+	}
+
+	pub fn get_utf8(&self, index: U2) -> Result<Utf8Ref<'a>, ConstantPoolError> {
+		match self.get(index)? {
+			CPInfoRef::Utf8(value) => Ok(*value),
+			CPInfoRef::Other(entry) => Err(unexpected(index, "Utf8", entry)),
+		}
+	}
+
+	/// Decodes a `Utf8` entry, borrowing from the class file unless it needs to be converted.
+	pub fn get_utf8_str(&self, index: U2) -> Result<Cow<'a, str>, ConstantPoolError> {
+		self.get_utf8(index)?.to_str().map_err(|error| ConstantPoolError::MalformedUtf8 {
+			index,
+			error,
+		})
+	}
+
+	pub fn get_class(&self, index: U2) -> Result<&Class, ConstantPoolError> {
+		match self.get_other(index, "Class")? {
+			CPInfo::Class(value) => Ok(value),
+			entry => Err(unexpected(index, "Class", entry)),
+		}
+	}
+
+	pub fn get_class_name(&self, index: U2) -> Result<Cow<'a, str>, ConstantPoolError> {
+		self.get_utf8_str(self.get_class(index)?.name_index)
+	}
+
+	/// Resolves a `NameAndType` entry into its name and descriptor.
+	pub fn get_name_and_type(&self, index: U2) -> Result<(Cow<'a, str>, Cow<'a, str>), ConstantPoolError> {
+		let name_and_type = match self.get_other(index, "NameAndType")? {
+			CPInfo::NameAndType(value) => value,
+			entry => return Err(unexpected(index, "NameAndType", entry)),
+		};
+		Ok((self.get_utf8_str(name_and_type.name_index)?, self.get_utf8_str(name_and_type.descriptor_index)?))
+	}
+
+	/// Resolves a `Fieldref`, `Methodref` or `InterfaceMethodref` entry.
+	pub fn get_member_ref(&self, index: U2) -> Result<MemberRef<'a>, ConstantPoolError> {
+		let expected = "Fieldref, Methodref or InterfaceMethodref";
+		let (class_index, name_and_type_index) = match self.get_other(index, expected)? {
+			CPInfo::Fieldref(value) => (value.class_index, value.name_and_type_index),
+			CPInfo::Methodref(value) => (value.class_index, value.name_and_type_index),
+			CPInfo::InterfaceMethodref(value) => (value.class_index, value.name_and_type_index),
+			entry => return Err(unexpected(index, expected, entry)),
+		};
+		let (name, descriptor) = self.get_name_and_type(name_and_type_index)?;
+		Ok(MemberRef {
+			class_name: self.get_class_name(class_index)?,
+			name,
+			descriptor,
+		})
+	}
+
+	/// Copies all entries into an owned constant pool, leaving this one as it is.
+	pub fn to_constant_pool(&self) -> ConstantPool {
+		ConstantPool {
+			slots: self.slots.iter().map(|slot| slot.as_ref().map(|entry| match entry {
+				CPInfoRef::Utf8(value) => CPInfo::Utf8(value.into_owned()),
+				CPInfoRef::Other(entry) => EntryKey::of(entry).to_cp_info(),
+			})).collect(),
+		}
+	}
+
+	/// Copies the borrowed `Utf8` entries into an owned constant pool.
+	pub fn into_owned(self) -> ConstantPool {
+		ConstantPool {
+			slots: self.slots.into_iter().map(|slot| slot.map(CPInfoRef::into_owned)).collect(),
+		}
+	}
+}
+
+/// Contents of a constant pool entry, identifying equal entries regardless of their index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum EntryKey {
+	Utf8(Vec<U1>),
+	Integer(U4),
+	Float(U4),
+	Long(U4, U4),
+	Double(U4, U4),
+	Class(U2),
+	String(U2),
+	Fieldref(U2, U2),
+	Methodref(U2, U2),
+	InterfaceMethodref(U2, U2),
+	NameAndType(U2, U2),
+	MethodHandle(U1, U2),
+	MethodType(U2),
+	Dynamic(U2, U2),
+	InvokeDynamic(U2, U2),
+	Module(U2),
+	Package(U2),
+}
+
+impl EntryKey {
+	fn of(entry: &CPInfo) -> Self {
+		match entry {
+			CPInfo::Utf8(value) => Self::Utf8(value.bytes.clone()),
+			CPInfo::Integer(value) => Self::Integer(value.bytes),
+			CPInfo::Float(value) => Self::Float(value.bytes),
+			CPInfo::Long(value) => Self::Long(value.high_bytes, value.low_bytes),
+			CPInfo::Double(value) => Self::Double(value.high_bytes, value.low_bytes),
+			CPInfo::Class(value) => Self::Class(value.name_index),
+			CPInfo::String(value) => Self::String(value.string_index),
+			CPInfo::Fieldref(value) => Self::Fieldref(value.class_index, value.name_and_type_index),
+			CPInfo::Methodref(value) => Self::Methodref(value.class_index, value.name_and_type_index),
+			CPInfo::InterfaceMethodref(value) => Self::InterfaceMethodref(value.class_index, value.name_and_type_index),
+			CPInfo::NameAndType(value) => Self::NameAndType(value.name_index, value.descriptor_index),
+			CPInfo::MethodHandle(value) => Self::MethodHandle(value.reference_kind, value.reference_index),
+			CPInfo::MethodType(value) => Self::MethodType(value.descriptor_index),
+			CPInfo::Dynamic(value) => Self::Dynamic(value.bootstrap_method_attr_index, value.name_and_type_index),
+			CPInfo::InvokeDynamic(value) => Self::InvokeDynamic(value.bootstrap_method_attr_index, value.name_and_type_index),
+			CPInfo::Module(value) => Self::Module(value.name_index),
+			CPInfo::Package(value) => Self::Package(value.name_index),
+		}
+	}
+
+	fn to_cp_info(&self) -> CPInfo {
+		match *self {
+			Self::Utf8(ref bytes) => CPInfo::Utf8(Utf8 { tag: cp_info::UTF8, length: bytes.len() as U2, bytes: bytes.clone() }),
+			Self::Integer(bytes) => CPInfo::Integer(Integer { tag: cp_info::INTEGER, bytes }),
+			Self::Float(bytes) => CPInfo::Float(Float { tag: cp_info::FLOAT, bytes }),
+			Self::Long(high_bytes, low_bytes) => CPInfo::Long(Long { tag: cp_info::LONG, high_bytes, low_bytes }),
+			Self::Double(high_bytes, low_bytes) => CPInfo::Double(Double { tag: cp_info::DOUBLE, high_bytes, low_bytes }),
+			Self::Class(name_index) => CPInfo::Class(Class { tag: cp_info::CLASS, name_index }),
+			Self::String(string_index) => CPInfo::String(cp_info::string::String { tag: cp_info::STRING, string_index }),
+			Self::Fieldref(class_index, name_and_type_index) => CPInfo::Fieldref(Fieldref { tag: cp_info::FIELDREF, class_index, name_and_type_index }),
+			Self::Methodref(class_index, name_and_type_index) => CPInfo::Methodref(Methodref { tag: cp_info::METHODREF, class_index, name_and_type_index }),
+			Self::InterfaceMethodref(class_index, name_and_type_index) => CPInfo::InterfaceMethodref(InterfaceMethodref { tag: cp_info::INTERFACEMETHODREF, class_index, name_and_type_index }),
+			Self::NameAndType(name_index, descriptor_index) => CPInfo::NameAndType(NameAndType { tag: cp_info::NAMEANDTYPE, name_index, descriptor_index }),
+			Self::MethodHandle(reference_kind, reference_index) => CPInfo::MethodHandle(MethodHandle { tag: cp_info::METHODHANDLE, reference_kind, reference_index }),
+			Self::MethodType(descriptor_index) => CPInfo::MethodType(MethodType { tag: cp_info::METHODTYPE, descriptor_index }),
+			Self::Dynamic(bootstrap_method_attr_index, name_and_type_index) => CPInfo::Dynamic(Dynamic { tag: cp_info::DYNAMIC, bootstrap_method_attr_index, name_and_type_index }),
+			Self::InvokeDynamic(bootstrap_method_attr_index, name_and_type_index) => CPInfo::InvokeDynamic(InvokeDynamic { tag: cp_info::INVOKEDYNAMIC, bootstrap_method_attr_index, name_and_type_index }),
+			Self::Module(name_index) => CPInfo::Module(Module { tag: cp_info::MODULE, name_index }),
+			Self::Package(name_index) => CPInfo::Package(Package { tag: cp_info::PACKAGE, name_index }),
+		}
+	}
+}
+
+/// Constant pool under construction, which adds every distinct constant only once.
+///
+/// Entries are added together with the entries they refer to, e.g. `class` also adds the `Utf8` holding the name.
+#[derive(Debug, Default)]
+pub struct ConstantPoolBuilder {
+	constant_pool: ConstantPool,
+	indices: HashMap<EntryKey, U2>,
+}
+
+impl ConstantPoolBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Starts with a copy of `constant_pool` that keeps every index, so existing references into it stay valid.
+	pub fn from_constant_pool(constant_pool: &ConstantPool) -> Self {
+		let mut builder = Self::new();
+		for (_, entry) in constant_pool.iter() {
+			let key = EntryKey::of(entry);
+			let index = builder.constant_pool.push(key.to_cp_info()).expect("Copied pool has as many slots as the original");
+			builder.indices.entry(key).or_insert(index);
+		}
+		builder
+	}
+
+	/// Number of slots including the unusable slot 0, i.e. the `constant_pool_count` the pool would be written with.
+	pub fn len(&self) -> usize {
+		self.constant_pool.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.constant_pool.is_empty()
+	}
+
+	/// The entries added so far.
+	pub fn constant_pool(&self) -> &ConstantPool {
+		&self.constant_pool
+	}
+
+	pub fn build(self) -> ConstantPool {
+		self.constant_pool
+	}
+
+	fn add(&mut self, key: EntryKey) -> Result<U2, ConstantPoolError> {
+		if let Some(index) = self.indices.get(&key) {
+			return Ok(*index);
+		}
+		let index = self.constant_pool.push(key.to_cp_info())?;
+		self.indices.insert(key, index);
+		Ok(index)
+	}
+
+	pub fn utf8(&mut self, value: &str) -> Result<U2, ConstantPoolError> {
+		self.add(EntryKey::Utf8(mutf8::encode_str(value)))
+	}
+
+	/// Adds a `Utf8` entry for a string that may contain unpaired surrogates.
+	pub fn java_utf8(&mut self, value: &JavaString) -> Result<U2, ConstantPoolError> {
+		self.add(EntryKey::Utf8(mutf8::encode(value.as_utf16())))
+	}
+
+	pub fn integer(&mut self, value: i32) -> Result<U2, ConstantPoolError> {
+		self.add(EntryKey::Integer(value as U4))
+	}
+
+	pub fn float(&mut self, value: f32) -> Result<U2, ConstantPoolError> {
+		self.add(EntryKey::Float(value.to_bits()))
+	}
+
+	pub fn long(&mut self, value: i64) -> Result<U2, ConstantPoolError> {
+		self.add(EntryKey::Long((value as u64 >> 32) as U4, value as U4))
+	}
+
+	pub fn double(&mut self, value: f64) -> Result<U2, ConstantPoolError> {
+		let bits = value.to_bits();
+		self.add(EntryKey::Double((bits >> 32) as U4, bits as U4))
+	}
+
+	/// Adds a `Class` entry for a class name in internal form or an array descriptor.
+	pub fn class(&mut self, class_name: &str) -> Result<U2, ConstantPoolError> {
+		let name_index = self.utf8(class_name)?;
+		self.add(EntryKey::Class(name_index))
+	}
+
+	/// Adds a `String` entry for the literal `value`.
+	pub fn string(&mut self, value: &str) -> Result<U2, ConstantPoolError> {
+		let string_index = self.utf8(value)?;
+		self.add(EntryKey::String(string_index))
+	}
+
+	pub fn java_string(&mut self, value: &JavaString) -> Result<U2, ConstantPoolError> {
+		let string_index = self.java_utf8(value)?;
+		self.add(EntryKey::String(string_index))
+	}
+
+	pub fn name_and_type(&mut self, name: &str, descriptor: &str) -> Result<U2, ConstantPoolError> {
+		let name_index = self.utf8(name)?;
+		let descriptor_index = self.utf8(descriptor)?;
+		self.add(EntryKey::NameAndType(name_index, descriptor_index))
+	}
+
+	pub fn fieldref(&mut self, owner: &str, name: &str, descriptor: &str) -> Result<U2, ConstantPoolError> {
+		let class_index = self.class(owner)?;
+		let name_and_type_index = self.name_and_type(name, descriptor)?;
+		self.add(EntryKey::Fieldref(class_index, name_and_type_index))
+	}
+
+	/// Adds a `Methodref`, or an `InterfaceMethodref` if `owner` is an interface.
+	pub fn methodref(&mut self, owner: &str, name: &str, descriptor: &str, is_interface: bool) -> Result<U2, ConstantPoolError> {
+		let class_index = self.class(owner)?;
+		let name_and_type_index = self.name_and_type(name, descriptor)?;
+		match is_interface {
+			true => self.add(EntryKey::InterfaceMethodref(class_index, name_and_type_index)),
+			false => self.add(EntryKey::Methodref(class_index, name_and_type_index)),
+		}
+	}
+
+	pub fn method_handle(&mut self, method_handle: &MethodHandleRef) -> Result<U2, ConstantPoolError> {
+		let reference_index = match method_handle.kind.is_field() {
+			true => self.fieldref(&method_handle.owner, &method_handle.name, &method_handle.descriptor)?,
+			false => self.methodref(&method_handle.owner, &method_handle.name, &method_handle.descriptor, method_handle.is_interface)?,
+		};
+		self.add(EntryKey::MethodHandle(method_handle.kind.as_u1(), reference_index))
+	}
+
+	pub fn method_type(&mut self, descriptor: &str) -> Result<U2, ConstantPoolError> {
+		let descriptor_index = self.utf8(descriptor)?;
+		self.add(EntryKey::MethodType(descriptor_index))
+	}
+
+	/// Adds a `Dynamic` entry, with `bootstrap_method_attr_index` referring to the `BootstrapMethods` attribute of the class.
+	pub fn dynamic(&mut self, bootstrap_method_attr_index: U2, name: &str, descriptor: &str) -> Result<U2, ConstantPoolError> {
+		let name_and_type_index = self.name_and_type(name, descriptor)?;
+		self.add(EntryKey::Dynamic(bootstrap_method_attr_index, name_and_type_index))
+	}
+
+	pub fn invoke_dynamic(&mut self, bootstrap_method_attr_index: U2, name: &str, descriptor: &str) -> Result<U2, ConstantPoolError> {
+		let name_and_type_index = self.name_and_type(name, descriptor)?;
+		self.add(EntryKey::InvokeDynamic(bootstrap_method_attr_index, name_and_type_index))
+	}
+
+	pub fn module(&mut self, module_name: &str) -> Result<U2, ConstantPoolError> {
+		let name_index = self.utf8(module_name)?;
+		self.add(EntryKey::Module(name_index))
+	}
+
+	pub fn package(&mut self, package_name: &str) -> Result<U2, ConstantPoolError> {
+		let name_index = self.utf8(package_name)?;
+		self.add(EntryKey::Package(name_index))
+	}
+}
+
+pub fn constant_pool_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ConstantPool, E> {
+	let (input, slots) = slots_parser(input, cp_info_parser::<'a, E>, CPInfo::is_wide)?;
+	Ok((input, ConstantPool { slots }))
+}
+
+pub fn constant_pool_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], ConstantPoolRef<'a>, E> {
+	let (input, slots) = slots_parser(input, cp_info_ref_parser::<'a, E>, CPInfoRef::is_wide)?;
+	Ok((input, ConstantPoolRef { slots }))
+}
+
+/// Parses `constant_pool_count` and the entries, leaving slot 0 and the slot after each `Long` and `Double` empty.
+fn slots_parser<'a, T, E, F>(input: &'a [u8], mut entry_parser: F, is_wide: fn(&T) -> bool) -> IResult<&'a [u8], Vec<Option<T>>, E>
+where
+	E: ClassParseError<&'a [u8]>,
+	F: FnMut(&'a [u8]) -> IResult<&'a [u8], T, E>,
+{
+	let (mut input, constant_pool_count) = be_u16(input)?;
+	let mut slots = Vec::with_capacity(constant_pool_count as usize);
+	slots.push(None);
+
+	while slots.len() < constant_pool_count as usize {
+		let index = slots.len();
+		let (rest, entry) = indexed(index, &mut entry_parser)(input)?;
+		let wide = is_wide(&entry);
+		if wide && index + 1 >= constant_pool_count as usize {
+			return indexed(index, |i| fail_with(i, ClassFormatErrorKind::ConstantPool(ConstantPoolError::IndexOutOfBounds((index + 1) as U2))))(input);
+		}
+		input = rest;
+		slots.push(Some(entry));
+		if wide {
+			slots.push(None);
+		}
+	}
+
+	Ok((input, slots))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::class_format_error::ClassFormatError;
+
+	use super::*;
+
+	#[test]
+	fn long_entries_take_two_slots() {
+		let input = [
+			0x00, 0x05,
+			0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2A,
+			0x01, 0x00, 0x01, b'A',
+			0x07, 0x00, 0x03,
+		];
+
+		let (rest, constant_pool) = constant_pool_parser::<ClassFormatError>(&input).expect("Failed to parse constant pool");
+		assert!(rest.is_empty());
+		assert_eq!(constant_pool.len(), 5);
+		assert!(matches!(constant_pool.get(1), Ok(CPInfo::Long(_))));
+		assert_eq!(constant_pool.get(2).unwrap_err(), ConstantPoolError::UnusableSlot(2));
+		assert_eq!(constant_pool.get_class_name(4).expect("Failed to resolve class name"), "A");
+		assert_eq!(constant_pool.get(5).unwrap_err(), ConstantPoolError::IndexOutOfBounds(5));
+		assert_eq!(constant_pool.get_utf8(4).unwrap_err(), ConstantPoolError::UnexpectedEntry { index: 4, expected: "Utf8", found: "Class" });
+	}
+
+	#[test]
+	fn long_entry_in_last_slot_is_rejected() {
+		let input = [
+			0x00, 0x02,
+			0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2A,
+		];
+
+		let error = match constant_pool_parser::<ClassFormatError>(&input) {
+			Err(nom::Err::Error(e)) => e.locate(&input),
+			v => panic!("Expected an error, got: {v:?}"),
+		};
+		assert_eq!(error.kind(), &ClassFormatErrorKind::ConstantPool(ConstantPoolError::IndexOutOfBounds(2)));
+		assert_eq!(error.path(), "[1]");
+		assert_eq!(error.offset(), 2);
+	}
+
+	#[test]
+	fn push_stops_at_the_last_slot() {
+		let mut constant_pool = ConstantPool::new();
+		while constant_pool.len() < U2::MAX as usize - 1 {
+			constant_pool.push(CPInfo::Integer(Integer { tag: cp_info::INTEGER, bytes: 0 })).unwrap();
+		}
+		assert_eq!(constant_pool.push(CPInfo::Long(Long { tag: cp_info::LONG, high_bytes: 0, low_bytes: 0 })), Err(ConstantPoolError::TooManyEntries));
+		assert_eq!(constant_pool.push(CPInfo::Integer(Integer { tag: cp_info::INTEGER, bytes: 0 })), Ok(U2::MAX - 1));
+		assert_eq!(constant_pool.push(CPInfo::Integer(Integer { tag: cp_info::INTEGER, bytes: 0 })), Err(ConstantPoolError::TooManyEntries));
+	}
+}
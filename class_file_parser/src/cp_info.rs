@@ -1,3 +1,5 @@
+use std::io::{self, Write};
+
 use class::{class_parser, Class};
 use double::{double_parser, Double};
 use dynamic::{dynamic_parser, Dynamic};
@@ -12,12 +14,12 @@ use method_type::{methodtype_parser, MethodType};
 use methodref::{methodref_parser, Methodref};
 use module::{module_parser, Module};
 use name_and_type::{nameandtype_parser, NameAndType};
-use nom::{error::ParseError, number::complete::be_u8, IResult};
+use nom::{error::context, number::complete::be_u8, IResult};
 use package::{package_parser, Package};
 use string::string_parser;
-use utf8::{utf8_parser, Utf8};
+use utf8::{utf8_parser, utf8_ref_parser, Utf8, Utf8Ref};
 
-use crate::U1;
+use crate::{class_format_error::{fail_with, ClassFormatErrorKind, ClassParseError}, writer::write_u1, U1};
 
 pub mod class;
 pub mod double;
@@ -58,96 +60,242 @@ pub enum CPInfo {
 	Package(Package),
 }
 
-const CLASS: U1 = 7;
-const FIELDREF: U1 = 9;
-const METHODREF: U1 = 10;
-const INTERFACEMETHODREF: U1 = 11;
-const STRING: U1 = 8;
-const INTEGER: U1 = 3;
-const FLOAT: U1 = 4;
-const LONG: U1 = 5;
-const DOUBLE: U1 = 6;
-const NAMEANDTYPE: U1 = 12;
-const UTF8: U1 = 1;
-const METHODHANDLE: U1 = 15;
-const METHODTYPE: U1 = 16;
-const DYNAMIC: U1 = 17;
-const INVOKEDYNAMIC: U1 = 18;
-const MODULE: U1 = 19;
-const PACKAGE: U1 = 20;
+impl CPInfo {
+	/// Name of the constant kind as used in the JVMS, e.g. `Utf8`.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Class(_) => "Class",
+			Self::Fieldref(_) => "Fieldref",
+			Self::Methodref(_) => "Methodref",
+			Self::InterfaceMethodref(_) => "InterfaceMethodref",
+			Self::String(_) => "String",
+			Self::Integer(_) => "Integer",
+			Self::Float(_) => "Float",
+			Self::Long(_) => "Long",
+			Self::Double(_) => "Double",
+			Self::NameAndType(_) => "NameAndType",
+			Self::Utf8(_) => "Utf8",
+			Self::MethodHandle(_) => "MethodHandle",
+			Self::MethodType(_) => "MethodType",
+			Self::Dynamic(_) => "Dynamic",
+			Self::InvokeDynamic(_) => "InvokeDynamic",
+			Self::Module(_) => "Module",
+			Self::Package(_) => "Package",
+		}
+	}
+
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		match self {
+			Self::Class(value) => {
+				write_u1(writer, CLASS)?;
+				value.write_to(writer)
+			},
+			Self::Fieldref(value) => {
+				write_u1(writer, FIELDREF)?;
+				value.write_to(writer)
+			},
+			Self::Methodref(value) => {
+				write_u1(writer, METHODREF)?;
+				value.write_to(writer)
+			},
+			Self::InterfaceMethodref(value) => {
+				write_u1(writer, INTERFACEMETHODREF)?;
+				value.write_to(writer)
+			},
+			Self::String(value) => {
+				write_u1(writer, STRING)?;
+				value.write_to(writer)
+			},
+			Self::Integer(value) => {
+				write_u1(writer, INTEGER)?;
+				value.write_to(writer)
+			},
+			Self::Float(value) => {
+				write_u1(writer, FLOAT)?;
+				value.write_to(writer)
+			},
+			Self::Long(value) => {
+				write_u1(writer, LONG)?;
+				value.write_to(writer)
+			},
+			Self::Double(value) => {
+				write_u1(writer, DOUBLE)?;
+				value.write_to(writer)
+			},
+			Self::NameAndType(value) => {
+				write_u1(writer, NAMEANDTYPE)?;
+				value.write_to(writer)
+			},
+			Self::Utf8(value) => {
+				write_u1(writer, UTF8)?;
+				value.write_to(writer)
+			},
+			Self::MethodHandle(value) => {
+				write_u1(writer, METHODHANDLE)?;
+				value.write_to(writer)
+			},
+			Self::MethodType(value) => {
+				write_u1(writer, METHODTYPE)?;
+				value.write_to(writer)
+			},
+			Self::Dynamic(value) => {
+				write_u1(writer, DYNAMIC)?;
+				value.write_to(writer)
+			},
+			Self::InvokeDynamic(value) => {
+				write_u1(writer, INVOKEDYNAMIC)?;
+				value.write_to(writer)
+			},
+			Self::Module(value) => {
+				write_u1(writer, MODULE)?;
+				value.write_to(writer)
+			},
+			Self::Package(value) => {
+				write_u1(writer, PACKAGE)?;
+				value.write_to(writer)
+			},
+		}
+	}
 
-pub fn cp_info_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], CPInfo> {
-	let (input, tag) = be_u8::<&[u8], E>(input).expect("Failed to read 'tag'");
+	/// `Long` and `Double` entries take up two constant pool slots.
+	pub fn is_wide(&self) -> bool {
+		matches!(self, Self::Long(_) | Self::Double(_))
+	}
+}
+
+/// Constant pool entry with the bytes of `Utf8` entries borrowed from the class file.
+///
+/// All other constants only hold indices and numbers, so they are stored as they are.
+#[derive(Debug)]
+pub enum CPInfoRef<'a> {
+	Utf8(Utf8Ref<'a>),
+	Other(CPInfo),
+}
+
+impl CPInfoRef<'_> {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Utf8(_) => "Utf8",
+			Self::Other(value) => value.name(),
+		}
+	}
+
+	pub fn is_wide(&self) -> bool {
+		match self {
+			Self::Utf8(_) => false,
+			Self::Other(value) => value.is_wide(),
+		}
+	}
+
+	pub fn into_owned(self) -> CPInfo {
+		match self {
+			Self::Utf8(value) => CPInfo::Utf8(value.into_owned()),
+			Self::Other(value) => value,
+		}
+	}
+}
+
+pub(crate) const CLASS: U1 = 7;
+pub(crate) const FIELDREF: U1 = 9;
+pub(crate) const METHODREF: U1 = 10;
+pub(crate) const INTERFACEMETHODREF: U1 = 11;
+pub(crate) const STRING: U1 = 8;
+pub(crate) const INTEGER: U1 = 3;
+pub(crate) const FLOAT: U1 = 4;
+pub(crate) const LONG: U1 = 5;
+pub(crate) const DOUBLE: U1 = 6;
+pub(crate) const NAMEANDTYPE: U1 = 12;
+pub(crate) const UTF8: U1 = 1;
+pub(crate) const METHODHANDLE: U1 = 15;
+pub(crate) const METHODTYPE: U1 = 16;
+pub(crate) const DYNAMIC: U1 = 17;
+pub(crate) const INVOKEDYNAMIC: U1 = 18;
+pub(crate) const MODULE: U1 = 19;
+pub(crate) const PACKAGE: U1 = 20;
+
+pub fn cp_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], CPInfo, E> {
+	let start = input;
+	let (input, tag) = context("tag", be_u8)(input)?;
 	
 	match tag {
 		CLASS => {
-			let (input, value) = class_parser::<'a, E>(input).expect("Failed to read 'CLASS'");
+			let (input, value) = context("Class", class_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Class(value)))
 		},
 		FIELDREF => {
-			let (input, value) = fieldref_parser::<'a, E>(input).expect("Failed to read 'FIELDREF'");
+			let (input, value) = context("Fieldref", fieldref_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Fieldref(value)))
 		},
 		METHODREF => {
-			let (input, value) = methodref_parser::<'a, E>(input).expect("Failed to read 'METHODREF'");
+			let (input, value) = context("Methodref", methodref_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Methodref(value)))
 		},
 		INTERFACEMETHODREF => {
-			let (input, value) = interfacemethodref_parser::<'a, E>(input).expect("Failed to read 'INTERFACEMETHODREF'");
+			let (input, value) = context("InterfaceMethodref", interfacemethodref_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::InterfaceMethodref(value)))
 		},
 		STRING => {
-			let (input, value) = string_parser::<'a, E>(input).expect("Failed to read 'STRING'");
+			let (input, value) = context("String", string_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::String(value)))
 		},
 		INTEGER => {
-			let (input, value) = integer_parser::<'a, E>(input).expect("Failed to read 'INTEGER'");
+			let (input, value) = context("Integer", integer_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Integer(value)))
 		},
 		FLOAT => {
-			let (input, value) = float_parser::<'a, E>(input).expect("Failed to read 'FLOAT'");
+			let (input, value) = context("Float", float_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Float(value)))
 		},
 		LONG => {
-			let (input, value) = long_parser::<'a, E>(input).expect("Failed to read 'LONG'");
+			let (input, value) = context("Long", long_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Long(value)))
 		},
 		DOUBLE => {
-			let (input, value) = double_parser::<'a, E>(input).expect("Failed to read 'DOUBLE'");
+			let (input, value) = context("Double", double_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Double(value)))
 		},
 		NAMEANDTYPE => {
-			let (input, value) = nameandtype_parser::<'a, E>(input).expect("Failed to read 'NAMEANDTYPE'");
+			let (input, value) = context("NameAndType", nameandtype_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::NameAndType(value)))
 		},
 		UTF8 => {
-			let (input, value) = utf8_parser::<'a, E>(input).expect("Failed to read 'UTF8'");
+			let (input, value) = context("Utf8", utf8_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Utf8(value)))
 		},
 		METHODHANDLE => {
-			let (input, value) = methodhandle_parser::<'a, E>(input).expect("Failed to read 'METHODHANDLE'");
+			let (input, value) = context("MethodHandle", methodhandle_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::MethodHandle(value)))
 		},
 		METHODTYPE => {
-			let (input, value) = methodtype_parser::<'a, E>(input).expect("Failed to read 'METHODTYPE'");
+			let (input, value) = context("MethodType", methodtype_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::MethodType(value)))
 		},
 		DYNAMIC => {
-			let (input, value) = dynamic_parser::<'a, E>(input).expect("Failed to read 'DYNAMIC'");
+			let (input, value) = context("Dynamic", dynamic_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Dynamic(value)))
 		},
 		INVOKEDYNAMIC => {
-			let (input, value) = invokedynamic_parser::<'a, E>(input).expect("Failed to read 'INVOKEDYNAMIC'");
+			let (input, value) = context("InvokeDynamic", invokedynamic_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::InvokeDynamic(value)))
 		},
 		MODULE => {
-			let (input, value) = module_parser::<'a, E>(input).expect("Failed to read 'MODULE'");
+			let (input, value) = context("Module", module_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Module(value)))
 		},
 		PACKAGE => {
-			let (input, value) = package_parser::<'a, E>(input).expect("Failed to read 'PACKAGE'");
+			let (input, value) = context("Package", package_parser::<'a, E>)(input)?;
 			Ok((input, CPInfo::Package(value)))
 		},
-		v => panic!("Unknown constant tag: '{v}'"),
+		v => fail_with(start, ClassFormatErrorKind::UnknownConstantTag(v)),
+	}
+}
+
+pub fn cp_info_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], CPInfoRef<'a>, E> {
+	let (rest, tag) = context("tag", be_u8)(input)?;
+	if tag == UTF8 {
+		let (rest, value) = context("Utf8", utf8_ref_parser::<'a, E>)(rest)?;
+		return Ok((rest, CPInfoRef::Utf8(value)));
 	}
+	let (rest, value) = cp_info_parser(input)?;
+	Ok((rest, CPInfoRef::Other(value)))
 }
\ No newline at end of file
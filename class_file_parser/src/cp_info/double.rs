@@ -1,6 +1,8 @@
-use nom::{error::ParseError, number::complete::be_u32, IResult};
+use std::io::{self, Write};
 
-use crate::{U1, U4};
+use nom::{error::context, number::complete::be_u32, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::write_u4, U1, U4};
 
 use super::DOUBLE;
 
@@ -11,9 +13,16 @@ pub struct Double {
     pub low_bytes: U4,
 }
 
-pub fn double_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], Double> {
-    let (input, high_bytes) = be_u32::<&[u8], E>(input).expect("Failed to read 'high_bytes'");
-    let (input, low_bytes) = be_u32::<&[u8], E>(input).expect("Failed to read 'low_bytes'");
+impl Double {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u4(writer, self.high_bytes)?;
+        write_u4(writer, self.low_bytes)
+    }
+}
+
+pub fn double_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Double, E> {
+    let (input, high_bytes) = context("high_bytes", be_u32)(input)?;
+    let (input, low_bytes) = context("low_bytes", be_u32)(input)?;
 
     Ok((input, Double {
         tag: DOUBLE,
@@ -1,6 +1,8 @@
-use nom::{error::ParseError, number::complete::be_u32, IResult};
+use std::io::{self, Write};
 
-use crate::{U1, U4};
+use nom::{error::context, number::complete::be_u32, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::write_u4, U1, U4};
 
 use super::INTEGER;
 
@@ -10,8 +12,14 @@ pub struct Integer {
     pub bytes: U4,
 }
 
-pub fn integer_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], Integer> {
-    let (input, bytes) = be_u32::<&[u8], E>(input).expect("Failed to read 'bytes'");
+impl Integer {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u4(writer, self.bytes)
+    }
+}
+
+pub fn integer_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Integer, E> {
+    let (input, bytes) = context("bytes", be_u32)(input)?;
 
     Ok((input, Integer {
         tag: INTEGER,
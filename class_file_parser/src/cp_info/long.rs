@@ -1,6 +1,8 @@
-use nom::{error::ParseError, number::complete::be_u32, IResult};
+use std::io::{self, Write};
 
-use crate::{U1, U4};
+use nom::{error::context, number::complete::be_u32, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::write_u4, U1, U4};
 
 use super::LONG;
 
@@ -11,9 +13,16 @@ pub struct Long {
     pub low_bytes: U4,
 }
 
-pub fn long_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], Long> {
-    let (input, high_bytes) = be_u32::<&[u8], E>(input).expect("Failed to read 'high_bytes'");
-    let (input, low_bytes) = be_u32::<&[u8], E>(input).expect("Failed to read 'low_bytes'");
+impl Long {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u4(writer, self.high_bytes)?;
+        write_u4(writer, self.low_bytes)
+    }
+}
+
+pub fn long_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Long, E> {
+    let (input, high_bytes) = context("high_bytes", be_u32)(input)?;
+    let (input, low_bytes) = context("low_bytes", be_u32)(input)?;
 
     Ok((input, Long {
         tag: LONG,
@@ -1,9 +1,67 @@
-use nom::{error::ParseError, number::complete::{be_u16, be_u8}, IResult};
+use std::{fmt::Display, io::{self, Write}};
 
-use crate::{U1, U2};
+use nom::{error::context, number::complete::{be_u16, be_u8}, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::{write_u1, write_u2}, U1, U2};
 
 use super::METHODHANDLE;
 
+/// Kind of a method handle (JVMS 5.4.3.5), stored as `reference_kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    GetField = 1,
+    GetStatic = 2,
+    PutField = 3,
+    PutStatic = 4,
+    InvokeVirtual = 5,
+    InvokeStatic = 6,
+    InvokeSpecial = 7,
+    NewInvokeSpecial = 8,
+    InvokeInterface = 9,
+}
+
+impl ReferenceKind {
+    pub fn from_u1(reference_kind: U1) -> Option<Self> {
+        Some(match reference_kind {
+            1 => Self::GetField,
+            2 => Self::GetStatic,
+            3 => Self::PutField,
+            4 => Self::PutStatic,
+            5 => Self::InvokeVirtual,
+            6 => Self::InvokeStatic,
+            7 => Self::InvokeSpecial,
+            8 => Self::NewInvokeSpecial,
+            9 => Self::InvokeInterface,
+            _ => return None,
+        })
+    }
+
+    pub fn as_u1(&self) -> U1 {
+        *self as U1
+    }
+
+    pub fn is_field(&self) -> bool {
+        matches!(self, Self::GetField | Self::GetStatic | Self::PutField | Self::PutStatic)
+    }
+}
+
+impl Display for ReferenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::GetField => "REF_getField",
+            Self::GetStatic => "REF_getStatic",
+            Self::PutField => "REF_putField",
+            Self::PutStatic => "REF_putStatic",
+            Self::InvokeVirtual => "REF_invokeVirtual",
+            Self::InvokeStatic => "REF_invokeStatic",
+            Self::InvokeSpecial => "REF_invokeSpecial",
+            Self::NewInvokeSpecial => "REF_newInvokeSpecial",
+            Self::InvokeInterface => "REF_invokeInterface",
+        };
+        write!(f, "{name}")
+    }
+}
+
 #[derive(Debug)]
 pub struct MethodHandle {
     pub tag: U1,
@@ -11,9 +69,16 @@ pub struct MethodHandle {
     pub reference_index: U2,
 }
 
-pub fn methodhandle_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], MethodHandle> {
-    let (input, reference_kind) = be_u8::<&[u8], E>(input).expect("Failed to read 'reference_kind'");
-    let (input, reference_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'reference_index'");
+impl MethodHandle {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u1(writer, self.reference_kind)?;
+        write_u2(writer, self.reference_index)
+    }
+}
+
+pub fn methodhandle_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], MethodHandle, E> {
+    let (input, reference_kind) = context("reference_kind", be_u8)(input)?;
+    let (input, reference_index) = context("reference_index", be_u16)(input)?;
 
     Ok((input, MethodHandle {
         tag: METHODHANDLE,
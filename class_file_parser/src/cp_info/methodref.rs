@@ -1,6 +1,8 @@
-use nom::{error::ParseError, number::complete::be_u16, IResult};
+use std::io::{self, Write};
 
-use crate::{U1, U2};
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::write_u2, U1, U2};
 
 use super::METHODREF;
 
@@ -11,9 +13,16 @@ pub struct Methodref {
     pub name_and_type_index: U2,
 }
 
-pub fn methodref_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], Methodref> {
-    let (input, class_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'class_index'");
-    let (input, name_and_type_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'name_and_type_index'");
+impl Methodref {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u2(writer, self.class_index)?;
+        write_u2(writer, self.name_and_type_index)
+    }
+}
+
+pub fn methodref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Methodref, E> {
+    let (input, class_index) = context("class_index", be_u16)(input)?;
+    let (input, name_and_type_index) = context("name_and_type_index", be_u16)(input)?;
 
     Ok((input, Methodref {
         tag: METHODREF,
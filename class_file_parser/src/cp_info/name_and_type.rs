@@ -1,6 +1,8 @@
-use nom::{error::ParseError, number::complete::be_u16, IResult};
+use std::io::{self, Write};
 
-use crate::{U1, U2};
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::write_u2, U1, U2};
 
 use super::NAMEANDTYPE;
 
@@ -11,9 +13,16 @@ pub struct NameAndType {
     pub descriptor_index: U2,
 }
 
-pub fn nameandtype_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], NameAndType> {
-    let (input, name_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'name_index'");
-    let (input, descriptor_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'descriptor_index'");
+impl NameAndType {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u2(writer, self.name_index)?;
+        write_u2(writer, self.descriptor_index)
+    }
+}
+
+pub fn nameandtype_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], NameAndType, E> {
+    let (input, name_index) = context("name_index", be_u16)(input)?;
+    let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
 
     Ok((input, NameAndType {
         tag: NAMEANDTYPE,
@@ -1,6 +1,8 @@
-use nom::{error::ParseError, number::complete::be_u16, IResult};
+use std::io::{self, Write};
 
-use crate::{U1, U2};
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{class_format_error::ClassParseError, writer::write_u2, U1, U2};
 
 use super::PACKAGE;
 
@@ -10,8 +12,14 @@ pub struct Package {
     pub name_index: U2,
 }
 
-pub fn package_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], Package> {
-    let (input, name_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'name_index'");
+impl Package {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_u2(writer, self.name_index)
+    }
+}
+
+pub fn package_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Package, E> {
+    let (input, name_index) = context("name_index", be_u16)(input)?;
 
     Ok((input, Package {
         tag: PACKAGE,
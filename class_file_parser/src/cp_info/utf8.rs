@@ -1,6 +1,8 @@
-use nom::{error::ParseError, multi::length_count, number::complete::{be_u16, be_u8}, IResult};
+use std::{borrow::Cow, io::{self, Write}};
 
-use crate::{U1, U2};
+use nom::{bytes::complete::take, error::context, number::complete::be_u16, IResult};
+
+use crate::{class_format_error::{fail_with, ClassFormatErrorKind, ClassParseError}, mutf8::{self, JavaString, MUtf8Error}, writer::write_length_u2, U1, U2};
 
 use super::UTF8;
 
@@ -11,12 +13,75 @@ pub struct Utf8 {
     pub bytes: Vec<U1>,
 }
 
-pub fn utf8_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], Utf8> {
-    let (input, bytes) = length_count(be_u16::<&[u8], E>, be_u8::<&[u8], E>)(input).expect("Failed to read 'length' or 'bytes'");
-	let length = bytes.len() as U2;
+impl Utf8 {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_length_u2(writer, self.bytes.len(), "bytes")?;
+        writer.write_all(&self.bytes)
+    }
+
+    pub fn from_java_string(value: &JavaString) -> Self {
+        let bytes = mutf8::encode(value.as_utf16());
+        Self {
+            tag: UTF8,
+            length: bytes.len() as U2,
+            bytes,
+        }
+    }
+
+    pub fn to_java_string(&self) -> Result<JavaString, MUtf8Error> {
+        mutf8::decode(&self.bytes)
+    }
+
+    pub fn to_str(&self) -> Result<Cow<'_, str>, MUtf8Error> {
+        mutf8::decode_to_str(&self.bytes)
+    }
+}
+
+/// `Utf8` whose bytes are borrowed from the class file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8Ref<'a> {
+    pub length: U2,
+    pub bytes: &'a [U1],
+}
+
+impl<'a> Utf8Ref<'a> {
+    pub fn to_java_string(&self) -> Result<JavaString, MUtf8Error> {
+        mutf8::decode(self.bytes)
+    }
+
+    /// Decodes the bytes, borrowing them if they are valid UTF-8 as well.
+    pub fn to_str(&self) -> Result<Cow<'a, str>, MUtf8Error> {
+        mutf8::decode_to_str(self.bytes)
+    }
+
+    pub fn into_owned(self) -> Utf8 {
+        Utf8 {
+            tag: UTF8,
+            length: self.length,
+            bytes: self.bytes.to_vec(),
+        }
+    }
+}
+
+impl From<&str> for Utf8 {
+    fn from(value: &str) -> Self {
+        Self::from_java_string(&JavaString::from(value))
+    }
+}
+
+pub fn utf8_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Utf8, E> {
+    let (input, utf8) = utf8_ref_parser(input)?;
+    Ok((input, utf8.into_owned()))
+}
+
+pub fn utf8_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], Utf8Ref<'a>, E> {
+    let (input, length) = context("length", be_u16)(input)?;
+    let (rest, bytes) = context("bytes", take(length))(input)?;
+    if let Err(error) = mutf8::validate(bytes) {
+        return context("bytes", |_| fail_with(&input[error.position..], ClassFormatErrorKind::MalformedUtf8(error)))(input);
+    }
 
-    Ok((input, Utf8 {
-        tag: UTF8,
+    Ok((rest, Utf8Ref {
         length,
         bytes,
     }))
@@ -0,0 +1,375 @@
+use std::{fmt::Display, str::FromStr};
+
+use types::{boolean::Boolean, byte::Byte, char::Char, double::Double, float::Float, int::Int, long::Long, reference::Reference, short::Short, Type, Types};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseType {
+	Byte,
+	Char,
+	Double,
+	Float,
+	Int,
+	Long,
+	Short,
+	Boolean,
+}
+
+impl BaseType {
+	pub fn from_char(c: char) -> Option<Self> {
+		match c {
+			'B' => Some(Self::Byte),
+			'C' => Some(Self::Char),
+			'D' => Some(Self::Double),
+			'F' => Some(Self::Float),
+			'I' => Some(Self::Int),
+			'J' => Some(Self::Long),
+			'S' => Some(Self::Short),
+			'Z' => Some(Self::Boolean),
+			_ => None,
+		}
+	}
+
+	pub fn as_char(&self) -> char {
+		match self {
+			Self::Byte => 'B',
+			Self::Char => 'C',
+			Self::Double => 'D',
+			Self::Float => 'F',
+			Self::Int => 'I',
+			Self::Long => 'J',
+			Self::Short => 'S',
+			Self::Boolean => 'Z',
+		}
+	}
+
+	/// Zero value of this type, which is also the initial value of fields and array elements.
+	pub fn default_value(&self) -> Types {
+		match self {
+			Self::Byte => Types::Byte(Byte::new()),
+			Self::Char => Types::Char(Char::new()),
+			Self::Double => Types::Double(Double::new()),
+			Self::Float => Types::Float(Float::new()),
+			Self::Int => Types::Int(Int::new()),
+			Self::Long => Types::Long(Long::new()),
+			Self::Short => Types::Short(Short::new()),
+			Self::Boolean => Types::Boolean(Boolean::new()),
+		}
+	}
+
+	/// Number of local variable or operand stack slots a value of this type occupies.
+	pub fn slots(&self) -> u16 {
+		match self {
+			Self::Long | Self::Double => 2,
+			_ => 1,
+		}
+	}
+}
+
+/// Field descriptor (JVMS 4.3.2).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+	Base(BaseType),
+	/// Class or interface in internal form, e.g. `java/lang/String`.
+	Object(String),
+	/// Array of `element`, which is never an array itself.
+	Array {
+		dimensions: u8,
+		element: Box<FieldType>,
+	},
+}
+
+impl FieldType {
+	pub fn slots(&self) -> u16 {
+		match self {
+			Self::Base(base_type) => base_type.slots(),
+			_ => 1,
+		}
+	}
+
+	pub fn is_reference(&self) -> bool {
+		!matches!(self, Self::Base(_))
+	}
+
+	pub fn default_value(&self) -> Types {
+		match self {
+			Self::Base(base_type) => base_type.default_value(),
+			_ => Types::Reference(Reference::new()),
+		}
+	}
+}
+
+impl Display for FieldType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Base(base_type) => write!(f, "{}", base_type.as_char()),
+			Self::Object(class_name) => write!(f, "L{class_name};"),
+			Self::Array { dimensions, element } => write!(f, "{}{element}", "[".repeat(*dimensions as usize)),
+		}
+	}
+}
+
+impl FromStr for FieldType {
+	type Err = DescriptorError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_field_descriptor(s)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReturnDescriptor {
+	Void,
+	Type(FieldType),
+}
+
+impl ReturnDescriptor {
+	pub fn slots(&self) -> u16 {
+		match self {
+			Self::Void => 0,
+			Self::Type(field_type) => field_type.slots(),
+		}
+	}
+}
+
+impl Display for ReturnDescriptor {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Void => write!(f, "V"),
+			Self::Type(field_type) => write!(f, "{field_type}"),
+		}
+	}
+}
+
+/// Method descriptor (JVMS 4.3.3).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+	pub parameters: Vec<FieldType>,
+	pub return_type: ReturnDescriptor,
+}
+
+impl MethodDescriptor {
+	/// Number of local variable slots taken by the parameters, not counting `this`.
+	pub fn argument_slots(&self) -> usize {
+		self.parameters.iter().map(|parameter| parameter.slots() as usize).sum()
+	}
+}
+
+impl Display for MethodDescriptor {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "(")?;
+		for parameter in &self.parameters {
+			write!(f, "{parameter}")?;
+		}
+		write!(f, "){}", self.return_type)
+	}
+}
+
+impl FromStr for MethodDescriptor {
+	type Err = DescriptorError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_method_descriptor(s)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptorErrorKind {
+	UnexpectedEnd,
+	UnexpectedCharacter(char),
+	InvalidClassName,
+	TooManyDimensions,
+	TooManyParameterSlots(usize),
+	TrailingCharacters,
+}
+
+/// Malformed descriptor, with `position` being the byte offset of the problem within `descriptor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorError {
+	pub descriptor: String,
+	pub position: usize,
+	pub kind: DescriptorErrorKind,
+}
+
+impl Display for DescriptorError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "invalid descriptor '{}' at position {}: ", self.descriptor, self.position)?;
+		match &self.kind {
+			DescriptorErrorKind::UnexpectedEnd => write!(f, "unexpected end"),
+			DescriptorErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{c}'"),
+			DescriptorErrorKind::InvalidClassName => write!(f, "invalid class name"),
+			DescriptorErrorKind::TooManyDimensions => write!(f, "more than 255 array dimensions"),
+			DescriptorErrorKind::TooManyParameterSlots(slots) => write!(f, "parameters take {slots} slots, more than 255"),
+			DescriptorErrorKind::TrailingCharacters => write!(f, "trailing characters"),
+		}
+	}
+}
+
+impl std::error::Error for DescriptorError {}
+
+struct DescriptorParser<'a> {
+	descriptor: &'a str,
+	position: usize,
+}
+
+impl DescriptorParser<'_> {
+	fn error(&self, position: usize, kind: DescriptorErrorKind) -> DescriptorError {
+		DescriptorError {
+			descriptor: self.descriptor.to_string(),
+			position,
+			kind,
+		}
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.descriptor[self.position..].chars().next()
+	}
+
+	fn next(&mut self) -> Result<char, DescriptorError> {
+		let c = self.peek().ok_or(self.error(self.position, DescriptorErrorKind::UnexpectedEnd))?;
+		self.position += c.len_utf8();
+		Ok(c)
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), DescriptorError> {
+		let position = self.position;
+		match self.next()? {
+			c if c == expected => Ok(()),
+			c => Err(self.error(position, DescriptorErrorKind::UnexpectedCharacter(c))),
+		}
+	}
+
+	fn class_name(&mut self) -> Result<String, DescriptorError> {
+		let start = self.position;
+		let end = match self.descriptor[start..].find(';') {
+			Some(length) => start + length,
+			None => return Err(self.error(self.descriptor.len(), DescriptorErrorKind::UnexpectedEnd)),
+		};
+		let class_name = &self.descriptor[start..end];
+		if class_name.split('/').any(|part| part.is_empty() || part.contains(['.', '['])) {
+			return Err(self.error(start, DescriptorErrorKind::InvalidClassName));
+		}
+		self.position = end + 1;
+		Ok(class_name.to_string())
+	}
+
+	fn field_type(&mut self) -> Result<FieldType, DescriptorError> {
+		let start = self.position;
+		let mut dimensions = 0usize;
+		while self.peek() == Some('[') {
+			self.position += 1;
+			dimensions += 1;
+		}
+		if dimensions > u8::MAX as usize {
+			return Err(self.error(start, DescriptorErrorKind::TooManyDimensions));
+		}
+
+		let position = self.position;
+		let element = match self.next()? {
+			'L' => FieldType::Object(self.class_name()?),
+			c => match BaseType::from_char(c) {
+				Some(base_type) => FieldType::Base(base_type),
+				None => return Err(self.error(position, DescriptorErrorKind::UnexpectedCharacter(c))),
+			},
+		};
+
+		if dimensions == 0 {
+			Ok(element)
+		} else {
+			Ok(FieldType::Array {
+				dimensions: dimensions as u8,
+				element: Box::new(element),
+			})
+		}
+	}
+
+	fn method_descriptor(&mut self) -> Result<MethodDescriptor, DescriptorError> {
+		self.expect('(')?;
+		let mut parameters = Vec::new();
+		while self.peek() != Some(')') {
+			parameters.push(self.field_type()?);
+		}
+		self.expect(')')?;
+
+		let return_type = if self.peek() == Some('V') {
+			self.position += 1;
+			ReturnDescriptor::Void
+		} else {
+			ReturnDescriptor::Type(self.field_type()?)
+		};
+
+		let method_descriptor = MethodDescriptor {
+			parameters,
+			return_type,
+		};
+		let slots = method_descriptor.argument_slots();
+		if slots > 255 {
+			return Err(self.error(0, DescriptorErrorKind::TooManyParameterSlots(slots)));
+		}
+		Ok(method_descriptor)
+	}
+
+	fn end(&self) -> Result<(), DescriptorError> {
+		if self.position < self.descriptor.len() {
+			return Err(self.error(self.position, DescriptorErrorKind::TrailingCharacters));
+		}
+		Ok(())
+	}
+}
+
+pub fn parse_field_descriptor(descriptor: &str) -> Result<FieldType, DescriptorError> {
+	let mut parser = DescriptorParser {
+		descriptor,
+		position: 0,
+	};
+	let field_type = parser.field_type()?;
+	parser.end()?;
+	Ok(field_type)
+}
+
+pub fn parse_method_descriptor(descriptor: &str) -> Result<MethodDescriptor, DescriptorError> {
+	let mut parser = DescriptorParser {
+		descriptor,
+		position: 0,
+	};
+	let method_descriptor = parser.method_descriptor()?;
+	parser.end()?;
+	Ok(method_descriptor)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_method_descriptor_with_wide_and_array_parameters() {
+		let descriptor = parse_method_descriptor("(I[Ljava/lang/String;J)V").expect("Failed to parse descriptor");
+		assert_eq!(descriptor.parameters, [
+			FieldType::Base(BaseType::Int),
+			FieldType::Array { dimensions: 1, element: Box::new(FieldType::Object("java/lang/String".to_string())) },
+			FieldType::Base(BaseType::Long),
+		]);
+		assert_eq!(descriptor.return_type, ReturnDescriptor::Void);
+		assert_eq!(descriptor.argument_slots(), 4);
+		assert_eq!(descriptor.to_string(), "(I[Ljava/lang/String;J)V");
+	}
+
+	#[test]
+	fn field_descriptor_default_values() {
+		assert!(matches!(parse_field_descriptor("D").unwrap().default_value(), Types::Double(_)));
+		assert!(matches!(parse_field_descriptor("[[Z").unwrap().default_value(), Types::Reference(_)));
+	}
+
+	#[test]
+	fn reject_malformed_descriptors() {
+		assert_eq!(parse_field_descriptor("Ljava/lang/String").unwrap_err().kind, DescriptorErrorKind::UnexpectedEnd);
+		assert_eq!(parse_field_descriptor("L;").unwrap_err().kind, DescriptorErrorKind::InvalidClassName);
+		assert_eq!(parse_field_descriptor("V").unwrap_err().kind, DescriptorErrorKind::UnexpectedCharacter('V'));
+		assert_eq!(parse_field_descriptor("II").unwrap_err(), DescriptorError { descriptor: "II".to_string(), position: 1, kind: DescriptorErrorKind::TrailingCharacters });
+		assert_eq!(parse_field_descriptor(&format!("{}I", "[".repeat(256))).unwrap_err().kind, DescriptorErrorKind::TooManyDimensions);
+		assert!(parse_field_descriptor(&format!("{}I", "[".repeat(255))).is_ok());
+		assert_eq!(parse_method_descriptor("()").unwrap_err().kind, DescriptorErrorKind::UnexpectedEnd);
+		assert_eq!(parse_method_descriptor(&format!("({})V", "J".repeat(128))).unwrap_err().kind, DescriptorErrorKind::TooManyParameterSlots(256));
+		// 65536 slots, which a 16-bit sum would wrap to 0
+		assert_eq!(parse_method_descriptor(&format!("({})V", "J".repeat(32768))).unwrap_err().kind, DescriptorErrorKind::TooManyParameterSlots(65536));
+	}
+}
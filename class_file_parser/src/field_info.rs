@@ -1,6 +1,8 @@
-use nom::{error::ParseError, multi::length_count, number::complete::be_u16, IResult};
+use std::{borrow::Cow, io::{self, Write}};
 
-use crate::{attribute_info::{attribute_info_parser, AttributeInfo}, U2};
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{access_flags::FieldAccess, attribute_info::{annotations::{self, Annotation, TypeAnnotation, Visibility}, attribute_info_parser, attribute_info_ref_parser, constant_value::{constant_value_attribute_parser, ConstantValueAttribute}, find_attribute, signature, write_attributes, AttributeInfo, AttributeInfoRef}, class_format_error::{indexed_length_count, ClassFormatError, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError, ConstantPoolRef}, signature::{parse_field_signature, ReferenceTypeSignature}, writer::write_u2, U2};
 
 #[derive(Debug)]
 pub struct FieldInfo {
@@ -11,12 +13,79 @@ pub struct FieldInfo {
 	pub attributes: Vec<AttributeInfo>,
 }
 
-pub fn field_info_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], FieldInfo> {
-	let (input, access_flags) = be_u16::<&[u8], E>(input).expect("Failed to read 'access_flags'");
-	let (input, name_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'name_index'");
-	let (input, descriptor_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'descriptor_index'");
+impl FieldInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.access_flags)?;
+		write_u2(writer, self.name_index)?;
+		write_u2(writer, self.descriptor_index)?;
+		write_attributes(writer, &self.attributes)
+	}
+
+	pub fn access(&self) -> FieldAccess {
+		FieldAccess(self.access_flags)
+	}
+
+	/// Decodes the `ConstantValue` attribute, which initializes static fields with a compile-time constant.
+	pub fn constant_value(&self, constant_pool: &ConstantPool) -> Result<Option<ConstantValueAttribute>, ClassFormatError> {
+		match find_attribute(&self.attributes, constant_pool, "ConstantValue")? {
+			Some(attribute) => Ok(Some(attribute.parse("ConstantValue", constant_value_attribute_parser)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Generic signature, which only fields of a generic type have.
+	pub fn signature(&self, constant_pool: &ConstantPool) -> Result<Option<ReferenceTypeSignature>, ClassFormatError> {
+		match signature::signature(&self.attributes, constant_pool)? {
+			Some(signature) => Ok(Some(parse_field_signature(&signature)?)),
+			None => Ok(None),
+		}
+	}
+
+	pub fn annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<Annotation>, ClassFormatError> {
+		annotations::annotations(&self.attributes, constant_pool, visibility)
+	}
+
+	pub fn type_annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<TypeAnnotation>, ClassFormatError> {
+		annotations::type_annotations(&self.attributes, constant_pool, visibility)
+	}
+}
+
+/// `FieldInfo` with attributes borrowed from the class file.
+#[derive(Debug, Clone)]
+pub struct FieldInfoRef<'a> {
+	pub access_flags: U2,
+	pub name_index: U2,
+	pub descriptor_index: U2,
+	pub attributes_count: U2,
+	pub attributes: Vec<AttributeInfoRef<'a>>,
+}
+
+impl<'a> FieldInfoRef<'a> {
+	pub fn access(&self) -> FieldAccess {
+		FieldAccess(self.access_flags)
+	}
+
+	pub fn name(&self, constant_pool: &ConstantPoolRef<'a>) -> Result<Cow<'a, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.name_index)
+	}
+
+	pub fn into_owned(self) -> FieldInfo {
+		FieldInfo {
+			access_flags: self.access_flags,
+			name_index: self.name_index,
+			descriptor_index: self.descriptor_index,
+			attributes_count: self.attributes_count,
+			attributes: self.attributes.into_iter().map(AttributeInfoRef::into_owned).collect(),
+		}
+	}
+}
+
+pub fn field_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], FieldInfo, E> {
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
 	
-	let (input, attributes) = length_count(be_u16, attribute_info_parser::<'a, E>)(input).expect("Failed to read 'attributes_count' or 'attributes'");
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_parser::<'a, E>))(input)?;
 	let attributes_count = attributes.len() as U2;
 
 	Ok((input, FieldInfo {
@@ -26,4 +95,21 @@ pub fn field_info_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'
 		attributes_count,
 		attributes,
 	}))
+}
+
+pub fn field_info_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], FieldInfoRef<'a>, E> {
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
+	
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_ref_parser::<'a, E>))(input)?;
+	let attributes_count = attributes.len() as U2;
+
+	Ok((input, FieldInfoRef {
+		access_flags,
+		name_index,
+		descriptor_index,
+		attributes_count,
+		attributes,
+	}))
 }
\ No newline at end of file
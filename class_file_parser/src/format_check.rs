@@ -0,0 +1,497 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{access_flags::{AccessFlagsError, ClassAccess, MethodAccess}, attribute_info::{code::code_attribute_parser, record::record_attribute_parser, AttributeInfo}, class_file::ClassFile, constant_pool::{ConstantPool, ConstantPoolError}, cp_info::{method_handle::ReferenceKind, CPInfo}, descriptor::{parse_field_descriptor, parse_method_descriptor, DescriptorError, FieldType, ReturnDescriptor}, U2, U4};
+
+pub const MAGIC: U4 = 0xCAFEBABE;
+/// Minor version of class files that depend on the preview features of their major version.
+pub const PREVIEW_MINOR_VERSION: U2 = 0xFFFF;
+
+/// Structure an attribute is attached to (JVMS 4.7, Table 4.7-C).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttributeOwner {
+	ClassFile,
+	Field,
+	Method,
+	Code,
+	RecordComponent,
+}
+
+/// Attributes that may occur more than once in the same structure.
+const REPEATABLE_ATTRIBUTES: [&str; 3] = ["LineNumberTable", "LocalVariableTable", "LocalVariableTypeTable"];
+
+/// Where a predefined attribute may appear, `None` for attributes this module does not know.
+fn attribute_owners(name: &str) -> Option<&'static [AttributeOwner]> {
+	use AttributeOwner::*;
+
+	Some(match name {
+		"SourceFile" | "InnerClasses" | "EnclosingMethod" | "SourceDebugExtension" | "BootstrapMethods" | "Module" | "ModulePackages" | "ModuleMainClass" | "NestHost" | "NestMembers" | "Record" | "PermittedSubclasses" => &[ClassFile],
+		"ConstantValue" => &[Field],
+		"Code" | "Exceptions" | "RuntimeVisibleParameterAnnotations" | "RuntimeInvisibleParameterAnnotations" | "AnnotationDefault" | "MethodParameters" => &[Method],
+		"LineNumberTable" | "LocalVariableTable" | "LocalVariableTypeTable" | "StackMapTable" => &[Code],
+		"Synthetic" | "Deprecated" => &[ClassFile, Field, Method],
+		"Signature" | "RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => &[ClassFile, Field, Method, RecordComponent],
+		"RuntimeVisibleTypeAnnotations" | "RuntimeInvisibleTypeAnnotations" => &[ClassFile, Field, Method, Code, RecordComponent],
+		_ => return None,
+	})
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatErrorKind {
+	InvalidMagic(U4),
+	UnsupportedVersion {
+		major: U2,
+		minor: U2,
+	},
+	ConstantPool(ConstantPoolError),
+	/// The constant pool entry called `.0` was only introduced in major version `.1`.
+	ConstantRequiresVersion(&'static str, U2),
+	InvalidClassName(String),
+	InvalidFieldName(String),
+	InvalidMethodName(String),
+	InvalidModuleName(String),
+	InvalidPackageName(String),
+	InvalidDescriptor(DescriptorError),
+	/// `<init>` or `<clinit>` with a descriptor other than one returning `void` (and taking no arguments for `<clinit>`).
+	InvalidInitializerDescriptor {
+		name: String,
+		descriptor: String,
+	},
+	/// Method handle whose reference kind does not fit the name of the referenced method.
+	///
+	/// `interface_method` is set if instead it refers to an interface method, which `REF_invokeStatic` and `REF_invokeSpecial` only may from version 52 on.
+	InvalidMethodHandle {
+		kind: ReferenceKind,
+		name: String,
+		interface_method: bool,
+	},
+	MissingSuperClass,
+	AccessFlags(AccessFlagsError),
+	/// Field or method with the same name and descriptor as an earlier one.
+	DuplicateMember {
+		name: String,
+		descriptor: String,
+	},
+	MisplacedAttribute(String),
+	DuplicateAttribute(String),
+	/// A method that is neither abstract nor native has no `Code` attribute.
+	MissingCode,
+	/// An abstract or native method has a `Code` attribute.
+	UnexpectedCode,
+	MalformedAttribute(String),
+}
+
+impl Display for FormatErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidMagic(magic) => write!(f, "invalid magic {magic:#010x}"),
+			Self::UnsupportedVersion { major, minor } => write!(f, "unsupported version {major}.{minor}"),
+			Self::ConstantPool(error) => write!(f, "{error}"),
+			Self::ConstantRequiresVersion(name, major) => write!(f, "{name} constants require major version {major}"),
+			Self::InvalidClassName(name) => write!(f, "invalid class name '{name}'"),
+			Self::InvalidFieldName(name) => write!(f, "invalid field name '{name}'"),
+			Self::InvalidMethodName(name) => write!(f, "invalid method name '{name}'"),
+			Self::InvalidModuleName(name) => write!(f, "invalid module name '{name}'"),
+			Self::InvalidPackageName(name) => write!(f, "invalid package name '{name}'"),
+			Self::InvalidDescriptor(error) => write!(f, "{error}"),
+			Self::InvalidInitializerDescriptor { name, descriptor } => write!(f, "invalid descriptor '{descriptor}' for {name}"),
+			Self::InvalidMethodHandle { kind, name, interface_method: true } => write!(f, "{kind} may not refer to interface method '{name}' before version 52"),
+			Self::InvalidMethodHandle { kind, name, interface_method: false } => write!(f, "{kind} may not refer to a method called '{name}'"),
+			Self::MissingSuperClass => write!(f, "only java/lang/Object and modules have no superclass"),
+			Self::AccessFlags(error) => write!(f, "{error}"),
+			Self::DuplicateMember { name, descriptor } => write!(f, "duplicate member {name}:{descriptor}"),
+			Self::MisplacedAttribute(name) => write!(f, "{name} attribute is not allowed here"),
+			Self::DuplicateAttribute(name) => write!(f, "duplicate {name} attribute"),
+			Self::MissingCode => write!(f, "missing Code attribute"),
+			Self::UnexpectedCode => write!(f, "abstract and native methods may not have a Code attribute"),
+			Self::MalformedAttribute(error) => write!(f, "{error}"),
+		}
+	}
+}
+
+impl From<ConstantPoolError> for FormatErrorKind {
+	fn from(value: ConstantPoolError) -> Self {
+		Self::ConstantPool(value)
+	}
+}
+
+impl From<DescriptorError> for FormatErrorKind {
+	fn from(value: DescriptorError) -> Self {
+		Self::InvalidDescriptor(value)
+	}
+}
+
+impl From<AccessFlagsError> for FormatErrorKind {
+	fn from(value: AccessFlagsError) -> Self {
+		Self::AccessFlags(value)
+	}
+}
+
+/// Violation of the format checks of JVMS 4.8, with `location` being a path like `methods[2].attributes[0]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+	pub location: String,
+	pub kind: FormatErrorKind,
+}
+
+impl Display for FormatError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "ClassFormatError in '{}': {}", self.location, self.kind)
+	}
+}
+
+impl std::error::Error for FormatError {}
+
+/// Unqualified name of a field or local variable (JVMS 4.2.2).
+pub fn is_unqualified_name(name: &str) -> bool {
+	!name.is_empty() && !name.contains(['.', ';', '[', '/'])
+}
+
+/// Unqualified name of a method, which may only contain `<` and `>` for `<init>` and `<clinit>`.
+pub fn is_method_name(name: &str) -> bool {
+	name == "<init>" || name == "<clinit>" || (is_unqualified_name(name) && !name.contains(['<', '>']))
+}
+
+/// Binary class or interface name in internal form (JVMS 4.2.1), e.g. `java/lang/Object`.
+pub fn is_class_name(name: &str) -> bool {
+	name.split('/').all(is_unqualified_name)
+}
+
+/// Module name (JVMS 4.2.3), which may contain `.` but no control characters or unescaped `:` and `@`.
+fn is_module_name(name: &str) -> bool {
+	let mut chars = name.chars();
+	if name.is_empty() {
+		return false;
+	}
+	while let Some(c) = chars.next() {
+		match c {
+			'\u{0000}'..='\u{001F}' => return false,
+			'\\' if !matches!(chars.next(), Some('\\' | ':' | '@')) => return false,
+			':' | '@' => return false,
+			_ => {},
+		}
+	}
+	true
+}
+
+struct FormatChecker<'a> {
+	class_file: &'a ClassFile,
+	constant_pool: &'a ConstantPool,
+	errors: Vec<FormatError>,
+}
+
+impl FormatChecker<'_> {
+	fn report(&mut self, location: &str, kind: impl Into<FormatErrorKind>) {
+		self.errors.push(FormatError {
+			location: location.to_string(),
+			kind: kind.into(),
+		});
+	}
+
+	/// Reports the error of `result` if there is one.
+	fn check<T, K: Into<FormatErrorKind>>(&mut self, location: &str, result: Result<T, K>) -> Option<T> {
+		match result {
+			Ok(value) => Some(value),
+			Err(kind) => {
+				self.report(location, kind);
+				None
+			},
+		}
+	}
+
+	fn check_version(&mut self, max_major_version: U2) {
+		let (major, minor) = (self.class_file.major_version, self.class_file.minor_version);
+		let supported = match major {
+			..=44 => false,
+			45 => true,
+			46..=55 => major <= max_major_version,
+			_ => major <= max_major_version && (minor == 0 || (minor == PREVIEW_MINOR_VERSION && major == max_major_version)),
+		};
+		if !supported {
+			self.report("major_version", FormatErrorKind::UnsupportedVersion { major, minor });
+		}
+	}
+
+	fn check_class_name(&mut self, location: &str, name: &str) {
+		let valid = match name.strip_prefix('[') {
+			Some(_) => matches!(parse_field_descriptor(name), Ok(FieldType::Array { .. })),
+			None => is_class_name(name),
+		};
+		if !valid {
+			self.report(location, FormatErrorKind::InvalidClassName(name.to_string()));
+		}
+	}
+
+	/// Checks the class, name and descriptor of a `Fieldref`, `Methodref` or `InterfaceMethodref`.
+	fn check_member_ref(&mut self, location: &str, class_index: U2, name_and_type_index: U2, is_field: bool) {
+		let constant_pool = self.constant_pool;
+		self.check(location, constant_pool.get_class(class_index));
+		let Some((name, descriptor)) = self.check(location, constant_pool.get_name_and_type(name_and_type_index)) else {
+			return;
+		};
+		if is_field {
+			if !is_unqualified_name(&name) {
+				self.report(location, FormatErrorKind::InvalidFieldName(name.into_owned()));
+			}
+			self.check(location, parse_field_descriptor(&descriptor));
+			return;
+		}
+
+		if (name.starts_with('<') && name != "<init>") || !is_method_name(&name) {
+			self.report(location, FormatErrorKind::InvalidMethodName(name.to_string()));
+		}
+		if let Some(method_descriptor) = self.check(location, parse_method_descriptor(&descriptor)) {
+			if name == "<init>" && method_descriptor.return_type != ReturnDescriptor::Void {
+				self.report(location, FormatErrorKind::InvalidInitializerDescriptor { name: name.into_owned(), descriptor: descriptor.into_owned() });
+			}
+		}
+	}
+
+	fn check_constant_pool(&mut self) {
+		let constant_pool = self.constant_pool;
+		let major_version = self.class_file.major_version;
+		for (index, entry) in constant_pool.iter() {
+			let location = format!("constant_pool[{index}]");
+			let required_version = match entry {
+				CPInfo::MethodHandle(_) | CPInfo::MethodType(_) | CPInfo::InvokeDynamic(_) => 51,
+				CPInfo::Module(_) | CPInfo::Package(_) => 53,
+				CPInfo::Dynamic(_) => 55,
+				_ => 45,
+			};
+			if major_version < required_version {
+				self.report(&location, FormatErrorKind::ConstantRequiresVersion(entry.name(), required_version));
+			}
+
+			match entry {
+				CPInfo::Class(value) => {
+					if let Some(name) = self.check(&location, constant_pool.get_utf8_str(value.name_index)) {
+						self.check_class_name(&location, &name);
+					}
+				},
+				CPInfo::Fieldref(value) => self.check_member_ref(&location, value.class_index, value.name_and_type_index, true),
+				CPInfo::Methodref(value) => self.check_member_ref(&location, value.class_index, value.name_and_type_index, false),
+				CPInfo::InterfaceMethodref(value) => self.check_member_ref(&location, value.class_index, value.name_and_type_index, false),
+				CPInfo::String(value) => {
+					self.check(&location, constant_pool.get_utf8(value.string_index));
+				},
+				CPInfo::NameAndType(value) => {
+					self.check(&location, constant_pool.get_utf8_str(value.name_index));
+					self.check(&location, constant_pool.get_utf8_str(value.descriptor_index));
+				},
+				CPInfo::MethodType(value) => {
+					if let Some(descriptor) = self.check(&location, constant_pool.get_utf8_str(value.descriptor_index)) {
+						self.check(&location, parse_method_descriptor(&descriptor));
+					}
+				},
+				CPInfo::MethodHandle(_) => {
+					let Some(method_handle) = self.check(&location, constant_pool.get_method_handle(index)) else {
+						continue;
+					};
+					let valid_name = match method_handle.kind {
+						kind if kind.is_field() => true,
+						ReferenceKind::NewInvokeSpecial => method_handle.name == "<init>",
+						_ => !method_handle.name.starts_with('<'),
+					};
+					let interface_method = major_version < 52 && method_handle.is_interface && matches!(method_handle.kind, ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial);
+					if !valid_name || interface_method {
+						// A bad name is reported first, it is wrong whatever the version.
+						self.report(&location, FormatErrorKind::InvalidMethodHandle { kind: method_handle.kind, name: method_handle.name.into_owned(), interface_method: valid_name && interface_method });
+					}
+				},
+				CPInfo::Dynamic(value) => {
+					if let Some((name, descriptor)) = self.check(&location, constant_pool.get_name_and_type(value.name_and_type_index)) {
+						if !is_unqualified_name(&name) {
+							self.report(&location, FormatErrorKind::InvalidFieldName(name.to_string()));
+						}
+						self.check(&location, parse_field_descriptor(&descriptor));
+					}
+				},
+				CPInfo::InvokeDynamic(value) => {
+					if let Some((name, descriptor)) = self.check(&location, constant_pool.get_name_and_type(value.name_and_type_index)) {
+						if !is_method_name(&name) || name.starts_with('<') {
+							self.report(&location, FormatErrorKind::InvalidMethodName(name.to_string()));
+						}
+						self.check(&location, parse_method_descriptor(&descriptor));
+					}
+				},
+				CPInfo::Module(value) => {
+					if let Some(name) = self.check(&location, constant_pool.get_utf8_str(value.name_index)) {
+						if !is_module_name(&name) {
+							self.report(&location, FormatErrorKind::InvalidModuleName(name.into_owned()));
+						}
+					}
+				},
+				CPInfo::Package(value) => {
+					if let Some(name) = self.check(&location, constant_pool.get_utf8_str(value.name_index)) {
+						if !is_class_name(&name) {
+							self.report(&location, FormatErrorKind::InvalidPackageName(name.into_owned()));
+						}
+					}
+				},
+				CPInfo::Integer(_) | CPInfo::Float(_) | CPInfo::Long(_) | CPInfo::Double(_) | CPInfo::Utf8(_) => {},
+			}
+		}
+	}
+
+	fn check_class(&mut self) {
+		let class_file = self.class_file;
+		let constant_pool = self.constant_pool;
+		let class_access = class_file.access();
+		self.check("access_flags", class_access.validate());
+
+		let this_class = self.check("this_class", constant_pool.get_class_name(class_file.this_class));
+		if let Some(this_class) = &this_class {
+			if this_class.starts_with('[') {
+				self.report("this_class", FormatErrorKind::InvalidClassName(this_class.to_string()));
+			}
+		}
+
+		let is_module = class_access.contains(ClassAccess::MODULE);
+		match class_file.super_class {
+			0 => if !is_module && this_class.as_deref() != Some("java/lang/Object") {
+				self.report("super_class", FormatErrorKind::MissingSuperClass);
+			},
+			index => {
+				if let Some(super_class) = self.check("super_class", constant_pool.get_class_name(index)) {
+					if super_class.starts_with('[') {
+						self.report("super_class", FormatErrorKind::InvalidClassName(super_class.into_owned()));
+					}
+				}
+			},
+		}
+		for (i, interface) in class_file.interfaces.iter().enumerate() {
+			self.check(&format!("interfaces[{i}]"), constant_pool.get_class(*interface));
+		}
+	}
+
+	fn check_fields(&mut self) {
+		let class_file = self.class_file;
+		let constant_pool = self.constant_pool;
+		let mut members = HashSet::new();
+		for (i, field) in class_file.fields.iter().enumerate() {
+			let location = format!("fields[{i}]");
+			self.check(&location, field.access().validate(class_file.access()));
+			let name = self.check(&location, constant_pool.get_utf8_str(field.name_index));
+			if let Some(name) = &name {
+				if !is_unqualified_name(name) {
+					self.report(&location, FormatErrorKind::InvalidFieldName(name.to_string()));
+				}
+			}
+			let descriptor = self.check(&location, constant_pool.get_utf8_str(field.descriptor_index));
+			if let Some(descriptor) = &descriptor {
+				self.check(&location, parse_field_descriptor(descriptor));
+			}
+			if let (Some(name), Some(descriptor)) = (name, descriptor) {
+				if !members.insert((name.clone(), descriptor.clone())) {
+					self.report(&location, FormatErrorKind::DuplicateMember { name: name.into_owned(), descriptor: descriptor.into_owned() });
+				}
+			}
+			self.check_attributes(&location, &field.attributes, AttributeOwner::Field);
+		}
+	}
+
+	fn check_methods(&mut self) {
+		let class_file = self.class_file;
+		let constant_pool = self.constant_pool;
+		let mut members = HashSet::new();
+		for (i, method) in class_file.methods.iter().enumerate() {
+			let location = format!("methods[{i}]");
+			let name = self.check(&location, constant_pool.get_utf8_str(method.name_index));
+			if let Some(name) = &name {
+				if !is_method_name(name) {
+					self.report(&location, FormatErrorKind::InvalidMethodName(name.to_string()));
+				}
+				self.check(&location, method.access().validate(class_file.access(), name, class_file.major_version));
+			}
+
+			let descriptor = self.check(&location, constant_pool.get_utf8_str(method.descriptor_index));
+			if let (Some(name), Some(descriptor)) = (&name, &descriptor) {
+				if let Some(method_descriptor) = self.check(&location, parse_method_descriptor(descriptor)) {
+					let is_valid = match name.as_ref() {
+						"<init>" => method_descriptor.return_type == ReturnDescriptor::Void,
+						"<clinit>" => class_file.major_version < 51 || (method_descriptor.return_type == ReturnDescriptor::Void && method_descriptor.parameters.is_empty()),
+						_ => true,
+					};
+					if !is_valid {
+						self.report(&location, FormatErrorKind::InvalidInitializerDescriptor { name: name.to_string(), descriptor: descriptor.to_string() });
+					}
+				}
+				if !members.insert((name.clone(), descriptor.clone())) {
+					self.report(&location, FormatErrorKind::DuplicateMember { name: name.to_string(), descriptor: descriptor.to_string() });
+				}
+			}
+
+			self.check_attributes(&location, &method.attributes, AttributeOwner::Method);
+			let has_code = method.attributes.iter().any(|attribute| attribute.name(constant_pool).is_ok_and(|name| name == "Code"));
+			let needs_code = !method.access().intersects(MethodAccess::ABSTRACT | MethodAccess::NATIVE);
+			match (needs_code, has_code) {
+				(true, false) => self.report(&location, FormatErrorKind::MissingCode),
+				(false, true) => self.report(&location, FormatErrorKind::UnexpectedCode),
+				_ => {},
+			}
+		}
+	}
+
+	/// Checks the placement of the predefined attributes among `attributes`, including those nested in `Code` and `Record`.
+	fn check_attributes(&mut self, location: &str, attributes: &[AttributeInfo], owner: AttributeOwner) {
+		let constant_pool = self.constant_pool;
+		let mut seen = HashSet::new();
+		for (i, attribute) in attributes.iter().enumerate() {
+			let location = match location {
+				"" => format!("attributes[{i}]"),
+				location => format!("{location}.attributes[{i}]"),
+			};
+			let Some(name) = self.check(&location, attribute.name(constant_pool)) else {
+				continue;
+			};
+			let Some(owners) = attribute_owners(&name) else {
+				continue;
+			};
+			if !owners.contains(&owner) {
+				self.report(&location, FormatErrorKind::MisplacedAttribute(name.into_owned()));
+				continue;
+			}
+			if !REPEATABLE_ATTRIBUTES.contains(&name.as_ref()) && !seen.insert(name.clone()) {
+				self.report(&location, FormatErrorKind::DuplicateAttribute(name.to_string()));
+			}
+
+			match name.as_ref() {
+				"Code" => match attribute.parse("Code", code_attribute_parser) {
+					Ok(code) => self.check_attributes(&location, &code.attributes, AttributeOwner::Code),
+					Err(error) => self.report(&location, FormatErrorKind::MalformedAttribute(error.to_string())),
+				},
+				"Record" => match attribute.parse("Record", record_attribute_parser) {
+					Ok(record) => for (j, component) in record.components.iter().enumerate() {
+						self.check_attributes(&format!("{location}.components[{j}]"), &component.attributes, AttributeOwner::RecordComponent);
+					},
+					Err(error) => self.report(&location, FormatErrorKind::MalformedAttribute(error.to_string())),
+				},
+				_ => {},
+			}
+		}
+	}
+}
+
+/// Runs the format checks of JVMS 4.8 that go beyond what parsing ensures, collecting every violation.
+///
+/// `max_major_version` is the latest class file version the caller supports, the only one that may use preview features.
+pub fn check_format(class_file: &ClassFile, max_major_version: U2) -> Result<(), Vec<FormatError>> {
+	let mut checker = FormatChecker {
+		class_file,
+		constant_pool: &class_file.constant_pool,
+		errors: Vec::new(),
+	};
+	if class_file.magic != MAGIC {
+		checker.report("magic", FormatErrorKind::InvalidMagic(class_file.magic));
+	}
+	checker.check_version(max_major_version);
+	checker.check_constant_pool();
+	checker.check_class();
+	checker.check_fields();
+	checker.check_methods();
+	checker.check_attributes("", &class_file.attributes, AttributeOwner::ClassFile);
+
+	match checker.errors.is_empty() {
+		true => Ok(()),
+		false => Err(checker.errors),
+	}
+}
@@ -0,0 +1,1432 @@
+use std::fmt::Display;
+
+use crate::{U1, U2};
+
+pub const NOP: U1 = 0x00;
+pub const ACONST_NULL: U1 = 0x01;
+pub const ICONST_M1: U1 = 0x02;
+pub const ICONST_0: U1 = 0x03;
+pub const ICONST_1: U1 = 0x04;
+pub const ICONST_2: U1 = 0x05;
+pub const ICONST_3: U1 = 0x06;
+pub const ICONST_4: U1 = 0x07;
+pub const ICONST_5: U1 = 0x08;
+pub const LCONST_0: U1 = 0x09;
+pub const LCONST_1: U1 = 0x0A;
+pub const FCONST_0: U1 = 0x0B;
+pub const FCONST_1: U1 = 0x0C;
+pub const FCONST_2: U1 = 0x0D;
+pub const DCONST_0: U1 = 0x0E;
+pub const DCONST_1: U1 = 0x0F;
+pub const BIPUSH: U1 = 0x10;
+pub const SIPUSH: U1 = 0x11;
+pub const LDC: U1 = 0x12;
+pub const LDC_W: U1 = 0x13;
+pub const LDC2_W: U1 = 0x14;
+pub const ILOAD: U1 = 0x15;
+pub const LLOAD: U1 = 0x16;
+pub const FLOAD: U1 = 0x17;
+pub const DLOAD: U1 = 0x18;
+pub const ALOAD: U1 = 0x19;
+pub const ILOAD_0: U1 = 0x1A;
+pub const ILOAD_1: U1 = 0x1B;
+pub const ILOAD_2: U1 = 0x1C;
+pub const ILOAD_3: U1 = 0x1D;
+pub const LLOAD_0: U1 = 0x1E;
+pub const LLOAD_1: U1 = 0x1F;
+pub const LLOAD_2: U1 = 0x20;
+pub const LLOAD_3: U1 = 0x21;
+pub const FLOAD_0: U1 = 0x22;
+pub const FLOAD_1: U1 = 0x23;
+pub const FLOAD_2: U1 = 0x24;
+pub const FLOAD_3: U1 = 0x25;
+pub const DLOAD_0: U1 = 0x26;
+pub const DLOAD_1: U1 = 0x27;
+pub const DLOAD_2: U1 = 0x28;
+pub const DLOAD_3: U1 = 0x29;
+pub const ALOAD_0: U1 = 0x2A;
+pub const ALOAD_1: U1 = 0x2B;
+pub const ALOAD_2: U1 = 0x2C;
+pub const ALOAD_3: U1 = 0x2D;
+pub const IALOAD: U1 = 0x2E;
+pub const LALOAD: U1 = 0x2F;
+pub const FALOAD: U1 = 0x30;
+pub const DALOAD: U1 = 0x31;
+pub const AALOAD: U1 = 0x32;
+pub const BALOAD: U1 = 0x33;
+pub const CALOAD: U1 = 0x34;
+pub const SALOAD: U1 = 0x35;
+pub const ISTORE: U1 = 0x36;
+pub const LSTORE: U1 = 0x37;
+pub const FSTORE: U1 = 0x38;
+pub const DSTORE: U1 = 0x39;
+pub const ASTORE: U1 = 0x3A;
+pub const ISTORE_0: U1 = 0x3B;
+pub const ISTORE_1: U1 = 0x3C;
+pub const ISTORE_2: U1 = 0x3D;
+pub const ISTORE_3: U1 = 0x3E;
+pub const LSTORE_0: U1 = 0x3F;
+pub const LSTORE_1: U1 = 0x40;
+pub const LSTORE_2: U1 = 0x41;
+pub const LSTORE_3: U1 = 0x42;
+pub const FSTORE_0: U1 = 0x43;
+pub const FSTORE_1: U1 = 0x44;
+pub const FSTORE_2: U1 = 0x45;
+pub const FSTORE_3: U1 = 0x46;
+pub const DSTORE_0: U1 = 0x47;
+pub const DSTORE_1: U1 = 0x48;
+pub const DSTORE_2: U1 = 0x49;
+pub const DSTORE_3: U1 = 0x4A;
+pub const ASTORE_0: U1 = 0x4B;
+pub const ASTORE_1: U1 = 0x4C;
+pub const ASTORE_2: U1 = 0x4D;
+pub const ASTORE_3: U1 = 0x4E;
+pub const IASTORE: U1 = 0x4F;
+pub const LASTORE: U1 = 0x50;
+pub const FASTORE: U1 = 0x51;
+pub const DASTORE: U1 = 0x52;
+pub const AASTORE: U1 = 0x53;
+pub const BASTORE: U1 = 0x54;
+pub const CASTORE: U1 = 0x55;
+pub const SASTORE: U1 = 0x56;
+pub const POP: U1 = 0x57;
+pub const POP2: U1 = 0x58;
+pub const DUP: U1 = 0x59;
+pub const DUP_X1: U1 = 0x5A;
+pub const DUP_X2: U1 = 0x5B;
+pub const DUP2: U1 = 0x5C;
+pub const DUP2_X1: U1 = 0x5D;
+pub const DUP2_X2: U1 = 0x5E;
+pub const SWAP: U1 = 0x5F;
+pub const IADD: U1 = 0x60;
+pub const LADD: U1 = 0x61;
+pub const FADD: U1 = 0x62;
+pub const DADD: U1 = 0x63;
+pub const ISUB: U1 = 0x64;
+pub const LSUB: U1 = 0x65;
+pub const FSUB: U1 = 0x66;
+pub const DSUB: U1 = 0x67;
+pub const IMUL: U1 = 0x68;
+pub const LMUL: U1 = 0x69;
+pub const FMUL: U1 = 0x6A;
+pub const DMUL: U1 = 0x6B;
+pub const IDIV: U1 = 0x6C;
+pub const LDIV: U1 = 0x6D;
+pub const FDIV: U1 = 0x6E;
+pub const DDIV: U1 = 0x6F;
+pub const IREM: U1 = 0x70;
+pub const LREM: U1 = 0x71;
+pub const FREM: U1 = 0x72;
+pub const DREM: U1 = 0x73;
+pub const INEG: U1 = 0x74;
+pub const LNEG: U1 = 0x75;
+pub const FNEG: U1 = 0x76;
+pub const DNEG: U1 = 0x77;
+pub const ISHL: U1 = 0x78;
+pub const LSHL: U1 = 0x79;
+pub const ISHR: U1 = 0x7A;
+pub const LSHR: U1 = 0x7B;
+pub const IUSHR: U1 = 0x7C;
+pub const LUSHR: U1 = 0x7D;
+pub const IAND: U1 = 0x7E;
+pub const LAND: U1 = 0x7F;
+pub const IOR: U1 = 0x80;
+pub const LOR: U1 = 0x81;
+pub const IXOR: U1 = 0x82;
+pub const LXOR: U1 = 0x83;
+pub const IINC: U1 = 0x84;
+pub const I2L: U1 = 0x85;
+pub const I2F: U1 = 0x86;
+pub const I2D: U1 = 0x87;
+pub const L2I: U1 = 0x88;
+pub const L2F: U1 = 0x89;
+pub const L2D: U1 = 0x8A;
+pub const F2I: U1 = 0x8B;
+pub const F2L: U1 = 0x8C;
+pub const F2D: U1 = 0x8D;
+pub const D2I: U1 = 0x8E;
+pub const D2L: U1 = 0x8F;
+pub const D2F: U1 = 0x90;
+pub const I2B: U1 = 0x91;
+pub const I2C: U1 = 0x92;
+pub const I2S: U1 = 0x93;
+pub const LCMP: U1 = 0x94;
+pub const FCMPL: U1 = 0x95;
+pub const FCMPG: U1 = 0x96;
+pub const DCMPL: U1 = 0x97;
+pub const DCMPG: U1 = 0x98;
+pub const IFEQ: U1 = 0x99;
+pub const IFNE: U1 = 0x9A;
+pub const IFLT: U1 = 0x9B;
+pub const IFGE: U1 = 0x9C;
+pub const IFGT: U1 = 0x9D;
+pub const IFLE: U1 = 0x9E;
+pub const IF_ICMPEQ: U1 = 0x9F;
+pub const IF_ICMPNE: U1 = 0xA0;
+pub const IF_ICMPLT: U1 = 0xA1;
+pub const IF_ICMPGE: U1 = 0xA2;
+pub const IF_ICMPGT: U1 = 0xA3;
+pub const IF_ICMPLE: U1 = 0xA4;
+pub const IF_ACMPEQ: U1 = 0xA5;
+pub const IF_ACMPNE: U1 = 0xA6;
+pub const GOTO: U1 = 0xA7;
+pub const JSR: U1 = 0xA8;
+pub const RET: U1 = 0xA9;
+pub const TABLESWITCH: U1 = 0xAA;
+pub const LOOKUPSWITCH: U1 = 0xAB;
+pub const IRETURN: U1 = 0xAC;
+pub const LRETURN: U1 = 0xAD;
+pub const FRETURN: U1 = 0xAE;
+pub const DRETURN: U1 = 0xAF;
+pub const ARETURN: U1 = 0xB0;
+pub const RETURN: U1 = 0xB1;
+pub const GETSTATIC: U1 = 0xB2;
+pub const PUTSTATIC: U1 = 0xB3;
+pub const GETFIELD: U1 = 0xB4;
+pub const PUTFIELD: U1 = 0xB5;
+pub const INVOKEVIRTUAL: U1 = 0xB6;
+pub const INVOKESPECIAL: U1 = 0xB7;
+pub const INVOKESTATIC: U1 = 0xB8;
+pub const INVOKEINTERFACE: U1 = 0xB9;
+pub const INVOKEDYNAMIC: U1 = 0xBA;
+pub const NEW: U1 = 0xBB;
+pub const NEWARRAY: U1 = 0xBC;
+pub const ANEWARRAY: U1 = 0xBD;
+pub const ARRAYLENGTH: U1 = 0xBE;
+pub const ATHROW: U1 = 0xBF;
+pub const CHECKCAST: U1 = 0xC0;
+pub const INSTANCEOF: U1 = 0xC1;
+pub const MONITORENTER: U1 = 0xC2;
+pub const MONITOREXIT: U1 = 0xC3;
+pub const WIDE: U1 = 0xC4;
+pub const MULTIANEWARRAY: U1 = 0xC5;
+pub const IFNULL: U1 = 0xC6;
+pub const IFNONNULL: U1 = 0xC7;
+pub const GOTO_W: U1 = 0xC8;
+pub const JSR_W: U1 = 0xC9;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSwitch {
+	pub default: i32,
+	pub low: i32,
+	pub high: i32,
+	pub offsets: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupSwitch {
+	pub default: i32,
+	pub pairs: Vec<(i32, i32)>,
+}
+
+/// Instructions that can follow a `wide` prefix, with their widened operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WideInstruction {
+	Iload(U2),
+	Lload(U2),
+	Fload(U2),
+	Dload(U2),
+	Aload(U2),
+	Istore(U2),
+	Lstore(U2),
+	Fstore(U2),
+	Dstore(U2),
+	Astore(U2),
+	Ret(U2),
+	Iinc(U2, i16),
+}
+
+/// A single JVM instruction (JVMS chapter 6) with its operands as they appear in the code array.
+///
+/// Branch offsets are relative to the address of the instruction itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+	Nop,
+	AconstNull,
+	IconstM1,
+	Iconst0,
+	Iconst1,
+	Iconst2,
+	Iconst3,
+	Iconst4,
+	Iconst5,
+	Lconst0,
+	Lconst1,
+	Fconst0,
+	Fconst1,
+	Fconst2,
+	Dconst0,
+	Dconst1,
+	Bipush(i8),
+	Sipush(i16),
+	Ldc(U1),
+	LdcW(U2),
+	Ldc2W(U2),
+	Iload(U1),
+	Lload(U1),
+	Fload(U1),
+	Dload(U1),
+	Aload(U1),
+	Iload0,
+	Iload1,
+	Iload2,
+	Iload3,
+	Lload0,
+	Lload1,
+	Lload2,
+	Lload3,
+	Fload0,
+	Fload1,
+	Fload2,
+	Fload3,
+	Dload0,
+	Dload1,
+	Dload2,
+	Dload3,
+	Aload0,
+	Aload1,
+	Aload2,
+	Aload3,
+	Iaload,
+	Laload,
+	Faload,
+	Daload,
+	Aaload,
+	Baload,
+	Caload,
+	Saload,
+	Istore(U1),
+	Lstore(U1),
+	Fstore(U1),
+	Dstore(U1),
+	Astore(U1),
+	Istore0,
+	Istore1,
+	Istore2,
+	Istore3,
+	Lstore0,
+	Lstore1,
+	Lstore2,
+	Lstore3,
+	Fstore0,
+	Fstore1,
+	Fstore2,
+	Fstore3,
+	Dstore0,
+	Dstore1,
+	Dstore2,
+	Dstore3,
+	Astore0,
+	Astore1,
+	Astore2,
+	Astore3,
+	Iastore,
+	Lastore,
+	Fastore,
+	Dastore,
+	Aastore,
+	Bastore,
+	Castore,
+	Sastore,
+	Pop,
+	Pop2,
+	Dup,
+	DupX1,
+	DupX2,
+	Dup2,
+	Dup2X1,
+	Dup2X2,
+	Swap,
+	Iadd,
+	Ladd,
+	Fadd,
+	Dadd,
+	Isub,
+	Lsub,
+	Fsub,
+	Dsub,
+	Imul,
+	Lmul,
+	Fmul,
+	Dmul,
+	Idiv,
+	Ldiv,
+	Fdiv,
+	Ddiv,
+	Irem,
+	Lrem,
+	Frem,
+	Drem,
+	Ineg,
+	Lneg,
+	Fneg,
+	Dneg,
+	Ishl,
+	Lshl,
+	Ishr,
+	Lshr,
+	Iushr,
+	Lushr,
+	Iand,
+	Land,
+	Ior,
+	Lor,
+	Ixor,
+	Lxor,
+	Iinc(U1, i8),
+	I2l,
+	I2f,
+	I2d,
+	L2i,
+	L2f,
+	L2d,
+	F2i,
+	F2l,
+	F2d,
+	D2i,
+	D2l,
+	D2f,
+	I2b,
+	I2c,
+	I2s,
+	Lcmp,
+	Fcmpl,
+	Fcmpg,
+	Dcmpl,
+	Dcmpg,
+	Ifeq(i16),
+	Ifne(i16),
+	Iflt(i16),
+	Ifge(i16),
+	Ifgt(i16),
+	Ifle(i16),
+	IfIcmpeq(i16),
+	IfIcmpne(i16),
+	IfIcmplt(i16),
+	IfIcmpge(i16),
+	IfIcmpgt(i16),
+	IfIcmple(i16),
+	IfAcmpeq(i16),
+	IfAcmpne(i16),
+	Goto(i16),
+	Jsr(i16),
+	Ret(U1),
+	Tableswitch(TableSwitch),
+	Lookupswitch(LookupSwitch),
+	Ireturn,
+	Lreturn,
+	Freturn,
+	Dreturn,
+	Areturn,
+	Return,
+	Getstatic(U2),
+	Putstatic(U2),
+	Getfield(U2),
+	Putfield(U2),
+	Invokevirtual(U2),
+	Invokespecial(U2),
+	Invokestatic(U2),
+	Invokeinterface(U2, U1),
+	Invokedynamic(U2),
+	New(U2),
+	Newarray(U1),
+	Anewarray(U2),
+	Arraylength,
+	Athrow,
+	Checkcast(U2),
+	Instanceof(U2),
+	Monitorenter,
+	Monitorexit,
+	Wide(WideInstruction),
+	Multianewarray(U2, U1),
+	Ifnull(i16),
+	Ifnonnull(i16),
+	GotoW(i32),
+	JsrW(i32),
+}
+
+impl Instruction {
+	pub fn opcode(&self) -> U1 {
+		match self {
+			Self::Nop => NOP,
+			Self::AconstNull => ACONST_NULL,
+			Self::IconstM1 => ICONST_M1,
+			Self::Iconst0 => ICONST_0,
+			Self::Iconst1 => ICONST_1,
+			Self::Iconst2 => ICONST_2,
+			Self::Iconst3 => ICONST_3,
+			Self::Iconst4 => ICONST_4,
+			Self::Iconst5 => ICONST_5,
+			Self::Lconst0 => LCONST_0,
+			Self::Lconst1 => LCONST_1,
+			Self::Fconst0 => FCONST_0,
+			Self::Fconst1 => FCONST_1,
+			Self::Fconst2 => FCONST_2,
+			Self::Dconst0 => DCONST_0,
+			Self::Dconst1 => DCONST_1,
+			Self::Bipush(_) => BIPUSH,
+			Self::Sipush(_) => SIPUSH,
+			Self::Ldc(_) => LDC,
+			Self::LdcW(_) => LDC_W,
+			Self::Ldc2W(_) => LDC2_W,
+			Self::Iload(_) => ILOAD,
+			Self::Lload(_) => LLOAD,
+			Self::Fload(_) => FLOAD,
+			Self::Dload(_) => DLOAD,
+			Self::Aload(_) => ALOAD,
+			Self::Iload0 => ILOAD_0,
+			Self::Iload1 => ILOAD_1,
+			Self::Iload2 => ILOAD_2,
+			Self::Iload3 => ILOAD_3,
+			Self::Lload0 => LLOAD_0,
+			Self::Lload1 => LLOAD_1,
+			Self::Lload2 => LLOAD_2,
+			Self::Lload3 => LLOAD_3,
+			Self::Fload0 => FLOAD_0,
+			Self::Fload1 => FLOAD_1,
+			Self::Fload2 => FLOAD_2,
+			Self::Fload3 => FLOAD_3,
+			Self::Dload0 => DLOAD_0,
+			Self::Dload1 => DLOAD_1,
+			Self::Dload2 => DLOAD_2,
+			Self::Dload3 => DLOAD_3,
+			Self::Aload0 => ALOAD_0,
+			Self::Aload1 => ALOAD_1,
+			Self::Aload2 => ALOAD_2,
+			Self::Aload3 => ALOAD_3,
+			Self::Iaload => IALOAD,
+			Self::Laload => LALOAD,
+			Self::Faload => FALOAD,
+			Self::Daload => DALOAD,
+			Self::Aaload => AALOAD,
+			Self::Baload => BALOAD,
+			Self::Caload => CALOAD,
+			Self::Saload => SALOAD,
+			Self::Istore(_) => ISTORE,
+			Self::Lstore(_) => LSTORE,
+			Self::Fstore(_) => FSTORE,
+			Self::Dstore(_) => DSTORE,
+			Self::Astore(_) => ASTORE,
+			Self::Istore0 => ISTORE_0,
+			Self::Istore1 => ISTORE_1,
+			Self::Istore2 => ISTORE_2,
+			Self::Istore3 => ISTORE_3,
+			Self::Lstore0 => LSTORE_0,
+			Self::Lstore1 => LSTORE_1,
+			Self::Lstore2 => LSTORE_2,
+			Self::Lstore3 => LSTORE_3,
+			Self::Fstore0 => FSTORE_0,
+			Self::Fstore1 => FSTORE_1,
+			Self::Fstore2 => FSTORE_2,
+			Self::Fstore3 => FSTORE_3,
+			Self::Dstore0 => DSTORE_0,
+			Self::Dstore1 => DSTORE_1,
+			Self::Dstore2 => DSTORE_2,
+			Self::Dstore3 => DSTORE_3,
+			Self::Astore0 => ASTORE_0,
+			Self::Astore1 => ASTORE_1,
+			Self::Astore2 => ASTORE_2,
+			Self::Astore3 => ASTORE_3,
+			Self::Iastore => IASTORE,
+			Self::Lastore => LASTORE,
+			Self::Fastore => FASTORE,
+			Self::Dastore => DASTORE,
+			Self::Aastore => AASTORE,
+			Self::Bastore => BASTORE,
+			Self::Castore => CASTORE,
+			Self::Sastore => SASTORE,
+			Self::Pop => POP,
+			Self::Pop2 => POP2,
+			Self::Dup => DUP,
+			Self::DupX1 => DUP_X1,
+			Self::DupX2 => DUP_X2,
+			Self::Dup2 => DUP2,
+			Self::Dup2X1 => DUP2_X1,
+			Self::Dup2X2 => DUP2_X2,
+			Self::Swap => SWAP,
+			Self::Iadd => IADD,
+			Self::Ladd => LADD,
+			Self::Fadd => FADD,
+			Self::Dadd => DADD,
+			Self::Isub => ISUB,
+			Self::Lsub => LSUB,
+			Self::Fsub => FSUB,
+			Self::Dsub => DSUB,
+			Self::Imul => IMUL,
+			Self::Lmul => LMUL,
+			Self::Fmul => FMUL,
+			Self::Dmul => DMUL,
+			Self::Idiv => IDIV,
+			Self::Ldiv => LDIV,
+			Self::Fdiv => FDIV,
+			Self::Ddiv => DDIV,
+			Self::Irem => IREM,
+			Self::Lrem => LREM,
+			Self::Frem => FREM,
+			Self::Drem => DREM,
+			Self::Ineg => INEG,
+			Self::Lneg => LNEG,
+			Self::Fneg => FNEG,
+			Self::Dneg => DNEG,
+			Self::Ishl => ISHL,
+			Self::Lshl => LSHL,
+			Self::Ishr => ISHR,
+			Self::Lshr => LSHR,
+			Self::Iushr => IUSHR,
+			Self::Lushr => LUSHR,
+			Self::Iand => IAND,
+			Self::Land => LAND,
+			Self::Ior => IOR,
+			Self::Lor => LOR,
+			Self::Ixor => IXOR,
+			Self::Lxor => LXOR,
+			Self::Iinc(..) => IINC,
+			Self::I2l => I2L,
+			Self::I2f => I2F,
+			Self::I2d => I2D,
+			Self::L2i => L2I,
+			Self::L2f => L2F,
+			Self::L2d => L2D,
+			Self::F2i => F2I,
+			Self::F2l => F2L,
+			Self::F2d => F2D,
+			Self::D2i => D2I,
+			Self::D2l => D2L,
+			Self::D2f => D2F,
+			Self::I2b => I2B,
+			Self::I2c => I2C,
+			Self::I2s => I2S,
+			Self::Lcmp => LCMP,
+			Self::Fcmpl => FCMPL,
+			Self::Fcmpg => FCMPG,
+			Self::Dcmpl => DCMPL,
+			Self::Dcmpg => DCMPG,
+			Self::Ifeq(_) => IFEQ,
+			Self::Ifne(_) => IFNE,
+			Self::Iflt(_) => IFLT,
+			Self::Ifge(_) => IFGE,
+			Self::Ifgt(_) => IFGT,
+			Self::Ifle(_) => IFLE,
+			Self::IfIcmpeq(_) => IF_ICMPEQ,
+			Self::IfIcmpne(_) => IF_ICMPNE,
+			Self::IfIcmplt(_) => IF_ICMPLT,
+			Self::IfIcmpge(_) => IF_ICMPGE,
+			Self::IfIcmpgt(_) => IF_ICMPGT,
+			Self::IfIcmple(_) => IF_ICMPLE,
+			Self::IfAcmpeq(_) => IF_ACMPEQ,
+			Self::IfAcmpne(_) => IF_ACMPNE,
+			Self::Goto(_) => GOTO,
+			Self::Jsr(_) => JSR,
+			Self::Ret(_) => RET,
+			Self::Tableswitch(_) => TABLESWITCH,
+			Self::Lookupswitch(_) => LOOKUPSWITCH,
+			Self::Ireturn => IRETURN,
+			Self::Lreturn => LRETURN,
+			Self::Freturn => FRETURN,
+			Self::Dreturn => DRETURN,
+			Self::Areturn => ARETURN,
+			Self::Return => RETURN,
+			Self::Getstatic(_) => GETSTATIC,
+			Self::Putstatic(_) => PUTSTATIC,
+			Self::Getfield(_) => GETFIELD,
+			Self::Putfield(_) => PUTFIELD,
+			Self::Invokevirtual(_) => INVOKEVIRTUAL,
+			Self::Invokespecial(_) => INVOKESPECIAL,
+			Self::Invokestatic(_) => INVOKESTATIC,
+			Self::Invokeinterface(..) => INVOKEINTERFACE,
+			Self::Invokedynamic(_) => INVOKEDYNAMIC,
+			Self::New(_) => NEW,
+			Self::Newarray(_) => NEWARRAY,
+			Self::Anewarray(_) => ANEWARRAY,
+			Self::Arraylength => ARRAYLENGTH,
+			Self::Athrow => ATHROW,
+			Self::Checkcast(_) => CHECKCAST,
+			Self::Instanceof(_) => INSTANCEOF,
+			Self::Monitorenter => MONITORENTER,
+			Self::Monitorexit => MONITOREXIT,
+			Self::Wide(_) => WIDE,
+			Self::Multianewarray(..) => MULTIANEWARRAY,
+			Self::Ifnull(_) => IFNULL,
+			Self::Ifnonnull(_) => IFNONNULL,
+			Self::GotoW(_) => GOTO_W,
+			Self::JsrW(_) => JSR_W,
+		}
+	}
+
+	pub fn mnemonic(&self) -> &'static str {
+		mnemonic(self.opcode()).expect("Every instruction has a mnemonic")
+	}
+}
+
+/// Mnemonic of `opcode` as used in JVMS chapter 6, e.g. `invokevirtual`.
+pub fn mnemonic(opcode: U1) -> Option<&'static str> {
+	match opcode {
+		NOP => Some("nop"),
+		ACONST_NULL => Some("aconst_null"),
+		ICONST_M1 => Some("iconst_m1"),
+		ICONST_0 => Some("iconst_0"),
+		ICONST_1 => Some("iconst_1"),
+		ICONST_2 => Some("iconst_2"),
+		ICONST_3 => Some("iconst_3"),
+		ICONST_4 => Some("iconst_4"),
+		ICONST_5 => Some("iconst_5"),
+		LCONST_0 => Some("lconst_0"),
+		LCONST_1 => Some("lconst_1"),
+		FCONST_0 => Some("fconst_0"),
+		FCONST_1 => Some("fconst_1"),
+		FCONST_2 => Some("fconst_2"),
+		DCONST_0 => Some("dconst_0"),
+		DCONST_1 => Some("dconst_1"),
+		BIPUSH => Some("bipush"),
+		SIPUSH => Some("sipush"),
+		LDC => Some("ldc"),
+		LDC_W => Some("ldc_w"),
+		LDC2_W => Some("ldc2_w"),
+		ILOAD => Some("iload"),
+		LLOAD => Some("lload"),
+		FLOAD => Some("fload"),
+		DLOAD => Some("dload"),
+		ALOAD => Some("aload"),
+		ILOAD_0 => Some("iload_0"),
+		ILOAD_1 => Some("iload_1"),
+		ILOAD_2 => Some("iload_2"),
+		ILOAD_3 => Some("iload_3"),
+		LLOAD_0 => Some("lload_0"),
+		LLOAD_1 => Some("lload_1"),
+		LLOAD_2 => Some("lload_2"),
+		LLOAD_3 => Some("lload_3"),
+		FLOAD_0 => Some("fload_0"),
+		FLOAD_1 => Some("fload_1"),
+		FLOAD_2 => Some("fload_2"),
+		FLOAD_3 => Some("fload_3"),
+		DLOAD_0 => Some("dload_0"),
+		DLOAD_1 => Some("dload_1"),
+		DLOAD_2 => Some("dload_2"),
+		DLOAD_3 => Some("dload_3"),
+		ALOAD_0 => Some("aload_0"),
+		ALOAD_1 => Some("aload_1"),
+		ALOAD_2 => Some("aload_2"),
+		ALOAD_3 => Some("aload_3"),
+		IALOAD => Some("iaload"),
+		LALOAD => Some("laload"),
+		FALOAD => Some("faload"),
+		DALOAD => Some("daload"),
+		AALOAD => Some("aaload"),
+		BALOAD => Some("baload"),
+		CALOAD => Some("caload"),
+		SALOAD => Some("saload"),
+		ISTORE => Some("istore"),
+		LSTORE => Some("lstore"),
+		FSTORE => Some("fstore"),
+		DSTORE => Some("dstore"),
+		ASTORE => Some("astore"),
+		ISTORE_0 => Some("istore_0"),
+		ISTORE_1 => Some("istore_1"),
+		ISTORE_2 => Some("istore_2"),
+		ISTORE_3 => Some("istore_3"),
+		LSTORE_0 => Some("lstore_0"),
+		LSTORE_1 => Some("lstore_1"),
+		LSTORE_2 => Some("lstore_2"),
+		LSTORE_3 => Some("lstore_3"),
+		FSTORE_0 => Some("fstore_0"),
+		FSTORE_1 => Some("fstore_1"),
+		FSTORE_2 => Some("fstore_2"),
+		FSTORE_3 => Some("fstore_3"),
+		DSTORE_0 => Some("dstore_0"),
+		DSTORE_1 => Some("dstore_1"),
+		DSTORE_2 => Some("dstore_2"),
+		DSTORE_3 => Some("dstore_3"),
+		ASTORE_0 => Some("astore_0"),
+		ASTORE_1 => Some("astore_1"),
+		ASTORE_2 => Some("astore_2"),
+		ASTORE_3 => Some("astore_3"),
+		IASTORE => Some("iastore"),
+		LASTORE => Some("lastore"),
+		FASTORE => Some("fastore"),
+		DASTORE => Some("dastore"),
+		AASTORE => Some("aastore"),
+		BASTORE => Some("bastore"),
+		CASTORE => Some("castore"),
+		SASTORE => Some("sastore"),
+		POP => Some("pop"),
+		POP2 => Some("pop2"),
+		DUP => Some("dup"),
+		DUP_X1 => Some("dup_x1"),
+		DUP_X2 => Some("dup_x2"),
+		DUP2 => Some("dup2"),
+		DUP2_X1 => Some("dup2_x1"),
+		DUP2_X2 => Some("dup2_x2"),
+		SWAP => Some("swap"),
+		IADD => Some("iadd"),
+		LADD => Some("ladd"),
+		FADD => Some("fadd"),
+		DADD => Some("dadd"),
+		ISUB => Some("isub"),
+		LSUB => Some("lsub"),
+		FSUB => Some("fsub"),
+		DSUB => Some("dsub"),
+		IMUL => Some("imul"),
+		LMUL => Some("lmul"),
+		FMUL => Some("fmul"),
+		DMUL => Some("dmul"),
+		IDIV => Some("idiv"),
+		LDIV => Some("ldiv"),
+		FDIV => Some("fdiv"),
+		DDIV => Some("ddiv"),
+		IREM => Some("irem"),
+		LREM => Some("lrem"),
+		FREM => Some("frem"),
+		DREM => Some("drem"),
+		INEG => Some("ineg"),
+		LNEG => Some("lneg"),
+		FNEG => Some("fneg"),
+		DNEG => Some("dneg"),
+		ISHL => Some("ishl"),
+		LSHL => Some("lshl"),
+		ISHR => Some("ishr"),
+		LSHR => Some("lshr"),
+		IUSHR => Some("iushr"),
+		LUSHR => Some("lushr"),
+		IAND => Some("iand"),
+		LAND => Some("land"),
+		IOR => Some("ior"),
+		LOR => Some("lor"),
+		IXOR => Some("ixor"),
+		LXOR => Some("lxor"),
+		IINC => Some("iinc"),
+		I2L => Some("i2l"),
+		I2F => Some("i2f"),
+		I2D => Some("i2d"),
+		L2I => Some("l2i"),
+		L2F => Some("l2f"),
+		L2D => Some("l2d"),
+		F2I => Some("f2i"),
+		F2L => Some("f2l"),
+		F2D => Some("f2d"),
+		D2I => Some("d2i"),
+		D2L => Some("d2l"),
+		D2F => Some("d2f"),
+		I2B => Some("i2b"),
+		I2C => Some("i2c"),
+		I2S => Some("i2s"),
+		LCMP => Some("lcmp"),
+		FCMPL => Some("fcmpl"),
+		FCMPG => Some("fcmpg"),
+		DCMPL => Some("dcmpl"),
+		DCMPG => Some("dcmpg"),
+		IFEQ => Some("ifeq"),
+		IFNE => Some("ifne"),
+		IFLT => Some("iflt"),
+		IFGE => Some("ifge"),
+		IFGT => Some("ifgt"),
+		IFLE => Some("ifle"),
+		IF_ICMPEQ => Some("if_icmpeq"),
+		IF_ICMPNE => Some("if_icmpne"),
+		IF_ICMPLT => Some("if_icmplt"),
+		IF_ICMPGE => Some("if_icmpge"),
+		IF_ICMPGT => Some("if_icmpgt"),
+		IF_ICMPLE => Some("if_icmple"),
+		IF_ACMPEQ => Some("if_acmpeq"),
+		IF_ACMPNE => Some("if_acmpne"),
+		GOTO => Some("goto"),
+		JSR => Some("jsr"),
+		RET => Some("ret"),
+		TABLESWITCH => Some("tableswitch"),
+		LOOKUPSWITCH => Some("lookupswitch"),
+		IRETURN => Some("ireturn"),
+		LRETURN => Some("lreturn"),
+		FRETURN => Some("freturn"),
+		DRETURN => Some("dreturn"),
+		ARETURN => Some("areturn"),
+		RETURN => Some("return"),
+		GETSTATIC => Some("getstatic"),
+		PUTSTATIC => Some("putstatic"),
+		GETFIELD => Some("getfield"),
+		PUTFIELD => Some("putfield"),
+		INVOKEVIRTUAL => Some("invokevirtual"),
+		INVOKESPECIAL => Some("invokespecial"),
+		INVOKESTATIC => Some("invokestatic"),
+		INVOKEINTERFACE => Some("invokeinterface"),
+		INVOKEDYNAMIC => Some("invokedynamic"),
+		NEW => Some("new"),
+		NEWARRAY => Some("newarray"),
+		ANEWARRAY => Some("anewarray"),
+		ARRAYLENGTH => Some("arraylength"),
+		ATHROW => Some("athrow"),
+		CHECKCAST => Some("checkcast"),
+		INSTANCEOF => Some("instanceof"),
+		MONITORENTER => Some("monitorenter"),
+		MONITOREXIT => Some("monitorexit"),
+		WIDE => Some("wide"),
+		MULTIANEWARRAY => Some("multianewarray"),
+		IFNULL => Some("ifnull"),
+		IFNONNULL => Some("ifnonnull"),
+		GOTO_W => Some("goto_w"),
+		JSR_W => Some("jsr_w"),
+		_ => None,
+	}
+}
+
+impl Display for Instruction {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Bipush(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Sipush(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ldc(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::LdcW(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Ldc2W(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Iload(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Lload(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Fload(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Dload(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Aload(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Istore(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Lstore(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Fstore(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Dstore(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Astore(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ifeq(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ifne(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Iflt(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ifge(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ifgt(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ifle(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfIcmpeq(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfIcmpne(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfIcmplt(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfIcmpge(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfIcmpgt(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfIcmple(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfAcmpeq(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::IfAcmpne(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Goto(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Jsr(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ret(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Getstatic(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Putstatic(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Getfield(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Putfield(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Invokevirtual(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Invokespecial(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Invokestatic(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::New(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Newarray(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Anewarray(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Checkcast(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Instanceof(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Ifnull(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Ifnonnull(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::GotoW(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::JsrW(value) => write!(f, "{} {value}", self.mnemonic()),
+			Self::Iinc(index, value) => write!(f, "{} {index} {value}", self.mnemonic()),
+			Self::Tableswitch(table) => write!(f, "{} {}..{} {:?} default {}", self.mnemonic(), table.low, table.high, table.offsets, table.default),
+			Self::Lookupswitch(lookup) => write!(f, "{} {:?} default {}", self.mnemonic(), lookup.pairs, lookup.default),
+			Self::Invokeinterface(index, count) => write!(f, "{} #{index} {count}", self.mnemonic()),
+			Self::Invokedynamic(index) => write!(f, "{} #{index}", self.mnemonic()),
+			Self::Multianewarray(index, dimensions) => write!(f, "{} #{index} {dimensions}", self.mnemonic()),
+			Self::Wide(WideInstruction::Iinc(index, value)) => write!(f, "{} iinc {index} {value}", self.mnemonic()),
+			Self::Wide(instruction) => {
+				let (opcode, index) = instruction.opcode_and_index();
+				write!(f, "{} {} {index}", self.mnemonic(), mnemonic(opcode).expect("Every wide instruction has a mnemonic"))
+			},
+			instruction => write!(f, "{}", instruction.mnemonic()),
+		}
+	}
+}
+
+impl WideInstruction {
+	/// Opcode of the widened instruction and the local variable index it operates on.
+	pub fn opcode_and_index(&self) -> (U1, U2) {
+		match *self {
+			Self::Iload(index) => (ILOAD, index),
+			Self::Lload(index) => (LLOAD, index),
+			Self::Fload(index) => (FLOAD, index),
+			Self::Dload(index) => (DLOAD, index),
+			Self::Aload(index) => (ALOAD, index),
+			Self::Istore(index) => (ISTORE, index),
+			Self::Lstore(index) => (LSTORE, index),
+			Self::Fstore(index) => (FSTORE, index),
+			Self::Dstore(index) => (DSTORE, index),
+			Self::Astore(index) => (ASTORE, index),
+			Self::Ret(index) => (RET, index),
+			Self::Iinc(index, _) => (IINC, index),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+	UnknownOpcode(U1),
+	TruncatedOperands(U1),
+	InvalidWideOpcode(U1),
+	InvalidSwitchRange {
+		low: i32,
+		high: i32,
+	},
+	NonZeroReservedByte(U1),
+}
+
+/// Failure to decode the instruction starting at `pc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+	pub pc: usize,
+	pub kind: DecodeErrorKind,
+}
+
+impl Display for DecodeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.kind {
+			DecodeErrorKind::UnknownOpcode(opcode) => write!(f, "unknown opcode 0x{opcode:02X} at pc {}", self.pc),
+			DecodeErrorKind::TruncatedOperands(opcode) => write!(f, "truncated operands of opcode 0x{opcode:02X} at pc {}", self.pc),
+			DecodeErrorKind::InvalidWideOpcode(opcode) => write!(f, "opcode 0x{opcode:02X} cannot be widened at pc {}", self.pc),
+			DecodeErrorKind::InvalidSwitchRange { low, high } => write!(f, "tableswitch with low {low} above high {high} at pc {}", self.pc),
+			DecodeErrorKind::NonZeroReservedByte(opcode) => write!(f, "non-zero reserved operand byte of opcode 0x{opcode:02X} at pc {}", self.pc),
+		}
+	}
+}
+
+impl std::error::Error for DecodeError {}
+
+struct Operands<'a> {
+	code: &'a [u8],
+	pc: usize,
+	position: usize,
+	opcode: U1,
+}
+
+impl Operands<'_> {
+	fn error(&self, kind: DecodeErrorKind) -> DecodeError {
+		DecodeError {
+			pc: self.pc,
+			kind,
+		}
+	}
+
+	fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+		let bytes = self.code.get(self.position..self.position + N).ok_or(self.error(DecodeErrorKind::TruncatedOperands(self.opcode)))?;
+		self.position += N;
+		Ok(bytes.try_into().expect("Slice has length N"))
+	}
+
+	fn u1(&mut self) -> Result<U1, DecodeError> {
+		Ok(U1::from_be_bytes(self.take()?))
+	}
+
+	fn u2(&mut self) -> Result<U2, DecodeError> {
+		Ok(U2::from_be_bytes(self.take()?))
+	}
+
+	fn i1(&mut self) -> Result<i8, DecodeError> {
+		Ok(i8::from_be_bytes(self.take()?))
+	}
+
+	fn i2(&mut self) -> Result<i16, DecodeError> {
+		Ok(i16::from_be_bytes(self.take()?))
+	}
+
+	fn i4(&mut self) -> Result<i32, DecodeError> {
+		Ok(i32::from_be_bytes(self.take()?))
+	}
+
+	/// Skips the padding that aligns switch operands to a multiple of four bytes from the start of the code.
+	fn align(&mut self) -> Result<(), DecodeError> {
+		let aligned = self.position.next_multiple_of(4);
+		if aligned > self.code.len() {
+			return Err(self.error(DecodeErrorKind::TruncatedOperands(self.opcode)));
+		}
+		self.position = aligned;
+		Ok(())
+	}
+}
+
+fn tableswitch_operands(operands: &mut Operands) -> Result<Instruction, DecodeError> {
+	operands.align()?;
+	let default = operands.i4()?;
+	let low = operands.i4()?;
+	let high = operands.i4()?;
+	if low > high {
+		return Err(operands.error(DecodeErrorKind::InvalidSwitchRange { low, high }));
+	}
+	let count = (high as i64 - low as i64 + 1) as usize;
+	if count > (operands.code.len() - operands.position) / 4 {
+		return Err(operands.error(DecodeErrorKind::TruncatedOperands(operands.opcode)));
+	}
+	let offsets = (0..count).map(|_| operands.i4()).collect::<Result<_, _>>()?;
+	Ok(Instruction::Tableswitch(TableSwitch {
+		default,
+		low,
+		high,
+		offsets,
+	}))
+}
+
+fn lookupswitch_operands(operands: &mut Operands) -> Result<Instruction, DecodeError> {
+	operands.align()?;
+	let default = operands.i4()?;
+	let npairs = operands.i4()?;
+	if npairs < 0 || npairs as usize > (operands.code.len() - operands.position) / 8 {
+		return Err(operands.error(DecodeErrorKind::TruncatedOperands(operands.opcode)));
+	}
+	let pairs = (0..npairs).map(|_| Ok((operands.i4()?, operands.i4()?))).collect::<Result<_, _>>()?;
+	Ok(Instruction::Lookupswitch(LookupSwitch {
+		default,
+		pairs,
+	}))
+}
+
+fn invokeinterface_operands(operands: &mut Operands) -> Result<Instruction, DecodeError> {
+	let index = operands.u2()?;
+	let count = operands.u1()?;
+	if operands.u1()? != 0 {
+		return Err(operands.error(DecodeErrorKind::NonZeroReservedByte(operands.opcode)));
+	}
+	Ok(Instruction::Invokeinterface(index, count))
+}
+
+fn invokedynamic_operands(operands: &mut Operands) -> Result<Instruction, DecodeError> {
+	let index = operands.u2()?;
+	if operands.u2()? != 0 {
+		return Err(operands.error(DecodeErrorKind::NonZeroReservedByte(operands.opcode)));
+	}
+	Ok(Instruction::Invokedynamic(index))
+}
+
+fn wide_operands(operands: &mut Operands) -> Result<Instruction, DecodeError> {
+	let opcode = operands.u1()?;
+	let instruction = match opcode {
+		ILOAD => WideInstruction::Iload(operands.u2()?),
+		LLOAD => WideInstruction::Lload(operands.u2()?),
+		FLOAD => WideInstruction::Fload(operands.u2()?),
+		DLOAD => WideInstruction::Dload(operands.u2()?),
+		ALOAD => WideInstruction::Aload(operands.u2()?),
+		ISTORE => WideInstruction::Istore(operands.u2()?),
+		LSTORE => WideInstruction::Lstore(operands.u2()?),
+		FSTORE => WideInstruction::Fstore(operands.u2()?),
+		DSTORE => WideInstruction::Dstore(operands.u2()?),
+		ASTORE => WideInstruction::Astore(operands.u2()?),
+		RET => WideInstruction::Ret(operands.u2()?),
+		IINC => WideInstruction::Iinc(operands.u2()?, operands.i2()?),
+		opcode => return Err(operands.error(DecodeErrorKind::InvalidWideOpcode(opcode))),
+	};
+	Ok(Instruction::Wide(instruction))
+}
+
+/// Decodes the instruction at `pc`, returning it together with its encoded length.
+pub(crate) fn decode_instruction(code: &[u8], pc: usize) -> Result<(Instruction, usize), DecodeError> {
+	let opcode = code[pc];
+	let mut operands = Operands {
+		code,
+		pc,
+		position: pc + 1,
+		opcode,
+	};
+	let instruction = match opcode {
+		NOP => Instruction::Nop,
+		ACONST_NULL => Instruction::AconstNull,
+		ICONST_M1 => Instruction::IconstM1,
+		ICONST_0 => Instruction::Iconst0,
+		ICONST_1 => Instruction::Iconst1,
+		ICONST_2 => Instruction::Iconst2,
+		ICONST_3 => Instruction::Iconst3,
+		ICONST_4 => Instruction::Iconst4,
+		ICONST_5 => Instruction::Iconst5,
+		LCONST_0 => Instruction::Lconst0,
+		LCONST_1 => Instruction::Lconst1,
+		FCONST_0 => Instruction::Fconst0,
+		FCONST_1 => Instruction::Fconst1,
+		FCONST_2 => Instruction::Fconst2,
+		DCONST_0 => Instruction::Dconst0,
+		DCONST_1 => Instruction::Dconst1,
+		BIPUSH => Instruction::Bipush(operands.i1()?),
+		SIPUSH => Instruction::Sipush(operands.i2()?),
+		LDC => Instruction::Ldc(operands.u1()?),
+		LDC_W => Instruction::LdcW(operands.u2()?),
+		LDC2_W => Instruction::Ldc2W(operands.u2()?),
+		ILOAD => Instruction::Iload(operands.u1()?),
+		LLOAD => Instruction::Lload(operands.u1()?),
+		FLOAD => Instruction::Fload(operands.u1()?),
+		DLOAD => Instruction::Dload(operands.u1()?),
+		ALOAD => Instruction::Aload(operands.u1()?),
+		ILOAD_0 => Instruction::Iload0,
+		ILOAD_1 => Instruction::Iload1,
+		ILOAD_2 => Instruction::Iload2,
+		ILOAD_3 => Instruction::Iload3,
+		LLOAD_0 => Instruction::Lload0,
+		LLOAD_1 => Instruction::Lload1,
+		LLOAD_2 => Instruction::Lload2,
+		LLOAD_3 => Instruction::Lload3,
+		FLOAD_0 => Instruction::Fload0,
+		FLOAD_1 => Instruction::Fload1,
+		FLOAD_2 => Instruction::Fload2,
+		FLOAD_3 => Instruction::Fload3,
+		DLOAD_0 => Instruction::Dload0,
+		DLOAD_1 => Instruction::Dload1,
+		DLOAD_2 => Instruction::Dload2,
+		DLOAD_3 => Instruction::Dload3,
+		ALOAD_0 => Instruction::Aload0,
+		ALOAD_1 => Instruction::Aload1,
+		ALOAD_2 => Instruction::Aload2,
+		ALOAD_3 => Instruction::Aload3,
+		IALOAD => Instruction::Iaload,
+		LALOAD => Instruction::Laload,
+		FALOAD => Instruction::Faload,
+		DALOAD => Instruction::Daload,
+		AALOAD => Instruction::Aaload,
+		BALOAD => Instruction::Baload,
+		CALOAD => Instruction::Caload,
+		SALOAD => Instruction::Saload,
+		ISTORE => Instruction::Istore(operands.u1()?),
+		LSTORE => Instruction::Lstore(operands.u1()?),
+		FSTORE => Instruction::Fstore(operands.u1()?),
+		DSTORE => Instruction::Dstore(operands.u1()?),
+		ASTORE => Instruction::Astore(operands.u1()?),
+		ISTORE_0 => Instruction::Istore0,
+		ISTORE_1 => Instruction::Istore1,
+		ISTORE_2 => Instruction::Istore2,
+		ISTORE_3 => Instruction::Istore3,
+		LSTORE_0 => Instruction::Lstore0,
+		LSTORE_1 => Instruction::Lstore1,
+		LSTORE_2 => Instruction::Lstore2,
+		LSTORE_3 => Instruction::Lstore3,
+		FSTORE_0 => Instruction::Fstore0,
+		FSTORE_1 => Instruction::Fstore1,
+		FSTORE_2 => Instruction::Fstore2,
+		FSTORE_3 => Instruction::Fstore3,
+		DSTORE_0 => Instruction::Dstore0,
+		DSTORE_1 => Instruction::Dstore1,
+		DSTORE_2 => Instruction::Dstore2,
+		DSTORE_3 => Instruction::Dstore3,
+		ASTORE_0 => Instruction::Astore0,
+		ASTORE_1 => Instruction::Astore1,
+		ASTORE_2 => Instruction::Astore2,
+		ASTORE_3 => Instruction::Astore3,
+		IASTORE => Instruction::Iastore,
+		LASTORE => Instruction::Lastore,
+		FASTORE => Instruction::Fastore,
+		DASTORE => Instruction::Dastore,
+		AASTORE => Instruction::Aastore,
+		BASTORE => Instruction::Bastore,
+		CASTORE => Instruction::Castore,
+		SASTORE => Instruction::Sastore,
+		POP => Instruction::Pop,
+		POP2 => Instruction::Pop2,
+		DUP => Instruction::Dup,
+		DUP_X1 => Instruction::DupX1,
+		DUP_X2 => Instruction::DupX2,
+		DUP2 => Instruction::Dup2,
+		DUP2_X1 => Instruction::Dup2X1,
+		DUP2_X2 => Instruction::Dup2X2,
+		SWAP => Instruction::Swap,
+		IADD => Instruction::Iadd,
+		LADD => Instruction::Ladd,
+		FADD => Instruction::Fadd,
+		DADD => Instruction::Dadd,
+		ISUB => Instruction::Isub,
+		LSUB => Instruction::Lsub,
+		FSUB => Instruction::Fsub,
+		DSUB => Instruction::Dsub,
+		IMUL => Instruction::Imul,
+		LMUL => Instruction::Lmul,
+		FMUL => Instruction::Fmul,
+		DMUL => Instruction::Dmul,
+		IDIV => Instruction::Idiv,
+		LDIV => Instruction::Ldiv,
+		FDIV => Instruction::Fdiv,
+		DDIV => Instruction::Ddiv,
+		IREM => Instruction::Irem,
+		LREM => Instruction::Lrem,
+		FREM => Instruction::Frem,
+		DREM => Instruction::Drem,
+		INEG => Instruction::Ineg,
+		LNEG => Instruction::Lneg,
+		FNEG => Instruction::Fneg,
+		DNEG => Instruction::Dneg,
+		ISHL => Instruction::Ishl,
+		LSHL => Instruction::Lshl,
+		ISHR => Instruction::Ishr,
+		LSHR => Instruction::Lshr,
+		IUSHR => Instruction::Iushr,
+		LUSHR => Instruction::Lushr,
+		IAND => Instruction::Iand,
+		LAND => Instruction::Land,
+		IOR => Instruction::Ior,
+		LOR => Instruction::Lor,
+		IXOR => Instruction::Ixor,
+		LXOR => Instruction::Lxor,
+		IINC => Instruction::Iinc(operands.u1()?, operands.i1()?),
+		I2L => Instruction::I2l,
+		I2F => Instruction::I2f,
+		I2D => Instruction::I2d,
+		L2I => Instruction::L2i,
+		L2F => Instruction::L2f,
+		L2D => Instruction::L2d,
+		F2I => Instruction::F2i,
+		F2L => Instruction::F2l,
+		F2D => Instruction::F2d,
+		D2I => Instruction::D2i,
+		D2L => Instruction::D2l,
+		D2F => Instruction::D2f,
+		I2B => Instruction::I2b,
+		I2C => Instruction::I2c,
+		I2S => Instruction::I2s,
+		LCMP => Instruction::Lcmp,
+		FCMPL => Instruction::Fcmpl,
+		FCMPG => Instruction::Fcmpg,
+		DCMPL => Instruction::Dcmpl,
+		DCMPG => Instruction::Dcmpg,
+		IFEQ => Instruction::Ifeq(operands.i2()?),
+		IFNE => Instruction::Ifne(operands.i2()?),
+		IFLT => Instruction::Iflt(operands.i2()?),
+		IFGE => Instruction::Ifge(operands.i2()?),
+		IFGT => Instruction::Ifgt(operands.i2()?),
+		IFLE => Instruction::Ifle(operands.i2()?),
+		IF_ICMPEQ => Instruction::IfIcmpeq(operands.i2()?),
+		IF_ICMPNE => Instruction::IfIcmpne(operands.i2()?),
+		IF_ICMPLT => Instruction::IfIcmplt(operands.i2()?),
+		IF_ICMPGE => Instruction::IfIcmpge(operands.i2()?),
+		IF_ICMPGT => Instruction::IfIcmpgt(operands.i2()?),
+		IF_ICMPLE => Instruction::IfIcmple(operands.i2()?),
+		IF_ACMPEQ => Instruction::IfAcmpeq(operands.i2()?),
+		IF_ACMPNE => Instruction::IfAcmpne(operands.i2()?),
+		GOTO => Instruction::Goto(operands.i2()?),
+		JSR => Instruction::Jsr(operands.i2()?),
+		RET => Instruction::Ret(operands.u1()?),
+		TABLESWITCH => tableswitch_operands(&mut operands)?,
+		LOOKUPSWITCH => lookupswitch_operands(&mut operands)?,
+		IRETURN => Instruction::Ireturn,
+		LRETURN => Instruction::Lreturn,
+		FRETURN => Instruction::Freturn,
+		DRETURN => Instruction::Dreturn,
+		ARETURN => Instruction::Areturn,
+		RETURN => Instruction::Return,
+		GETSTATIC => Instruction::Getstatic(operands.u2()?),
+		PUTSTATIC => Instruction::Putstatic(operands.u2()?),
+		GETFIELD => Instruction::Getfield(operands.u2()?),
+		PUTFIELD => Instruction::Putfield(operands.u2()?),
+		INVOKEVIRTUAL => Instruction::Invokevirtual(operands.u2()?),
+		INVOKESPECIAL => Instruction::Invokespecial(operands.u2()?),
+		INVOKESTATIC => Instruction::Invokestatic(operands.u2()?),
+		INVOKEINTERFACE => invokeinterface_operands(&mut operands)?,
+		INVOKEDYNAMIC => invokedynamic_operands(&mut operands)?,
+		NEW => Instruction::New(operands.u2()?),
+		NEWARRAY => Instruction::Newarray(operands.u1()?),
+		ANEWARRAY => Instruction::Anewarray(operands.u2()?),
+		ARRAYLENGTH => Instruction::Arraylength,
+		ATHROW => Instruction::Athrow,
+		CHECKCAST => Instruction::Checkcast(operands.u2()?),
+		INSTANCEOF => Instruction::Instanceof(operands.u2()?),
+		MONITORENTER => Instruction::Monitorenter,
+		MONITOREXIT => Instruction::Monitorexit,
+		WIDE => wide_operands(&mut operands)?,
+		MULTIANEWARRAY => Instruction::Multianewarray(operands.u2()?, operands.u1()?),
+		IFNULL => Instruction::Ifnull(operands.i2()?),
+		IFNONNULL => Instruction::Ifnonnull(operands.i2()?),
+		GOTO_W => Instruction::GotoW(operands.i4()?),
+		JSR_W => Instruction::JsrW(operands.i4()?),
+		opcode => return Err(operands.error(DecodeErrorKind::UnknownOpcode(opcode))),
+	};
+	Ok((instruction, operands.position - pc))
+}
+
+/// Iterator over the instructions of a code array, see `decode`.
+pub struct Decoder<'a> {
+	code: &'a [u8],
+	pc: usize,
+	failed: bool,
+}
+
+impl Iterator for Decoder<'_> {
+	type Item = Result<(usize, Instruction), DecodeError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.failed || self.pc >= self.code.len() {
+			return None;
+		}
+		match decode_instruction(self.code, self.pc) {
+			Ok((instruction, length)) => {
+				let pc = self.pc;
+				self.pc += length;
+				Some(Ok((pc, instruction)))
+			},
+			Err(error) => {
+				self.failed = true;
+				Some(Err(error))
+			},
+		}
+	}
+}
+
+/// Decodes `code` into `(pc, instruction)` pairs, stopping after the first error.
+pub fn decode(code: &[u8]) -> Decoder<'_> {
+	Decoder {
+		code,
+		pc: 0,
+		failed: false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decode_simple_code() {
+		let code = [0x04, 0x3C, 0x05, 0x3D, 0x1B, 0x1C, 0x60, 0x3E, 0xB2, 0x00, 0x07, 0x1D, 0xB6, 0x00, 0x0D, 0xB1];
+		let instructions = decode(&code).collect::<Result<Vec<_>, _>>().expect("Failed to decode code");
+		assert_eq!(instructions, [
+			(0, Instruction::Iconst1),
+			(1, Instruction::Istore1),
+			(2, Instruction::Iconst2),
+			(3, Instruction::Istore2),
+			(4, Instruction::Iload1),
+			(5, Instruction::Iload2),
+			(6, Instruction::Iadd),
+			(7, Instruction::Istore3),
+			(8, Instruction::Getstatic(7)),
+			(11, Instruction::Iload3),
+			(12, Instruction::Invokevirtual(13)),
+			(15, Instruction::Return),
+		]);
+		assert_eq!(instructions[10].1.to_string(), "invokevirtual #13");
+	}
+
+	#[test]
+	fn decode_switches_with_padding() {
+		let code = [
+			0x1A,
+			0xAA, 0x00, 0x00,
+			0x00, 0x00, 0x00, 0x20,
+			0x00, 0x00, 0x00, 0x01,
+			0x00, 0x00, 0x00, 0x02,
+			0x00, 0x00, 0x00, 0x18,
+			0x00, 0x00, 0x00, 0x1C,
+			0xAB, 0x00, 0x00, 0x00,
+			0x00, 0x00, 0x00, 0x08,
+			0x00, 0x00, 0x00, 0x01,
+			0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x04,
+		];
+		let instructions = decode(&code).collect::<Result<Vec<_>, _>>().expect("Failed to decode code");
+		assert_eq!(instructions[1], (1, Instruction::Tableswitch(TableSwitch { default: 32, low: 1, high: 2, offsets: vec![24, 28] })));
+		assert_eq!(instructions[2], (24, Instruction::Lookupswitch(LookupSwitch { default: 8, pairs: vec![(5, 4)] })));
+	}
+
+	#[test]
+	fn decode_wide_and_invoke_operands() {
+		let code = [0xC4, 0x84, 0x01, 0x00, 0xFF, 0xFF, 0xB9, 0x00, 0x05, 0x02, 0x00, 0xBA, 0x00, 0x06, 0x00, 0x00];
+		let instructions = decode(&code).collect::<Result<Vec<_>, _>>().expect("Failed to decode code");
+		assert_eq!(instructions, [
+			(0, Instruction::Wide(WideInstruction::Iinc(256, -1))),
+			(6, Instruction::Invokeinterface(5, 2)),
+			(11, Instruction::Invokedynamic(6)),
+		]);
+	}
+
+	#[test]
+	fn report_errors_instead_of_panicking() {
+		assert_eq!(decode(&[0x00, 0x11, 0x01]).last(), Some(Err(DecodeError { pc: 1, kind: DecodeErrorKind::TruncatedOperands(SIPUSH) })));
+		assert_eq!(decode(&[0xCA]).last(), Some(Err(DecodeError { pc: 0, kind: DecodeErrorKind::UnknownOpcode(0xCA) })));
+		assert_eq!(decode(&[0xC4, 0x60]).last(), Some(Err(DecodeError { pc: 0, kind: DecodeErrorKind::InvalidWideOpcode(IADD) })));
+		assert_eq!(decode(&[0xBA, 0x00, 0x06, 0x00, 0x01]).last(), Some(Err(DecodeError { pc: 0, kind: DecodeErrorKind::NonZeroReservedByte(INVOKEDYNAMIC) })));
+		assert_eq!(decode(&[0xAA, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02]).count(), 1);
+	}
+}
@@ -0,0 +1,229 @@
+use std::{borrow::Cow, sync::OnceLock};
+
+use crate::{access_flags::{ClassAccess, FieldAccess, MethodAccess}, attribute_info::{code::{code_attribute_parser, CodeAttribute}, find_attribute_ref, AttributeInfoRef}, class_file::{parse_class_file, parse_class_file_ref, ClassFile}, class_format_error::ClassFormatError, constant_pool::{ConstantPool, ConstantPoolError, ConstantPoolRef}, field_info::FieldInfo, method_info::MethodInfo, U2, U4};
+
+/// Field of a `LazyClassFile`.
+#[derive(Debug)]
+pub struct LazyField<'a> {
+	pub access_flags: U2,
+	pub name_index: U2,
+	pub descriptor_index: U2,
+	pub attributes: Vec<AttributeInfoRef<'a>>,
+	field_info: OnceLock<FieldInfo>,
+}
+
+impl<'a> LazyField<'a> {
+	pub fn access(&self) -> FieldAccess {
+		FieldAccess(self.access_flags)
+	}
+
+	pub fn name(&self, constant_pool: &ConstantPoolRef<'a>) -> Result<Cow<'a, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.name_index)
+	}
+
+	/// Copies the field with all its attributes on first use, giving access to everything `FieldInfo` decodes.
+	pub fn field_info(&self) -> &FieldInfo {
+		self.field_info.get_or_init(|| FieldInfo {
+			access_flags: self.access_flags,
+			name_index: self.name_index,
+			descriptor_index: self.descriptor_index,
+			attributes_count: self.attributes.len() as U2,
+			attributes: self.attributes.iter().copied().map(AttributeInfoRef::into_owned).collect(),
+		})
+	}
+}
+
+/// Method of a `LazyClassFile`, with its `Code` decoded when it is first needed.
+#[derive(Debug)]
+pub struct LazyMethod<'a> {
+	pub access_flags: U2,
+	pub name_index: U2,
+	pub descriptor_index: U2,
+	pub attributes: Vec<AttributeInfoRef<'a>>,
+	code: OnceLock<Result<Option<CodeAttribute>, ClassFormatError>>,
+	method_info: OnceLock<MethodInfo>,
+}
+
+impl<'a> LazyMethod<'a> {
+	pub fn access(&self) -> MethodAccess {
+		MethodAccess(self.access_flags)
+	}
+
+	pub fn name(&self, constant_pool: &ConstantPoolRef<'a>) -> Result<Cow<'a, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.name_index)
+	}
+
+	/// Decodes the `Code` attribute on first use, later calls and other threads get the same result.
+	///
+	/// `constant_pool` has to be the one of the class this method belongs to.
+	pub fn code(&self, constant_pool: &ConstantPoolRef<'a>) -> Result<Option<&CodeAttribute>, ClassFormatError> {
+		let code = self.code.get_or_init(|| match find_attribute_ref(&self.attributes, constant_pool, "Code")? {
+			Some(attribute) => Ok(Some(attribute.parse("Code", code_attribute_parser)?)),
+			None => Ok(None),
+		});
+		code.as_ref().map(Option::as_ref).map_err(Clone::clone)
+	}
+
+	/// Copies the method with all its attributes on first use, giving access to everything `MethodInfo` decodes.
+	pub fn method_info(&self) -> &MethodInfo {
+		self.method_info.get_or_init(|| MethodInfo {
+			access_flags: self.access_flags,
+			name_index: self.name_index,
+			descriptor_index: self.descriptor_index,
+			attributes_count: self.attributes.len() as U2,
+			attributes: self.attributes.iter().copied().map(AttributeInfoRef::into_owned).collect(),
+		})
+	}
+}
+
+/// Class file that decodes attributes such as `Code` only when they are accessed.
+///
+/// The first pass is `parse_class_file_ref`, which checks the structure of the whole class file and records where members, strings and attributes are.
+/// Only the contents of attributes are decoded on access, memoized so the class can be shared between threads.
+#[derive(Debug)]
+pub struct LazyClassFile<'a> {
+	data: &'a [u8],
+	pub magic: U4,
+	pub minor_version: U2,
+	pub major_version: U2,
+	pub constant_pool: ConstantPoolRef<'a>,
+	pub access_flags: U2,
+	pub this_class: U2,
+	pub super_class: U2,
+	pub interfaces: Vec<U2>,
+	pub fields: Vec<LazyField<'a>>,
+	pub methods: Vec<LazyMethod<'a>>,
+	pub attributes: Vec<AttributeInfoRef<'a>>,
+	owned_constant_pool: OnceLock<ConstantPool>,
+}
+
+impl<'a> LazyClassFile<'a> {
+	pub fn parse(data: &'a [u8]) -> Result<Self, ClassFormatError> {
+		let class_file = parse_class_file_ref(data)?;
+		Ok(Self {
+			data,
+			magic: class_file.magic,
+			minor_version: class_file.minor_version,
+			major_version: class_file.major_version,
+			constant_pool: class_file.constant_pool,
+			access_flags: class_file.access_flags,
+			this_class: class_file.this_class,
+			super_class: class_file.super_class,
+			interfaces: class_file.interfaces,
+			fields: class_file.fields.into_iter().map(|field| LazyField {
+				access_flags: field.access_flags,
+				name_index: field.name_index,
+				descriptor_index: field.descriptor_index,
+				attributes: field.attributes,
+				field_info: OnceLock::new(),
+			}).collect(),
+			methods: class_file.methods.into_iter().map(|method| LazyMethod {
+				access_flags: method.access_flags,
+				name_index: method.name_index,
+				descriptor_index: method.descriptor_index,
+				attributes: method.attributes,
+				code: OnceLock::new(),
+				method_info: OnceLock::new(),
+			}).collect(),
+			attributes: class_file.attributes,
+			owned_constant_pool: OnceLock::new(),
+		})
+	}
+
+	pub fn access(&self) -> ClassAccess {
+		ClassAccess(self.access_flags)
+	}
+
+	pub fn this_class_name(&self) -> Result<Cow<'a, str>, ConstantPoolError> {
+		self.constant_pool.get_class_name(self.this_class)
+	}
+
+	/// The class file as it was parsed.
+	pub fn bytes(&self) -> &'a [u8] {
+		self.data
+	}
+
+	/// Copy of the constant pool made on first use, for decoding the attributes of `field_info` and `method_info`.
+	pub fn owned_constant_pool(&self) -> &ConstantPool {
+		self.owned_constant_pool.get_or_init(|| self.constant_pool.to_constant_pool())
+	}
+
+	/// Finds a method by name and descriptor.
+	pub fn find_method(&self, name: &str, descriptor: &str) -> Result<Option<&LazyMethod<'a>>, ConstantPoolError> {
+		for method in &self.methods {
+			if method.name(&self.constant_pool)? == name && self.constant_pool.get_utf8_str(method.descriptor_index)? == descriptor {
+				return Ok(Some(method));
+			}
+		}
+		Ok(None)
+	}
+
+	/// Parses the complete class file, e.g. to modify and write it.
+	pub fn to_class_file(&self) -> Result<ClassFile, ClassFormatError> {
+		parse_class_file(self.data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, thread};
+
+	use super::*;
+
+	#[test]
+	fn decode_on_demand() {
+		let data = fs::read("./Sample.class").expect("Failed to read class file");
+		let class_file = LazyClassFile::parse(&data).expect("Failed to parse class file");
+		let constant_pool = &class_file.constant_pool;
+		assert_eq!(class_file.this_class_name().unwrap(), "Sample");
+		assert_eq!((class_file.fields.len(), class_file.methods.len()), (4, 7));
+		let source_file = find_attribute_ref(&class_file.attributes, constant_pool, "SourceFile").unwrap().unwrap();
+		assert_eq!(source_file.info.len(), 2);
+
+		let classify = class_file.find_method("classify", "(I)I").unwrap().expect("Missing method 'classify'");
+		let code = classify.code(constant_pool).unwrap().expect("Missing Code");
+		assert_eq!(code.max_stack, 1);
+		assert!(std::ptr::eq(code, classify.code(constant_pool).unwrap().unwrap()));
+
+		let owned_constant_pool = class_file.owned_constant_pool();
+		let mut written = Vec::new();
+		owned_constant_pool.write_to(&mut written).unwrap();
+		// The constant pool follows `magic`, `minor_version` and `major_version`.
+		assert_eq!(written, class_file.bytes()[8..8 + written.len()]);
+		let collect = class_file.methods.iter().find(|method| method.name(constant_pool).unwrap() == "collect").unwrap();
+		assert_eq!(collect.method_info().exceptions(owned_constant_pool).unwrap(), ["java/lang/Exception"]);
+		let big = class_file.fields.iter().find(|field| field.name(constant_pool).unwrap() == "BIG").unwrap();
+		let constant_value = big.field_info().constant_value(owned_constant_pool).unwrap().unwrap();
+		assert_eq!(owned_constant_pool.get_long(constant_value.constantvalue_index).unwrap(), 1234567890123);
+
+		assert_eq!(class_file.to_class_file().unwrap().to_bytes().unwrap(), class_file.bytes());
+	}
+
+	#[test]
+	fn share_decoded_code_between_threads() {
+		let data = fs::read("./Sample.class").expect("Failed to read class file");
+		let class_file = LazyClassFile::parse(&data).expect("Failed to parse class file");
+		let codes: Vec<usize> = thread::scope(|scope| {
+			let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| {
+				class_file.methods.iter().map(|method| method.code(&class_file.constant_pool).unwrap().map_or(0, |code| code as *const CodeAttribute as usize)).sum()
+			})).collect();
+			handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+		});
+		assert!(codes.iter().all(|code| *code == codes[0]));
+	}
+
+	#[test]
+	fn report_errors_of_later_attributes_on_access() {
+		let mut data = fs::read("./Main.class").expect("Failed to read class file");
+		let class_file = LazyClassFile::parse(&data).unwrap();
+		let code = find_attribute_ref(&class_file.methods[1].attributes, &class_file.constant_pool, "Code").unwrap().unwrap();
+		let code_offset = code.info.as_ptr() as usize - data.as_ptr() as usize;
+
+		// A code_length beyond the attribute only fails when the Code is decoded.
+		data[code_offset + 4..code_offset + 8].copy_from_slice(&0xFFFFu32.to_be_bytes());
+		let class_file = LazyClassFile::parse(&data).unwrap();
+		let error = class_file.methods[1].code(&class_file.constant_pool).unwrap_err();
+		assert_eq!(error.path(), "Code.code");
+		assert_eq!(class_file.methods[1].code(&class_file.constant_pool).unwrap_err(), error);
+	}
+}
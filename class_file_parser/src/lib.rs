@@ -2,20 +2,51 @@ pub type U1 = u8;
 pub type U2 = u16;
 pub type U4 = u32;
 
+pub mod access_flags;
+pub mod analysis;
+pub mod archive;
+pub mod class_builder;
 pub mod class_file;
+pub mod class_format_error;
+pub mod class_path;
+pub mod class_writer;
+pub mod constant_pool;
 pub mod cp_info;
+pub mod descriptor;
 pub mod field_info;
+pub mod format_check;
+pub mod instruction;
+pub mod lazy;
 pub mod method_info;
+pub mod mutf8;
+pub mod signature;
+pub mod verifier;
+pub mod visitor;
+mod writer;
 pub mod attribute_info;
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 	use std::fs;
+    use attribute_info::AttributeBody;
     use class_file::class_file_parser;
+    use class_format_error::ClassFormatErrorKind;
 
     use super::*;
 
     const CLASS_FILE_PATH: &str = "./Main.class";
+	const SAMPLE_CLASS_FILE_PATH: &str = "./Sample.class";
+
+	fn read_sample() -> class_file::ClassFile {
+		let class_file_raw = fs::read(SAMPLE_CLASS_FILE_PATH).expect("Failed to read class file");
+		class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file")
+	}
+
+	fn find_method<'a>(class_file: &'a class_file::ClassFile, name: &str) -> &'a method_info::MethodInfo {
+		class_file.methods.iter()
+			.find(|method| class_file.constant_pool.get_utf8_str(method.name_index).unwrap() == name)
+			.unwrap_or_else(|| panic!("Missing method '{name}'"))
+	}
 
 	#[test]
     fn parse_class_file() {
@@ -32,4 +63,480 @@ mod tests {
             panic!("Failed to parse complete class file. Have {} bytes remaining!", rest.len());
         }
     }
-}
\ No newline at end of file
+
+	#[test]
+	fn truncated_class_file_reports_location() {
+		let class_file_raw = fs::read(CLASS_FILE_PATH).expect("Failed to read class file");
+
+		let error = class_file::parse_class_file(&class_file_raw[..0x20]).expect_err("Parsed truncated class file");
+		assert_eq!(error.kind(), &ClassFormatErrorKind::UnexpectedEof);
+		assert_eq!(error.path(), "constant_pool[4].Utf8.bytes");
+		assert_eq!(error.offset(), 0x1A);
+	}
+
+	#[test]
+	fn unknown_constant_tag_reports_location() {
+		let mut class_file_raw = fs::read(CLASS_FILE_PATH).expect("Failed to read class file");
+		class_file_raw[0x0A] = 2;
+
+		let error = class_file::parse_class_file(&class_file_raw).expect_err("Parsed class file with unknown constant tag");
+		assert_eq!(error.kind(), &ClassFormatErrorKind::UnknownConstantTag(2));
+		assert_eq!(error.path(), "constant_pool[1]");
+		assert_eq!(error.offset(), 0x0A);
+	}
+
+	#[test]
+	fn malformed_utf8_reports_location() {
+		let mut class_file_raw = fs::read(CLASS_FILE_PATH).expect("Failed to read class file");
+		class_file_raw[0x1C] = 0xFF;
+
+		let error = class_file::parse_class_file(&class_file_raw).expect_err("Parsed class file with malformed Utf8");
+		assert!(matches!(error.kind(), ClassFormatErrorKind::MalformedUtf8(_)));
+		assert_eq!(error.path(), "constant_pool[4].Utf8.bytes");
+		assert_eq!(error.offset(), 0x1C);
+	}
+
+	#[test]
+	fn decode_code_attribute() {
+		let class_file_raw = fs::read(CLASS_FILE_PATH).expect("Failed to read class file");
+		let class_file = class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file");
+
+		let main = &class_file.methods[1];
+		assert_eq!(class_file.constant_pool.get_utf8_str(main.name_index).unwrap(), "main");
+		let code = main.code(&class_file.constant_pool).expect("Failed to decode Code").expect("Missing Code");
+		assert_eq!(code.max_stack, 2);
+		assert_eq!(code.max_locals, 4);
+		assert_eq!(code.code, [0x04, 0x3C, 0x05, 0x3D, 0x1B, 0x1C, 0x60, 0x3E, 0xB2, 0x00, 0x07, 0x1D, 0xB6, 0x00, 0x0D, 0xB1]);
+		assert!(code.exception_table.is_empty());
+		assert_eq!(code.attributes[0].name(&class_file.constant_pool).unwrap(), "LineNumberTable");
+	}
+
+	#[test]
+	fn write_round_trips_unmodified_class_files() {
+		for path in [CLASS_FILE_PATH, "./Sample.class", "./Sample$Marker.class", "./Sample$Point.class", "./Sample$Shape.class"] {
+			let class_file_raw = fs::read(path).expect("Failed to read class file");
+			let class_file = class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file");
+			assert_eq!(class_file.to_bytes().expect("Failed to write class file"), class_file_raw, "{path} did not round-trip");
+		}
+	}
+
+	#[test]
+	fn write_recomputes_counts() {
+		let class_file_raw = fs::read(CLASS_FILE_PATH).expect("Failed to read class file");
+		let mut class_file = class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file");
+		class_file.attributes.clear();
+		class_file.methods[1].attributes[0].info.truncate(0);
+
+		let written = class_file.to_bytes().expect("Failed to write class file");
+		let reparsed = class_file::parse_class_file(&written).expect("Failed to parse written class file");
+		assert_eq!(reparsed.attributes_count, 0);
+		assert_eq!(reparsed.methods[1].attributes[0].attribute_length, 0);
+		assert_eq!(written.len(), class_file_raw.len() - 8 - 0x38);
+	}
+
+	#[test]
+	fn expand_stack_map_frames() {
+		use attribute_info::stack_map_table::{Frame, VerificationType};
+
+		let class_file = read_sample();
+		let object = |name: &str| VerificationType::Object(name.to_string());
+
+		let frames = find_method(&class_file, "collect").stack_map_frames(&class_file).expect("Failed to expand frames");
+		assert_eq!(frames, [
+			Frame { pc: 11, locals: vec![object("Sample"), object("java/util/function/Supplier"), VerificationType::Integer, object("java/util/List"), VerificationType::Integer], stack: vec![] },
+			Frame { pc: 39, locals: vec![object("Sample"), object("java/util/function/Supplier"), VerificationType::Integer, object("java/util/List")], stack: vec![] },
+		]);
+
+		let frames = find_method(&class_file, "mix").stack_map_frames(&class_file).expect("Failed to expand frames");
+		let locals = vec![VerificationType::Long, VerificationType::Top, VerificationType::Double, VerificationType::Top, object("java/lang/String")];
+		assert_eq!(frames, [
+			Frame { pc: 28, locals: locals.clone(), stack: vec![object("java/lang/NumberFormatException")] },
+			Frame { pc: 50, locals, stack: vec![object("java/lang/Throwable")] },
+		]);
+	}
+
+	#[test]
+	fn stack_map_tables_round_trip() {
+		let class_file = read_sample();
+		for method in &class_file.methods {
+			let Some(code) = method.code(&class_file.constant_pool).expect("Failed to decode Code") else {
+				continue;
+			};
+			let Some(attribute) = attribute_info::find_attribute(&code.attributes, &class_file.constant_pool, "StackMapTable").unwrap() else {
+				continue;
+			};
+			let stack_map_table = code.stack_map_table(&class_file.constant_pool).unwrap().unwrap();
+			let mut written = Vec::new();
+			stack_map_table.write_to(&mut written).unwrap();
+			assert_eq!(written, attribute.info);
+		}
+	}
+
+	pub(crate) fn sample_hierarchy() -> verifier::ClassHierarchyMap {
+		let mut hierarchy = verifier::ClassHierarchyMap::new();
+		for (class_name, super_class) in [
+			("java/lang/NumberFormatException", "java/lang/IllegalArgumentException"),
+			("java/lang/IllegalArgumentException", "java/lang/RuntimeException"),
+			("java/lang/RuntimeException", "java/lang/Exception"),
+			("java/lang/Exception", "java/lang/Throwable"),
+			("java/lang/Throwable", "java/lang/Object"),
+		] {
+			hierarchy.insert(class_name, Some(super_class), false);
+		}
+		for interface in ["java/util/List", "java/util/function/Supplier", "java/lang/Comparable", "java/io/Serializable", "java/lang/annotation/Annotation"] {
+			hierarchy.insert(interface, Some("java/lang/Object"), true);
+		}
+		hierarchy
+	}
+
+	#[test]
+	fn verify_sample_classes() {
+		let mut hierarchy = sample_hierarchy();
+		let mut class_files = Vec::new();
+		for path in ["./Main.class", "./Sample.class", "./Sample$Circle.class", "./Sample$Inner.class", "./Sample$Marker.class", "./Sample$Point.class", "./Sample$Shape.class", "./Sample$Square.class"] {
+			let class_file_raw = fs::read(path).expect("Failed to read class file");
+			let class_file = class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file");
+			hierarchy.insert_class_file(&class_file).unwrap();
+			class_files.push(class_file);
+		}
+		for class_file in &class_files {
+			verifier::verify_class(class_file, &hierarchy).unwrap_or_else(|e| panic!("{e}"));
+		}
+	}
+
+	#[test]
+	fn verify_reports_type_mismatch() {
+		let mut class_file_raw = fs::read(CLASS_FILE_PATH).expect("Failed to read class file");
+		let code = [0x04, 0x3C, 0x05, 0x3D];
+		let position = class_file_raw.windows(code.len()).position(|window| window == code).expect("Missing code");
+		// istore_1 -> fstore_1
+		class_file_raw[position + 1] = 0x44;
+		let class_file = class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file");
+
+		let error = verifier::verify_class(&class_file, &sample_hierarchy()).expect_err("Verified ill-typed code");
+		assert_eq!(error.method, "main([Ljava/lang/String;)V");
+		assert_eq!(error.pc, Some(1));
+		assert_eq!(error.kind, verifier::VerifyErrorKind::TypeMismatch {
+			expected: verifier::ExpectedType::Type(attribute_info::stack_map_table::VerificationType::Float),
+			actual: attribute_info::stack_map_table::VerificationType::Integer,
+		});
+	}
+
+	#[test]
+	fn verify_checks_handlers_after_stores() {
+		use access_flags::{ClassAccess, MethodAccess};
+		use class_builder::ClassBuilder;
+		use class_writer::Compute;
+		use instruction::{ISTORE, POP, RETURN};
+		use visitor::{ClassVersion, FrameValue};
+
+		let mut class = ClassBuilder::new(ClassVersion { major: 61, minor: 0 }, ClassAccess::PUBLIC, "Handler", Some("java/lang/Object"), &[]);
+		class.compute(Compute::Maxs);
+		let mut code = class.method(MethodAccess::STATIC, "run", "(Ljava/lang/String;)V").code();
+		let (start, end, handler) = (code.new_label(), code.new_label(), code.new_label());
+		// the handler expects the String that the try block overwrites with an int
+		code.try_catch(start, end, handler, None)
+			.label(start).push_int(0).var_insn(ISTORE, 0).label(end).insn(RETURN)
+			.label(handler).frame(&[FrameValue::Object("java/lang/String".to_string())], &[FrameValue::Object("java/lang/Throwable".to_string())]).insn(POP).insn(RETURN);
+		code.end();
+		let class_file = class.build().unwrap();
+
+		let error = verifier::verify_class(&class_file, &sample_hierarchy()).expect_err("Verified a store the handler does not allow");
+		assert_eq!(error.pc, Some(1));
+		assert!(matches!(error.kind, verifier::VerifyErrorKind::LocalMismatch { index: 0, .. }), "{error}");
+	}
+
+	#[test]
+	fn decode_annotations() {
+		use attribute_info::annotations::{Annotation, ElementValue, Visibility};
+
+		let class_file = read_sample();
+		let marker = |value: &str| Annotation {
+			type_name: "LSample$Marker;".to_string(),
+			elements: vec![("value".to_string(), ElementValue::String(value.into()))],
+		};
+		let element_type = |name: &str| ElementValue::Enum {
+			type_name: "Ljava/lang/annotation/ElementType;".to_string(),
+			const_name: name.to_string(),
+		};
+
+		let annotations = class_file.annotations(Visibility::Visible).unwrap();
+		assert_eq!(annotations.len(), 1);
+		assert_eq!(annotations[0].element("level"), Some(&ElementValue::Int(3)));
+		assert_eq!(annotations[0].element("kinds"), Some(&ElementValue::Array(vec![element_type("TYPE"), element_type("METHOD")])));
+		assert!(class_file.annotations(Visibility::Invisible).unwrap().is_empty());
+
+		let collect = find_method(&class_file, "collect");
+		assert_eq!(collect.annotations(&class_file.constant_pool, Visibility::Visible).unwrap(), [marker("method")]);
+		assert_eq!(collect.parameter_annotations(&class_file.constant_pool, Visibility::Visible).unwrap(), [vec![marker("parameter")], vec![]]);
+
+		let marker_raw = fs::read("./Sample$Marker.class").expect("Failed to read class file");
+		let marker_class = class_file::parse_class_file(&marker_raw).expect("Failed to parse class file");
+		let defaults = ["value", "level", "kinds"].map(|name| find_method(&marker_class, name).annotation_default(&marker_class.constant_pool).unwrap());
+		assert_eq!(defaults, [Some(ElementValue::String("marker".into())), Some(ElementValue::Int(1)), Some(ElementValue::Array(vec![]))]);
+	}
+
+	#[test]
+	fn decode_module_attributes() {
+		use access_flags::RequiresFlags;
+
+		let class_file_raw = fs::read("./module-info.class").expect("Failed to read class file");
+		let class_file = class_file::parse_class_file(&class_file_raw).expect("Failed to parse class file");
+
+		let module = class_file.module().unwrap().expect("Missing Module");
+		assert_eq!(module.name, "sample.module");
+		assert_eq!(module.version.as_deref(), Some("1.0"));
+		let requires = module.requires.iter().map(|requires| (requires.module.as_str(), requires.flags)).collect::<Vec<_>>();
+		assert_eq!(requires, [("java.base", RequiresFlags::MANDATED), ("java.logging", RequiresFlags::TRANSITIVE), ("java.sql", RequiresFlags::STATIC_PHASE)]);
+		assert!(module.exports_to("sample/api", "any.module"));
+		assert!(module.exports_to("sample/impl", "java.logging"));
+		assert!(!module.exports_to("sample/impl", "any.module"));
+		assert!(module.opens_to("sample/impl", "java.logging"));
+		assert!(!module.opens_to("sample/api", "java.logging"));
+		assert_eq!(module.uses, ["sample/api/Service"]);
+		assert_eq!(module.provides[0].service, "sample/api/Service");
+		assert_eq!(module.provides[0].with, ["sample/impl/ServiceImpl"]);
+
+		assert_eq!(class_file.module_packages().unwrap(), Some(vec!["sample/api".to_string(), "sample/impl".to_string()]));
+		assert_eq!(class_file.module_main_class().unwrap().as_deref(), Some("sample/impl/ServiceImpl"));
+		assert_eq!(read_sample().module().unwrap(), None);
+
+		let attribute = attribute_info::find_attribute(&class_file.attributes, &class_file.constant_pool, "Module").unwrap().unwrap();
+		let mut written = Vec::new();
+		attribute.parse("Module", attribute_info::module::module_attribute_parser).unwrap().write_to(&mut written).unwrap();
+		assert_eq!(written, attribute.info);
+	}
+
+	#[test]
+	fn resolve_call_sites() {
+		use attribute_info::bootstrap_methods::Constant;
+		use cp_info::method_handle::ReferenceKind;
+
+		let class_file = read_sample();
+		let bootstrap_methods = class_file.bootstrap_methods().unwrap().expect("Missing BootstrapMethods");
+		let call_site = |name: &str| {
+			let code = find_method(&class_file, name).code(&class_file.constant_pool).unwrap().unwrap();
+			let index = code.instructions().find_map(|instruction| match instruction.unwrap().1 {
+				instruction::Instruction::Invokedynamic(index) => Some(index),
+				_ => None,
+			}).expect("Missing invokedynamic");
+			bootstrap_methods.resolve_call_site(&class_file.constant_pool, index).unwrap()
+		};
+
+		let concat = call_site("mix");
+		assert_eq!(concat.bootstrap_method.kind, ReferenceKind::InvokeStatic);
+		assert_eq!(concat.bootstrap_method.owner, "java/lang/invoke/StringConcatFactory");
+		assert_eq!(concat.bootstrap_method.name, "makeConcatWithConstants");
+		assert_eq!(concat.name, "makeConcatWithConstants");
+		assert_eq!(concat.descriptor, "(J)Ljava/lang/String;");
+		assert_eq!(concat.static_arguments, [Constant::String("done \u{1}".into())]);
+
+		let lambda = call_site("size");
+		assert_eq!(lambda.bootstrap_method.to_string(), "REF_invokeStatic java/lang/invoke/LambdaMetafactory.metafactory:(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;");
+		assert_eq!(lambda.name, "get");
+		assert_eq!(lambda.descriptor, "(LSample;)Ljava/util/function/Supplier;");
+		let Constant::MethodHandle(implementation) = &lambda.static_arguments[1] else {
+			panic!("Expected a method handle, got: {:?}", lambda.static_arguments[1]);
+		};
+		assert_eq!((implementation.kind, implementation.owner.as_ref(), implementation.name.as_ref()), (ReferenceKind::InvokeVirtual, "Sample", "lambda$size$0"));
+		assert_eq!(lambda.static_arguments[2], Constant::MethodType("()Ljava/lang/Integer;".to_string()));
+	}
+
+	#[test]
+	fn decode_debug_info() {
+		let class_file = read_sample();
+		assert_eq!(class_file.source_file().unwrap().as_deref(), Some("Sample.java"));
+		assert_eq!(class_file.source_debug_extension().unwrap(), None);
+
+		let code = find_method(&class_file, "collect").code(&class_file.constant_pool).unwrap().unwrap();
+		assert_eq!(code.line_for_pc(&class_file.constant_pool, 0).unwrap(), Some(42));
+		assert_eq!(code.line_for_pc(&class_file.constant_pool, 20).unwrap(), Some(44));
+		assert_eq!(code.line_for_pc(&class_file.constant_pool, 36).unwrap(), Some(43));
+		assert_eq!(code.pcs_for_line(&class_file.constant_pool, 43).unwrap(), [8, 33]);
+
+		let locals = code.locals_live_at(&class_file.constant_pool, 20).unwrap();
+		let locals = locals.iter().map(|local| (local.index, local.name.as_str(), local.descriptor.as_str(), local.signature.as_deref())).collect::<Vec<_>>();
+		assert_eq!(locals, [
+			(0, "this", "LSample;", Some("LSample<TT;>;")),
+			(1, "supplier", "Ljava/util/function/Supplier;", Some("Ljava/util/function/Supplier<TR;>;")),
+			(2, "count", "I", None),
+			(3, "result", "Ljava/util/List;", Some("Ljava/util/List<TR;>;")),
+			(4, "i", "I", None),
+		]);
+		assert_eq!(code.locals_live_at(&class_file.constant_pool, 5).unwrap().len(), 3);
+	}
+
+	#[test]
+	fn decode_signatures() {
+		let class_file = read_sample();
+		let signature = class_file.signature().unwrap().expect("Sample is generic");
+		assert_eq!(signature.to_string(), "<T::Ljava/lang/Comparable<-TT;>;>Ljava/lang/Object;Ljava/io/Serializable;");
+		assert_eq!(signature.type_parameters[0].name, "T");
+		assert_eq!(signature.superinterfaces[0].class_name(), "java/io/Serializable");
+
+		let field = class_file.fields.iter().find(|field| class_file.constant_pool.get_utf8_str(field.name_index).unwrap() == "values").unwrap();
+		assert_eq!(field.signature(&class_file.constant_pool).unwrap().unwrap().to_string(), "Ljava/util/List<TT;>;");
+
+		let collect = find_method(&class_file, "collect").signature(&class_file.constant_pool).unwrap().unwrap();
+		assert_eq!(collect.to_string(), "<R:TT;>(Ljava/util/function/Supplier<TR;>;I)Ljava/util/List<TR;>;");
+		assert_eq!(collect.type_parameters[0].class_bound, Some(signature::ReferenceTypeSignature::TypeVariable("T".to_string())));
+		assert!(collect.throws.is_empty());
+		assert_eq!(find_method(&class_file, "collect").exceptions(&class_file.constant_pool).unwrap(), ["java/lang/Exception"]);
+		assert_eq!(find_method(&class_file, "classify").signature(&class_file.constant_pool).unwrap(), None);
+	}
+
+	#[test]
+	fn decode_nest_and_record_attributes() {
+		let read_class = |path: &str| class_file::parse_class_file(&fs::read(path).expect("Failed to read class file")).expect("Failed to parse class file");
+		let class_file = read_sample();
+		assert_eq!(class_file.nest_host().unwrap(), None);
+		let mut nest_members = class_file.nest_members().unwrap();
+		nest_members.sort();
+		assert_eq!(nest_members, ["Sample$Circle", "Sample$Inner", "Sample$Marker", "Sample$Point", "Sample$Shape", "Sample$Square"]);
+		assert!(!class_file.is_record().unwrap());
+		assert!(class_file.record_components().unwrap().is_empty());
+		assert_eq!(class_file.enclosing_method().unwrap(), None);
+
+		let inner = class_file.inner_classes().unwrap().into_iter().find(|class| class.inner_class == "Sample$Inner").unwrap();
+		assert_eq!(inner.outer_class.as_deref(), Some("Sample"));
+		assert_eq!(inner.inner_name.as_deref(), Some("Inner"));
+		assert_eq!(inner.access_flags, access_flags::InnerClassAccess::PRIVATE);
+
+		let point = read_class("./Sample$Point.class");
+		assert_eq!(point.nest_host().unwrap().as_deref(), Some("Sample"));
+		assert!(point.is_record().unwrap());
+		let components = point.record_components().unwrap();
+		let components = components.iter().map(|component| (component.name(&point.constant_pool).unwrap().into_owned(), component.descriptor(&point.constant_pool).unwrap())).collect::<Vec<_>>();
+		assert_eq!(components, [("x".to_string(), descriptor::FieldType::Base(descriptor::BaseType::Int)), ("y".to_string(), descriptor::FieldType::Base(descriptor::BaseType::Int))]);
+		let parameters = find_method(&point, "<init>").parameters(&point.constant_pool).unwrap();
+		assert_eq!(parameters.iter().map(|parameter| parameter.name.as_deref()).collect::<Vec<_>>(), [Some("x"), Some("y")]);
+		assert!(parameters.iter().all(|parameter| parameter.access_flags.is_empty()));
+
+		let shape = read_class("./Sample$Shape.class");
+		assert_eq!(shape.permitted_subclasses().unwrap().unwrap(), ["Sample$Circle", "Sample$Square"]);
+		assert_eq!(read_class("./Sample$Circle.class").permitted_subclasses().unwrap(), None);
+	}
+
+	#[test]
+	fn validate_access_flags() {
+		for path in ["./Main.class", "./Sample.class", "./Sample$Marker.class", "./Sample$Point.class", "./Sample$Shape.class", "./module-info.class"] {
+			let class_file = class_file::parse_class_file(&fs::read(path).expect("Failed to read class file")).expect("Failed to parse class file");
+			let class_access = class_file.access();
+			class_access.validate().unwrap_or_else(|error| panic!("{path}: {error}"));
+			for field in &class_file.fields {
+				field.access().validate(class_access).unwrap_or_else(|error| panic!("{path}: {error}"));
+			}
+			for method in &class_file.methods {
+				let name = class_file.constant_pool.get_utf8_str(method.name_index).unwrap();
+				method.access().validate(class_access, &name, class_file.major_version).unwrap_or_else(|error| panic!("{path}.{name}: {error}"));
+			}
+			for inner_class in class_file.inner_classes().unwrap() {
+				inner_class.access_flags.validate().unwrap_or_else(|error| panic!("{path}: {error}"));
+			}
+		}
+
+		let class_file = read_sample();
+		assert_eq!(class_file.access().to_string(), "public");
+		assert_eq!(class_file.access().names(), ["ACC_PUBLIC", "ACC_SUPER"]);
+		let big = class_file.fields.iter().find(|field| class_file.constant_pool.get_utf8_str(field.name_index).unwrap() == "BIG").unwrap();
+		assert_eq!(big.access().to_string(), "public static final");
+		let constant_value = big.constant_value(&class_file.constant_pool).unwrap().expect("BIG is a constant");
+		assert_eq!(class_file.constant_pool.get_long(constant_value.constantvalue_index).unwrap(), 1234567890123);
+	}
+
+	#[test]
+	fn check_class_format() {
+		use format_check::{check_format, FormatErrorKind};
+
+		for path in ["./Main.class", "./Sample.class", "./Sample$Inner.class", "./Sample$Marker.class", "./Sample$Point.class", "./Sample$Shape.class", "./module-info.class"] {
+			let class_file = class_file::parse_class_file(&fs::read(path).expect("Failed to read class file")).expect("Failed to parse class file");
+			check_format(&class_file, 65).unwrap_or_else(|errors| panic!("{path}: {errors:?}"));
+		}
+
+		let mut class_file = read_sample();
+		class_file.magic = 0xCAFEBABF;
+		class_file.minor_version = 0xFFFF;
+		class_file.access_flags = 0x0211;
+		let source_file = class_file.attributes.iter().find(|attribute| attribute.name(&class_file.constant_pool).unwrap() == "SourceFile").unwrap();
+		let duplicate = attribute_info::AttributeInfo {
+			attribute_name_index: source_file.attribute_name_index,
+			attribute_length: source_file.attribute_length,
+			info: source_file.info.clone(),
+		};
+		class_file.attributes.push(duplicate);
+		let name_index = class_file.constant_pool.push(cp_info::CPInfo::Utf8(cp_info::utf8::Utf8::from("java.lang.String"))).unwrap();
+		let class_index = class_file.constant_pool.push(cp_info::CPInfo::Class(cp_info::class::Class { tag: 7, name_index })).unwrap();
+
+		let errors = check_format(&class_file, 65).unwrap_err();
+		let kinds = errors.iter().map(|error| (error.location.as_str(), &error.kind)).collect::<Vec<_>>();
+		assert_eq!(kinds[0], ("magic", &FormatErrorKind::InvalidMagic(0xCAFEBABF)));
+		assert_eq!(kinds[1], ("major_version", &FormatErrorKind::UnsupportedVersion { major: class_file.major_version, minor: 0xFFFF }));
+		assert_eq!(kinds[2], (format!("constant_pool[{class_index}]").as_str(), &FormatErrorKind::InvalidClassName("java.lang.String".to_string())));
+		assert!(matches!(kinds[3], ("access_flags", FormatErrorKind::AccessFlags(_))));
+		assert!(matches!(kinds[4], ("fields[3]", FormatErrorKind::AccessFlags(_))), "private fields are not allowed in interfaces");
+		assert_eq!(errors.last().unwrap().kind, FormatErrorKind::DuplicateAttribute("SourceFile".to_string()));
+		assert_eq!(errors.len(), 6, "{errors:?}");
+
+		class_file.major_version = 65;
+		let errors = check_format(&class_file, 65).unwrap_err();
+		assert!(errors.iter().all(|error| error.location != "major_version"));
+
+		// e.g. written by a ClassWriter that was never given a version
+		class_file.minor_version = 0;
+		for major in [0, 44] {
+			class_file.major_version = major;
+			let errors = check_format(&class_file, 65).unwrap_err();
+			assert!(errors.iter().any(|error| error.kind == FormatErrorKind::UnsupportedVersion { major, minor: 0 }), "{major}");
+		}
+	}
+
+	#[test]
+	fn check_method_handle_versions() {
+		use cp_info::{class::Class, interface_methodref::InterfaceMethodref, method_handle::{MethodHandle, ReferenceKind}, name_and_type::NameAndType, utf8::Utf8, CPInfo};
+		use format_check::{check_format, FormatErrorKind};
+
+		let mut class_file = read_sample();
+		let constant_pool = &mut class_file.constant_pool;
+		let class_name_index = constant_pool.push(CPInfo::Utf8(Utf8::from("java/util/List"))).unwrap();
+		let class_index = constant_pool.push(CPInfo::Class(Class { tag: 7, name_index: class_name_index })).unwrap();
+		let name_index = constant_pool.push(CPInfo::Utf8(Utf8::from("of"))).unwrap();
+		let descriptor_index = constant_pool.push(CPInfo::Utf8(Utf8::from("()Ljava/util/List;"))).unwrap();
+		let name_and_type_index = constant_pool.push(CPInfo::NameAndType(NameAndType { tag: 12, name_index, descriptor_index })).unwrap();
+		let reference_index = constant_pool.push(CPInfo::InterfaceMethodref(InterfaceMethodref { tag: 11, class_index, name_and_type_index })).unwrap();
+		let method_handle_index = constant_pool.push(CPInfo::MethodHandle(MethodHandle { tag: 15, reference_kind: ReferenceKind::InvokeStatic as u8, reference_index })).unwrap();
+		let location = format!("constant_pool[{method_handle_index}]");
+
+		class_file.major_version = 52;
+		check_format(&class_file, 65).unwrap_or_else(|errors| panic!("{errors:?}"));
+
+		class_file.major_version = 51;
+		let errors = check_format(&class_file, 65).unwrap_err();
+		let error = errors.iter().find(|error| error.location == location).expect("Missing method handle error");
+		assert_eq!(error.kind, FormatErrorKind::InvalidMethodHandle { kind: ReferenceKind::InvokeStatic, name: "of".to_string(), interface_method: true });
+	}
+
+	#[test]
+	fn borrow_from_class_file() {
+		use std::borrow::Cow;
+
+		for path in [CLASS_FILE_PATH, "./Sample.class", "./Sample$Point.class", "./module-info.class"] {
+			let class_file_raw = fs::read(path).expect("Failed to read class file");
+			let class_file = class_file::parse_class_file_ref(&class_file_raw).expect("Failed to parse class file");
+			assert_eq!(class_file.into_owned().to_bytes().expect("Failed to write class file"), class_file_raw, "{path} did not round-trip");
+		}
+
+		let class_file_raw = fs::read(SAMPLE_CLASS_FILE_PATH).expect("Failed to read class file");
+		let class_file = class_file::parse_class_file_ref(&class_file_raw).expect("Failed to parse class file");
+		assert!(matches!(class_file.this_class_name().unwrap(), Cow::Borrowed("Sample")));
+		assert_eq!(class_file.super_class_name().unwrap().as_deref(), Some("java/lang/Object"));
+		let constant_pool = &class_file.constant_pool;
+		let classify = class_file.methods.iter().find(|method| method.name(constant_pool).unwrap() == "classify").expect("Missing method 'classify'");
+		let code = attribute_info::find_attribute_ref(&classify.attributes, constant_pool, "Code").unwrap().expect("Missing Code");
+		let start = code.info.as_ptr() as usize - class_file_raw.as_ptr() as usize;
+		assert_eq!(&class_file_raw[start..start + code.info.len()], code.info);
+		assert_eq!(code.parse("Code", attribute_info::code::code_attribute_parser).unwrap().max_stack, 1);
+
+		let error = class_file::parse_class_file_ref(&class_file_raw[..0x20]).expect_err("Parsed truncated class file");
+		assert_eq!(error.kind(), &ClassFormatErrorKind::UnexpectedEof);
+	}
+}
@@ -1,6 +1,8 @@
-use nom::{error::ParseError, multi::length_count, number::complete::be_u16, IResult};
+use std::{borrow::Cow, io::{self, Write}};
 
-use crate::{attribute_info::{attribute_info_parser, AttributeInfo}, U2};
+use nom::{error::context, number::complete::be_u16, IResult};
+
+use crate::{access_flags::MethodAccess, attribute_info::{annotations::{self, annotation_default_attribute_parser, Annotation, ElementValue, TypeAnnotation, Visibility}, attribute_info_parser, attribute_info_ref_parser, code::{code_attribute_parser, CodeAttribute}, exceptions::exceptions_attribute_parser, find_attribute, method_parameters::{method_parameters_attribute_parser, MethodParameter}, signature, stack_map_table::{initial_locals, Frame}, write_attributes, AttributeInfo, AttributeInfoRef}, class_file::ClassFile, class_format_error::{indexed_length_count, ClassFormatError, ClassParseError}, constant_pool::{ConstantPool, ConstantPoolError, ConstantPoolRef}, descriptor::{parse_method_descriptor, MethodDescriptor}, signature::{parse_method_signature, MethodSignature}, writer::write_u2, U2};
 
 #[derive(Debug)]
 pub struct MethodInfo {
@@ -11,12 +13,126 @@ pub struct MethodInfo {
 	pub attributes: Vec<AttributeInfo>,
 }
 
-pub fn method_info_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &'a[u8]) -> IResult<&[u8], MethodInfo> {
-	let (input, access_flags) = be_u16::<&[u8], E>(input).expect("Failed to read 'access_flags'");
-	let (input, name_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'name_index'");
-	let (input, descriptor_index) = be_u16::<&[u8], E>(input).expect("Failed to read 'descriptor_index'");
+impl MethodInfo {
+	pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		write_u2(writer, self.access_flags)?;
+		write_u2(writer, self.name_index)?;
+		write_u2(writer, self.descriptor_index)?;
+		write_attributes(writer, &self.attributes)
+	}
+
+	pub fn access(&self) -> MethodAccess {
+		MethodAccess(self.access_flags)
+	}
+
+	/// Decodes the `Code` attribute, which abstract and native methods do not have.
+	pub fn code(&self, constant_pool: &ConstantPool) -> Result<Option<CodeAttribute>, ClassFormatError> {
+		match find_attribute(&self.attributes, constant_pool, "Code")? {
+			Some(attribute) => Ok(Some(attribute.parse("Code", code_attribute_parser)?)),
+			None => Ok(None),
+		}
+	}
+
+	pub fn descriptor(&self, constant_pool: &ConstantPool) -> Result<MethodDescriptor, ClassFormatError> {
+		Ok(parse_method_descriptor(&constant_pool.get_utf8_str(self.descriptor_index)?)?)
+	}
+
+	/// Checked exceptions from the `throws` clause in internal form.
+	pub fn exceptions(&self, constant_pool: &ConstantPool) -> Result<Vec<String>, ClassFormatError> {
+		match find_attribute(&self.attributes, constant_pool, "Exceptions")? {
+			Some(attribute) => Ok(attribute.parse("Exceptions", exceptions_attribute_parser)?.resolve(constant_pool)?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Names and flags of the formal parameters, empty if the method has no `MethodParameters` attribute.
+	pub fn parameters(&self, constant_pool: &ConstantPool) -> Result<Vec<MethodParameter>, ClassFormatError> {
+		match find_attribute(&self.attributes, constant_pool, "MethodParameters")? {
+			Some(attribute) => Ok(attribute.parse("MethodParameters", method_parameters_attribute_parser)?.resolve(constant_pool)?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Generic signature, which only methods with type parameters or generic parameter, return or thrown types have.
+	pub fn signature(&self, constant_pool: &ConstantPool) -> Result<Option<MethodSignature>, ClassFormatError> {
+		match signature::signature(&self.attributes, constant_pool)? {
+			Some(signature) => Ok(Some(parse_method_signature(&signature)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Absolute frames for every entry of the `StackMapTable`, which is empty if the method has none.
+	///
+	/// The implicit frame at method entry is not included.
+	pub fn stack_map_frames(&self, class_file: &ClassFile) -> Result<Vec<Frame>, ClassFormatError> {
+		let constant_pool = &class_file.constant_pool;
+		let Some(stack_map_table) = self.code(constant_pool)?.map(|code| code.stack_map_table(constant_pool)).transpose()?.flatten() else {
+			return Ok(Vec::new());
+		};
+		let class_name = constant_pool.get_class_name(class_file.this_class)?;
+		let method_name = constant_pool.get_utf8_str(self.name_index)?;
+		let locals = initial_locals(&class_name, &method_name, self.access().contains(MethodAccess::STATIC), &self.descriptor(constant_pool)?);
+		stack_map_table.expand(constant_pool, &locals)
+	}
+
+	pub fn annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<Annotation>, ClassFormatError> {
+		annotations::annotations(&self.attributes, constant_pool, visibility)
+	}
+
+	pub fn parameter_annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<Vec<Annotation>>, ClassFormatError> {
+		annotations::parameter_annotations(&self.attributes, constant_pool, visibility)
+	}
+
+	/// Type annotations on the method signature, those within the code are attached to the `Code` attribute.
+	pub fn type_annotations(&self, constant_pool: &ConstantPool, visibility: Visibility) -> Result<Vec<TypeAnnotation>, ClassFormatError> {
+		annotations::type_annotations(&self.attributes, constant_pool, visibility)
+	}
+
+	/// Default value of an annotation interface element.
+	pub fn annotation_default(&self, constant_pool: &ConstantPool) -> Result<Option<ElementValue>, ClassFormatError> {
+		match find_attribute(&self.attributes, constant_pool, "AnnotationDefault")? {
+			Some(attribute) => Ok(Some(attribute.parse("AnnotationDefault", annotation_default_attribute_parser)?.default_value.resolve(constant_pool)?)),
+			None => Ok(None),
+		}
+	}
+}
+
+/// `MethodInfo` with attributes borrowed from the class file.
+#[derive(Debug, Clone)]
+pub struct MethodInfoRef<'a> {
+	pub access_flags: U2,
+	pub name_index: U2,
+	pub descriptor_index: U2,
+	pub attributes_count: U2,
+	pub attributes: Vec<AttributeInfoRef<'a>>,
+}
+
+impl<'a> MethodInfoRef<'a> {
+	pub fn access(&self) -> MethodAccess {
+		MethodAccess(self.access_flags)
+	}
+
+	pub fn name(&self, constant_pool: &ConstantPoolRef<'a>) -> Result<Cow<'a, str>, ConstantPoolError> {
+		constant_pool.get_utf8_str(self.name_index)
+	}
+
+	pub fn into_owned(self) -> MethodInfo {
+		MethodInfo {
+			access_flags: self.access_flags,
+			name_index: self.name_index,
+			descriptor_index: self.descriptor_index,
+			attributes_count: self.attributes_count,
+			attributes: self.attributes.into_iter().map(AttributeInfoRef::into_owned).collect(),
+		}
+	}
+}
+
+pub fn method_info_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], MethodInfo, E> {
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
 	
-	let (input, attributes) = length_count(be_u16, attribute_info_parser::<'a, E>)(input).expect("Failed to read 'attributes_count' or 'attributes'");
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_parser::<'a, E>))(input)?;
 	let attributes_count = attributes.len() as U2;
 
 	Ok((input, MethodInfo {
@@ -26,4 +142,21 @@ pub fn method_info_parser<'a, E: ParseError<&'a[u8]> + std::fmt::Debug>(input: &
 		attributes_count,
 		attributes,
 	}))
+}
+
+pub fn method_info_ref_parser<'a, E: ClassParseError<&'a [u8]>>(input: &'a [u8]) -> IResult<&'a [u8], MethodInfoRef<'a>, E> {
+	let (input, access_flags) = context("access_flags", be_u16)(input)?;
+	let (input, name_index) = context("name_index", be_u16)(input)?;
+	let (input, descriptor_index) = context("descriptor_index", be_u16)(input)?;
+	
+	let (input, attributes) = context("attributes", indexed_length_count(be_u16, attribute_info_ref_parser::<'a, E>))(input)?;
+	let attributes_count = attributes.len() as U2;
+
+	Ok((input, MethodInfoRef {
+		access_flags,
+		name_index,
+		descriptor_index,
+		attributes_count,
+		attributes,
+	}))
 }
\ No newline at end of file
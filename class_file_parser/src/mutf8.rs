@@ -0,0 +1,221 @@
+use std::{borrow::Cow, fmt::Display, string::FromUtf16Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MUtf8ErrorKind {
+	NulByte,
+	InvalidLeadByte(u8),
+	InvalidContinuationByte(u8),
+	Truncated,
+	UnpairedSurrogate(u16),
+}
+
+/// Malformed modified UTF-8, with `position` being the offset of the offending byte sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MUtf8Error {
+	pub position: usize,
+	pub kind: MUtf8ErrorKind,
+}
+
+impl Display for MUtf8Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.kind {
+			MUtf8ErrorKind::NulByte => write!(f, "NUL byte at position {} in modified UTF-8", self.position),
+			MUtf8ErrorKind::InvalidLeadByte(b) => write!(f, "invalid lead byte 0x{b:02X} at position {} in modified UTF-8", self.position),
+			MUtf8ErrorKind::InvalidContinuationByte(b) => write!(f, "invalid continuation byte 0x{b:02X} at position {} in modified UTF-8", self.position),
+			MUtf8ErrorKind::Truncated => write!(f, "truncated sequence at position {} in modified UTF-8", self.position),
+			MUtf8ErrorKind::UnpairedSurrogate(unit) => write!(f, "unpaired surrogate 0x{unit:04X} at position {} cannot be represented as a Rust string", self.position),
+		}
+	}
+}
+
+impl std::error::Error for MUtf8Error {}
+
+/// Java string as a sequence of UTF-16 code units.
+///
+/// Unlike a Rust `String` this can hold unpaired surrogates, which are legal in Java strings and class files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JavaString {
+	units: Vec<u16>,
+}
+
+impl JavaString {
+	pub fn from_utf16(units: Vec<u16>) -> Self {
+		Self {
+			units,
+		}
+	}
+
+	pub fn as_utf16(&self) -> &[u16] {
+		&self.units
+	}
+
+	pub fn len(&self) -> usize {
+		self.units.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.units.is_empty()
+	}
+
+	pub fn try_to_string(&self) -> Result<String, FromUtf16Error> {
+		String::from_utf16(&self.units)
+	}
+
+	/// Converts to a Rust string, replacing unpaired surrogates with U+FFFD.
+	pub fn to_string_lossy(&self) -> String {
+		String::from_utf16_lossy(&self.units)
+	}
+}
+
+impl From<&str> for JavaString {
+	fn from(value: &str) -> Self {
+		Self::from_utf16(value.encode_utf16().collect())
+	}
+}
+
+impl Display for JavaString {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.to_string_lossy())
+	}
+}
+
+/// Reads the next code unit from `bytes` at `position`, returning it and the length of its encoding.
+fn decode_unit(bytes: &[u8], position: usize) -> Result<(u16, usize), MUtf8Error> {
+	let error = |kind| MUtf8Error {
+		position,
+		kind,
+	};
+	let continuation = |offset: usize| -> Result<u16, MUtf8Error> {
+		match bytes.get(position + offset) {
+			Some(b) if b & 0xC0 == 0x80 => Ok((b & 0x3F) as u16),
+			Some(b) => Err(error(MUtf8ErrorKind::InvalidContinuationByte(*b))),
+			None => Err(error(MUtf8ErrorKind::Truncated)),
+		}
+	};
+
+	match bytes[position] {
+		0x00 => Err(error(MUtf8ErrorKind::NulByte)),
+		b @ 0x01..=0x7F => Ok((b as u16, 1)),
+		b @ 0xC0..=0xDF => Ok((((b & 0x1F) as u16) << 6 | continuation(1)?, 2)),
+		b @ 0xE0..=0xEF => Ok((((b & 0x0F) as u16) << 12 | continuation(1)? << 6 | continuation(2)?, 3)),
+		b => Err(error(MUtf8ErrorKind::InvalidLeadByte(b))),
+	}
+}
+
+/// Checks that `bytes` is well-formed modified UTF-8 without building the decoded string.
+pub fn validate(bytes: &[u8]) -> Result<(), MUtf8Error> {
+	let mut position = 0;
+	while position < bytes.len() {
+		let (_, length) = decode_unit(bytes, position)?;
+		position += length;
+	}
+	Ok(())
+}
+
+/// Decodes modified UTF-8 (JVMS 4.4.7) losslessly into UTF-16 code units.
+pub fn decode(bytes: &[u8]) -> Result<JavaString, MUtf8Error> {
+	let mut units = Vec::with_capacity(bytes.len());
+	let mut position = 0;
+	while position < bytes.len() {
+		let (unit, length) = decode_unit(bytes, position)?;
+		units.push(unit);
+		position += length;
+	}
+	Ok(JavaString::from_utf16(units))
+}
+
+/// Decodes modified UTF-8 into a Rust string, borrowing `bytes` if they are also valid standard UTF-8.
+///
+/// Fails for unpaired surrogates, as they have no representation in a Rust string.
+pub fn decode_to_str(bytes: &[u8]) -> Result<Cow<'_, str>, MUtf8Error> {
+	// Standard UTF-8 rejects overlong NULs and encoded surrogates, so only 4 byte sequences and raw NULs need special care.
+	if !bytes.iter().any(|b| *b == 0x00 || *b >= 0xF0) {
+		if let Ok(value) = std::str::from_utf8(bytes) {
+			return Ok(Cow::Borrowed(value));
+		}
+	}
+
+	let mut result = String::with_capacity(bytes.len());
+	let mut position = 0;
+	while position < bytes.len() {
+		let (unit, length) = decode_unit(bytes, position)?;
+		if (0xD800..0xDC00).contains(&unit) && position + length < bytes.len() {
+			let (low, low_length) = decode_unit(bytes, position + length)?;
+			if (0xDC00..0xE000).contains(&low) {
+				let code_point = 0x10000 + (((unit - 0xD800) as u32) << 10 | (low - 0xDC00) as u32);
+				result.push(char::from_u32(code_point).expect("Surrogate pair is always a valid code point"));
+				position += length + low_length;
+				continue;
+			}
+		}
+		match char::from_u32(unit as u32) {
+			Some(c) => result.push(c),
+			None => return Err(MUtf8Error {
+				position,
+				kind: MUtf8ErrorKind::UnpairedSurrogate(unit),
+			}),
+		}
+		position += length;
+	}
+	Ok(Cow::Owned(result))
+}
+
+/// Encodes UTF-16 code units as modified UTF-8.
+pub fn encode(units: &[u16]) -> Vec<u8> {
+	let mut bytes = Vec::with_capacity(units.len());
+	for unit in units {
+		match *unit {
+			0x0001..=0x007F => bytes.push(*unit as u8),
+			0x0000 | 0x0080..=0x07FF => {
+				bytes.push(0xC0 | (unit >> 6) as u8);
+				bytes.push(0x80 | (unit & 0x3F) as u8);
+			},
+			_ => {
+				bytes.push(0xE0 | (unit >> 12) as u8);
+				bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+				bytes.push(0x80 | (unit & 0x3F) as u8);
+			},
+		}
+	}
+	bytes
+}
+
+pub fn encode_str(value: &str) -> Vec<u8> {
+	encode(&value.encode_utf16().collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nul_and_supplementary_characters_round_trip() {
+		let value = "a\0\u{e9}\u{20ac}\u{1f600}";
+		let bytes = encode_str(value);
+		assert_eq!(bytes, [0x61, 0xC0, 0x80, 0xC3, 0xA9, 0xE2, 0x82, 0xAC, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+		assert_eq!(decode_to_str(&bytes).unwrap(), value);
+		assert_eq!(decode(&bytes).unwrap(), JavaString::from(value));
+	}
+
+	#[test]
+	fn ascii_is_borrowed() {
+		assert!(matches!(decode_to_str(b"java/lang/Object"), Ok(Cow::Borrowed("java/lang/Object"))));
+	}
+
+	#[test]
+	fn unpaired_surrogates_are_kept() {
+		let bytes = [0x61, 0xED, 0xA0, 0xBD];
+		let value = decode(&bytes).unwrap();
+		assert_eq!(value.as_utf16(), [0x61, 0xD83D]);
+		assert_eq!(encode(value.as_utf16()), bytes);
+		assert_eq!(decode_to_str(&bytes).unwrap_err(), MUtf8Error { position: 1, kind: MUtf8ErrorKind::UnpairedSurrogate(0xD83D) });
+	}
+
+	#[test]
+	fn malformed_sequences_are_rejected() {
+		assert_eq!(validate(&[0x61, 0x00]).unwrap_err(), MUtf8Error { position: 1, kind: MUtf8ErrorKind::NulByte });
+		assert_eq!(validate(&[0xF0, 0x9F, 0x98, 0x80]).unwrap_err(), MUtf8Error { position: 0, kind: MUtf8ErrorKind::InvalidLeadByte(0xF0) });
+		assert_eq!(validate(&[0x61, 0xE2, 0x82]).unwrap_err(), MUtf8Error { position: 1, kind: MUtf8ErrorKind::Truncated });
+		assert_eq!(validate(&[0xC3, 0x41]).unwrap_err(), MUtf8Error { position: 0, kind: MUtf8ErrorKind::InvalidContinuationByte(0x41) });
+	}
+}
@@ -0,0 +1,664 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::descriptor::BaseType;
+
+/// Deepest nesting of type arguments a signature may have, which keeps parsing and dropping it off the end of the stack.
+///
+/// Array dimensions are limited to 255 on their own, as in descriptors (JVMS 4.3.2).
+const MAX_NESTING_DEPTH: usize = 255;
+
+/// Type in a signature that may also be primitive (JVMS 4.7.9.1 `JavaTypeSignature`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JavaTypeSignature {
+	Base(BaseType),
+	Reference(ReferenceTypeSignature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReferenceTypeSignature {
+	Class(ClassTypeSignature),
+	/// Type variable by name, e.g. `T` for `TT;`.
+	TypeVariable(String),
+	Array(Box<JavaTypeSignature>),
+}
+
+/// Class name with its type arguments, e.g. `List<TT;>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimpleClassTypeSignature {
+	pub name: String,
+	pub type_arguments: Vec<TypeArgument>,
+}
+
+/// Possibly parameterized class type, e.g. `Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassTypeSignature {
+	/// Package in internal form without a trailing `/`, empty for the unnamed package.
+	pub package: String,
+	pub class: SimpleClassTypeSignature,
+	/// Inner classes, each nested in the previous one.
+	pub suffixes: Vec<SimpleClassTypeSignature>,
+}
+
+impl ClassTypeSignature {
+	/// Erased class name in internal form, e.g. `java/util/Map$Entry`.
+	pub fn class_name(&self) -> String {
+		let mut class_name = String::new();
+		if !self.package.is_empty() {
+			class_name.push_str(&self.package);
+			class_name.push('/');
+		}
+		class_name.push_str(&self.class.name);
+		for suffix in &self.suffixes {
+			class_name.push('$');
+			class_name.push_str(&suffix.name);
+		}
+		class_name
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeArgument {
+	/// `*`
+	Any,
+	Exact(ReferenceTypeSignature),
+	/// `+`, i.e. `? extends`
+	Extends(ReferenceTypeSignature),
+	/// `-`, i.e. `? super`
+	Super(ReferenceTypeSignature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParameter {
+	pub name: String,
+	/// Bound after the first `:`, which is left out if the first bound is an interface.
+	pub class_bound: Option<ReferenceTypeSignature>,
+	pub interface_bounds: Vec<ReferenceTypeSignature>,
+}
+
+/// Generic signature of a class or interface (JVMS 4.7.9.1 `ClassSignature`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassSignature {
+	pub type_parameters: Vec<TypeParameter>,
+	pub superclass: ClassTypeSignature,
+	pub superinterfaces: Vec<ClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReturnSignature {
+	Void,
+	Type(JavaTypeSignature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ThrowsSignature {
+	Class(ClassTypeSignature),
+	TypeVariable(String),
+}
+
+/// Generic signature of a method (JVMS 4.7.9.1 `MethodSignature`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodSignature {
+	pub type_parameters: Vec<TypeParameter>,
+	pub parameters: Vec<JavaTypeSignature>,
+	pub return_type: ReturnSignature,
+	pub throws: Vec<ThrowsSignature>,
+}
+
+impl Display for JavaTypeSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Base(base_type) => write!(f, "{}", base_type.as_char()),
+			Self::Reference(reference) => write!(f, "{reference}"),
+		}
+	}
+}
+
+impl Display for ReferenceTypeSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Class(class) => write!(f, "{class}"),
+			Self::TypeVariable(name) => write!(f, "T{name};"),
+			Self::Array(component) => write!(f, "[{component}"),
+		}
+	}
+}
+
+impl Display for SimpleClassTypeSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.name)?;
+		if !self.type_arguments.is_empty() {
+			write!(f, "<")?;
+			for type_argument in &self.type_arguments {
+				write!(f, "{type_argument}")?;
+			}
+			write!(f, ">")?;
+		}
+		Ok(())
+	}
+}
+
+impl Display for ClassTypeSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "L")?;
+		if !self.package.is_empty() {
+			write!(f, "{}/", self.package)?;
+		}
+		write!(f, "{}", self.class)?;
+		for suffix in &self.suffixes {
+			write!(f, ".{suffix}")?;
+		}
+		write!(f, ";")
+	}
+}
+
+impl Display for TypeArgument {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Any => write!(f, "*"),
+			Self::Exact(reference) => write!(f, "{reference}"),
+			Self::Extends(reference) => write!(f, "+{reference}"),
+			Self::Super(reference) => write!(f, "-{reference}"),
+		}
+	}
+}
+
+fn write_type_parameters(f: &mut std::fmt::Formatter<'_>, type_parameters: &[TypeParameter]) -> std::fmt::Result {
+	if type_parameters.is_empty() {
+		return Ok(());
+	}
+	write!(f, "<")?;
+	for type_parameter in type_parameters {
+		write!(f, "{}:", type_parameter.name)?;
+		if let Some(class_bound) = &type_parameter.class_bound {
+			write!(f, "{class_bound}")?;
+		}
+		for interface_bound in &type_parameter.interface_bounds {
+			write!(f, ":{interface_bound}")?;
+		}
+	}
+	write!(f, ">")
+}
+
+impl Display for ClassSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write_type_parameters(f, &self.type_parameters)?;
+		write!(f, "{}", self.superclass)?;
+		for superinterface in &self.superinterfaces {
+			write!(f, "{superinterface}")?;
+		}
+		Ok(())
+	}
+}
+
+impl Display for ReturnSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Void => write!(f, "V"),
+			Self::Type(java_type) => write!(f, "{java_type}"),
+		}
+	}
+}
+
+impl Display for ThrowsSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Class(class) => write!(f, "^{class}"),
+			Self::TypeVariable(name) => write!(f, "^T{name};"),
+		}
+	}
+}
+
+impl Display for MethodSignature {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write_type_parameters(f, &self.type_parameters)?;
+		write!(f, "(")?;
+		for parameter in &self.parameters {
+			write!(f, "{parameter}")?;
+		}
+		write!(f, "){}", self.return_type)?;
+		for throws in &self.throws {
+			write!(f, "{throws}")?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for ClassSignature {
+	type Err = SignatureError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_class_signature(s)
+	}
+}
+
+impl FromStr for MethodSignature {
+	type Err = SignatureError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_method_signature(s)
+	}
+}
+
+impl FromStr for ReferenceTypeSignature {
+	type Err = SignatureError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_field_signature(s)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureErrorKind {
+	UnexpectedEnd,
+	UnexpectedCharacter(char),
+	EmptyIdentifier,
+	EmptyTypeArguments,
+	EmptyTypeParameters,
+	TrailingCharacters,
+	/// An array type has more than 255 dimensions.
+	TooManyDimensions,
+	NestingTooDeep,
+}
+
+/// Malformed signature, with `position` being the byte offset of the problem within `signature`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureError {
+	pub signature: String,
+	pub position: usize,
+	pub kind: SignatureErrorKind,
+}
+
+impl Display for SignatureError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "invalid signature '{}' at position {}: ", self.signature, self.position)?;
+		match &self.kind {
+			SignatureErrorKind::UnexpectedEnd => write!(f, "unexpected end"),
+			SignatureErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{c}'"),
+			SignatureErrorKind::EmptyIdentifier => write!(f, "empty identifier"),
+			SignatureErrorKind::EmptyTypeArguments => write!(f, "empty type argument list"),
+			SignatureErrorKind::EmptyTypeParameters => write!(f, "empty type parameter list"),
+			SignatureErrorKind::TrailingCharacters => write!(f, "trailing characters"),
+			SignatureErrorKind::TooManyDimensions => write!(f, "array type has more than 255 dimensions"),
+			SignatureErrorKind::NestingTooDeep => write!(f, "types are nested more than {MAX_NESTING_DEPTH} levels deep"),
+		}
+	}
+}
+
+impl std::error::Error for SignatureError {}
+
+struct SignatureParser<'a> {
+	signature: &'a str,
+	position: usize,
+	/// Array dimensions and type argument lists around the type being parsed.
+	depth: usize,
+}
+
+impl SignatureParser<'_> {
+	fn error(&self, position: usize, kind: SignatureErrorKind) -> SignatureError {
+		SignatureError {
+			signature: self.signature.to_string(),
+			position,
+			kind,
+		}
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.signature[self.position..].chars().next()
+	}
+
+	fn next(&mut self) -> Result<char, SignatureError> {
+		let c = self.peek().ok_or(self.error(self.position, SignatureErrorKind::UnexpectedEnd))?;
+		self.position += c.len_utf8();
+		Ok(c)
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), SignatureError> {
+		let position = self.position;
+		match self.next()? {
+			c if c == expected => Ok(()),
+			c => Err(self.error(position, SignatureErrorKind::UnexpectedCharacter(c))),
+		}
+	}
+
+	fn enter(&mut self) -> Result<(), SignatureError> {
+		self.depth += 1;
+		if self.depth > MAX_NESTING_DEPTH {
+			return Err(self.error(self.position, SignatureErrorKind::NestingTooDeep));
+		}
+		Ok(())
+	}
+
+	fn identifier(&mut self) -> Result<String, SignatureError> {
+		let start = self.position;
+		let length = self.signature[start..].find(['.', ';', '[', '/', '<', '>', ':']).unwrap_or(self.signature.len() - start);
+		if length == 0 {
+			return match self.peek() {
+				Some(_) => Err(self.error(start, SignatureErrorKind::EmptyIdentifier)),
+				None => Err(self.error(start, SignatureErrorKind::UnexpectedEnd)),
+			};
+		}
+		self.position += length;
+		Ok(self.signature[start..self.position].to_string())
+	}
+
+	fn type_arguments(&mut self) -> Result<Vec<TypeArgument>, SignatureError> {
+		let mut type_arguments = Vec::new();
+		if self.peek() != Some('<') {
+			return Ok(type_arguments);
+		}
+		self.position += 1;
+		self.enter()?;
+		while self.peek() != Some('>') {
+			let type_argument = match self.peek() {
+				Some('*') => {
+					self.position += 1;
+					TypeArgument::Any
+				},
+				Some('+') => {
+					self.position += 1;
+					TypeArgument::Extends(self.reference_type()?)
+				},
+				Some('-') => {
+					self.position += 1;
+					TypeArgument::Super(self.reference_type()?)
+				},
+				_ => TypeArgument::Exact(self.reference_type()?),
+			};
+			type_arguments.push(type_argument);
+		}
+		if type_arguments.is_empty() {
+			return Err(self.error(self.position, SignatureErrorKind::EmptyTypeArguments));
+		}
+		self.position += 1;
+		self.depth -= 1;
+		Ok(type_arguments)
+	}
+
+	fn class_type(&mut self) -> Result<ClassTypeSignature, SignatureError> {
+		self.expect('L')?;
+		let mut package = String::new();
+		let mut name = self.identifier()?;
+		while self.peek() == Some('/') {
+			self.position += 1;
+			if !package.is_empty() {
+				package.push('/');
+			}
+			package.push_str(&name);
+			name = self.identifier()?;
+		}
+		let class = SimpleClassTypeSignature {
+			name,
+			type_arguments: self.type_arguments()?,
+		};
+
+		let mut suffixes = Vec::new();
+		while self.peek() == Some('.') {
+			self.position += 1;
+			suffixes.push(SimpleClassTypeSignature {
+				name: self.identifier()?,
+				type_arguments: self.type_arguments()?,
+			});
+		}
+		self.expect(';')?;
+
+		Ok(ClassTypeSignature {
+			package,
+			class,
+			suffixes,
+		})
+	}
+
+	fn type_variable(&mut self) -> Result<String, SignatureError> {
+		self.expect('T')?;
+		let name = self.identifier()?;
+		self.expect(';')?;
+		Ok(name)
+	}
+
+	fn reference_type(&mut self) -> Result<ReferenceTypeSignature, SignatureError> {
+		match self.peek() {
+			Some('L') => Ok(ReferenceTypeSignature::Class(self.class_type()?)),
+			Some('T') => Ok(ReferenceTypeSignature::TypeVariable(self.type_variable()?)),
+			Some('[') => {
+				let start = self.position;
+				let mut dimensions = 0usize;
+				while self.peek() == Some('[') {
+					self.position += 1;
+					dimensions += 1;
+				}
+				if dimensions > u8::MAX as usize {
+					return Err(self.error(start, SignatureErrorKind::TooManyDimensions));
+				}
+				let mut array = ReferenceTypeSignature::Array(Box::new(self.java_type()?));
+				for _ in 1..dimensions {
+					array = ReferenceTypeSignature::Array(Box::new(JavaTypeSignature::Reference(array)));
+				}
+				Ok(array)
+			},
+			Some(c) => Err(self.error(self.position, SignatureErrorKind::UnexpectedCharacter(c))),
+			None => Err(self.error(self.position, SignatureErrorKind::UnexpectedEnd)),
+		}
+	}
+
+	fn java_type(&mut self) -> Result<JavaTypeSignature, SignatureError> {
+		match self.peek().and_then(BaseType::from_char) {
+			Some(base_type) => {
+				self.position += 1;
+				Ok(JavaTypeSignature::Base(base_type))
+			},
+			None => Ok(JavaTypeSignature::Reference(self.reference_type()?)),
+		}
+	}
+
+	fn type_parameters(&mut self) -> Result<Vec<TypeParameter>, SignatureError> {
+		let mut type_parameters = Vec::new();
+		if self.peek() != Some('<') {
+			return Ok(type_parameters);
+		}
+		self.position += 1;
+		self.enter()?;
+		while self.peek() != Some('>') {
+			let name = self.identifier()?;
+			self.expect(':')?;
+			let class_bound = match self.peek() {
+				Some(':') => None,
+				_ => Some(self.reference_type()?),
+			};
+			let mut interface_bounds = Vec::new();
+			while self.peek() == Some(':') {
+				self.position += 1;
+				interface_bounds.push(self.reference_type()?);
+			}
+			type_parameters.push(TypeParameter {
+				name,
+				class_bound,
+				interface_bounds,
+			});
+		}
+		if type_parameters.is_empty() {
+			return Err(self.error(self.position, SignatureErrorKind::EmptyTypeParameters));
+		}
+		self.position += 1;
+		self.depth -= 1;
+		Ok(type_parameters)
+	}
+
+	fn class_signature(&mut self) -> Result<ClassSignature, SignatureError> {
+		let type_parameters = self.type_parameters()?;
+		let superclass = self.class_type()?;
+		let mut superinterfaces = Vec::new();
+		while self.peek().is_some() {
+			superinterfaces.push(self.class_type()?);
+		}
+		Ok(ClassSignature {
+			type_parameters,
+			superclass,
+			superinterfaces,
+		})
+	}
+
+	fn method_signature(&mut self) -> Result<MethodSignature, SignatureError> {
+		let type_parameters = self.type_parameters()?;
+		self.expect('(')?;
+		let mut parameters = Vec::new();
+		while self.peek() != Some(')') {
+			parameters.push(self.java_type()?);
+		}
+		self.expect(')')?;
+
+		let return_type = if self.peek() == Some('V') {
+			self.position += 1;
+			ReturnSignature::Void
+		} else {
+			ReturnSignature::Type(self.java_type()?)
+		};
+
+		let mut throws = Vec::new();
+		while self.peek() == Some('^') {
+			self.position += 1;
+			throws.push(match self.peek() {
+				Some('T') => ThrowsSignature::TypeVariable(self.type_variable()?),
+				_ => ThrowsSignature::Class(self.class_type()?),
+			});
+		}
+
+		Ok(MethodSignature {
+			type_parameters,
+			parameters,
+			return_type,
+			throws,
+		})
+	}
+
+	fn end(&self) -> Result<(), SignatureError> {
+		if self.position < self.signature.len() {
+			return Err(self.error(self.position, SignatureErrorKind::TrailingCharacters));
+		}
+		Ok(())
+	}
+}
+
+pub fn parse_class_signature(signature: &str) -> Result<ClassSignature, SignatureError> {
+	let mut parser = SignatureParser {
+		signature,
+		position: 0,
+		depth: 0,
+	};
+	let class_signature = parser.class_signature()?;
+	parser.end()?;
+	Ok(class_signature)
+}
+
+pub fn parse_method_signature(signature: &str) -> Result<MethodSignature, SignatureError> {
+	let mut parser = SignatureParser {
+		signature,
+		position: 0,
+		depth: 0,
+	};
+	let method_signature = parser.method_signature()?;
+	parser.end()?;
+	Ok(method_signature)
+}
+
+/// Parses the signature of a field, record component or local variable, which is always a reference type.
+pub fn parse_field_signature(signature: &str) -> Result<ReferenceTypeSignature, SignatureError> {
+	let mut parser = SignatureParser {
+		signature,
+		position: 0,
+		depth: 0,
+	};
+	let field_signature = parser.reference_type()?;
+	parser.end()?;
+	Ok(field_signature)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn class(package: &str, name: &str, type_arguments: Vec<TypeArgument>) -> ClassTypeSignature {
+		ClassTypeSignature {
+			package: package.to_string(),
+			class: SimpleClassTypeSignature { name: name.to_string(), type_arguments },
+			suffixes: Vec::new(),
+		}
+	}
+
+	fn type_variable(name: &str) -> ReferenceTypeSignature {
+		ReferenceTypeSignature::TypeVariable(name.to_string())
+	}
+
+	#[test]
+	fn parse_class_signature_with_bounds() {
+		let source = "<K::Ljava/lang/Comparable<-TK;>;V:Ljava/lang/Object;>Ljava/util/AbstractMap<TK;TV;>;Ljava/io/Serializable;";
+		let signature = parse_class_signature(source).expect("Failed to parse signature");
+		assert_eq!(signature.type_parameters, [
+			TypeParameter {
+				name: "K".to_string(),
+				class_bound: None,
+				interface_bounds: vec![ReferenceTypeSignature::Class(class("java/lang", "Comparable", vec![TypeArgument::Super(type_variable("K"))]))],
+			},
+			TypeParameter {
+				name: "V".to_string(),
+				class_bound: Some(ReferenceTypeSignature::Class(class("java/lang", "Object", vec![]))),
+				interface_bounds: vec![],
+			},
+		]);
+		assert_eq!(signature.superclass, class("java/util", "AbstractMap", vec![TypeArgument::Exact(type_variable("K")), TypeArgument::Exact(type_variable("V"))]));
+		assert_eq!(signature.superinterfaces, [class("java/io", "Serializable", vec![])]);
+		assert_eq!(signature.to_string(), source);
+	}
+
+	#[test]
+	fn parse_method_signature_with_throws() {
+		let source = "<X:Ljava/lang/Throwable;>([TX;I*)Ljava/util/Map<TX;*>.Entry<+[I;Ljava/lang/String;>;^TX;^Ljava/io/IOException;";
+		assert_eq!(parse_method_signature(source).unwrap_err(), SignatureError {
+			signature: source.to_string(),
+			position: 31,
+			kind: SignatureErrorKind::UnexpectedCharacter('*'),
+		});
+
+		let source = "<X:Ljava/lang/Throwable;>([TX;I)Ljava/util/Map<TX;*>.Entry<+[ILjava/lang/String;>;^TX;^Ljava/io/IOException;";
+		let signature = parse_method_signature(source).expect("Failed to parse signature");
+		assert_eq!(signature.parameters, [
+			JavaTypeSignature::Reference(ReferenceTypeSignature::Array(Box::new(JavaTypeSignature::Reference(type_variable("X"))))),
+			JavaTypeSignature::Base(BaseType::Int),
+		]);
+		let ReturnSignature::Type(JavaTypeSignature::Reference(ReferenceTypeSignature::Class(return_type))) = &signature.return_type else {
+			panic!("Expected a class type, got: {:?}", signature.return_type);
+		};
+		assert_eq!(return_type.class_name(), "java/util/Map$Entry");
+		assert_eq!(return_type.class.type_arguments, [TypeArgument::Exact(type_variable("X")), TypeArgument::Any]);
+		assert_eq!(return_type.suffixes[0].type_arguments[0], TypeArgument::Extends(ReferenceTypeSignature::Array(Box::new(JavaTypeSignature::Base(BaseType::Int)))));
+		assert_eq!(signature.throws, [ThrowsSignature::TypeVariable("X".to_string()), ThrowsSignature::Class(class("java/io", "IOException", vec![]))]);
+		assert_eq!(signature.to_string(), source);
+	}
+
+	#[test]
+	fn reject_malformed_signatures() {
+		assert_eq!(parse_field_signature("Ljava/util/List<>;").unwrap_err().kind, SignatureErrorKind::EmptyTypeArguments);
+		assert_eq!(parse_field_signature("Ljava//List;").unwrap_err().kind, SignatureErrorKind::EmptyIdentifier);
+		assert_eq!(parse_field_signature("I").unwrap_err().kind, SignatureErrorKind::UnexpectedCharacter('I'));
+		assert_eq!(parse_field_signature("TT").unwrap_err().kind, SignatureErrorKind::UnexpectedEnd);
+		assert_eq!(parse_field_signature("TT;TT;").unwrap_err(), SignatureError { signature: "TT;TT;".to_string(), position: 3, kind: SignatureErrorKind::TrailingCharacters });
+		assert_eq!(parse_class_signature("<>Ljava/lang/Object;").unwrap_err().kind, SignatureErrorKind::EmptyTypeParameters);
+		assert_eq!(parse_method_signature("()V^I").unwrap_err().kind, SignatureErrorKind::UnexpectedCharacter('I'));
+	}
+
+	#[test]
+	fn reject_deeply_nested_signatures() {
+		let arrays = |dimensions: usize| format!("{}TT;", "[".repeat(dimensions));
+		assert_eq!(parse_field_signature(&arrays(255)).unwrap().to_string(), arrays(255));
+		assert_eq!(parse_field_signature(&arrays(60000)).unwrap_err().kind, SignatureErrorKind::TooManyDimensions);
+
+		let lists = |depth: usize, element: &str| format!("{}{element}{}", "Ljava/util/List<".repeat(depth), ">;".repeat(depth));
+		assert!(parse_field_signature(&lists(MAX_NESTING_DEPTH, "TT;")).is_ok());
+		assert_eq!(parse_field_signature(&lists(60000, "TT;")).unwrap_err().kind, SignatureErrorKind::NestingTooDeep);
+		// Dimensions are limited on their own, they do not add to the nesting of type arguments.
+		assert!(parse_field_signature(&lists(1, &arrays(255))).is_ok());
+		assert!(parse_method_signature(&format!("<T:Ljava/lang/Object;>({})V", arrays(255))).is_ok());
+		// Type parameters are left before the rest of the signature is parsed.
+		assert!(parse_class_signature(&format!("<T:Ljava/lang/Object;>{}", lists(MAX_NESTING_DEPTH, "TT;"))).is_ok());
+		assert!(parse_method_signature(&format!("<T:Ljava/lang/Object;>({})V", lists(MAX_NESTING_DEPTH, "TT;"))).is_ok());
+	}
+}
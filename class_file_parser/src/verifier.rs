@@ -0,0 +1,1340 @@
+use std::{cell::RefCell, collections::{hash_map::Entry, BTreeSet, HashMap, HashSet}, fmt::Display};
+
+use crate::{access_flags::MethodAccess, attribute_info::{code::CodeAttribute, stack_map_table::{initial_locals, Frame, VerificationType}}, class_file::ClassFile, class_format_error::ClassFormatError, constant_pool::{ConstantPool, ConstantPoolError}, cp_info::CPInfo, descriptor::{parse_field_descriptor, parse_method_descriptor, BaseType, FieldType, MethodDescriptor, ReturnDescriptor}, instruction::{DecodeError, Instruction, LookupSwitch, TableSwitch, WideInstruction, NEW}, method_info::MethodInfo, U1, U2};
+
+/// First class file version whose methods are verified by type checking against a `StackMapTable`.
+pub const TYPE_CHECKING_MAJOR_VERSION: U2 = 50;
+
+/// Class hierarchy the verifier uses to check assignability of class types.
+pub trait ClassHierarchy {
+	fn is_interface(&self, class_name: &str) -> bool;
+
+	/// Direct superclass of `class_name`, `None` for `java/lang/Object` and unknown classes.
+	fn super_class(&self, class_name: &str) -> Option<String>;
+
+	fn is_subclass_of(&self, class_name: &str, super_name: &str) -> bool {
+		let mut visited = HashSet::new();
+		let mut current = class_name.to_string();
+		loop {
+			if current == super_name {
+				return true;
+			}
+			if !visited.insert(current.clone()) {
+				return false;
+			}
+			match self.super_class(&current) {
+				Some(super_class) => current = super_class,
+				None => return false,
+			}
+		}
+	}
+
+	/// Most specific superclass of both classes `a` and `b`, the type of a value that is either when frames are computed.
+	///
+	/// Interfaces only have `java/lang/Object` in common with other types.
+	fn common_super_class(&self, a: &str, b: &str) -> String {
+		common_super_class(self, a, b)
+	}
+}
+
+/// Default of `ClassHierarchy::common_super_class`, which follows the superclass chains of `a` and `b`.
+pub(crate) fn common_super_class<H: ClassHierarchy + ?Sized>(hierarchy: &H, a: &str, b: &str) -> String {
+	if hierarchy.is_interface(a) || hierarchy.is_interface(b) {
+		return JAVA_LANG_OBJECT.to_string();
+	}
+	let mut super_classes = HashSet::new();
+	let mut current = Some(a.to_string());
+	while let Some(class_name) = current {
+		current = hierarchy.super_class(&class_name);
+		if !super_classes.insert(class_name) {
+			break;
+		}
+	}
+	let mut visited = HashSet::new();
+	let mut current = Some(b.to_string());
+	while let Some(class_name) = current {
+		if super_classes.contains(&class_name) {
+			return class_name;
+		}
+		current = hierarchy.super_class(&class_name);
+		if !visited.insert(class_name) {
+			break;
+		}
+	}
+	JAVA_LANG_OBJECT.to_string()
+}
+
+/// `ClassHierarchy` backed by explicitly registered classes.
+#[derive(Debug, Clone, Default)]
+pub struct ClassHierarchyMap {
+	classes: HashMap<String, (Option<String>, bool)>,
+}
+
+impl ClassHierarchyMap {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn insert(&mut self, class_name: &str, super_class: Option<&str>, is_interface: bool) {
+		self.classes.insert(class_name.to_string(), (super_class.map(str::to_string), is_interface));
+	}
+
+	pub fn insert_class_file(&mut self, class_file: &ClassFile) -> Result<(), ConstantPoolError> {
+		let constant_pool = &class_file.constant_pool;
+		let class_name = constant_pool.get_class_name(class_file.this_class)?;
+		let super_class = match class_file.super_class {
+			0 => None,
+			index => Some(constant_pool.get_class_name(index)?),
+		};
+		self.insert(&class_name, super_class.as_deref(), class_file.access().is_interface());
+		Ok(())
+	}
+}
+
+impl ClassHierarchy for ClassHierarchyMap {
+	fn is_interface(&self, class_name: &str) -> bool {
+		self.classes.get(class_name).is_some_and(|(_, is_interface)| *is_interface)
+	}
+
+	fn super_class(&self, class_name: &str) -> Option<String> {
+		self.classes.get(class_name).and_then(|(super_class, _)| super_class.clone())
+	}
+}
+
+const JAVA_LANG_OBJECT: &str = "java/lang/Object";
+const JAVA_LANG_THROWABLE: &str = "java/lang/Throwable";
+
+/// What the verifier required where it found `actual`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpectedType {
+	Type(VerificationType),
+	Reference,
+	Array,
+	Category1,
+	Uninitialized,
+}
+
+impl Display for ExpectedType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Type(verification_type) => write!(f, "{verification_type}"),
+			Self::Reference => write!(f, "reference"),
+			Self::Array => write!(f, "array"),
+			Self::Category1 => write!(f, "category 1 value"),
+			Self::Uninitialized => write!(f, "uninitialized object"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyErrorKind {
+	ClassFormat(Box<ClassFormatError>),
+	Decode(DecodeError),
+	UnsupportedVersion(U2),
+	UnsupportedInstruction(&'static str),
+	EmptyCode,
+	StackOverflow {
+		max_stack: U2,
+	},
+	StackUnderflow,
+	LocalOutOfBounds {
+		index: usize,
+		max_locals: U2,
+	},
+	TypeMismatch {
+		expected: ExpectedType,
+		actual: VerificationType,
+	},
+	LocalMismatch {
+		index: usize,
+		expected: ExpectedType,
+		actual: VerificationType,
+	},
+	StackHeightMismatch {
+		target: usize,
+		expected: usize,
+		actual: usize,
+	},
+	InvalidBranchTarget(i64),
+	MissingStackMapFrame(usize),
+	InvalidExceptionHandler(usize),
+	InvalidConstant(U2),
+	InvalidArrayType(U1),
+	InvalidInvokeinterfaceCount {
+		expected: usize,
+		actual: U1,
+	},
+	IllegalMethodInvocation(String),
+	InvalidReturn,
+	UninitializedThisOnReturn,
+	FallsOffEnd,
+	/// No path reaches the instruction, so no frame can be computed for it.
+	UnreachableCode,
+}
+
+impl Display for VerifyErrorKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ClassFormat(error) => write!(f, "{error}"),
+			Self::Decode(error) => write!(f, "{error}"),
+			Self::UnsupportedVersion(major_version) => write!(f, "class file version {major_version} is not verified by type checking"),
+			Self::UnsupportedInstruction(mnemonic) => write!(f, "'{mnemonic}' is not allowed in type checked code"),
+			Self::EmptyCode => write!(f, "code is empty"),
+			Self::StackOverflow { max_stack } => write!(f, "operand stack exceeds max_stack {max_stack}"),
+			Self::StackUnderflow => write!(f, "operand stack underflow"),
+			Self::LocalOutOfBounds { index, max_locals } => write!(f, "local variable {index} exceeds max_locals {max_locals}"),
+			Self::TypeMismatch { expected, actual } => write!(f, "expected {expected} on the operand stack, found {actual}"),
+			Self::LocalMismatch { index, expected, actual } => write!(f, "expected {expected} in local variable {index}, found {actual}"),
+			Self::StackHeightMismatch { target, expected, actual } => write!(f, "operand stack has {actual} entries, but the frame at {target} expects {expected}"),
+			Self::InvalidBranchTarget(target) => write!(f, "branch target {target} is not the start of an instruction"),
+			Self::MissingStackMapFrame(pc) => write!(f, "missing stack map frame at {pc}"),
+			Self::InvalidExceptionHandler(index) => write!(f, "invalid exception handler {index}"),
+			Self::InvalidConstant(index) => write!(f, "constant pool index {index} cannot be loaded by this instruction"),
+			Self::InvalidArrayType(atype) => write!(f, "invalid array type {atype}"),
+			Self::InvalidInvokeinterfaceCount { expected, actual } => write!(f, "invokeinterface count is {actual}, expected {expected}"),
+			Self::IllegalMethodInvocation(name) => write!(f, "method '{name}' cannot be invoked by this instruction"),
+			Self::InvalidReturn => write!(f, "return instruction does not match the method's return type"),
+			Self::UninitializedThisOnReturn => write!(f, "constructor returns before 'this' is initialized"),
+			Self::FallsOffEnd => write!(f, "execution falls off the end of the code"),
+			Self::UnreachableCode => write!(f, "instruction is unreachable"),
+		}
+	}
+}
+
+/// Reason why a method was rejected, with `pc` being the offending instruction if there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+	pub method: String,
+	pub pc: Option<usize>,
+	pub kind: VerifyErrorKind,
+}
+
+impl Display for VerifyError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.pc {
+			Some(pc) => write!(f, "VerifyError in {} at pc {pc}: {}", self.method, self.kind),
+			None => write!(f, "VerifyError in {}: {}", self.method, self.kind),
+		}
+	}
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Locals in slot form padded to `max_locals`, operand stack with one entry per value from bottom to top.
+#[derive(Debug, Clone)]
+struct State {
+	locals: Vec<VerificationType>,
+	stack: Vec<VerificationType>,
+}
+
+impl State {
+	fn stack_size(&self) -> usize {
+		self.stack.iter().map(size).sum()
+	}
+}
+
+fn size(verification_type: &VerificationType) -> usize {
+	if verification_type.is_wide() {
+		2
+	} else {
+		1
+	}
+}
+
+fn is_reference(verification_type: &VerificationType) -> bool {
+	matches!(verification_type, VerificationType::Null | VerificationType::Object(_) | VerificationType::UninitializedThis | VerificationType::Uninitialized(_))
+}
+
+fn object(class_name: &str) -> VerificationType {
+	VerificationType::Object(class_name.to_string())
+}
+
+/// Component type of the array type `class_name`, e.g. `java/lang/String` for `[Ljava/lang/String;`.
+fn array_component(class_name: &str) -> Option<FieldType> {
+	parse_field_descriptor(class_name.strip_prefix('[')?).ok()
+}
+
+/// Array type with elements of `class_name`, e.g. `[Ljava/lang/String;` for `java/lang/String`.
+fn array_of(class_name: &str) -> String {
+	if class_name.starts_with('[') {
+		format!("[{class_name}")
+	} else {
+		format!("[L{class_name};")
+	}
+}
+
+/// Whether a value of class type `from` may be used as `to` (JVMS 4.10.1.2 isJavaAssignable).
+pub fn is_java_assignable<H: ClassHierarchy>(hierarchy: &H, from: &str, to: &str) -> bool {
+	if from == to || to == JAVA_LANG_OBJECT {
+		return true;
+	}
+	match (array_component(from), array_component(to)) {
+		(Some(from_component), Some(to_component)) => match (from_component, to_component) {
+			(FieldType::Base(from_base), FieldType::Base(to_base)) => from_base == to_base,
+			(FieldType::Base(_), _) | (_, FieldType::Base(_)) => false,
+			(from_component, to_component) => is_java_assignable(hierarchy, &reference_name(&from_component), &reference_name(&to_component)),
+		},
+		(Some(_), None) => to == "java/lang/Cloneable" || to == "java/io/Serializable",
+		(None, Some(_)) => false,
+		(None, None) => hierarchy.is_interface(to) || hierarchy.is_subclass_of(from, to),
+	}
+}
+
+fn reference_name(field_type: &FieldType) -> String {
+	match field_type {
+		FieldType::Object(class_name) => class_name.clone(),
+		field_type => field_type.to_string(),
+	}
+}
+
+/// Whether a value of type `from` may be used where `to` is expected (JVMS 4.10.1.2 isAssignable).
+pub fn is_assignable<H: ClassHierarchy>(hierarchy: &H, from: &VerificationType, to: &VerificationType) -> bool {
+	match (from, to) {
+		(from, to) if from == to => true,
+		(_, VerificationType::Top) => true,
+		(VerificationType::Null, VerificationType::Object(_)) => true,
+		(VerificationType::Object(from), VerificationType::Object(to)) => is_java_assignable(hierarchy, from, to),
+		_ => false,
+	}
+}
+
+/// Verifies every method with code in `class_file` by type checking (JVMS 4.10.1).
+pub fn verify_class<H: ClassHierarchy>(class_file: &ClassFile, hierarchy: &H) -> Result<(), VerifyError> {
+	for method in &class_file.methods {
+		verify_method(class_file, method, hierarchy)?;
+	}
+	Ok(())
+}
+
+/// Verifies a single method of `class_file` by type checking, methods without code are accepted.
+pub fn verify_method<H: ClassHierarchy>(class_file: &ClassFile, method: &MethodInfo, hierarchy: &H) -> Result<(), VerifyError> {
+	let constant_pool = &class_file.constant_pool;
+	let method_name = constant_pool.get_utf8_str(method.name_index).map(|name| name.into_owned()).unwrap_or_else(|_| format!("#{}", method.name_index));
+	let method_descriptor = constant_pool.get_utf8_str(method.descriptor_index).map(|descriptor| descriptor.into_owned()).unwrap_or_default();
+	let method_id = format!("{method_name}{method_descriptor}");
+	let class_format_error = |error: ClassFormatError| VerifyError {
+		method: method_id.clone(),
+		pc: None,
+		kind: VerifyErrorKind::ClassFormat(Box::new(error)),
+	};
+
+	if class_file.major_version < TYPE_CHECKING_MAJOR_VERSION {
+		return Err(VerifyError {
+			method: method_id,
+			pc: None,
+			kind: VerifyErrorKind::UnsupportedVersion(class_file.major_version),
+		});
+	}
+	let Some(code) = method.code(constant_pool).map_err(class_format_error)? else {
+		return Ok(());
+	};
+	let descriptor = method.descriptor(constant_pool).map_err(class_format_error)?;
+	let frames = method.stack_map_frames(class_file).map_err(class_format_error)?;
+	let class_name = constant_pool.get_class_name(class_file.this_class).map_err(|e| class_format_error(e.into()))?.into_owned();
+	let super_class_name = match class_file.super_class {
+		0 => None,
+		index => Some(constant_pool.get_class_name(index).map_err(|e| class_format_error(e.into()))?.into_owned()),
+	};
+
+	let verifier = MethodVerifier {
+		constant_pool,
+		hierarchy,
+		method_id,
+		class_name,
+		super_class_name,
+		is_init: method_name == "<init>",
+		descriptor,
+		code: &code,
+		frames: HashMap::new(),
+		instruction_starts: HashSet::new(),
+		edges: None,
+	};
+	verifier.verify(method.access().contains(MethodAccess::STATIC), &method_name, frames)
+}
+
+/// Computes the frames at every branch target and exception handler of `code` by data-flow analysis.
+///
+/// Runs the type checker's transfer function over all paths, merging the states where paths meet.
+pub(crate) fn infer_frames<H: ClassHierarchy>(code: &CodeAttribute, constant_pool: &ConstantPool, class_name: &str, method_name: &str, is_static: bool, descriptor: &MethodDescriptor, hierarchy: &H) -> Result<Vec<Frame>, VerifyError> {
+	let verifier = MethodVerifier {
+		constant_pool,
+		hierarchy,
+		method_id: format!("{method_name}{descriptor}"),
+		class_name: class_name.to_string(),
+		super_class_name: hierarchy.super_class(class_name),
+		is_init: method_name == "<init>",
+		descriptor: descriptor.clone(),
+		code,
+		frames: HashMap::new(),
+		instruction_starts: HashSet::new(),
+		edges: Some(RefCell::new(Vec::new())),
+	};
+	verifier.infer(is_static, method_name)
+}
+
+struct MethodVerifier<'a, H: ClassHierarchy> {
+	constant_pool: &'a ConstantPool,
+	hierarchy: &'a H,
+	method_id: String,
+	class_name: String,
+	super_class_name: Option<String>,
+	is_init: bool,
+	descriptor: MethodDescriptor,
+	code: &'a CodeAttribute,
+	frames: HashMap<usize, State>,
+	instruction_starts: HashSet<usize>,
+	/// Set when inferring frames, branches and handlers then record the state they pass on instead of checking it.
+	edges: Option<RefCell<Vec<(usize, State)>>>,
+}
+
+impl<H: ClassHierarchy> MethodVerifier<'_, H> {
+	fn error(&self, pc: usize, kind: VerifyErrorKind) -> VerifyError {
+		VerifyError {
+			method: self.method_id.clone(),
+			pc: Some(pc),
+			kind,
+		}
+	}
+
+	fn mismatch(&self, pc: usize, expected: ExpectedType, actual: &VerificationType) -> VerifyError {
+		self.error(pc, VerifyErrorKind::TypeMismatch {
+			expected,
+			actual: actual.clone(),
+		})
+	}
+
+	fn constant_pool_error(&self, pc: usize, error: ConstantPoolError) -> VerifyError {
+		self.error(pc, VerifyErrorKind::ClassFormat(Box::new(error.into())))
+	}
+
+	fn verify(mut self, is_static: bool, method_name: &str, frames: Vec<Frame>) -> Result<(), VerifyError> {
+		if self.code.code.is_empty() {
+			return Err(self.error(0, VerifyErrorKind::EmptyCode));
+		}
+
+		let mut instructions = Vec::new();
+		for instruction in self.code.instructions() {
+			let (pc, instruction) = instruction.map_err(|error| self.error(error.pc, VerifyErrorKind::Decode(error)))?;
+			self.instruction_starts.insert(pc);
+			instructions.push((pc, instruction));
+		}
+
+		for frame in frames {
+			let pc = frame.pc as usize;
+			if !self.instruction_starts.contains(&pc) {
+				return Err(self.error(pc, VerifyErrorKind::InvalidBranchTarget(pc as i64)));
+			}
+			let state = self.frame_state(pc, frame.locals, frame.stack)?;
+			self.frames.insert(pc, state);
+		}
+
+		let locals = initial_locals(&self.class_name, method_name, is_static, &self.descriptor);
+		let locals = locals.iter().flat_map(|local| if local.is_wide() { vec![local.clone(), VerificationType::Top] } else { vec![local.clone()] }).collect();
+		let mut state = self.frame_state(0, locals, Vec::new())?;
+
+		self.check_exception_table()?;
+
+		let mut falls_through = true;
+		let mut last_pc = 0;
+		for (pc, instruction) in &instructions {
+			let pc = *pc;
+			if let Some(frame) = self.frames.get(&pc) {
+				if falls_through {
+					self.check_frame_assignable(pc, &state, frame, pc)?;
+				}
+				state = frame.clone();
+			} else if !falls_through {
+				return Err(self.error(pc, VerifyErrorKind::MissingStackMapFrame(pc)));
+			}
+			// Handlers see the locals before and after the instruction, which may store to one of them.
+			self.check_handlers(pc, &state)?;
+			falls_through = self.execute(pc, instruction, &mut state)?;
+			self.check_handlers(pc, &state)?;
+			last_pc = pc;
+		}
+
+		if falls_through {
+			return Err(self.error(last_pc, VerifyErrorKind::FallsOffEnd));
+		}
+		Ok(())
+	}
+
+	/// Follows every path through the code from method entry until the states at all instructions are stable.
+	fn infer(mut self, is_static: bool, method_name: &str) -> Result<Vec<Frame>, VerifyError> {
+		if self.code.code.is_empty() {
+			return Err(self.error(0, VerifyErrorKind::EmptyCode));
+		}
+		let mut instructions = Vec::new();
+		let mut indices = HashMap::new();
+		for instruction in self.code.instructions() {
+			let (pc, instruction) = instruction.map_err(|error| self.error(error.pc, VerifyErrorKind::Decode(error)))?;
+			self.instruction_starts.insert(pc);
+			indices.insert(pc, instructions.len());
+			instructions.push((pc, instruction));
+		}
+		self.check_exception_table()?;
+
+		let locals = initial_locals(&self.class_name, method_name, is_static, &self.descriptor);
+		let locals = locals.iter().flat_map(|local| if local.is_wide() { vec![local.clone(), VerificationType::Top] } else { vec![local.clone()] }).collect();
+		let mut states = HashMap::from([(0, self.frame_state(0, locals, Vec::new())?)]);
+		let mut targets = BTreeSet::new();
+		let mut pending = vec![0];
+		while let Some(pc) = pending.pop() {
+			let index = indices[&pc];
+			let mut state = states[&pc].clone();
+			// Handlers see the locals before and after the instruction, which may store to one of them.
+			self.check_handlers(pc, &state)?;
+			let falls_through = self.execute(pc, &instructions[index].1, &mut state)?;
+			self.check_handlers(pc, &state)?;
+
+			let mut edges = std::mem::take(&mut *self.edges.as_ref().expect("Inferring frames").borrow_mut());
+			targets.extend(edges.iter().map(|(target, _)| *target));
+			if falls_through {
+				let Some((next_pc, _)) = instructions.get(index + 1) else {
+					return Err(self.error(pc, VerifyErrorKind::FallsOffEnd));
+				};
+				edges.push((*next_pc, state));
+			}
+			for (target, state) in edges {
+				if self.merge_state(pc, &mut states, target, state)? {
+					pending.push(target);
+				}
+			}
+		}
+
+		if let Some((pc, _)) = instructions.iter().find(|(pc, _)| !states.contains_key(pc)) {
+			return Err(self.error(*pc, VerifyErrorKind::UnreachableCode));
+		}
+		Ok(targets.into_iter().map(|pc| {
+			let State { mut locals, stack } = states.remove(&pc).expect("Targets have a state");
+			while locals.last() == Some(&VerificationType::Top) && !(locals.len() >= 2 && locals[locals.len() - 2].is_wide()) {
+				locals.pop();
+			}
+			Frame {
+				pc: pc as U2,
+				locals,
+				stack: stack.into_iter().flat_map(|entry| if entry.is_wide() { vec![entry, VerificationType::Top] } else { vec![entry] }).collect(),
+			}
+		}).collect())
+	}
+
+	/// Merges `incoming` into the state at `target`, returning whether that state changed.
+	///
+	/// Locals that cannot be merged become `Top`, stack entries have to be compatible.
+	fn merge_state(&self, pc: usize, states: &mut HashMap<usize, State>, target: usize, incoming: State) -> Result<bool, VerifyError> {
+		let state = match states.entry(target) {
+			Entry::Vacant(entry) => {
+				entry.insert(incoming);
+				return Ok(true);
+			},
+			Entry::Occupied(entry) => entry.into_mut(),
+		};
+		if state.stack.len() != incoming.stack.len() {
+			return Err(self.error(pc, VerifyErrorKind::StackHeightMismatch {
+				target,
+				expected: state.stack.len(),
+				actual: incoming.stack.len(),
+			}));
+		}
+		let mut changed = false;
+		for (local, incoming) in state.locals.iter_mut().zip(&incoming.locals) {
+			let merged = self.merge_types(local, incoming).unwrap_or(VerificationType::Top);
+			if *local != merged {
+				*local = merged;
+				changed = true;
+			}
+		}
+		for (entry, incoming) in state.stack.iter_mut().zip(&incoming.stack) {
+			let merged = self.merge_types(entry, incoming).ok_or_else(|| self.mismatch(pc, ExpectedType::Type(entry.clone()), incoming))?;
+			if *entry != merged {
+				*entry = merged;
+				changed = true;
+			}
+		}
+		Ok(changed)
+	}
+
+	/// Most specific type both `a` and `b` are assignable to, `None` unless both are the same or references.
+	fn merge_types(&self, a: &VerificationType, b: &VerificationType) -> Option<VerificationType> {
+		match (a, b) {
+			(a, b) if a == b => Some(a.clone()),
+			(VerificationType::Null, VerificationType::Object(_)) => Some(b.clone()),
+			(VerificationType::Object(_), VerificationType::Null) => Some(a.clone()),
+			(VerificationType::Object(a), VerificationType::Object(b)) => Some(object(&self.common_super_type(a, b))),
+			_ => None,
+		}
+	}
+
+	fn common_super_type(&self, a: &str, b: &str) -> String {
+		if is_java_assignable(self.hierarchy, a, b) {
+			return b.to_string();
+		}
+		if is_java_assignable(self.hierarchy, b, a) {
+			return a.to_string();
+		}
+		match (array_component(a), array_component(b)) {
+			(Some(FieldType::Base(_)), _) | (_, Some(FieldType::Base(_))) => JAVA_LANG_OBJECT.to_string(),
+			(Some(a), Some(b)) => array_of(&self.common_super_type(&reference_name(&a), &reference_name(&b))),
+			(None, None) => self.hierarchy.common_super_class(a, b),
+			_ => JAVA_LANG_OBJECT.to_string(),
+		}
+	}
+
+	/// Converts expanded frame contents into a `State`, padding locals to `max_locals`.
+	fn frame_state(&self, pc: usize, mut locals: Vec<VerificationType>, stack: Vec<VerificationType>) -> Result<State, VerifyError> {
+		let max_locals = self.code.max_locals as usize;
+		if locals.len() > max_locals {
+			return Err(self.error(pc, VerifyErrorKind::LocalOutOfBounds { index: locals.len() - 1, max_locals: self.code.max_locals }));
+		}
+		locals.resize(max_locals, VerificationType::Top);
+
+		let mut entries = Vec::with_capacity(stack.len());
+		let mut slots = stack.into_iter();
+		while let Some(entry) = slots.next() {
+			if entry.is_wide() {
+				slots.next();
+			}
+			entries.push(entry);
+		}
+		let state = State {
+			locals,
+			stack: entries,
+		};
+		if state.stack_size() > self.code.max_stack as usize {
+			return Err(self.error(pc, VerifyErrorKind::StackOverflow { max_stack: self.code.max_stack }));
+		}
+		Ok(state)
+	}
+
+	fn check_frame_assignable(&self, pc: usize, from: &State, to: &State, target: usize) -> Result<(), VerifyError> {
+		for (index, (from_local, to_local)) in from.locals.iter().zip(&to.locals).enumerate() {
+			if !is_assignable(self.hierarchy, from_local, to_local) {
+				return Err(self.error(pc, VerifyErrorKind::LocalMismatch {
+					index,
+					expected: ExpectedType::Type(to_local.clone()),
+					actual: from_local.clone(),
+				}));
+			}
+		}
+		if from.stack.len() != to.stack.len() {
+			return Err(self.error(pc, VerifyErrorKind::StackHeightMismatch {
+				target,
+				expected: to.stack.len(),
+				actual: from.stack.len(),
+			}));
+		}
+		for (from_entry, to_entry) in from.stack.iter().zip(&to.stack) {
+			if !is_assignable(self.hierarchy, from_entry, to_entry) {
+				return Err(self.mismatch(pc, ExpectedType::Type(to_entry.clone()), from_entry));
+			}
+		}
+		Ok(())
+	}
+
+	fn check_target(&self, pc: usize, offset: i64, state: &State) -> Result<(), VerifyError> {
+		let target = pc as i64 + offset;
+		if target < 0 || !self.instruction_starts.contains(&(target as usize)) {
+			return Err(self.error(pc, VerifyErrorKind::InvalidBranchTarget(target)));
+		}
+		let target = target as usize;
+		if let Some(edges) = &self.edges {
+			edges.borrow_mut().push((target, state.clone()));
+			return Ok(());
+		}
+		let frame = self.frames.get(&target).ok_or(self.error(pc, VerifyErrorKind::MissingStackMapFrame(target)))?;
+		self.check_frame_assignable(pc, state, frame, target)
+	}
+
+	fn catch_type(&self, pc: usize, catch_type: U2) -> Result<VerificationType, VerifyError> {
+		match catch_type {
+			0 => Ok(object(JAVA_LANG_THROWABLE)),
+			index => Ok(VerificationType::Object(self.constant_pool.get_class_name(index).map_err(|e| self.constant_pool_error(pc, e))?.into_owned())),
+		}
+	}
+
+	fn check_exception_table(&self) -> Result<(), VerifyError> {
+		let code_length = self.code.code.len();
+		for (index, entry) in self.code.exception_table.iter().enumerate() {
+			let (start_pc, end_pc, handler_pc) = (entry.start_pc as usize, entry.end_pc as usize, entry.handler_pc as usize);
+			let valid_end = end_pc == code_length || self.instruction_starts.contains(&end_pc);
+			if start_pc >= end_pc || !self.instruction_starts.contains(&start_pc) || !valid_end || !self.instruction_starts.contains(&handler_pc) {
+				return Err(self.error(handler_pc, VerifyErrorKind::InvalidExceptionHandler(index)));
+			}
+			if self.edges.is_none() && !self.frames.contains_key(&handler_pc) {
+				return Err(self.error(handler_pc, VerifyErrorKind::MissingStackMapFrame(handler_pc)));
+			}
+			let catch_type = self.catch_type(handler_pc, entry.catch_type)?;
+			if !is_assignable(self.hierarchy, &catch_type, &object(JAVA_LANG_THROWABLE)) {
+				return Err(self.mismatch(handler_pc, ExpectedType::Type(object(JAVA_LANG_THROWABLE)), &catch_type));
+			}
+		}
+		Ok(())
+	}
+
+	/// Checks that the handlers covering `pc` accept the locals at `pc` with the caught exception on the stack.
+	fn check_handlers(&self, pc: usize, state: &State) -> Result<(), VerifyError> {
+		for entry in &self.code.exception_table {
+			if (entry.start_pc as usize..entry.end_pc as usize).contains(&pc) {
+				let exception_state = State {
+					locals: state.locals.clone(),
+					stack: vec![self.catch_type(pc, entry.catch_type)?],
+				};
+				match &self.edges {
+					Some(edges) => edges.borrow_mut().push((entry.handler_pc as usize, exception_state)),
+					None => self.check_frame_assignable(pc, &exception_state, &self.frames[&(entry.handler_pc as usize)], entry.handler_pc as usize)?,
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn push(&self, pc: usize, state: &mut State, verification_type: VerificationType) -> Result<(), VerifyError> {
+		if state.stack_size() + size(&verification_type) > self.code.max_stack as usize {
+			return Err(self.error(pc, VerifyErrorKind::StackOverflow { max_stack: self.code.max_stack }));
+		}
+		state.stack.push(verification_type);
+		Ok(())
+	}
+
+	fn pop(&self, pc: usize, state: &mut State) -> Result<VerificationType, VerifyError> {
+		state.stack.pop().ok_or(self.error(pc, VerifyErrorKind::StackUnderflow))
+	}
+
+	fn pop_expect(&self, pc: usize, state: &mut State, expected: &VerificationType) -> Result<VerificationType, VerifyError> {
+		let actual = self.pop(pc, state)?;
+		if !is_assignable(self.hierarchy, &actual, expected) {
+			return Err(self.mismatch(pc, ExpectedType::Type(expected.clone()), &actual));
+		}
+		Ok(actual)
+	}
+
+	fn pop_category1(&self, pc: usize, state: &mut State) -> Result<VerificationType, VerifyError> {
+		let actual = self.pop(pc, state)?;
+		if actual.is_wide() {
+			return Err(self.mismatch(pc, ExpectedType::Category1, &actual));
+		}
+		Ok(actual)
+	}
+
+	fn pop_reference(&self, pc: usize, state: &mut State) -> Result<VerificationType, VerifyError> {
+		let actual = self.pop(pc, state)?;
+		if !is_reference(&actual) {
+			return Err(self.mismatch(pc, ExpectedType::Reference, &actual));
+		}
+		Ok(actual)
+	}
+
+	/// Pops an array reference and returns its component type, or `None` for `null`.
+	fn pop_array(&self, pc: usize, state: &mut State) -> Result<Option<FieldType>, VerifyError> {
+		let actual = self.pop(pc, state)?;
+		match &actual {
+			VerificationType::Null => Ok(None),
+			VerificationType::Object(class_name) => match array_component(class_name) {
+				Some(component) => Ok(Some(component)),
+				None => Err(self.mismatch(pc, ExpectedType::Array, &actual)),
+			},
+			_ => Err(self.mismatch(pc, ExpectedType::Array, &actual)),
+		}
+	}
+
+	fn array_load(&self, pc: usize, state: &mut State, components: &[BaseType], result: VerificationType) -> Result<(), VerifyError> {
+		self.pop_expect(pc, state, &VerificationType::Integer)?;
+		match self.pop_array(pc, state)? {
+			None => {},
+			Some(FieldType::Base(base_type)) if components.contains(&base_type) => {},
+			Some(component) => return Err(self.mismatch(pc, ExpectedType::Type(object(&array_of(&components[0].as_char().to_string()))), &object(&array_of(&component.to_string())))),
+		}
+		self.push(pc, state, result)
+	}
+
+	fn array_store(&self, pc: usize, state: &mut State, components: &[BaseType], value: VerificationType) -> Result<(), VerifyError> {
+		self.pop_expect(pc, state, &value)?;
+		self.pop_expect(pc, state, &VerificationType::Integer)?;
+		match self.pop_array(pc, state)? {
+			None => Ok(()),
+			Some(FieldType::Base(base_type)) if components.contains(&base_type) => Ok(()),
+			Some(component) => Err(self.mismatch(pc, ExpectedType::Type(object(&array_of(&components[0].as_char().to_string()))), &object(&array_of(&component.to_string())))),
+		}
+	}
+
+	fn check_local(&self, pc: usize, index: usize, width: usize) -> Result<(), VerifyError> {
+		if index + width > self.code.max_locals as usize {
+			return Err(self.error(pc, VerifyErrorKind::LocalOutOfBounds { index: index + width - 1, max_locals: self.code.max_locals }));
+		}
+		Ok(())
+	}
+
+	fn check_local_type(&self, pc: usize, state: &State, index: usize, expected: &VerificationType) -> Result<(), VerifyError> {
+		self.check_local(pc, index, size(expected))?;
+		let actual = &state.locals[index];
+		if actual != expected || (expected.is_wide() && state.locals[index + 1] != VerificationType::Top) {
+			return Err(self.error(pc, VerifyErrorKind::LocalMismatch { index, expected: ExpectedType::Type(expected.clone()), actual: actual.clone() }));
+		}
+		Ok(())
+	}
+
+	fn load(&self, pc: usize, state: &mut State, index: usize, expected: VerificationType) -> Result<(), VerifyError> {
+		self.check_local_type(pc, state, index, &expected)?;
+		self.push(pc, state, expected)
+	}
+
+	fn load_reference(&self, pc: usize, state: &mut State, index: usize) -> Result<(), VerifyError> {
+		self.check_local(pc, index, 1)?;
+		let actual = state.locals[index].clone();
+		if !is_reference(&actual) {
+			return Err(self.error(pc, VerifyErrorKind::LocalMismatch { index, expected: ExpectedType::Reference, actual }));
+		}
+		self.push(pc, state, actual)
+	}
+
+	fn store_local(&self, pc: usize, state: &mut State, index: usize, value: VerificationType) -> Result<(), VerifyError> {
+		self.check_local(pc, index, size(&value))?;
+		if index > 0 && state.locals[index - 1].is_wide() {
+			state.locals[index - 1] = VerificationType::Top;
+		}
+		if value.is_wide() {
+			state.locals[index + 1] = VerificationType::Top;
+		}
+		state.locals[index] = value;
+		Ok(())
+	}
+
+	fn store(&self, pc: usize, state: &mut State, index: usize, expected: VerificationType) -> Result<(), VerifyError> {
+		let value = self.pop_expect(pc, state, &expected)?;
+		self.store_local(pc, state, index, value)
+	}
+
+	fn store_reference(&self, pc: usize, state: &mut State, index: usize) -> Result<(), VerifyError> {
+		let value = self.pop_reference(pc, state)?;
+		self.store_local(pc, state, index, value)
+	}
+
+	fn binary(&self, pc: usize, state: &mut State, left: VerificationType, right: VerificationType, result: VerificationType) -> Result<(), VerifyError> {
+		self.pop_expect(pc, state, &right)?;
+		self.pop_expect(pc, state, &left)?;
+		self.push(pc, state, result)
+	}
+
+	fn unary(&self, pc: usize, state: &mut State, operand: VerificationType, result: VerificationType) -> Result<(), VerifyError> {
+		self.pop_expect(pc, state, &operand)?;
+		self.push(pc, state, result)
+	}
+
+	fn push_all(&self, pc: usize, state: &mut State, values: &[&VerificationType]) -> Result<(), VerifyError> {
+		for value in values {
+			self.push(pc, state, (*value).clone())?;
+		}
+		Ok(())
+	}
+
+	fn ldc(&self, pc: usize, state: &mut State, index: U2, wide: bool) -> Result<(), VerifyError> {
+		let entry = self.constant_pool.get(index).map_err(|e| self.constant_pool_error(pc, e))?;
+		let verification_type = match entry {
+			CPInfo::Integer(_) if !wide => VerificationType::Integer,
+			CPInfo::Float(_) if !wide => VerificationType::Float,
+			CPInfo::String(_) if !wide => object("java/lang/String"),
+			CPInfo::Class(_) if !wide => object("java/lang/Class"),
+			CPInfo::MethodType(_) if !wide => object("java/lang/invoke/MethodType"),
+			CPInfo::MethodHandle(_) if !wide => object("java/lang/invoke/MethodHandle"),
+			CPInfo::Long(_) if wide => VerificationType::Long,
+			CPInfo::Double(_) if wide => VerificationType::Double,
+			CPInfo::Dynamic(dynamic) => {
+				let (_, descriptor) = self.constant_pool.get_name_and_type(dynamic.name_and_type_index).map_err(|e| self.constant_pool_error(pc, e))?;
+				let field_type = parse_field_descriptor(&descriptor).map_err(|e| self.error(pc, VerifyErrorKind::ClassFormat(Box::new(e.into()))))?;
+				let verification_type = VerificationType::from_field_type(&field_type);
+				if verification_type.is_wide() != wide {
+					return Err(self.error(pc, VerifyErrorKind::InvalidConstant(index)));
+				}
+				verification_type
+			},
+			_ => return Err(self.error(pc, VerifyErrorKind::InvalidConstant(index))),
+		};
+		self.push(pc, state, verification_type)
+	}
+
+	fn field_type(&self, pc: usize, index: U2) -> Result<(String, FieldType), VerifyError> {
+		let member_ref = self.constant_pool.get_member_ref(index).map_err(|e| self.constant_pool_error(pc, e))?;
+		let field_type = parse_field_descriptor(&member_ref.descriptor).map_err(|e| self.error(pc, VerifyErrorKind::ClassFormat(Box::new(e.into()))))?;
+		Ok((member_ref.class_name.into_owned(), field_type))
+	}
+
+	fn field_access(&self, pc: usize, state: &mut State, index: U2, is_static: bool, is_put: bool) -> Result<(), VerifyError> {
+		let (class_name, field_type) = self.field_type(pc, index)?;
+		let value = VerificationType::from_field_type(&field_type);
+		if is_put {
+			self.pop_expect(pc, state, &value)?;
+		}
+		if !is_static {
+			let receiver = self.pop(pc, state)?;
+			let own_field_in_init = is_put && self.is_init && receiver == VerificationType::UninitializedThis && class_name == self.class_name;
+			if !own_field_in_init && !is_assignable(self.hierarchy, &receiver, &object(&class_name)) {
+				return Err(self.mismatch(pc, ExpectedType::Type(object(&class_name)), &receiver));
+			}
+		}
+		if !is_put {
+			self.push(pc, state, value)?;
+		}
+		Ok(())
+	}
+
+	fn pop_arguments(&self, pc: usize, state: &mut State, descriptor: &MethodDescriptor) -> Result<(), VerifyError> {
+		for parameter in descriptor.parameters.iter().rev() {
+			self.pop_expect(pc, state, &VerificationType::from_field_type(parameter))?;
+		}
+		Ok(())
+	}
+
+	fn push_return(&self, pc: usize, state: &mut State, descriptor: &MethodDescriptor) -> Result<(), VerifyError> {
+		match &descriptor.return_type {
+			ReturnDescriptor::Void => Ok(()),
+			ReturnDescriptor::Type(field_type) => self.push(pc, state, VerificationType::from_field_type(field_type)),
+		}
+	}
+
+	fn method_ref(&self, pc: usize, index: U2) -> Result<(String, String, MethodDescriptor), VerifyError> {
+		let member_ref = self.constant_pool.get_member_ref(index).map_err(|e| self.constant_pool_error(pc, e))?;
+		let descriptor = parse_method_descriptor(&member_ref.descriptor).map_err(|e| self.error(pc, VerifyErrorKind::ClassFormat(Box::new(e.into()))))?;
+		Ok((member_ref.class_name.into_owned(), member_ref.name.into_owned(), descriptor))
+	}
+
+	fn invoke(&self, pc: usize, state: &mut State, instruction: &Instruction) -> Result<(), VerifyError> {
+		let index = match instruction {
+			Instruction::Invokevirtual(index) | Instruction::Invokespecial(index) | Instruction::Invokestatic(index) | Instruction::Invokeinterface(index, _) => *index,
+			_ => unreachable!("Only invoke instructions are passed"),
+		};
+		let (class_name, name, descriptor) = self.method_ref(pc, index)?;
+		let is_init = name == "<init>";
+		if name.starts_with('<') && !(is_init && matches!(instruction, Instruction::Invokespecial(_))) {
+			return Err(self.error(pc, VerifyErrorKind::IllegalMethodInvocation(name)));
+		}
+		if let Instruction::Invokeinterface(_, count) = instruction {
+			let expected = descriptor.argument_slots() + 1;
+			if *count as usize != expected {
+				return Err(self.error(pc, VerifyErrorKind::InvalidInvokeinterfaceCount { expected, actual: *count }));
+			}
+		}
+
+		self.pop_arguments(pc, state, &descriptor)?;
+		match instruction {
+			Instruction::Invokestatic(_) => {},
+			Instruction::Invokespecial(_) if is_init => self.initialize(pc, state, &class_name)?,
+			Instruction::Invokespecial(_) => {
+				self.pop_expect(pc, state, &object(&self.class_name))?;
+			},
+			Instruction::Invokeinterface(..) => {
+				self.pop_reference(pc, state)?;
+			},
+			_ => {
+				self.pop_expect(pc, state, &object(&class_name))?;
+			},
+		}
+		self.push_return(pc, state, &descriptor)
+	}
+
+	/// Handles the receiver of `invokespecial <init>`, marking the object it refers to as initialized.
+	fn initialize(&self, pc: usize, state: &mut State, class_name: &str) -> Result<(), VerifyError> {
+		let receiver = self.pop(pc, state)?;
+		let initialized = match &receiver {
+			VerificationType::UninitializedThis => {
+				if class_name != self.class_name && Some(class_name) != self.super_class_name.as_deref() {
+					return Err(self.mismatch(pc, ExpectedType::Type(object(&self.class_name)), &object(class_name)));
+				}
+				object(&self.class_name)
+			},
+			VerificationType::Uninitialized(offset) => {
+				let new_class = match self.code.code.get(*offset as usize..*offset as usize + 3) {
+					Some([NEW, high, low]) => self.constant_pool.get_class_name(U2::from_be_bytes([*high, *low])).map_err(|e| self.constant_pool_error(pc, e))?,
+					_ => return Err(self.error(pc, VerifyErrorKind::InvalidBranchTarget(*offset as i64))),
+				};
+				if new_class != class_name {
+					return Err(self.mismatch(pc, ExpectedType::Type(object(&new_class)), &object(class_name)));
+				}
+				object(class_name)
+			},
+			_ => return Err(self.mismatch(pc, ExpectedType::Uninitialized, &receiver)),
+		};
+		for entry in state.locals.iter_mut().chain(state.stack.iter_mut()) {
+			if *entry == receiver {
+				*entry = initialized.clone();
+			}
+		}
+		Ok(())
+	}
+
+	fn return_value(&self, pc: usize, state: &mut State, expected: Option<VerificationType>) -> Result<bool, VerifyError> {
+		let return_type = match &self.descriptor.return_type {
+			ReturnDescriptor::Void => None,
+			ReturnDescriptor::Type(field_type) => Some(VerificationType::from_field_type(field_type)),
+		};
+		match (expected, return_type) {
+			(None, None) => {
+				if self.is_init && state.locals.contains(&VerificationType::UninitializedThis) {
+					return Err(self.error(pc, VerifyErrorKind::UninitializedThisOnReturn));
+				}
+			},
+			(Some(VerificationType::Object(_)), Some(return_type @ VerificationType::Object(_))) => {
+				self.pop_expect(pc, state, &return_type)?;
+			},
+			(Some(expected), Some(return_type)) if expected == return_type => {
+				self.pop_expect(pc, state, &expected)?;
+			},
+			_ => return Err(self.error(pc, VerifyErrorKind::InvalidReturn)),
+		}
+		Ok(false)
+	}
+
+	fn tableswitch(&self, pc: usize, state: &mut State, table: &TableSwitch) -> Result<bool, VerifyError> {
+		self.pop_expect(pc, state, &VerificationType::Integer)?;
+		self.check_target(pc, table.default as i64, state)?;
+		for offset in &table.offsets {
+			self.check_target(pc, *offset as i64, state)?;
+		}
+		Ok(false)
+	}
+
+	fn lookupswitch(&self, pc: usize, state: &mut State, lookup: &LookupSwitch) -> Result<bool, VerifyError> {
+		self.pop_expect(pc, state, &VerificationType::Integer)?;
+		self.check_target(pc, lookup.default as i64, state)?;
+		for (_, offset) in &lookup.pairs {
+			self.check_target(pc, *offset as i64, state)?;
+		}
+		Ok(false)
+	}
+
+	fn branch(&self, pc: usize, state: &mut State, operands: &[VerificationType], offset: i16) -> Result<bool, VerifyError> {
+		for operand in operands {
+			match operand {
+				VerificationType::Object(_) => {
+					self.pop_reference(pc, state)?;
+				},
+				operand => {
+					self.pop_expect(pc, state, operand)?;
+				},
+			}
+		}
+		self.check_target(pc, offset as i64, state)?;
+		Ok(true)
+	}
+
+	/// Applies `instruction` to `state`, returning whether execution can continue with the next instruction.
+	fn execute(&self, pc: usize, instruction: &Instruction, state: &mut State) -> Result<bool, VerifyError> {
+		use VerificationType::{Double, Float, Integer, Long};
+
+		let reference = object(JAVA_LANG_OBJECT);
+		match instruction {
+			Instruction::Nop => {},
+			Instruction::AconstNull => self.push(pc, state, VerificationType::Null)?,
+			Instruction::IconstM1 | Instruction::Iconst0 | Instruction::Iconst1 | Instruction::Iconst2 | Instruction::Iconst3 | Instruction::Iconst4 | Instruction::Iconst5 | Instruction::Bipush(_) | Instruction::Sipush(_) => self.push(pc, state, Integer)?,
+			Instruction::Lconst0 | Instruction::Lconst1 => self.push(pc, state, Long)?,
+			Instruction::Fconst0 | Instruction::Fconst1 | Instruction::Fconst2 => self.push(pc, state, Float)?,
+			Instruction::Dconst0 | Instruction::Dconst1 => self.push(pc, state, Double)?,
+			Instruction::Ldc(index) => self.ldc(pc, state, *index as U2, false)?,
+			Instruction::LdcW(index) => self.ldc(pc, state, *index, false)?,
+			Instruction::Ldc2W(index) => self.ldc(pc, state, *index, true)?,
+
+			Instruction::Iload(index) => self.load(pc, state, *index as usize, Integer)?,
+			Instruction::Lload(index) => self.load(pc, state, *index as usize, Long)?,
+			Instruction::Fload(index) => self.load(pc, state, *index as usize, Float)?,
+			Instruction::Dload(index) => self.load(pc, state, *index as usize, Double)?,
+			Instruction::Aload(index) => self.load_reference(pc, state, *index as usize)?,
+			Instruction::Iload0 => self.load(pc, state, 0, Integer)?,
+			Instruction::Iload1 => self.load(pc, state, 1, Integer)?,
+			Instruction::Iload2 => self.load(pc, state, 2, Integer)?,
+			Instruction::Iload3 => self.load(pc, state, 3, Integer)?,
+			Instruction::Lload0 => self.load(pc, state, 0, Long)?,
+			Instruction::Lload1 => self.load(pc, state, 1, Long)?,
+			Instruction::Lload2 => self.load(pc, state, 2, Long)?,
+			Instruction::Lload3 => self.load(pc, state, 3, Long)?,
+			Instruction::Fload0 => self.load(pc, state, 0, Float)?,
+			Instruction::Fload1 => self.load(pc, state, 1, Float)?,
+			Instruction::Fload2 => self.load(pc, state, 2, Float)?,
+			Instruction::Fload3 => self.load(pc, state, 3, Float)?,
+			Instruction::Dload0 => self.load(pc, state, 0, Double)?,
+			Instruction::Dload1 => self.load(pc, state, 1, Double)?,
+			Instruction::Dload2 => self.load(pc, state, 2, Double)?,
+			Instruction::Dload3 => self.load(pc, state, 3, Double)?,
+			Instruction::Aload0 => self.load_reference(pc, state, 0)?,
+			Instruction::Aload1 => self.load_reference(pc, state, 1)?,
+			Instruction::Aload2 => self.load_reference(pc, state, 2)?,
+			Instruction::Aload3 => self.load_reference(pc, state, 3)?,
+
+			Instruction::Iaload => self.array_load(pc, state, &[BaseType::Int], Integer)?,
+			Instruction::Laload => self.array_load(pc, state, &[BaseType::Long], Long)?,
+			Instruction::Faload => self.array_load(pc, state, &[BaseType::Float], Float)?,
+			Instruction::Daload => self.array_load(pc, state, &[BaseType::Double], Double)?,
+			Instruction::Baload => self.array_load(pc, state, &[BaseType::Byte, BaseType::Boolean], Integer)?,
+			Instruction::Caload => self.array_load(pc, state, &[BaseType::Char], Integer)?,
+			Instruction::Saload => self.array_load(pc, state, &[BaseType::Short], Integer)?,
+			Instruction::Aaload => {
+				self.pop_expect(pc, state, &Integer)?;
+				let component = match self.pop_array(pc, state)? {
+					None => VerificationType::Null,
+					Some(FieldType::Base(base_type)) => return Err(self.mismatch(pc, ExpectedType::Type(object("[Ljava/lang/Object;")), &object(&array_of(&base_type.as_char().to_string())))),
+					Some(component) => VerificationType::from_field_type(&component),
+				};
+				self.push(pc, state, component)?;
+			},
+
+			Instruction::Istore(index) => self.store(pc, state, *index as usize, Integer)?,
+			Instruction::Lstore(index) => self.store(pc, state, *index as usize, Long)?,
+			Instruction::Fstore(index) => self.store(pc, state, *index as usize, Float)?,
+			Instruction::Dstore(index) => self.store(pc, state, *index as usize, Double)?,
+			Instruction::Astore(index) => self.store_reference(pc, state, *index as usize)?,
+			Instruction::Istore0 => self.store(pc, state, 0, Integer)?,
+			Instruction::Istore1 => self.store(pc, state, 1, Integer)?,
+			Instruction::Istore2 => self.store(pc, state, 2, Integer)?,
+			Instruction::Istore3 => self.store(pc, state, 3, Integer)?,
+			Instruction::Lstore0 => self.store(pc, state, 0, Long)?,
+			Instruction::Lstore1 => self.store(pc, state, 1, Long)?,
+			Instruction::Lstore2 => self.store(pc, state, 2, Long)?,
+			Instruction::Lstore3 => self.store(pc, state, 3, Long)?,
+			Instruction::Fstore0 => self.store(pc, state, 0, Float)?,
+			Instruction::Fstore1 => self.store(pc, state, 1, Float)?,
+			Instruction::Fstore2 => self.store(pc, state, 2, Float)?,
+			Instruction::Fstore3 => self.store(pc, state, 3, Float)?,
+			Instruction::Dstore0 => self.store(pc, state, 0, Double)?,
+			Instruction::Dstore1 => self.store(pc, state, 1, Double)?,
+			Instruction::Dstore2 => self.store(pc, state, 2, Double)?,
+			Instruction::Dstore3 => self.store(pc, state, 3, Double)?,
+			Instruction::Astore0 => self.store_reference(pc, state, 0)?,
+			Instruction::Astore1 => self.store_reference(pc, state, 1)?,
+			Instruction::Astore2 => self.store_reference(pc, state, 2)?,
+			Instruction::Astore3 => self.store_reference(pc, state, 3)?,
+
+			Instruction::Iastore => self.array_store(pc, state, &[BaseType::Int], Integer)?,
+			Instruction::Lastore => self.array_store(pc, state, &[BaseType::Long], Long)?,
+			Instruction::Fastore => self.array_store(pc, state, &[BaseType::Float], Float)?,
+			Instruction::Dastore => self.array_store(pc, state, &[BaseType::Double], Double)?,
+			Instruction::Bastore => self.array_store(pc, state, &[BaseType::Byte, BaseType::Boolean], Integer)?,
+			Instruction::Castore => self.array_store(pc, state, &[BaseType::Char], Integer)?,
+			Instruction::Sastore => self.array_store(pc, state, &[BaseType::Short], Integer)?,
+			Instruction::Aastore => {
+				// Whether the value fits the array is checked at runtime.
+				self.pop_expect(pc, state, &reference)?;
+				self.pop_expect(pc, state, &Integer)?;
+				if let Some(FieldType::Base(base_type)) = self.pop_array(pc, state)? {
+					return Err(self.mismatch(pc, ExpectedType::Type(object("[Ljava/lang/Object;")), &object(&array_of(&base_type.as_char().to_string()))));
+				}
+			},
+
+			Instruction::Pop => {
+				self.pop_category1(pc, state)?;
+			},
+			Instruction::Pop2 => {
+				if !self.pop(pc, state)?.is_wide() {
+					self.pop_category1(pc, state)?;
+				}
+			},
+			Instruction::Dup => {
+				let value1 = self.pop_category1(pc, state)?;
+				self.push_all(pc, state, &[&value1, &value1])?;
+			},
+			Instruction::DupX1 => {
+				let value1 = self.pop_category1(pc, state)?;
+				let value2 = self.pop_category1(pc, state)?;
+				self.push_all(pc, state, &[&value1, &value2, &value1])?;
+			},
+			Instruction::DupX2 => {
+				let value1 = self.pop_category1(pc, state)?;
+				let value2 = self.pop(pc, state)?;
+				if value2.is_wide() {
+					self.push_all(pc, state, &[&value1, &value2, &value1])?;
+				} else {
+					let value3 = self.pop_category1(pc, state)?;
+					self.push_all(pc, state, &[&value1, &value3, &value2, &value1])?;
+				}
+			},
+			Instruction::Dup2 => {
+				let value1 = self.pop(pc, state)?;
+				if value1.is_wide() {
+					self.push_all(pc, state, &[&value1, &value1])?;
+				} else {
+					let value2 = self.pop_category1(pc, state)?;
+					self.push_all(pc, state, &[&value2, &value1, &value2, &value1])?;
+				}
+			},
+			Instruction::Dup2X1 => {
+				let value1 = self.pop(pc, state)?;
+				if value1.is_wide() {
+					let value2 = self.pop_category1(pc, state)?;
+					self.push_all(pc, state, &[&value1, &value2, &value1])?;
+				} else {
+					let value2 = self.pop_category1(pc, state)?;
+					let value3 = self.pop_category1(pc, state)?;
+					self.push_all(pc, state, &[&value2, &value1, &value3, &value2, &value1])?;
+				}
+			},
+			Instruction::Dup2X2 => {
+				let value1 = self.pop(pc, state)?;
+				if value1.is_wide() {
+					let value2 = self.pop(pc, state)?;
+					if value2.is_wide() {
+						self.push_all(pc, state, &[&value1, &value2, &value1])?;
+					} else {
+						let value3 = self.pop_category1(pc, state)?;
+						self.push_all(pc, state, &[&value1, &value3, &value2, &value1])?;
+					}
+				} else {
+					let value2 = self.pop_category1(pc, state)?;
+					let value3 = self.pop(pc, state)?;
+					if value3.is_wide() {
+						self.push_all(pc, state, &[&value2, &value1, &value3, &value2, &value1])?;
+					} else {
+						let value4 = self.pop_category1(pc, state)?;
+						self.push_all(pc, state, &[&value2, &value1, &value4, &value3, &value2, &value1])?;
+					}
+				}
+			},
+			Instruction::Swap => {
+				let value1 = self.pop_category1(pc, state)?;
+				let value2 = self.pop_category1(pc, state)?;
+				self.push_all(pc, state, &[&value1, &value2])?;
+			},
+
+			Instruction::Iadd | Instruction::Isub | Instruction::Imul | Instruction::Idiv | Instruction::Irem | Instruction::Ishl | Instruction::Ishr | Instruction::Iushr | Instruction::Iand | Instruction::Ior | Instruction::Ixor => self.binary(pc, state, Integer, Integer, Integer)?,
+			Instruction::Ladd | Instruction::Lsub | Instruction::Lmul | Instruction::Ldiv | Instruction::Lrem | Instruction::Land | Instruction::Lor | Instruction::Lxor => self.binary(pc, state, Long, Long, Long)?,
+			Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => self.binary(pc, state, Long, Integer, Long)?,
+			Instruction::Fadd | Instruction::Fsub | Instruction::Fmul | Instruction::Fdiv | Instruction::Frem => self.binary(pc, state, Float, Float, Float)?,
+			Instruction::Dadd | Instruction::Dsub | Instruction::Dmul | Instruction::Ddiv | Instruction::Drem => self.binary(pc, state, Double, Double, Double)?,
+			Instruction::Ineg => self.unary(pc, state, Integer, Integer)?,
+			Instruction::Lneg => self.unary(pc, state, Long, Long)?,
+			Instruction::Fneg => self.unary(pc, state, Float, Float)?,
+			Instruction::Dneg => self.unary(pc, state, Double, Double)?,
+			Instruction::Iinc(index, _) => self.check_local_type(pc, state, *index as usize, &Integer)?,
+			Instruction::I2l => self.unary(pc, state, Integer, Long)?,
+			Instruction::I2f => self.unary(pc, state, Integer, Float)?,
+			Instruction::I2d => self.unary(pc, state, Integer, Double)?,
+			Instruction::L2i => self.unary(pc, state, Long, Integer)?,
+			Instruction::L2f => self.unary(pc, state, Long, Float)?,
+			Instruction::L2d => self.unary(pc, state, Long, Double)?,
+			Instruction::F2i => self.unary(pc, state, Float, Integer)?,
+			Instruction::F2l => self.unary(pc, state, Float, Long)?,
+			Instruction::F2d => self.unary(pc, state, Float, Double)?,
+			Instruction::D2i => self.unary(pc, state, Double, Integer)?,
+			Instruction::D2l => self.unary(pc, state, Double, Long)?,
+			Instruction::D2f => self.unary(pc, state, Double, Float)?,
+			Instruction::I2b | Instruction::I2c | Instruction::I2s => self.unary(pc, state, Integer, Integer)?,
+			Instruction::Lcmp => self.binary(pc, state, Long, Long, Integer)?,
+			Instruction::Fcmpl | Instruction::Fcmpg => self.binary(pc, state, Float, Float, Integer)?,
+			Instruction::Dcmpl | Instruction::Dcmpg => self.binary(pc, state, Double, Double, Integer)?,
+
+			Instruction::Ifeq(offset) | Instruction::Ifne(offset) | Instruction::Iflt(offset) | Instruction::Ifge(offset) | Instruction::Ifgt(offset) | Instruction::Ifle(offset) => return self.branch(pc, state, &[Integer], *offset),
+			Instruction::IfIcmpeq(offset) | Instruction::IfIcmpne(offset) | Instruction::IfIcmplt(offset) | Instruction::IfIcmpge(offset) | Instruction::IfIcmpgt(offset) | Instruction::IfIcmple(offset) => return self.branch(pc, state, &[Integer, Integer], *offset),
+			Instruction::IfAcmpeq(offset) | Instruction::IfAcmpne(offset) => return self.branch(pc, state, &[reference.clone(), reference], *offset),
+			Instruction::Ifnull(offset) | Instruction::Ifnonnull(offset) => return self.branch(pc, state, &[reference], *offset),
+			Instruction::Goto(offset) => {
+				self.check_target(pc, *offset as i64, state)?;
+				return Ok(false);
+			},
+			Instruction::GotoW(offset) => {
+				self.check_target(pc, *offset as i64, state)?;
+				return Ok(false);
+			},
+			Instruction::Jsr(_) | Instruction::JsrW(_) | Instruction::Ret(_) | Instruction::Wide(WideInstruction::Ret(_)) => return Err(self.error(pc, VerifyErrorKind::UnsupportedInstruction(instruction.mnemonic()))),
+			Instruction::Tableswitch(table) => return self.tableswitch(pc, state, table),
+			Instruction::Lookupswitch(lookup) => return self.lookupswitch(pc, state, lookup),
+
+			Instruction::Ireturn => return self.return_value(pc, state, Some(Integer)),
+			Instruction::Lreturn => return self.return_value(pc, state, Some(Long)),
+			Instruction::Freturn => return self.return_value(pc, state, Some(Float)),
+			Instruction::Dreturn => return self.return_value(pc, state, Some(Double)),
+			Instruction::Areturn => return self.return_value(pc, state, Some(reference)),
+			Instruction::Return => return self.return_value(pc, state, None),
+
+			Instruction::Getstatic(index) => self.field_access(pc, state, *index, true, false)?,
+			Instruction::Putstatic(index) => self.field_access(pc, state, *index, true, true)?,
+			Instruction::Getfield(index) => self.field_access(pc, state, *index, false, false)?,
+			Instruction::Putfield(index) => self.field_access(pc, state, *index, false, true)?,
+			Instruction::Invokevirtual(_) | Instruction::Invokespecial(_) | Instruction::Invokestatic(_) | Instruction::Invokeinterface(..) => self.invoke(pc, state, instruction)?,
+			Instruction::Invokedynamic(index) => {
+				let (name, descriptor) = match self.constant_pool.get(*index).map_err(|e| self.constant_pool_error(pc, e))? {
+					CPInfo::InvokeDynamic(invoke_dynamic) => self.constant_pool.get_name_and_type(invoke_dynamic.name_and_type_index).map_err(|e| self.constant_pool_error(pc, e))?,
+					_ => return Err(self.error(pc, VerifyErrorKind::InvalidConstant(*index))),
+				};
+				if name.starts_with('<') {
+					return Err(self.error(pc, VerifyErrorKind::IllegalMethodInvocation(name.into_owned())));
+				}
+				let descriptor = parse_method_descriptor(&descriptor).map_err(|e| self.error(pc, VerifyErrorKind::ClassFormat(Box::new(e.into()))))?;
+				self.pop_arguments(pc, state, &descriptor)?;
+				self.push_return(pc, state, &descriptor)?;
+			},
+
+			Instruction::New(index) => {
+				self.constant_pool.get_class_name(*index).map_err(|e| self.constant_pool_error(pc, e))?;
+				let uninitialized = VerificationType::Uninitialized(pc as U2);
+				if state.stack.contains(&uninitialized) {
+					return Err(self.mismatch(pc, ExpectedType::Uninitialized, &uninitialized));
+				}
+				for local in state.locals.iter_mut().filter(|local| **local == uninitialized) {
+					*local = VerificationType::Top;
+				}
+				self.push(pc, state, uninitialized)?;
+			},
+			Instruction::Newarray(atype) => {
+				let element = match atype {
+					4 => 'Z',
+					5 => 'C',
+					6 => 'F',
+					7 => 'D',
+					8 => 'B',
+					9 => 'S',
+					10 => 'I',
+					11 => 'J',
+					atype => return Err(self.error(pc, VerifyErrorKind::InvalidArrayType(*atype))),
+				};
+				self.unary(pc, state, Integer, object(&format!("[{element}")))?;
+			},
+			Instruction::Anewarray(index) => {
+				let class_name = self.constant_pool.get_class_name(*index).map_err(|e| self.constant_pool_error(pc, e))?;
+				self.unary(pc, state, Integer, object(&array_of(&class_name)))?;
+			},
+			Instruction::Arraylength => {
+				self.pop_array(pc, state)?;
+				self.push(pc, state, Integer)?;
+			},
+			Instruction::Athrow => {
+				self.pop_expect(pc, state, &object(JAVA_LANG_THROWABLE))?;
+				return Ok(false);
+			},
+			Instruction::Checkcast(index) => {
+				let class_name = self.constant_pool.get_class_name(*index).map_err(|e| self.constant_pool_error(pc, e))?;
+				self.pop_expect(pc, state, &reference)?;
+				self.push(pc, state, object(&class_name))?;
+			},
+			Instruction::Instanceof(index) => {
+				self.constant_pool.get_class_name(*index).map_err(|e| self.constant_pool_error(pc, e))?;
+				self.pop_expect(pc, state, &reference)?;
+				self.push(pc, state, Integer)?;
+			},
+			Instruction::Monitorenter | Instruction::Monitorexit => {
+				self.pop_expect(pc, state, &reference)?;
+			},
+			Instruction::Wide(wide) => match *wide {
+				WideInstruction::Iload(index) => self.load(pc, state, index as usize, Integer)?,
+				WideInstruction::Lload(index) => self.load(pc, state, index as usize, Long)?,
+				WideInstruction::Fload(index) => self.load(pc, state, index as usize, Float)?,
+				WideInstruction::Dload(index) => self.load(pc, state, index as usize, Double)?,
+				WideInstruction::Aload(index) => self.load_reference(pc, state, index as usize)?,
+				WideInstruction::Istore(index) => self.store(pc, state, index as usize, Integer)?,
+				WideInstruction::Lstore(index) => self.store(pc, state, index as usize, Long)?,
+				WideInstruction::Fstore(index) => self.store(pc, state, index as usize, Float)?,
+				WideInstruction::Dstore(index) => self.store(pc, state, index as usize, Double)?,
+				WideInstruction::Astore(index) => self.store_reference(pc, state, index as usize)?,
+				WideInstruction::Iinc(index, _) => self.check_local_type(pc, state, index as usize, &Integer)?,
+				WideInstruction::Ret(_) => unreachable!("Handled together with jsr"),
+			},
+			Instruction::Multianewarray(index, dimensions) => {
+				let class_name = self.constant_pool.get_class_name(*index).map_err(|e| self.constant_pool_error(pc, e))?;
+				let array_dimensions = class_name.chars().take_while(|c| *c == '[').count();
+				if *dimensions == 0 || *dimensions as usize > array_dimensions {
+					return Err(self.error(pc, VerifyErrorKind::InvalidConstant(*index)));
+				}
+				for _ in 0..*dimensions {
+					self.pop_expect(pc, state, &Integer)?;
+				}
+				self.push(pc, state, object(&class_name))?;
+			},
+		}
+		Ok(true)
+	}
+}
@@ -0,0 +1,681 @@
+use std::{borrow::Cow, collections::{HashMap, HashSet}, sync::atomic::{AtomicU32, Ordering}};
+
+use crate::{attribute_info::{bootstrap_methods::{BootstrapMethodsAttribute, CallSite, Constant}, code::CodeAttribute, signature::signature, stack_map_table::VerificationType}, class_file::ClassFile, class_format_error::{ClassFormatError, ClassFormatErrorKind}, cp_info::CPInfo, field_info::FieldInfo, instruction::*, method_info::MethodInfo, U1, U2};
+
+/// Position in the code of a method.
+///
+/// Labels can be used by instructions before they are bound to a position with `MethodVisitor::visit_label`.
+/// Every label is distinct from all labels created before, so labels of different visitors in a chain never clash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Label(u32);
+
+impl Label {
+	pub fn new() -> Self {
+		static NEXT_LABEL: AtomicU32 = AtomicU32::new(0);
+		Self(NEXT_LABEL.fetch_add(1, Ordering::Relaxed))
+	}
+}
+
+impl Default for Label {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassVersion {
+	pub major: U2,
+	pub minor: U2,
+}
+
+/// Type of a local variable or operand stack entry in a stack map frame.
+///
+/// Unlike `VerificationType`, long and double values are a single entry and uninitialized objects refer to the label of their `new` instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FrameValue {
+	Top,
+	Integer,
+	Float,
+	Long,
+	Double,
+	Null,
+	UninitializedThis,
+	/// Class, interface or array type in internal form.
+	Object(String),
+	Uninitialized(Label),
+}
+
+/// Receives the contents of a class as a sequence of events, see `accept`.
+///
+/// Every event is forwarded to `delegate` unless the method is overridden, so an adapter only implements the events it changes.
+/// Events are called in the order `visit`, `visit_source`, `visit_attribute`, `visit_field`, `visit_method` and `visit_end`.
+pub trait ClassVisitor {
+	/// Visitor that events are forwarded to by default, `None` to drop them.
+	fn delegate(&mut self) -> Option<&mut dyn ClassVisitor> {
+		None
+	}
+
+	/// Header of the class, with `super_name` being `None` only for `java/lang/Object` and modules.
+	fn visit(&mut self, version: ClassVersion, access: U2, name: &str, signature: Option<&str>, super_name: Option<&str>, interfaces: &[&str]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit(version, access, name, signature, super_name, interfaces);
+		}
+	}
+
+	fn visit_source(&mut self, source: &str) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_source(source);
+		}
+	}
+
+	/// Attribute that is not turned into events, with its contents as stored in the class file.
+	///
+	/// Constant pool indices in `info` only stay valid if the class is written with `ClassWriter::from_class_file`.
+	fn visit_attribute(&mut self, name: &str, info: &[U1]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_attribute(name, info);
+		}
+	}
+
+	/// Starts a field, returning `None` to skip its attributes.
+	fn visit_field(&mut self, access: U2, name: &str, descriptor: &str, signature: Option<&str>, value: Option<&Constant>) -> Option<Box<dyn FieldVisitor + '_>> {
+		self.delegate()?.visit_field(access, name, descriptor, signature, value)
+	}
+
+	/// Starts a method, returning `None` to skip its code and attributes.
+	fn visit_method(&mut self, access: U2, name: &str, descriptor: &str, signature: Option<&str>, exceptions: &[&str]) -> Option<Box<dyn MethodVisitor + '_>> {
+		self.delegate()?.visit_method(access, name, descriptor, signature, exceptions)
+	}
+
+	fn visit_end(&mut self) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_end();
+		}
+	}
+}
+
+/// Receives the attributes of a field, ending with `visit_end`.
+pub trait FieldVisitor {
+	fn delegate(&mut self) -> Option<&mut dyn FieldVisitor> {
+		None
+	}
+
+	fn visit_attribute(&mut self, name: &str, info: &[U1]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_attribute(name, info);
+		}
+	}
+
+	fn visit_end(&mut self) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_end();
+		}
+	}
+}
+
+/// Receives the attributes and code of a method.
+///
+/// Attributes come first. Code starts with `visit_code`, followed by the try-catch blocks and the instructions.
+/// Labels, line numbers and frames precede the instruction they belong to.
+/// Local variables and `visit_maxs` follow the instructions, and `visit_end` comes last.
+///
+/// Instructions are passed with their symbolic operands, writers choose the encoding.
+/// E.g. `iload_1`, `iload 1` and `wide iload 1` are all `visit_var_insn(ILOAD, 1)`, and `goto_w` is `visit_jump_insn(GOTO, ..)`.
+pub trait MethodVisitor {
+	fn delegate(&mut self) -> Option<&mut dyn MethodVisitor> {
+		None
+	}
+
+	fn visit_attribute(&mut self, name: &str, info: &[U1]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_attribute(name, info);
+		}
+	}
+
+	fn visit_code(&mut self) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_code();
+		}
+	}
+
+	/// Instruction without operands, e.g. `iadd` or `return`.
+	fn visit_insn(&mut self, opcode: U1) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_insn(opcode);
+		}
+	}
+
+	/// `bipush`, `sipush` or `newarray` with its immediate operand.
+	fn visit_int_insn(&mut self, opcode: U1, operand: i32) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_int_insn(opcode, operand);
+		}
+	}
+
+	/// Load, store or `ret` of the local variable `var`.
+	fn visit_var_insn(&mut self, opcode: U1, var: U2) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_var_insn(opcode, var);
+		}
+	}
+
+	/// `new`, `anewarray`, `checkcast` or `instanceof` of a class in internal form or an array descriptor.
+	fn visit_type_insn(&mut self, opcode: U1, type_name: &str) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_type_insn(opcode, type_name);
+		}
+	}
+
+	fn visit_field_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_field_insn(opcode, owner, name, descriptor);
+		}
+	}
+
+	/// Method invocation other than `invokedynamic`, with `is_interface` telling whether `owner` is an interface.
+	fn visit_method_insn(&mut self, opcode: U1, owner: &str, name: &str, descriptor: &str, is_interface: bool) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_method_insn(opcode, owner, name, descriptor, is_interface);
+		}
+	}
+
+	fn visit_invoke_dynamic_insn(&mut self, call_site: &CallSite) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_invoke_dynamic_insn(call_site);
+		}
+	}
+
+	/// Conditional branch, `goto` or `jsr` to `label`.
+	fn visit_jump_insn(&mut self, opcode: U1, label: Label) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_jump_insn(opcode, label);
+		}
+	}
+
+	/// Binds `label` to the position of the next instruction.
+	fn visit_label(&mut self, label: Label) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_label(label);
+		}
+	}
+
+	/// `ldc`, `ldc_w` or `ldc2_w` loading `constant`.
+	fn visit_ldc_insn(&mut self, constant: &Constant) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_ldc_insn(constant);
+		}
+	}
+
+	fn visit_iinc_insn(&mut self, var: U2, increment: i16) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_iinc_insn(var, increment);
+		}
+	}
+
+	/// `tableswitch` with `labels` for the keys `low` to `high`.
+	fn visit_table_switch_insn(&mut self, low: i32, high: i32, default: Label, labels: &[Label]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_table_switch_insn(low, high, default, labels);
+		}
+	}
+
+	/// `lookupswitch` jumping to `labels[i]` for `keys[i]`.
+	fn visit_lookup_switch_insn(&mut self, default: Label, keys: &[i32], labels: &[Label]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_lookup_switch_insn(default, keys, labels);
+		}
+	}
+
+	fn visit_multi_anewarray_insn(&mut self, descriptor: &str, dimensions: U1) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_multi_anewarray_insn(descriptor, dimensions);
+		}
+	}
+
+	/// Exception handler at `handler` for the instructions from `start` up to `end`, catching everything if `catch_type` is `None`.
+	fn visit_try_catch(&mut self, start: Label, end: Label, handler: Label, catch_type: Option<&str>) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_try_catch(start, end, handler, catch_type);
+		}
+	}
+
+	/// Source line of the instructions starting at `start`.
+	fn visit_line_number(&mut self, line: U2, start: Label) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_line_number(line, start);
+		}
+	}
+
+	fn visit_local_variable(&mut self, name: &str, descriptor: &str, signature: Option<&str>, start: Label, end: Label, index: U2) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_local_variable(name, descriptor, signature, start, end, index);
+		}
+	}
+
+	/// Complete state of locals and operand stack at the next instruction, with the stack listed from bottom to top.
+	fn visit_frame(&mut self, locals: &[FrameValue], stack: &[FrameValue]) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_frame(locals, stack);
+		}
+	}
+
+	fn visit_maxs(&mut self, max_stack: U2, max_locals: U2) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_maxs(max_stack, max_locals);
+		}
+	}
+
+	fn visit_end(&mut self) {
+		if let Some(delegate) = self.delegate() {
+			delegate.visit_end();
+		}
+	}
+}
+
+/// Attributes that `accept` turns into events instead of passing them to `visit_attribute`.
+const CLASS_EVENT_ATTRIBUTES: [&str; 3] = ["SourceFile", "Signature", "BootstrapMethods"];
+const FIELD_EVENT_ATTRIBUTES: [&str; 2] = ["ConstantValue", "Signature"];
+const METHOD_EVENT_ATTRIBUTES: [&str; 3] = ["Code", "Exceptions", "Signature"];
+
+/// Drives `visitor` with the contents of `class_file`, resolving all constant pool references.
+///
+/// Attributes of `Code` other than `LineNumberTable`, `LocalVariableTable`, `LocalVariableTypeTable` and `StackMapTable` are not visited,
+/// as the offsets they hold would not survive changes to the code.
+pub fn accept(class_file: &ClassFile, visitor: &mut dyn ClassVisitor) -> Result<(), ClassFormatError> {
+	let constant_pool = &class_file.constant_pool;
+	let name = constant_pool.get_class_name(class_file.this_class)?;
+	let super_name = match class_file.super_class {
+		0 => None,
+		index => Some(constant_pool.get_class_name(index)?),
+	};
+	let interfaces = class_file.interfaces.iter().map(|index| constant_pool.get_class_name(*index)).collect::<Result<Vec<_>, _>>()?;
+	let version = ClassVersion {
+		major: class_file.major_version,
+		minor: class_file.minor_version,
+	};
+	let signature = signature(&class_file.attributes, constant_pool)?;
+	visitor.visit(version, class_file.access_flags, &name, signature.as_deref(), super_name.as_deref(), &interfaces.iter().map(Cow::as_ref).collect::<Vec<_>>());
+
+	if let Some(source) = class_file.source_file()? {
+		visitor.visit_source(&source);
+	}
+	for attribute in &class_file.attributes {
+		let attribute_name = attribute.name(constant_pool)?;
+		if !CLASS_EVENT_ATTRIBUTES.contains(&attribute_name.as_ref()) {
+			visitor.visit_attribute(&attribute_name, &attribute.info);
+		}
+	}
+
+	let bootstrap_methods = class_file.bootstrap_methods()?.unwrap_or_default();
+	for field in &class_file.fields {
+		accept_field(class_file, field, &bootstrap_methods, visitor)?;
+	}
+	for method in &class_file.methods {
+		accept_method(class_file, method, &bootstrap_methods, visitor)?;
+	}
+	visitor.visit_end();
+	Ok(())
+}
+
+fn accept_field(class_file: &ClassFile, field: &FieldInfo, bootstrap_methods: &BootstrapMethodsAttribute, visitor: &mut dyn ClassVisitor) -> Result<(), ClassFormatError> {
+	let constant_pool = &class_file.constant_pool;
+	let name = constant_pool.get_utf8_str(field.name_index)?;
+	let descriptor = constant_pool.get_utf8_str(field.descriptor_index)?;
+	let signature = signature(&field.attributes, constant_pool)?;
+	let value = match field.constant_value(constant_pool)? {
+		Some(constant_value) => Some(bootstrap_methods.resolve_constant(constant_pool, constant_value.constantvalue_index)?),
+		None => None,
+	};
+
+	let Some(mut field_visitor) = visitor.visit_field(field.access_flags, &name, &descriptor, signature.as_deref(), value.as_ref()) else {
+		return Ok(());
+	};
+	for attribute in &field.attributes {
+		let attribute_name = attribute.name(constant_pool)?;
+		if !FIELD_EVENT_ATTRIBUTES.contains(&attribute_name.as_ref()) {
+			field_visitor.visit_attribute(&attribute_name, &attribute.info);
+		}
+	}
+	field_visitor.visit_end();
+	Ok(())
+}
+
+fn accept_method(class_file: &ClassFile, method: &MethodInfo, bootstrap_methods: &BootstrapMethodsAttribute, visitor: &mut dyn ClassVisitor) -> Result<(), ClassFormatError> {
+	let constant_pool = &class_file.constant_pool;
+	let name = constant_pool.get_utf8_str(method.name_index)?;
+	let descriptor = constant_pool.get_utf8_str(method.descriptor_index)?;
+	let signature = signature(&method.attributes, constant_pool)?;
+	let exceptions = method.exceptions(constant_pool)?;
+
+	let Some(mut method_visitor) = visitor.visit_method(method.access_flags, &name, &descriptor, signature.as_deref(), &exceptions.iter().map(String::as_str).collect::<Vec<_>>()) else {
+		return Ok(());
+	};
+	for attribute in &method.attributes {
+		let attribute_name = attribute.name(constant_pool)?;
+		if !METHOD_EVENT_ATTRIBUTES.contains(&attribute_name.as_ref()) {
+			method_visitor.visit_attribute(&attribute_name, &attribute.info);
+		}
+	}
+	if let Some(code) = method.code(constant_pool)? {
+		accept_code(class_file, method, &code, bootstrap_methods, method_visitor.as_mut())?;
+	}
+	method_visitor.visit_end();
+	Ok(())
+}
+
+/// Labels for the offsets of a code array that are referred to, which have to be instruction starts or the end of the code.
+struct Labels {
+	instruction_starts: HashSet<usize>,
+	code_length: usize,
+	labels: HashMap<usize, Label>,
+}
+
+impl Labels {
+	fn at(&mut self, pc: i64) -> Result<Label, ClassFormatError> {
+		let offset = usize::try_from(pc).ok().filter(|offset| self.instruction_starts.contains(offset) || *offset == self.code_length);
+		let offset = offset.ok_or_else(|| ClassFormatError::new(&[], ClassFormatErrorKind::InvalidCodeOffset(pc)))?;
+		Ok(*self.labels.entry(offset).or_default())
+	}
+
+	fn get(&self, pc: usize) -> Option<Label> {
+		self.labels.get(&pc).copied()
+	}
+}
+
+/// Opcode and offset of a branch, with `goto_w` and `jsr_w` reported as `goto` and `jsr`.
+fn jump(instruction: &Instruction) -> Option<(U1, i32)> {
+	Some(match *instruction {
+		Instruction::Ifeq(offset)
+		| Instruction::Ifne(offset)
+		| Instruction::Iflt(offset)
+		| Instruction::Ifge(offset)
+		| Instruction::Ifgt(offset)
+		| Instruction::Ifle(offset)
+		| Instruction::IfIcmpeq(offset)
+		| Instruction::IfIcmpne(offset)
+		| Instruction::IfIcmplt(offset)
+		| Instruction::IfIcmpge(offset)
+		| Instruction::IfIcmpgt(offset)
+		| Instruction::IfIcmple(offset)
+		| Instruction::IfAcmpeq(offset)
+		| Instruction::IfAcmpne(offset)
+		| Instruction::Goto(offset)
+		| Instruction::Jsr(offset)
+		| Instruction::Ifnull(offset)
+		| Instruction::Ifnonnull(offset) => (instruction.opcode(), offset as i32),
+		Instruction::GotoW(offset) => (GOTO, offset),
+		Instruction::JsrW(offset) => (JSR, offset),
+		_ => return None,
+	})
+}
+
+/// Offsets of the targets of a branch or switch instruction, relative to the instruction.
+fn branch_offsets(instruction: &Instruction) -> Vec<i32> {
+	match instruction {
+		Instruction::Tableswitch(table) => table.offsets.iter().copied().chain([table.default]).collect(),
+		Instruction::Lookupswitch(lookup) => lookup.pairs.iter().map(|(_, offset)| *offset).chain([lookup.default]).collect(),
+		instruction => jump(instruction).map(|(_, offset)| offset).into_iter().collect(),
+	}
+}
+
+/// Drops the `Top` that follows every long and double in the expanded form of `VerificationType`.
+fn frame_values(types: &[VerificationType], labels: &mut Labels) -> Result<Vec<FrameValue>, ClassFormatError> {
+	let mut values = Vec::with_capacity(types.len());
+	let mut types = types.iter();
+	while let Some(verification_type) = types.next() {
+		values.push(match verification_type {
+			VerificationType::Top => FrameValue::Top,
+			VerificationType::Integer => FrameValue::Integer,
+			VerificationType::Float => FrameValue::Float,
+			VerificationType::Long => FrameValue::Long,
+			VerificationType::Double => FrameValue::Double,
+			VerificationType::Null => FrameValue::Null,
+			VerificationType::UninitializedThis => FrameValue::UninitializedThis,
+			VerificationType::Object(class_name) => FrameValue::Object(class_name.clone()),
+			VerificationType::Uninitialized(offset) => FrameValue::Uninitialized(labels.at(*offset as i64)?),
+		});
+		if verification_type.is_wide() {
+			types.next();
+		}
+	}
+	Ok(values)
+}
+
+fn accept_code(class_file: &ClassFile, method: &MethodInfo, code: &CodeAttribute, bootstrap_methods: &BootstrapMethodsAttribute, visitor: &mut dyn MethodVisitor) -> Result<(), ClassFormatError> {
+	let constant_pool = &class_file.constant_pool;
+	let instructions = code.instructions().collect::<Result<Vec<_>, _>>().map_err(|error| ClassFormatError::new(&[], ClassFormatErrorKind::InvalidInstruction(error)))?;
+	let line_numbers = code.line_numbers(constant_pool)?;
+	let local_variables = code.local_variables(constant_pool)?;
+	let mut labels = Labels {
+		instruction_starts: instructions.iter().map(|(pc, _)| *pc).collect(),
+		code_length: code.code.len(),
+		labels: HashMap::new(),
+	};
+
+	// Every label has to exist before the instructions are visited, as branches may refer to later instructions.
+	for (pc, instruction) in &instructions {
+		for offset in branch_offsets(instruction) {
+			labels.at(*pc as i64 + offset as i64)?;
+		}
+	}
+	let mut try_catches = Vec::with_capacity(code.exception_table.len());
+	for entry in &code.exception_table {
+		let catch_type = match entry.catch_type {
+			0 => None,
+			index => Some(constant_pool.get_class_name(index)?),
+		};
+		try_catches.push((labels.at(entry.start_pc as i64)?, labels.at(entry.end_pc as i64)?, labels.at(entry.handler_pc as i64)?, catch_type));
+	}
+	let mut lines: HashMap<usize, Vec<U2>> = HashMap::new();
+	for entry in &line_numbers {
+		labels.at(entry.start_pc as i64)?;
+		lines.entry(entry.start_pc as usize).or_default().push(entry.line_number);
+	}
+	let mut frames = HashMap::new();
+	for frame in method.stack_map_frames(class_file)? {
+		labels.at(frame.pc as i64)?;
+		let locals = frame_values(&frame.locals, &mut labels)?;
+		let stack = frame_values(&frame.stack, &mut labels)?;
+		frames.insert(frame.pc as usize, (locals, stack));
+	}
+	let mut variables = Vec::with_capacity(local_variables.len());
+	for variable in &local_variables {
+		let start = labels.at(variable.start_pc as i64)?;
+		let end = labels.at(variable.start_pc as i64 + variable.length as i64)?;
+		variables.push((variable, start, end));
+	}
+
+	visitor.visit_code();
+	for (start, end, handler, catch_type) in &try_catches {
+		visitor.visit_try_catch(*start, *end, *handler, catch_type.as_deref());
+	}
+	for (pc, instruction) in &instructions {
+		if let Some(label) = labels.get(*pc) {
+			visitor.visit_label(label);
+			for line in lines.get(pc).into_iter().flatten() {
+				visitor.visit_line_number(*line, label);
+			}
+		}
+		if let Some((locals, stack)) = frames.get(pc) {
+			visitor.visit_frame(locals, stack);
+		}
+		accept_instruction(class_file, bootstrap_methods, *pc, instruction, &mut labels, visitor)?;
+	}
+	if let Some(label) = labels.get(code.code.len()) {
+		visitor.visit_label(label);
+	}
+	for (variable, start, end) in variables {
+		visitor.visit_local_variable(&variable.name, &variable.descriptor, variable.signature.as_deref(), start, end, variable.index);
+	}
+	visitor.visit_maxs(code.max_stack, code.max_locals);
+	Ok(())
+}
+
+fn accept_instruction(class_file: &ClassFile, bootstrap_methods: &BootstrapMethodsAttribute, pc: usize, instruction: &Instruction, labels: &mut Labels, visitor: &mut dyn MethodVisitor) -> Result<(), ClassFormatError> {
+	let constant_pool = &class_file.constant_pool;
+	let opcode = instruction.opcode();
+	match *instruction {
+		Instruction::Bipush(value) => visitor.visit_int_insn(opcode, value as i32),
+		Instruction::Sipush(value) => visitor.visit_int_insn(opcode, value as i32),
+		Instruction::Newarray(atype) => visitor.visit_int_insn(opcode, atype as i32),
+		Instruction::Ldc(index) => visitor.visit_ldc_insn(&bootstrap_methods.resolve_constant(constant_pool, index as U2)?),
+		Instruction::LdcW(index) | Instruction::Ldc2W(index) => visitor.visit_ldc_insn(&bootstrap_methods.resolve_constant(constant_pool, index)?),
+		Instruction::Iload(var)
+		| Instruction::Lload(var)
+		| Instruction::Fload(var)
+		| Instruction::Dload(var)
+		| Instruction::Aload(var)
+		| Instruction::Istore(var)
+		| Instruction::Lstore(var)
+		| Instruction::Fstore(var)
+		| Instruction::Dstore(var)
+		| Instruction::Astore(var)
+		| Instruction::Ret(var) => visitor.visit_var_insn(opcode, var as U2),
+		_ if (ILOAD_0..=ALOAD_3).contains(&opcode) => visitor.visit_var_insn(ILOAD + (opcode - ILOAD_0) / 4, ((opcode - ILOAD_0) % 4) as U2),
+		_ if (ISTORE_0..=ASTORE_3).contains(&opcode) => visitor.visit_var_insn(ISTORE + (opcode - ISTORE_0) / 4, ((opcode - ISTORE_0) % 4) as U2),
+		Instruction::Iinc(var, increment) => visitor.visit_iinc_insn(var as U2, increment as i16),
+		Instruction::Wide(WideInstruction::Iinc(var, increment)) => visitor.visit_iinc_insn(var, increment),
+		Instruction::Wide(wide) => {
+			let (opcode, var) = wide.opcode_and_index();
+			visitor.visit_var_insn(opcode, var);
+		},
+		Instruction::Tableswitch(ref table) => {
+			let default = labels.at(pc as i64 + table.default as i64)?;
+			let targets = table.offsets.iter().map(|offset| labels.at(pc as i64 + *offset as i64)).collect::<Result<Vec<_>, _>>()?;
+			visitor.visit_table_switch_insn(table.low, table.high, default, &targets);
+		},
+		Instruction::Lookupswitch(ref lookup) => {
+			let default = labels.at(pc as i64 + lookup.default as i64)?;
+			let keys = lookup.pairs.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+			let targets = lookup.pairs.iter().map(|(_, offset)| labels.at(pc as i64 + *offset as i64)).collect::<Result<Vec<_>, _>>()?;
+			visitor.visit_lookup_switch_insn(default, &keys, &targets);
+		},
+		Instruction::Getstatic(index) | Instruction::Putstatic(index) | Instruction::Getfield(index) | Instruction::Putfield(index) => {
+			let field = constant_pool.get_member_ref(index)?;
+			visitor.visit_field_insn(opcode, &field.class_name, &field.name, &field.descriptor);
+		},
+		Instruction::Invokevirtual(index) | Instruction::Invokespecial(index) | Instruction::Invokestatic(index) | Instruction::Invokeinterface(index, _) => {
+			let method = constant_pool.get_member_ref(index)?;
+			let is_interface = matches!(constant_pool.get(index)?, CPInfo::InterfaceMethodref(_));
+			visitor.visit_method_insn(opcode, &method.class_name, &method.name, &method.descriptor, is_interface);
+		},
+		Instruction::Invokedynamic(index) => visitor.visit_invoke_dynamic_insn(&bootstrap_methods.resolve_call_site(constant_pool, index)?),
+		Instruction::New(index) | Instruction::Anewarray(index) | Instruction::Checkcast(index) | Instruction::Instanceof(index) => {
+			visitor.visit_type_insn(opcode, &constant_pool.get_class_name(index)?);
+		},
+		Instruction::Multianewarray(index, dimensions) => visitor.visit_multi_anewarray_insn(&constant_pool.get_class_name(index)?, dimensions),
+		ref instruction => match jump(instruction) {
+			Some((opcode, offset)) => visitor.visit_jump_insn(opcode, labels.at(pc as i64 + offset as i64)?),
+			None => visitor.visit_insn(opcode),
+		},
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::class_file::parse_class_file;
+
+	use super::*;
+
+	/// Records the events of one method as text, naming labels in the order they first appear.
+	#[derive(Default)]
+	struct MethodRecorder {
+		events: Vec<String>,
+		labels: HashMap<Label, usize>,
+	}
+
+	impl MethodRecorder {
+		fn label(&mut self, label: Label) -> String {
+			let next = self.labels.len();
+			format!("L{}", self.labels.entry(label).or_insert(next))
+		}
+	}
+
+	impl MethodVisitor for MethodRecorder {
+		fn visit_insn(&mut self, opcode: U1) {
+			self.events.push(format!("insn {opcode:#04x}"));
+		}
+
+		fn visit_int_insn(&mut self, opcode: U1, operand: i32) {
+			self.events.push(format!("int {opcode:#04x} {operand}"));
+		}
+
+		fn visit_var_insn(&mut self, opcode: U1, var: U2) {
+			self.events.push(format!("var {opcode:#04x} {var}"));
+		}
+
+		fn visit_table_switch_insn(&mut self, low: i32, high: i32, default: Label, labels: &[Label]) {
+			let labels = labels.iter().map(|label| self.label(*label)).collect::<Vec<_>>().join(" ");
+			let default = self.label(default);
+			self.events.push(format!("tableswitch {low}..{high} [{labels}] default {default}"));
+		}
+
+		fn visit_label(&mut self, label: Label) {
+			let label = self.label(label);
+			self.events.push(format!("{label}:"));
+		}
+
+		fn visit_line_number(&mut self, line: U2, start: Label) {
+			let start = self.label(start);
+			self.events.push(format!("line {line} {start}"));
+		}
+
+		fn visit_frame(&mut self, locals: &[FrameValue], stack: &[FrameValue]) {
+			self.events.push(format!("frame {locals:?} {stack:?}"));
+		}
+
+		fn visit_maxs(&mut self, max_stack: U2, max_locals: U2) {
+			self.events.push(format!("maxs {max_stack} {max_locals}"));
+		}
+	}
+
+	struct ClassRecorder {
+		method: &'static str,
+		recorder: MethodRecorder,
+		methods: Vec<String>,
+	}
+
+	impl ClassVisitor for ClassRecorder {
+		fn visit_method(&mut self, _access: U2, name: &str, descriptor: &str, _signature: Option<&str>, _exceptions: &[&str]) -> Option<Box<dyn MethodVisitor + '_>> {
+			self.methods.push(format!("{name}{descriptor}"));
+			match name == self.method {
+				true => Some(Box::new(&mut self.recorder)),
+				false => None,
+			}
+		}
+	}
+
+	impl MethodVisitor for &mut MethodRecorder {
+		fn delegate(&mut self) -> Option<&mut dyn MethodVisitor> {
+			Some(&mut **self)
+		}
+	}
+
+	#[test]
+	fn accept_visits_code_with_labels() {
+		let class_file_raw = fs::read("./Sample.class").expect("Failed to read class file");
+		let class_file = parse_class_file(&class_file_raw).expect("Failed to parse class file");
+		let mut visitor = ClassRecorder {
+			method: "classify",
+			recorder: MethodRecorder::default(),
+			methods: Vec::new(),
+		};
+		class_file.accept(&mut visitor).unwrap();
+
+		assert!(visitor.methods.contains(&"classify(I)I".to_string()));
+		let events = visitor.recorder.events;
+		assert_eq!(&events[..5], [
+			"L0:",
+			"line 50 L0",
+			"var 0x15 0",
+			"tableswitch 1..3 [L1 L2 L3] default L4",
+			"L1:",
+		]);
+		assert!(events.contains(&"int 0x10 20".to_string()));
+		assert_eq!(events.iter().filter(|event| event.starts_with("frame")).count(), 4);
+		assert_eq!(events.last().unwrap(), "maxs 1 1");
+	}
+}
@@ -0,0 +1,33 @@
+use std::io::{self, Write};
+
+use crate::{U1, U2, U4};
+
+pub(crate) fn write_u1<W: Write>(writer: &mut W, value: U1) -> io::Result<()> {
+	writer.write_all(&[value])
+}
+
+pub(crate) fn write_u2<W: Write>(writer: &mut W, value: U2) -> io::Result<()> {
+	writer.write_all(&value.to_be_bytes())
+}
+
+pub(crate) fn write_u4<W: Write>(writer: &mut W, value: U4) -> io::Result<()> {
+	writer.write_all(&value.to_be_bytes())
+}
+
+/// Writes `length` as a `u1` count, failing if it does not fit.
+pub(crate) fn write_length_u1<W: Write>(writer: &mut W, length: usize, name: &str) -> io::Result<()> {
+	let length = U1::try_from(length).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("'{name}' has {length} entries, more than a class file can hold")))?;
+	write_u1(writer, length)
+}
+
+/// Writes `length` as a `u2` count, failing if it does not fit.
+pub(crate) fn write_length_u2<W: Write>(writer: &mut W, length: usize, name: &str) -> io::Result<()> {
+	let length = U2::try_from(length).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("'{name}' has {length} entries, more than a class file can hold")))?;
+	write_u2(writer, length)
+}
+
+/// Writes `length` as a `u4` count, failing if it does not fit.
+pub(crate) fn write_length_u4<W: Write>(writer: &mut W, length: usize, name: &str) -> io::Result<()> {
+	let length = U4::try_from(length).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("'{name}' has {length} bytes, more than a class file can hold")))?;
+	write_u4(writer, length)
+}
@@ -0,0 +1,311 @@
+use std::fmt::{Display, Write};
+
+use class_file_parser::{attribute_info::{code::CodeAttribute, signature, stack_map_table::StackMapFrame}, class_file::ClassFile, constant_pool::ConstantPool, field_info::FieldInfo, method_info::MethodInfo, U1, U2, U4};
+
+use crate::{render, Error};
+
+/// JSON value, with object keys kept in insertion order so the output is stable for diffing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+	Null,
+	Bool(bool),
+	Number(i64),
+	String(String),
+	Array(Vec<Json>),
+	Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+	fn write_string(out: &mut String, value: &str) {
+		out.push('"');
+		for c in value.chars() {
+			match c {
+				'"' => out.push_str("\\\""),
+				'\\' => out.push_str("\\\\"),
+				'\n' => out.push_str("\\n"),
+				'\r' => out.push_str("\\r"),
+				'\t' => out.push_str("\\t"),
+				c if (c as u32) < 0x20 => {
+					let _ = write!(out, "\\u{:04x}", c as u32);
+				},
+				c => out.push(c),
+			}
+		}
+		out.push('"');
+	}
+
+	/// Writes the value with two spaces of indentation per level, starting at `level`.
+	fn write(&self, out: &mut String, level: usize) {
+		let indent = |out: &mut String, level: usize| out.push_str(&"  ".repeat(level));
+		match self {
+			Self::Null => out.push_str("null"),
+			Self::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+			Self::Number(value) => out.push_str(&value.to_string()),
+			Self::String(value) => Self::write_string(out, value),
+			Self::Array(values) if values.is_empty() => out.push_str("[]"),
+			Self::Array(values) => {
+				out.push_str("[\n");
+				for (i, value) in values.iter().enumerate() {
+					indent(out, level + 1);
+					value.write(out, level + 1);
+					out.push_str(if i + 1 < values.len() { ",\n" } else { "\n" });
+				}
+				indent(out, level);
+				out.push(']');
+			},
+			Self::Object(members) if members.is_empty() => out.push_str("{}"),
+			Self::Object(members) => {
+				out.push_str("{\n");
+				for (i, (key, value)) in members.iter().enumerate() {
+					indent(out, level + 1);
+					Self::write_string(out, key);
+					out.push_str(": ");
+					value.write(out, level + 1);
+					out.push_str(if i + 1 < members.len() { ",\n" } else { "\n" });
+				}
+				indent(out, level);
+				out.push('}');
+			},
+		}
+	}
+}
+
+impl Display for Json {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut out = String::new();
+		self.write(&mut out, 0);
+		write!(f, "{out}")
+	}
+}
+
+impl From<bool> for Json {
+	fn from(value: bool) -> Self {
+		Self::Bool(value)
+	}
+}
+
+macro_rules! json_number {
+	($($number:ty),*) => {
+		$(
+			impl From<$number> for Json {
+				fn from(value: $number) -> Self {
+					Self::Number(value as i64)
+				}
+			}
+		)*
+	};
+}
+
+json_number!(U1, U2, U4, i32, i64, usize);
+
+impl From<&str> for Json {
+	fn from(value: &str) -> Self {
+		Self::String(value.to_string())
+	}
+}
+
+impl From<String> for Json {
+	fn from(value: String) -> Self {
+		Self::String(value)
+	}
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+	fn from(value: Option<T>) -> Self {
+		value.map_or(Self::Null, Into::into)
+	}
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+	fn from(values: Vec<T>) -> Self {
+		Self::Array(values.into_iter().map(Into::into).collect())
+	}
+}
+
+fn attribute_names(constant_pool: &ConstantPool, attributes: &[class_file_parser::attribute_info::AttributeInfo]) -> Result<Json, Error> {
+	let names = attributes.iter().map(|attribute| Ok(attribute.name(constant_pool)?.into_owned())).collect::<Result<Vec<_>, Error>>()?;
+	Ok(names.into())
+}
+
+fn field(constant_pool: &ConstantPool, field: &FieldInfo) -> Result<Json, Error> {
+	let constant_value = match field.constant_value(constant_pool)? {
+		Some(constant_value) => Some(render::constant(constant_pool, constant_value.constantvalue_index)?),
+		None => None,
+	};
+	Ok(Json::Object(vec![
+		("name", constant_pool.get_utf8_str(field.name_index)?.into_owned().into()),
+		("descriptor", constant_pool.get_utf8_str(field.descriptor_index)?.into_owned().into()),
+		("access_flags", field.access_flags.into()),
+		("flags", field.access().names().into()),
+		("signature", signature::signature(&field.attributes, constant_pool)?.into()),
+		("constant_value", constant_value.into()),
+		("attributes", attribute_names(constant_pool, &field.attributes)?),
+	]))
+}
+
+fn stack_map_frame(constant_pool: &ConstantPool, frame: &StackMapFrame) -> Result<Json, Error> {
+	let types = |types: &[_]| -> Result<Json, Error> {
+		Ok(types.iter().map(|info| render::verification_type(constant_pool, info)).collect::<Result<Vec<_>, _>>()?.into())
+	};
+	let (locals, stack) = match frame {
+		StackMapFrame::SameFrame { .. } | StackMapFrame::ChopFrame { .. } | StackMapFrame::SameFrameExtended { .. } => (Json::Null, Json::Null),
+		StackMapFrame::SameLocals1StackItemFrame { stack, .. } | StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => (Json::Null, types(std::slice::from_ref(stack))?),
+		StackMapFrame::AppendFrame { locals, .. } => (types(locals)?, Json::Null),
+		StackMapFrame::FullFrame { locals, stack, .. } => (types(locals)?, types(stack)?),
+	};
+	Ok(Json::Object(vec![
+		("frame_type", frame.frame_type().into()),
+		("kind", render::frame_kind(frame.frame_type()).into()),
+		("offset_delta", frame.offset_delta().into()),
+		("locals", locals),
+		("stack", stack),
+	]))
+}
+
+fn code(constant_pool: &ConstantPool, this_class: &str, code: &CodeAttribute) -> Result<Json, Error> {
+	let mut instructions = Vec::new();
+	for decoded in code.instructions() {
+		let (pc, instruction) = decoded?;
+		let line = render::instruction_line(constant_pool, this_class, pc, &instruction)?;
+		let mut members = vec![
+			("pc", line.pc.into()),
+			("mnemonic", line.mnemonic.into()),
+			("operands", line.operands.into()),
+		];
+		if !line.cases.is_empty() {
+			let cases = line.cases.iter().map(|(key, target)| Json::Object(vec![("key", (*key).into()), ("target", (*target).into())])).collect();
+			members.push(("cases", Json::Array(cases)));
+		}
+		members.push(("comment", line.comment.into()));
+		instructions.push(Json::Object(members));
+	}
+
+	let mut exception_table = Vec::new();
+	for entry in &code.exception_table {
+		let catch_type = match entry.catch_type {
+			0 => None,
+			index => Some(constant_pool.get_class_name(index)?.into_owned()),
+		};
+		exception_table.push(Json::Object(vec![
+			("start_pc", entry.start_pc.into()),
+			("end_pc", entry.end_pc.into()),
+			("handler_pc", entry.handler_pc.into()),
+			("catch_type", catch_type.into()),
+		]));
+	}
+
+	let line_numbers = code.line_numbers(constant_pool)?.iter().map(|entry| Json::Object(vec![
+		("start_pc", entry.start_pc.into()),
+		("line_number", entry.line_number.into()),
+	])).collect();
+	let local_variables = code.local_variables(constant_pool)?.into_iter().map(|variable| Json::Object(vec![
+		("start_pc", variable.start_pc.into()),
+		("length", variable.length.into()),
+		("index", variable.index.into()),
+		("name", variable.name.into()),
+		("descriptor", variable.descriptor.into()),
+		("signature", variable.signature.into()),
+	])).collect();
+	let stack_map_frames = match code.stack_map_table(constant_pool)? {
+		Some(stack_map_table) => stack_map_table.entries.iter().map(|frame| stack_map_frame(constant_pool, frame)).collect::<Result<Vec<_>, _>>()?,
+		None => Vec::new(),
+	};
+
+	Ok(Json::Object(vec![
+		("max_stack", code.max_stack.into()),
+		("max_locals", code.max_locals.into()),
+		("instructions", Json::Array(instructions)),
+		("exception_table", Json::Array(exception_table)),
+		("line_numbers", Json::Array(line_numbers)),
+		("local_variables", Json::Array(local_variables)),
+		("stack_map_frames", Json::Array(stack_map_frames)),
+		("attributes", attribute_names(constant_pool, &code.attributes)?),
+	]))
+}
+
+fn method(constant_pool: &ConstantPool, this_class: &str, method: &MethodInfo) -> Result<Json, Error> {
+	let code = match method.code(constant_pool)? {
+		Some(attribute) => self::code(constant_pool, this_class, &attribute)?,
+		None => Json::Null,
+	};
+	let parameters: Vec<Json> = method.parameters(constant_pool)?.into_iter().map(|parameter| Json::Object(vec![
+		("name", parameter.name.into()),
+		("flags", parameter.access_flags.names().into()),
+	])).collect();
+	Ok(Json::Object(vec![
+		("name", constant_pool.get_utf8_str(method.name_index)?.into_owned().into()),
+		("descriptor", constant_pool.get_utf8_str(method.descriptor_index)?.into_owned().into()),
+		("access_flags", method.access_flags.into()),
+		("flags", method.access().names().into()),
+		("signature", signature::signature(&method.attributes, constant_pool)?.into()),
+		("exceptions", method.exceptions(constant_pool)?.into()),
+		("parameters", parameters.into()),
+		("code", code),
+		("attributes", attribute_names(constant_pool, &method.attributes)?),
+	]))
+}
+
+/// Describes the whole class file read from `path` as a JSON object.
+pub fn class_file(path: &str, class_file: &ClassFile) -> Result<Json, Error> {
+	let constant_pool = &class_file.constant_pool;
+	let this_class = constant_pool.get_class_name(class_file.this_class)?.into_owned();
+	let super_class = match class_file.super_class {
+		0 => None,
+		index => Some(constant_pool.get_class_name(index)?.into_owned()),
+	};
+	let interfaces = class_file.interfaces.iter().map(|index| Ok(constant_pool.get_class_name(*index)?.into_owned())).collect::<Result<Vec<_>, Error>>()?;
+
+	let mut entries = Vec::new();
+	for (index, entry) in constant_pool.iter() {
+		let (operands, comment) = render::constant_pool_entry(constant_pool, index, entry)?;
+		entries.push(Json::Object(vec![
+			("index", index.into()),
+			("tag", entry.name().into()),
+			("operands", operands.into()),
+			("value", comment.map(|comment| comment.trim_start().to_string()).into()),
+		]));
+	}
+
+	let fields = class_file.fields.iter().map(|field| self::field(constant_pool, field)).collect::<Result<Vec<_>, _>>()?;
+	let methods = class_file.methods.iter().map(|method| self::method(constant_pool, &this_class, method)).collect::<Result<Vec<_>, _>>()?;
+
+	let inner_classes = class_file.inner_classes()?.into_iter().map(|inner_class| Json::Object(vec![
+		("inner_class", inner_class.inner_class.into()),
+		("outer_class", inner_class.outer_class.into()),
+		("inner_name", inner_class.inner_name.into()),
+		("flags", inner_class.access_flags.names().into()),
+	])).collect();
+	let mut bootstrap_methods = Vec::new();
+	if let Some(attribute) = class_file.bootstrap_methods()? {
+		for bootstrap_method in &attribute.bootstrap_methods {
+			let arguments = bootstrap_method.bootstrap_arguments.iter().map(|argument| render::loadable_constant(constant_pool, *argument)).collect::<Result<Vec<_>, _>>()?;
+			bootstrap_methods.push(Json::Object(vec![
+				("method", constant_pool.get_method_handle(bootstrap_method.bootstrap_method_ref)?.to_string().into()),
+				("arguments", arguments.into()),
+			]));
+		}
+	}
+
+	let access = class_file.access();
+	Ok(Json::Object(vec![
+		("file", path.into()),
+		("minor_version", class_file.minor_version.into()),
+		("major_version", class_file.major_version.into()),
+		("access_flags", class_file.access_flags.into()),
+		("flags", access.names().into()),
+		("this_class", this_class.into()),
+		("super_class", super_class.into()),
+		("interfaces", interfaces.into()),
+		("signature", signature::signature(&class_file.attributes, constant_pool)?.into()),
+		("source_file", class_file.source_file()?.into()),
+		("constant_pool", Json::Array(entries)),
+		("fields", Json::Array(fields)),
+		("methods", Json::Array(methods)),
+		("inner_classes", Json::Array(inner_classes)),
+		("nest_host", class_file.nest_host()?.into()),
+		("nest_members", class_file.nest_members()?.into()),
+		("permitted_subclasses", class_file.permitted_subclasses()?.into()),
+		("bootstrap_methods", Json::Array(bootstrap_methods)),
+		("attributes", attribute_names(constant_pool, &class_file.attributes)?),
+	]))
+}
@@ -0,0 +1,166 @@
+use std::{env, fs, process::ExitCode};
+
+use class_file_parser::class_file::parse_class_file;
+use json::Json;
+
+mod json;
+mod render;
+mod text;
+
+pub type Error = Box<dyn std::error::Error>;
+
+const USAGE: &str = "usage: dione-javap [--json] <class file>...
+
+Prints the version, flags, constant pool, fields, methods with their bytecode and the attributes of each class file,
+like `javap -v -p -c` does. With --json a single class is printed as a JSON object, several as an array of them.";
+
+enum Format {
+	Text,
+	Json,
+}
+
+fn main() -> ExitCode {
+	let mut format = Format::Text;
+	let mut paths = Vec::new();
+	for argument in env::args().skip(1) {
+		match argument.as_str() {
+			"--json" => format = Format::Json,
+			"-h" | "--help" => {
+				println!("{USAGE}");
+				return ExitCode::SUCCESS;
+			},
+			option if option.starts_with('-') => {
+				eprintln!("unknown option '{option}'\n{USAGE}");
+				return ExitCode::from(2);
+			},
+			_ => paths.push(argument),
+		}
+	}
+	if paths.is_empty() {
+		eprintln!("{USAGE}");
+		return ExitCode::from(2);
+	}
+
+	let mut status = ExitCode::SUCCESS;
+	let mut documents = Vec::new();
+	for path in &paths {
+		let result = fs::read(path).map_err(Error::from).and_then(|bytes| {
+			let class_file = parse_class_file(&bytes)?;
+			match format {
+				Format::Text => {
+					print!("{}", text::disassemble(path, bytes.len(), &class_file)?);
+					Ok(())
+				},
+				Format::Json => {
+					documents.push(json::class_file(path, &class_file)?);
+					Ok(())
+				},
+			}
+		});
+		if let Err(error) = result {
+			eprintln!("error: {path}: {error}");
+			status = ExitCode::FAILURE;
+		}
+	}
+	if let Format::Json = format {
+		match documents.len() {
+			0 => {},
+			1 => println!("{}", documents.remove(0)),
+			_ => println!("{}", Json::Array(documents)),
+		}
+	}
+	status
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use class_file_parser::class_file::parse_class_file;
+
+	use super::*;
+
+	const SAMPLE_CLASS_FILE_PATH: &str = "../class_file_parser/Sample.class";
+
+	fn read_sample() -> (usize, class_file_parser::class_file::ClassFile) {
+		let class_file_raw = fs::read(SAMPLE_CLASS_FILE_PATH).expect("Failed to read class file");
+		(class_file_raw.len(), parse_class_file(&class_file_raw).expect("Failed to parse class file"))
+	}
+
+	fn member<'a>(object: &'a Json, key: &str) -> &'a Json {
+		match object {
+			Json::Object(members) => members.iter().find(|(name, _)| *name == key).map(|(_, value)| value).unwrap_or_else(|| panic!("Missing key '{key}'")),
+			_ => panic!("Not an object"),
+		}
+	}
+
+	#[test]
+	fn disassemble_like_javap() {
+		let (size, class_file) = read_sample();
+		let text = text::disassemble(SAMPLE_CLASS_FILE_PATH, size, &class_file).unwrap();
+		let lines: Vec<&str> = text.lines().collect();
+
+		// Lines as printed by javap -v -p -c of JDK 17.
+		for expected in [
+			"public class Sample<T extends java.lang.Comparable<? super T>> extends java.lang.Object implements java.io.Serializable",
+			"  flags: (0x0021) ACC_PUBLIC, ACC_SUPER",
+			"  this_class: #11                         // Sample",
+			"  interfaces: 1, fields: 4, methods: 7, attributes: 6",
+			"    #1 = Methodref          #2.#3         // java/lang/Object.\"<init>\":()V",
+			"   #54 = Long               -1l",
+			"  #160 = MethodHandle       6:#161        // REF_invokeStatic java/lang/invoke/StringConcatFactory.makeConcatWithConstants:(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/String;[Ljava/lang/Object;)Ljava/lang/invoke/CallSite;",
+			"  public static final long BIG;",
+			"    ConstantValue: long 1234567890123l",
+			"  private final java.util.List<T> values;",
+			"    Signature: #94                          // Ljava/util/List<TT;>;",
+			"  public <R extends T> java.util.List<R> collect(java.util.function.Supplier<R>, int) throws java.lang.Exception;",
+			"        12: putfield      #10                 // Field values:Ljava/util/List;",
+			"        19: invokeinterface #16,  1           // InterfaceMethod java/util/function/Supplier.get:()Ljava/lang/Object;",
+			"        36: goto          11",
+			"         1: tableswitch   { // 1 to 3",
+			"                 default: 37",
+			"         1: lookupswitch  { // 3",
+			"                  100000: 40",
+			"      stack=4, locals=9, args_size=3",
+			"             0    13    28   Class java/lang/NumberFormatException",
+			"            28    35    50   any",
+			"        30: ldc2_w        #54                 // long -1l",
+			"        17: invokedynamic #42,  0             // InvokeDynamic #0:makeConcatWithConstants:(J)Ljava/lang/String;",
+			"            0      41     1 supplier   Ljava/util/function/Supplier<TR;>;",
+			"        frame_type = 253 /* append */",
+			"          locals = [ class java/util/List, int ]",
+			"          stack = [ class java/lang/Throwable ]",
+			"      throws java.lang.Exception",
+			"  private java.lang.Integer lambda$size$0();",
+			"SourceFile: \"Sample.java\"",
+			"      #176 REF_invokeVirtual Sample.lambda$size$0:()Ljava/lang/Integer;",
+			"  public static final #185= #155 of #11;  // Point=class Sample$Point of class Sample",
+		] {
+			assert!(lines.contains(&expected), "Missing line '{expected}'");
+		}
+	}
+
+	#[test]
+	fn describe_as_json() {
+		let (_, class_file) = read_sample();
+		let json = json::class_file(SAMPLE_CLASS_FILE_PATH, &class_file).unwrap();
+		assert_eq!(member(&json, "this_class"), &Json::from("Sample"));
+		assert_eq!(member(&json, "flags"), &Json::from(vec!["ACC_PUBLIC", "ACC_SUPER"]));
+		assert_eq!(member(&json, "signature"), &Json::from("<T::Ljava/lang/Comparable<-TT;>;>Ljava/lang/Object;Ljava/io/Serializable;"));
+
+		let Json::Array(methods) = member(&json, "methods") else { panic!("methods is not an array") };
+		let classify = methods.iter().find(|method| member(method, "name") == &Json::from("classify")).unwrap();
+		let Json::Array(instructions) = member(member(classify, "code"), "instructions") else { panic!("instructions is not an array") };
+		assert_eq!(instructions[1], Json::Object(vec![
+			("pc", Json::from(1)),
+			("mnemonic", Json::from("tableswitch")),
+			("operands", Json::from("1 to 3")),
+			("cases", Json::Array([(Some(1), 28), (Some(2), 31), (Some(3), 34), (None, 37)].into_iter().map(|(key, target)| Json::Object(vec![("key", Json::from(key)), ("target", Json::from(target))])).collect())),
+			("comment", Json::Null),
+		]));
+
+		let text = json.to_string();
+		assert!(text.starts_with("{\n  \"file\": \"../class_file_parser/Sample.class\",\n  \"minor_version\": 0,\n"));
+		assert!(text.contains(r#""value": "Sample\\u0000\\u00e9\\u20ac\\ud83d\\ude00""#), "Constants are escaped like javap prints them, then once more by JSON");
+	}
+}
@@ -0,0 +1,434 @@
+use class_file_parser::{attribute_info::{annotations::{Annotation, ElementValue}, stack_map_table::VerificationTypeInfo}, constant_pool::{ConstantPool, ConstantPoolError}, cp_info::CPInfo, descriptor::{BaseType, FieldType, ReturnDescriptor}, instruction::{Instruction, WideInstruction}, signature::{ClassTypeSignature, JavaTypeSignature, ReferenceTypeSignature, ReturnSignature, SimpleClassTypeSignature, ThrowsSignature, TypeArgument, TypeParameter}, U1, U2};
+
+/// Column at which javap starts `//` comments, relative to the indentation of the line.
+const COMMENT_COLUMN: usize = 40;
+
+/// Appends `// comment` to `text`, aligned the way javap does.
+pub fn commented(text: &str, comment: &str) -> String {
+	format!("{text:<COMMENT_COLUMN$}// {comment}")
+}
+
+pub fn java_class_name(class_name: &str) -> String {
+	class_name.replace('/', ".")
+}
+
+pub fn base_type(base_type: BaseType) -> &'static str {
+	match base_type {
+		BaseType::Byte => "byte",
+		BaseType::Char => "char",
+		BaseType::Double => "double",
+		BaseType::Float => "float",
+		BaseType::Int => "int",
+		BaseType::Long => "long",
+		BaseType::Short => "short",
+		BaseType::Boolean => "boolean",
+	}
+}
+
+/// Field type in Java syntax, e.g. `java.lang.String[]`.
+pub fn field_type(field_type: &FieldType) -> String {
+	match field_type {
+		FieldType::Base(base) => base_type(*base).to_string(),
+		FieldType::Object(class_name) => java_class_name(class_name),
+		FieldType::Array { dimensions, element } => format!("{}{}", self::field_type(element), "[]".repeat(*dimensions as usize)),
+	}
+}
+
+pub fn return_type(return_type: &ReturnDescriptor) -> String {
+	match return_type {
+		ReturnDescriptor::Void => "void".to_string(),
+		ReturnDescriptor::Type(field_type) => self::field_type(field_type),
+	}
+}
+
+pub fn java_type_signature(signature: &JavaTypeSignature) -> String {
+	match signature {
+		JavaTypeSignature::Base(base) => base_type(*base).to_string(),
+		JavaTypeSignature::Reference(reference) => reference_type_signature(reference),
+	}
+}
+
+pub fn reference_type_signature(signature: &ReferenceTypeSignature) -> String {
+	match signature {
+		ReferenceTypeSignature::Class(class) => class_type_signature(class),
+		ReferenceTypeSignature::TypeVariable(name) => name.clone(),
+		ReferenceTypeSignature::Array(component) => format!("{}[]", java_type_signature(component)),
+	}
+}
+
+pub fn class_type_signature(signature: &ClassTypeSignature) -> String {
+	let mut text = String::new();
+	if !signature.package.is_empty() {
+		text.push_str(&java_class_name(&signature.package));
+		text.push('.');
+	}
+	text.push_str(&simple_class_type_signature(&signature.class));
+	for suffix in &signature.suffixes {
+		text.push('.');
+		text.push_str(&simple_class_type_signature(suffix));
+	}
+	text
+}
+
+fn simple_class_type_signature(signature: &SimpleClassTypeSignature) -> String {
+	if signature.type_arguments.is_empty() {
+		return signature.name.clone();
+	}
+	let arguments: Vec<String> = signature.type_arguments.iter().map(type_argument).collect();
+	format!("{}<{}>", signature.name, arguments.join(", "))
+}
+
+fn type_argument(argument: &TypeArgument) -> String {
+	match argument {
+		TypeArgument::Any => "?".to_string(),
+		TypeArgument::Exact(reference) => reference_type_signature(reference),
+		TypeArgument::Extends(reference) => format!("? extends {}", reference_type_signature(reference)),
+		TypeArgument::Super(reference) => format!("? super {}", reference_type_signature(reference)),
+	}
+}
+
+/// Type parameter declarations including the angle brackets, empty if there are none.
+pub fn type_parameters(type_parameters: &[TypeParameter]) -> String {
+	if type_parameters.is_empty() {
+		return String::new();
+	}
+	let declarations: Vec<String> = type_parameters.iter().map(|type_parameter| {
+		let bounds: Vec<String> = type_parameter.class_bound.iter().chain(&type_parameter.interface_bounds).map(reference_type_signature).collect();
+		if bounds.is_empty() {
+			type_parameter.name.clone()
+		} else {
+			format!("{} extends {}", type_parameter.name, bounds.join(" & "))
+		}
+	}).collect();
+	format!("<{}>", declarations.join(", "))
+}
+
+pub fn return_signature(signature: &ReturnSignature) -> String {
+	match signature {
+		ReturnSignature::Void => "void".to_string(),
+		ReturnSignature::Type(java_type) => java_type_signature(java_type),
+	}
+}
+
+pub fn throws_signature(signature: &ThrowsSignature) -> String {
+	match signature {
+		ThrowsSignature::Class(class) => class_type_signature(class),
+		ThrowsSignature::TypeVariable(name) => name.clone(),
+	}
+}
+
+/// Escapes control and non-ASCII characters as `\uXXXX`, like javap does for string constants.
+pub fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'\t' => escaped.push_str("\\t"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			' '..='~' => escaped.push(c),
+			c => {
+				let mut units = [0; 2];
+				for unit in c.encode_utf16(&mut units) {
+					escaped.push_str(&format!("\\u{unit:04x}"));
+				}
+			},
+		}
+	}
+	escaped
+}
+
+/// Appends `.0` to whole numbers, as Java prints them.
+fn decimal(text: String) -> String {
+	if text.chars().all(|c| c.is_ascii_digit() || c == '-') {
+		text + ".0"
+	} else {
+		text
+	}
+}
+
+pub fn float(value: f32) -> String {
+	match value {
+		value if value.is_nan() => "NaNf".to_string(),
+		value if value.is_infinite() => format!("{}Infinityf", if value < 0.0 { "-" } else { "" }),
+		value => format!("{}f", decimal(value.to_string())),
+	}
+}
+
+pub fn double(value: f64) -> String {
+	match value {
+		value if value.is_nan() => "NaNd".to_string(),
+		value if value.is_infinite() => format!("{}Infinityd", if value < 0.0 { "-" } else { "" }),
+		value => format!("{}d", decimal(value.to_string())),
+	}
+}
+
+/// Quotes special method names, e.g. `"<init>"`.
+fn member_name(name: &str) -> String {
+	if name.starts_with('<') {
+		format!("\"{name}\"")
+	} else {
+		name.to_string()
+	}
+}
+
+/// Quotes array types, which javap prints as `"[I"`.
+fn class_reference(class_name: &str) -> String {
+	if class_name.starts_with('[') {
+		format!("\"{class_name}\"")
+	} else {
+		class_name.to_string()
+	}
+}
+
+/// Kind and value of a numeric or string constant as javap prints them, e.g. `long` and `-1l`.
+fn constant_value(constant_pool: &ConstantPool, index: U2, entry: &CPInfo) -> Result<Option<(&'static str, String)>, ConstantPoolError> {
+	Ok(Some(match entry {
+		CPInfo::Integer(_) => ("int", constant_pool.get_integer(index)?.to_string()),
+		CPInfo::Float(_) => ("float", float(constant_pool.get_float(index)?)),
+		CPInfo::Long(_) => ("long", format!("{}l", constant_pool.get_long(index)?)),
+		CPInfo::Double(_) => ("double", double(constant_pool.get_double(index)?)),
+		CPInfo::String(_) => ("String", escape(&constant_pool.get_string(index)?.to_string_lossy())),
+		_ => return Ok(None),
+	}))
+}
+
+/// Text after the tag in the `Constant pool:` listing, split into the operands and the comment resolving them.
+pub fn constant_pool_entry(constant_pool: &ConstantPool, index: U2, entry: &CPInfo) -> Result<(String, Option<String>), ConstantPoolError> {
+	Ok(match entry {
+		CPInfo::Class(class) => (format!("#{}", class.name_index), Some(class_reference(&constant_pool.get_class_name(index)?))),
+		CPInfo::Fieldref(member) => (format!("#{}.#{}", member.class_index, member.name_and_type_index), Some(member_reference(constant_pool, index, None)?)),
+		CPInfo::Methodref(member) => (format!("#{}.#{}", member.class_index, member.name_and_type_index), Some(member_reference(constant_pool, index, None)?)),
+		CPInfo::InterfaceMethodref(member) => (format!("#{}.#{}", member.class_index, member.name_and_type_index), Some(member_reference(constant_pool, index, None)?)),
+		CPInfo::String(string) => (format!("#{}", string.string_index), constant_value(constant_pool, index, entry)?.map(|(_, value)| value)),
+		CPInfo::Integer(_) | CPInfo::Float(_) | CPInfo::Long(_) | CPInfo::Double(_) => (constant_value(constant_pool, index, entry)?.map(|(_, value)| value).unwrap_or_default(), None),
+		CPInfo::NameAndType(name_and_type) => {
+			let (name, descriptor) = constant_pool.get_name_and_type(index)?;
+			(format!("#{}:#{}", name_and_type.name_index, name_and_type.descriptor_index), Some(format!("{}:{descriptor}", member_name(&name))))
+		},
+		CPInfo::Utf8(_) => (escape(&constant_pool.get_utf8_str(index)?), None),
+		CPInfo::MethodHandle(method_handle) => (format!("{}:#{}", method_handle.reference_kind, method_handle.reference_index), Some(constant_pool.get_method_handle(index)?.to_string())),
+		CPInfo::MethodType(method_type) => (format!("#{}", method_type.descriptor_index), Some(format!(" {}", constant_pool.get_utf8_str(method_type.descriptor_index)?))),
+		CPInfo::Dynamic(dynamic) => (format!("#{}:#{}", dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index), Some(dynamic_reference(constant_pool, dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index)?)),
+		CPInfo::InvokeDynamic(dynamic) => (format!("#{}:#{}", dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index), Some(dynamic_reference(constant_pool, dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index)?)),
+		CPInfo::Module(module) => (format!("#{}", module.name_index), Some(constant_pool.get_module_name(index)?.into_owned())),
+		CPInfo::Package(package) => (format!("#{}", package.name_index), Some(constant_pool.get_package_name(index)?.into_owned())),
+	})
+}
+
+fn dynamic_reference(constant_pool: &ConstantPool, bootstrap_method_attr_index: U2, name_and_type_index: U2) -> Result<String, ConstantPoolError> {
+	let (name, descriptor) = constant_pool.get_name_and_type(name_and_type_index)?;
+	Ok(format!("#{bootstrap_method_attr_index}:{name}:{descriptor}"))
+}
+
+/// `class.name:descriptor` of a member reference, leaving out the class if it is `this_class`.
+fn member_reference(constant_pool: &ConstantPool, index: U2, this_class: Option<&str>) -> Result<String, ConstantPoolError> {
+	let member = constant_pool.get_member_ref(index)?;
+	let name = member_name(&member.name);
+	if this_class == Some(member.class_name.as_ref()) {
+		Ok(format!("{name}:{}", member.descriptor))
+	} else {
+		Ok(format!("{}.{name}:{}", class_reference(&member.class_name), member.descriptor))
+	}
+}
+
+/// Comment javap prints after an instruction referring to the constant at `index`, e.g. `Method java/lang/Object."<init>":()V`.
+pub fn constant_comment(constant_pool: &ConstantPool, index: U2, this_class: &str) -> Result<String, ConstantPoolError> {
+	let entry = constant_pool.get(index)?;
+	Ok(match entry {
+		CPInfo::Class(_) => format!("class {}", class_reference(&constant_pool.get_class_name(index)?)),
+		CPInfo::Fieldref(_) => format!("Field {}", member_reference(constant_pool, index, Some(this_class))?),
+		CPInfo::Methodref(_) => format!("Method {}", member_reference(constant_pool, index, Some(this_class))?),
+		CPInfo::InterfaceMethodref(_) => format!("InterfaceMethod {}", member_reference(constant_pool, index, Some(this_class))?),
+		CPInfo::MethodHandle(_) => format!("MethodHandle {}", constant_pool.get_method_handle(index)?),
+		CPInfo::MethodType(method_type) => format!("MethodType {}", constant_pool.get_utf8_str(method_type.descriptor_index)?),
+		CPInfo::Dynamic(dynamic) => format!("Dynamic {}", dynamic_reference(constant_pool, dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index)?),
+		CPInfo::InvokeDynamic(dynamic) => format!("InvokeDynamic {}", dynamic_reference(constant_pool, dynamic.bootstrap_method_attr_index, dynamic.name_and_type_index)?),
+		entry => match constant_value(constant_pool, index, entry)? {
+			Some((kind, value)) => format!("{kind} {value}"),
+			None => entry.name().to_string(),
+		},
+	})
+}
+
+/// Value of a `ConstantValue` attribute with its kind, e.g. `long 1234567890123l`.
+pub fn constant(constant_pool: &ConstantPool, index: U2) -> Result<String, ConstantPoolError> {
+	let entry = constant_pool.get(index)?;
+	Ok(match constant_value(constant_pool, index, entry)? {
+		Some((kind, value)) => format!("{kind} {value}"),
+		None => entry.name().to_string(),
+	})
+}
+
+/// Static argument of a bootstrap method, e.g. `()Ljava/lang/Object;` for a `MethodType`.
+pub fn loadable_constant(constant_pool: &ConstantPool, index: U2) -> Result<String, ConstantPoolError> {
+	let (operands, comment) = constant_pool_entry(constant_pool, index, constant_pool.get(index)?)?;
+	Ok(match comment {
+		Some(comment) => comment.trim_start().to_string(),
+		None => operands,
+	})
+}
+
+pub fn verification_type(constant_pool: &ConstantPool, info: &VerificationTypeInfo) -> Result<String, ConstantPoolError> {
+	Ok(match info {
+		VerificationTypeInfo::Top => "top".to_string(),
+		VerificationTypeInfo::Integer => "int".to_string(),
+		VerificationTypeInfo::Float => "float".to_string(),
+		VerificationTypeInfo::Long => "long".to_string(),
+		VerificationTypeInfo::Double => "double".to_string(),
+		VerificationTypeInfo::Null => "null".to_string(),
+		VerificationTypeInfo::UninitializedThis => "this".to_string(),
+		VerificationTypeInfo::Object(cpool_index) => format!("class {}", class_reference(&constant_pool.get_class_name(*cpool_index)?)),
+		VerificationTypeInfo::Uninitialized(offset) => format!("uninitialized {offset}"),
+	})
+}
+
+/// Name of the frame kind javap shows next to the `frame_type`.
+pub fn frame_kind(frame_type: U1) -> &'static str {
+	match frame_type {
+		0..=63 => "same",
+		64..=127 => "same_locals_1_stack_item",
+		247 => "same_locals_1_stack_item_frame_extended",
+		248..=250 => "chop",
+		251 => "same_frame_extended",
+		252..=254 => "append",
+		255 => "full_frame",
+		_ => "reserved",
+	}
+}
+
+/// Annotation interface name from the descriptor, e.g. `java.lang.Deprecated` for `Ljava/lang/Deprecated;`.
+pub fn annotation_type(annotation: &Annotation) -> String {
+	let type_name = &annotation.type_name;
+	java_class_name(type_name.strip_prefix('L').and_then(|name| name.strip_suffix(';')).unwrap_or(type_name))
+}
+
+pub fn element_value(value: &ElementValue) -> String {
+	match value {
+		ElementValue::Byte(value) => value.to_string(),
+		ElementValue::Char(value) => format!("'{}'", escape(&String::from_utf16_lossy(&[*value]))),
+		ElementValue::Double(value) => double(*value),
+		ElementValue::Float(value) => float(*value),
+		ElementValue::Int(value) => value.to_string(),
+		ElementValue::Long(value) => format!("{value}l"),
+		ElementValue::Short(value) => value.to_string(),
+		ElementValue::Boolean(value) => value.to_string(),
+		ElementValue::String(value) => format!("\"{}\"", escape(&value.to_string_lossy())),
+		ElementValue::Enum { type_name, const_name } => format!("{type_name}.{const_name}"),
+		ElementValue::Class(descriptor) => format!("class {descriptor}"),
+		ElementValue::Annotation(annotation) => {
+			let elements: Vec<String> = annotation.elements.iter().map(|(name, value)| format!("{name}={}", element_value(value))).collect();
+			format!("@{}({})", annotation_type(annotation), elements.join(","))
+		},
+		ElementValue::Array(values) => format!("[{}]", values.iter().map(element_value).collect::<Vec<_>>().join(",")),
+	}
+}
+
+fn array_type(atype: U1) -> String {
+	match atype {
+		4 => "boolean".to_string(),
+		5 => "char".to_string(),
+		6 => "float".to_string(),
+		7 => "double".to_string(),
+		8 => "byte".to_string(),
+		9 => "short".to_string(),
+		10 => "int".to_string(),
+		11 => "long".to_string(),
+		atype => atype.to_string(),
+	}
+}
+
+/// A disassembled instruction with branch targets made absolute and constants resolved.
+#[derive(Debug)]
+pub struct InstructionLine {
+	pub pc: usize,
+	pub mnemonic: &'static str,
+	/// Operands with constant pool indices as `#index`, the range or number of cases for switches.
+	pub operands: String,
+	/// Keys and absolute targets of `tableswitch` and `lookupswitch`, the default case last with no key.
+	pub cases: Vec<(Option<i32>, i64)>,
+	pub comment: Option<String>,
+}
+
+pub fn instruction_line(constant_pool: &ConstantPool, this_class: &str, pc: usize, instruction: &Instruction) -> Result<InstructionLine, ConstantPoolError> {
+	let target = |offset: i64| pc as i64 + offset;
+	let reference = |index: U2| -> Result<(String, Option<String>), ConstantPoolError> {
+		Ok((format!("#{index}"), Some(constant_comment(constant_pool, index, this_class)?)))
+	};
+	let mut cases = Vec::new();
+	let (operands, comment) = match instruction {
+		Instruction::Bipush(value) => (value.to_string(), None),
+		Instruction::Sipush(value) => (value.to_string(), None),
+		Instruction::Ldc(index) => reference(*index as U2)?,
+		Instruction::LdcW(index)
+		| Instruction::Ldc2W(index)
+		| Instruction::Getstatic(index)
+		| Instruction::Putstatic(index)
+		| Instruction::Getfield(index)
+		| Instruction::Putfield(index)
+		| Instruction::Invokevirtual(index)
+		| Instruction::Invokespecial(index)
+		| Instruction::Invokestatic(index)
+		| Instruction::New(index)
+		| Instruction::Anewarray(index)
+		| Instruction::Checkcast(index)
+		| Instruction::Instanceof(index) => reference(*index)?,
+		Instruction::Invokeinterface(index, count) => (format!("#{index},  {count}"), reference(*index)?.1),
+		Instruction::Invokedynamic(index) => (format!("#{index},  0"), reference(*index)?.1),
+		Instruction::Multianewarray(index, dimensions) => (format!("#{index},  {dimensions}"), reference(*index)?.1),
+		Instruction::Iload(index)
+		| Instruction::Lload(index)
+		| Instruction::Fload(index)
+		| Instruction::Dload(index)
+		| Instruction::Aload(index)
+		| Instruction::Istore(index)
+		| Instruction::Lstore(index)
+		| Instruction::Fstore(index)
+		| Instruction::Dstore(index)
+		| Instruction::Astore(index)
+		| Instruction::Ret(index) => (index.to_string(), None),
+		Instruction::Iinc(index, value) => (format!("{index}, {value}"), None),
+		Instruction::Ifeq(offset)
+		| Instruction::Ifne(offset)
+		| Instruction::Iflt(offset)
+		| Instruction::Ifge(offset)
+		| Instruction::Ifgt(offset)
+		| Instruction::Ifle(offset)
+		| Instruction::IfIcmpeq(offset)
+		| Instruction::IfIcmpne(offset)
+		| Instruction::IfIcmplt(offset)
+		| Instruction::IfIcmpge(offset)
+		| Instruction::IfIcmpgt(offset)
+		| Instruction::IfIcmple(offset)
+		| Instruction::IfAcmpeq(offset)
+		| Instruction::IfAcmpne(offset)
+		| Instruction::Goto(offset)
+		| Instruction::Jsr(offset)
+		| Instruction::Ifnull(offset)
+		| Instruction::Ifnonnull(offset) => (target(*offset as i64).to_string(), None),
+		Instruction::GotoW(offset) | Instruction::JsrW(offset) => (target(*offset as i64).to_string(), None),
+		Instruction::Newarray(atype) => (array_type(*atype), None),
+		Instruction::Tableswitch(table) => {
+			cases.extend(table.offsets.iter().enumerate().map(|(i, offset)| (Some(table.low + i as i32), target(*offset as i64))));
+			cases.push((None, target(table.default as i64)));
+			(format!("{} to {}", table.low, table.high), None)
+		},
+		Instruction::Lookupswitch(lookup) => {
+			cases.extend(lookup.pairs.iter().map(|(key, offset)| (Some(*key), target(*offset as i64))));
+			cases.push((None, target(lookup.default as i64)));
+			(lookup.pairs.len().to_string(), None)
+		},
+		Instruction::Wide(WideInstruction::Iinc(index, value)) => (format!("iinc {index}, {value}"), None),
+		Instruction::Wide(wide) => {
+			let (opcode, index) = wide.opcode_and_index();
+			(format!("{} {index}", class_file_parser::instruction::mnemonic(opcode).unwrap_or_default()), None)
+		},
+		_ => (String::new(), None),
+	};
+	Ok(InstructionLine {
+		pc,
+		mnemonic: instruction.mnemonic(),
+		operands,
+		cases,
+		comment,
+	})
+}
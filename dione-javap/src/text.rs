@@ -0,0 +1,557 @@
+use class_file_parser::{access_flags::{ClassAccess, InnerClassAccess, MethodAccess}, attribute_info::{annotations::{annotation_default_attribute_parser, annotations_attribute_parser, parameter_annotations_attribute_parser, Annotation}, code::CodeAttribute, debug_info::{line_number_table_attribute_parser, local_variable_table_attribute_parser, source_debug_extension_attribute_parser}, exceptions::exceptions_attribute_parser, inner_classes::{enclosing_method_attribute_parser, inner_classes_attribute_parser}, module::module_main_class_attribute_parser, nest::{class_list_attribute_parser, nest_host_attribute_parser}, record::record_attribute_parser, signature::signature_attribute_parser, stack_map_table::{stack_map_table_parser, StackMapFrame, VerificationTypeInfo}, AttributeInfo}, class_file::ClassFile, constant_pool::ConstantPool, field_info::FieldInfo, method_info::MethodInfo};
+
+use crate::{render::{self, commented, java_class_name}, Error};
+
+/// Column of the `//` comments in the `Constant pool:` listing.
+const CONSTANT_POOL_COMMENT_COLUMN: usize = 42;
+
+/// Builds the text one line at a time, javap indents with spaces only.
+struct Printer<'a> {
+	class_file: &'a ClassFile,
+	constant_pool: &'a ConstantPool,
+	this_class: String,
+	out: String,
+}
+
+impl<'a> Printer<'a> {
+	fn line(&mut self, indent: usize, text: &str) {
+		self.out.push_str(&" ".repeat(indent));
+		self.out.push_str(text.trim_end());
+		self.out.push('\n');
+	}
+
+	fn class_declaration(&self) -> Result<String, Error> {
+		let access = self.class_file.access();
+		if access.contains(ClassAccess::MODULE) {
+			return Ok(match self.class_file.module()? {
+				Some(module) => {
+					let open = if module.is_open() { "open " } else { "" };
+					match module.version {
+						Some(version) => format!("{open}module {}@{version}", module.name),
+						None => format!("{open}module {}", module.name),
+					}
+				},
+				None => "module-info".to_string(),
+			});
+		}
+
+		let is_interface = access.is_interface();
+		let mut declaration: Vec<&str> = access.keywords().into_iter().filter(|keyword| !(is_interface && *keyword == "abstract")).collect();
+		declaration.push(if is_interface { "interface" } else { "class" });
+		let mut declaration = declaration.join(" ");
+		declaration.push(' ');
+		declaration.push_str(&java_class_name(&self.this_class));
+
+		let (superclass, interfaces) = match self.class_file.signature()? {
+			Some(signature) => {
+				declaration.push_str(&render::type_parameters(&signature.type_parameters));
+				(Some(render::class_type_signature(&signature.superclass)), signature.superinterfaces.iter().map(render::class_type_signature).collect())
+			},
+			None => {
+				// Without a signature javap only mentions superclasses other than `Object`.
+				let superclass = match self.class_file.super_class {
+					0 => None,
+					index => Some(java_class_name(&self.constant_pool.get_class_name(index)?)).filter(|superclass| superclass != "java.lang.Object"),
+				};
+				let interfaces = self.class_file.interfaces.iter().map(|index| Ok(java_class_name(&self.constant_pool.get_class_name(*index)?))).collect::<Result<Vec<_>, Error>>()?;
+				(superclass, interfaces)
+			},
+		};
+		if is_interface {
+			if !interfaces.is_empty() {
+				declaration.push_str(&format!(" extends {}", interfaces.join(", ")));
+			}
+		} else {
+			if let Some(superclass) = superclass {
+				declaration.push_str(&format!(" extends {superclass}"));
+			}
+			if !interfaces.is_empty() {
+				declaration.push_str(&format!(" implements {}", interfaces.join(", ")));
+			}
+		}
+		Ok(declaration)
+	}
+
+	fn header(&mut self, path: &str, size: usize) -> Result<(), Error> {
+		let class_file = self.class_file;
+		self.line(0, &format!("Classfile {path}"));
+		self.line(2, &format!("size {size} bytes"));
+		if let Some(source_file) = class_file.source_file()? {
+			self.line(2, &format!("Compiled from \"{source_file}\""));
+		}
+		let declaration = self.class_declaration()?;
+		self.line(0, &declaration);
+		self.line(2, &format!("minor version: {}", class_file.minor_version));
+		self.line(2, &format!("major version: {}", class_file.major_version));
+		let access = class_file.access();
+		self.line(2, &format!("flags: (0x{:04x}) {}", access.bits(), access.names().join(", ")));
+		let this_class = self.this_class.clone();
+		self.line(2, &commented(&format!("this_class: #{}", class_file.this_class), &this_class));
+		match class_file.super_class {
+			0 => self.line(2, "super_class: #0"),
+			index => {
+				let super_class = self.constant_pool.get_class_name(index)?;
+				self.line(2, &commented(&format!("super_class: #{index}"), &super_class));
+			},
+		}
+		self.line(2, &format!("interfaces: {}, fields: {}, methods: {}, attributes: {}", class_file.interfaces.len(), class_file.fields.len(), class_file.methods.len(), class_file.attributes.len()));
+		Ok(())
+	}
+
+	fn constant_pool(&mut self) -> Result<(), Error> {
+		self.line(0, "Constant pool:");
+		let width = format!("#{}", self.constant_pool.len()).len() + 2;
+		for (index, entry) in self.constant_pool.iter() {
+			let (operands, comment) = render::constant_pool_entry(self.constant_pool, index, entry)?;
+			let text = format!("{:>width$} = {:<18} {operands}", format!("#{index}"), entry.name());
+			match comment {
+				Some(comment) => self.line(0, &format!("{text:<CONSTANT_POOL_COMMENT_COLUMN$}// {comment}")),
+				None => self.line(0, &text),
+			}
+		}
+		Ok(())
+	}
+
+	fn signature(&mut self, indent: usize, attribute: &AttributeInfo) -> Result<(), Error> {
+		let signature = attribute.parse("Signature", signature_attribute_parser)?;
+		let text = self.constant_pool.get_utf8_str(signature.signature_index)?;
+		let line = commented(&format!("Signature: #{}", signature.signature_index), &text);
+		self.line(indent, &line);
+		Ok(())
+	}
+
+	fn annotation(&mut self, indent: usize, label: &str, annotation: &Annotation) {
+		self.line(indent, &format!("{label}{}(", render::annotation_type(annotation)));
+		for (name, value) in &annotation.elements {
+			self.line(indent + 2, &format!("{name}={}", render::element_value(value)));
+		}
+		self.line(indent, ")");
+	}
+
+	/// Prints the attributes shared by classes, fields, methods and record components, returning whether `attribute` was one of them.
+	fn common_attribute(&mut self, indent: usize, name: &str, attribute: &AttributeInfo) -> Result<bool, Error> {
+		match name {
+			"Signature" => self.signature(indent, attribute)?,
+			"RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => {
+				let annotations = attribute.parse("RuntimeVisibleAnnotations", annotations_attribute_parser)?.resolve(self.constant_pool)?;
+				self.line(indent, &format!("{name}:"));
+				for (i, annotation) in annotations.iter().enumerate() {
+					self.annotation(indent + 2, &format!("{i}: "), annotation);
+				}
+			},
+			"Synthetic" | "Deprecated" => self.line(indent, &format!("{name}: true")),
+			_ => return Ok(false),
+		}
+		Ok(true)
+	}
+
+	fn unknown_attribute(&mut self, indent: usize, name: &str, attribute: &AttributeInfo) {
+		self.line(indent, &format!("{name}: length = 0x{:x} (unknown attribute)", attribute.info.len()));
+		for chunk in attribute.info.chunks(16) {
+			let bytes: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+			self.line(indent + 3, &bytes.join(" "));
+		}
+	}
+
+	fn field(&mut self, field: &FieldInfo) -> Result<(), Error> {
+		let constant_pool = self.constant_pool;
+		let name = constant_pool.get_utf8_str(field.name_index)?;
+		let descriptor = constant_pool.get_utf8_str(field.descriptor_index)?;
+		let field_type = match field.signature(constant_pool)? {
+			Some(signature) => render::reference_type_signature(&signature),
+			None => render::field_type(&descriptor.parse()?),
+		};
+		let access = field.access();
+		self.line(2, format!("{access} {field_type} {name};").trim_start());
+		self.line(4, &format!("descriptor: {descriptor}"));
+		self.line(4, &format!("flags: (0x{:04x}) {}", access.bits(), access.names().join(", ")));
+		for attribute in &field.attributes {
+			let attribute_name = attribute.name(constant_pool)?;
+			match attribute_name.as_ref() {
+				"ConstantValue" => {
+					let constant_value = field.constant_value(constant_pool)?.expect("The attribute is present");
+					let constant = render::constant(constant_pool, constant_value.constantvalue_index)?;
+					self.line(4, &format!("ConstantValue: {constant}"));
+				},
+				name => {
+					if !self.common_attribute(4, name, attribute)? {
+						self.unknown_attribute(4, name, attribute);
+					}
+				},
+			}
+		}
+		Ok(())
+	}
+
+	fn method_declaration(&self, method: &MethodInfo) -> Result<String, Error> {
+		let constant_pool = self.constant_pool;
+		let access = method.access();
+		let name = constant_pool.get_utf8_str(method.name_index)?;
+		let mut declaration = access.to_string();
+		if name == "<clinit>" {
+			return Ok(format!("{declaration} {{}};").trim_start().to_string());
+		}
+
+		let descriptor = method.descriptor(constant_pool)?;
+		let (type_parameters, return_type, mut parameters, mut throws) = match method.signature(constant_pool)? {
+			Some(signature) => (
+				render::type_parameters(&signature.type_parameters),
+				render::return_signature(&signature.return_type),
+				signature.parameters.iter().map(render::java_type_signature).collect::<Vec<_>>(),
+				signature.throws.iter().map(render::throws_signature).collect::<Vec<_>>(),
+			),
+			None => (String::new(), render::return_type(&descriptor.return_type), descriptor.parameters.iter().map(render::field_type).collect(), Vec::new()),
+		};
+		if throws.is_empty() {
+			throws = method.exceptions(constant_pool)?.iter().map(|exception| java_class_name(exception)).collect();
+		}
+		if access.contains(MethodAccess::VARARGS) {
+			if let Some(last) = parameters.last_mut() {
+				if let Some(element) = last.strip_suffix("[]") {
+					*last = format!("{element}...");
+				}
+			}
+		}
+
+		for part in [type_parameters.as_str(), if name == "<init>" { "" } else { return_type.as_str() }] {
+			if !part.is_empty() {
+				declaration.push(' ');
+				declaration.push_str(part);
+			}
+		}
+		declaration.push(' ');
+		declaration.push_str(&if name == "<init>" { java_class_name(&self.this_class) } else { name.into_owned() });
+		declaration.push_str(&format!("({})", parameters.join(", ")));
+		if !throws.is_empty() {
+			declaration.push_str(&format!(" throws {}", throws.join(", ")));
+		}
+		declaration.push(';');
+		Ok(declaration.trim_start().to_string())
+	}
+
+	fn method(&mut self, method: &MethodInfo) -> Result<(), Error> {
+		let constant_pool = self.constant_pool;
+		let declaration = self.method_declaration(method)?;
+		self.line(2, &declaration);
+		let descriptor = constant_pool.get_utf8_str(method.descriptor_index)?;
+		self.line(4, &format!("descriptor: {descriptor}"));
+		let access = method.access();
+		self.line(4, &format!("flags: (0x{:04x}) {}", access.bits(), access.names().join(", ")));
+		for attribute in &method.attributes {
+			let name = attribute.name(constant_pool)?;
+			match name.as_ref() {
+				"Code" => {
+					let code = method.code(constant_pool)?.expect("The attribute is present");
+					let args_size = method.descriptor(constant_pool)?.parameters.len() + if access.contains(MethodAccess::STATIC) { 0 } else { 1 };
+					self.code(&code, args_size)?;
+				},
+				"Exceptions" => {
+					let exceptions = attribute.parse("Exceptions", exceptions_attribute_parser)?.resolve(constant_pool)?;
+					self.line(4, "Exceptions:");
+					let exceptions: Vec<String> = exceptions.iter().map(|exception| java_class_name(exception)).collect();
+					self.line(6, &format!("throws {}", exceptions.join(", ")));
+				},
+				"RuntimeVisibleParameterAnnotations" | "RuntimeInvisibleParameterAnnotations" => {
+					let parameters = attribute.parse("RuntimeVisibleParameterAnnotations", parameter_annotations_attribute_parser)?;
+					self.line(4, &format!("{name}:"));
+					for (parameter, annotations) in parameters.parameter_annotations.iter().enumerate() {
+						self.line(6, &format!("parameter {parameter}:"));
+						for (i, annotation) in annotations.resolve(constant_pool)?.iter().enumerate() {
+							self.annotation(8, &format!("{i}: "), annotation);
+						}
+					}
+				},
+				"MethodParameters" => {
+					self.line(4, "MethodParameters:");
+					self.line(6, &format!("{:<30} Flags", "Name"));
+					for parameter in method.parameters(constant_pool)? {
+						let name = parameter.name.as_deref().unwrap_or("<no name>");
+						self.line(6, &format!("{name:<30} {}", parameter.access_flags.keywords().join(" ")));
+					}
+				},
+				"AnnotationDefault" => {
+					let default_value = attribute.parse("AnnotationDefault", annotation_default_attribute_parser)?.default_value.resolve(constant_pool)?;
+					self.line(4, "AnnotationDefault:");
+					self.line(6, &format!("default_value: {}", render::element_value(&default_value)));
+				},
+				name => {
+					if !self.common_attribute(4, name, attribute)? {
+						self.unknown_attribute(4, name, attribute);
+					}
+				},
+			}
+		}
+		Ok(())
+	}
+
+	fn code(&mut self, code: &CodeAttribute, args_size: usize) -> Result<(), Error> {
+		let constant_pool = self.constant_pool;
+		self.line(4, "Code:");
+		self.line(6, &format!("stack={}, locals={}, args_size={args_size}", code.max_stack, code.max_locals));
+		for decoded in code.instructions() {
+			let (pc, instruction) = decoded?;
+			let line = render::instruction_line(constant_pool, &self.this_class, pc, &instruction)?;
+			let operands = if line.cases.is_empty() { line.operands } else { format!("{{ // {}", line.operands) };
+			let text = format!("{:>4}: {:<13} {operands}", line.pc, line.mnemonic);
+			match &line.comment {
+				Some(comment) => self.line(6, &commented(&text, comment)),
+				None => self.line(6, &text),
+			}
+			if !line.cases.is_empty() {
+				for (key, target) in &line.cases {
+					let key = key.map_or_else(|| "default".to_string(), |key| key.to_string());
+					self.line(6, &format!("{key:>18}: {target}"));
+				}
+				self.line(12, "}");
+			}
+		}
+
+		if !code.exception_table.is_empty() {
+			self.line(6, "Exception table:");
+			self.line(9, "from    to  target type");
+			for entry in &code.exception_table {
+				let catch_type = match entry.catch_type {
+					0 => "any".to_string(),
+					index => format!("Class {}", constant_pool.get_class_name(index)?),
+				};
+				self.line(6, &format!("{:>8}{:>6}{:>6}   {catch_type}", entry.start_pc, entry.end_pc, entry.handler_pc));
+			}
+		}
+
+		for attribute in &code.attributes {
+			let name = attribute.name(constant_pool)?;
+			match name.as_ref() {
+				"LineNumberTable" => {
+					self.line(6, "LineNumberTable:");
+					for entry in attribute.parse("LineNumberTable", line_number_table_attribute_parser)?.line_number_table {
+						self.line(8, &format!("line {}: {}", entry.line_number, entry.start_pc));
+					}
+				},
+				"LocalVariableTable" | "LocalVariableTypeTable" => {
+					self.line(6, &format!("{name}:"));
+					self.line(8, "Start  Length  Slot  Name   Signature");
+					for entry in attribute.parse("LocalVariableTable", local_variable_table_attribute_parser)?.local_variable_table {
+						let variable_name = constant_pool.get_utf8_str(entry.name_index)?;
+						let descriptor = constant_pool.get_utf8_str(entry.descriptor_index)?;
+						self.line(6, &format!("{:>7}{:>8}{:>6} {variable_name:>5}   {descriptor}", entry.start_pc, entry.length, entry.index));
+					}
+				},
+				"StackMapTable" => {
+					let stack_map_table = attribute.parse("StackMapTable", stack_map_table_parser)?;
+					self.line(6, &format!("StackMapTable: number_of_entries = {}", stack_map_table.entries.len()));
+					for frame in &stack_map_table.entries {
+						self.stack_map_frame(frame)?;
+					}
+				},
+				name => self.unknown_attribute(6, name, attribute),
+			}
+		}
+		Ok(())
+	}
+
+	fn verification_types(&self, types: &[VerificationTypeInfo]) -> Result<String, Error> {
+		let types = types.iter().map(|info| render::verification_type(self.constant_pool, info)).collect::<Result<Vec<_>, _>>()?;
+		Ok(format!("[ {} ]", types.join(", ")))
+	}
+
+	fn stack_map_frame(&mut self, frame: &StackMapFrame) -> Result<(), Error> {
+		let frame_type = frame.frame_type();
+		self.line(8, &format!("frame_type = {frame_type} /* {} */", render::frame_kind(frame_type)));
+		let offset_delta = format!("offset_delta = {}", frame.offset_delta());
+		match frame {
+			StackMapFrame::SameFrame { .. } => {},
+			StackMapFrame::SameLocals1StackItemFrame { stack, .. } => {
+				let stack = self.verification_types(std::slice::from_ref(stack))?;
+				self.line(10, &format!("stack = {stack}"));
+			},
+			StackMapFrame::SameLocals1StackItemFrameExtended { stack, .. } => {
+				let stack = self.verification_types(std::slice::from_ref(stack))?;
+				self.line(10, &offset_delta);
+				self.line(10, &format!("stack = {stack}"));
+			},
+			StackMapFrame::ChopFrame { .. } | StackMapFrame::SameFrameExtended { .. } => self.line(10, &offset_delta),
+			StackMapFrame::AppendFrame { locals, .. } => {
+				let locals = self.verification_types(locals)?;
+				self.line(10, &offset_delta);
+				self.line(10, &format!("locals = {locals}"));
+			},
+			StackMapFrame::FullFrame { locals, stack, .. } => {
+				let locals = self.verification_types(locals)?;
+				let stack = self.verification_types(stack)?;
+				self.line(10, &offset_delta);
+				self.line(10, &format!("locals = {locals}"));
+				self.line(10, &format!("stack = {stack}"));
+			},
+		}
+		Ok(())
+	}
+
+	fn class_names(&mut self, indent: usize, class_names: &[String]) {
+		for class_name in class_names {
+			self.line(indent, class_name);
+		}
+	}
+
+	fn class_attribute(&mut self, attribute: &AttributeInfo) -> Result<(), Error> {
+		let class_file = self.class_file;
+		let constant_pool = self.constant_pool;
+		let name = attribute.name(constant_pool)?;
+		match name.as_ref() {
+			"SourceFile" => {
+				let source_file = class_file.source_file()?.expect("The attribute is present");
+				self.line(0, &format!("SourceFile: \"{source_file}\""));
+			},
+			"SourceDebugExtension" => {
+				let debug_extension = attribute.parse("SourceDebugExtension", source_debug_extension_attribute_parser)?.debug_extension;
+				self.line(0, "SourceDebugExtension:");
+				for line in String::from_utf8_lossy(&debug_extension).lines() {
+					self.line(2, line);
+				}
+			},
+			"NestHost" => {
+				let nest_host = attribute.parse("NestHost", nest_host_attribute_parser)?;
+				self.line(0, &format!("NestHost: class {}", constant_pool.get_class_name(nest_host.host_class_index)?));
+			},
+			"NestMembers" | "PermittedSubclasses" => {
+				let classes = attribute.parse("NestMembers", class_list_attribute_parser)?.resolve(constant_pool)?;
+				self.line(0, &format!("{name}:"));
+				self.class_names(2, &classes);
+			},
+			"InnerClasses" => {
+				self.line(0, "InnerClasses:");
+				for inner_class in attribute.parse("InnerClasses", inner_classes_attribute_parser)?.classes {
+					let access = InnerClassAccess(inner_class.inner_class_access_flags);
+					let keywords: Vec<&str> = access.keywords().into_iter().filter(|keyword| !(access.is_interface() && *keyword == "abstract")).collect();
+					let mut modifiers = keywords.join(" ");
+					if !modifiers.is_empty() {
+						modifiers.push(' ');
+					}
+					let inner = format!("class {}", constant_pool.get_class_name(inner_class.inner_class_info_index)?);
+					let (text, comment) = match (inner_class.inner_name_index, inner_class.outer_class_info_index) {
+						(0, 0) => (format!("{modifiers}#{};", inner_class.inner_class_info_index), inner),
+						(0, outer) => (format!("{modifiers}#{} of #{outer};", inner_class.inner_class_info_index), format!("{inner} of class {}", constant_pool.get_class_name(outer)?)),
+						(name, 0) => (format!("{modifiers}#{name}= #{};", inner_class.inner_class_info_index), format!("{}={inner}", constant_pool.get_utf8_str(name)?)),
+						(name, outer) => (format!("{modifiers}#{name}= #{} of #{outer};", inner_class.inner_class_info_index), format!("{}={inner} of class {}", constant_pool.get_utf8_str(name)?, constant_pool.get_class_name(outer)?)),
+					};
+					self.line(2, &commented(&text, &comment));
+				}
+			},
+			"EnclosingMethod" => {
+				let enclosing_method = attribute.parse("EnclosingMethod", enclosing_method_attribute_parser)?;
+				let resolved = enclosing_method.resolve(constant_pool)?;
+				let comment = match resolved.method {
+					Some((method_name, _)) => format!("{}.{method_name}", resolved.class),
+					None => resolved.class,
+				};
+				self.line(0, &commented(&format!("EnclosingMethod: #{}.#{}", enclosing_method.class_index, enclosing_method.method_index), &comment));
+			},
+			"BootstrapMethods" => {
+				let bootstrap_methods = class_file.bootstrap_methods()?.expect("The attribute is present");
+				self.line(0, "BootstrapMethods:");
+				for (i, bootstrap_method) in bootstrap_methods.bootstrap_methods.iter().enumerate() {
+					let method_handle = constant_pool.get_method_handle(bootstrap_method.bootstrap_method_ref)?;
+					self.line(2, &format!("{i}: #{} {method_handle}", bootstrap_method.bootstrap_method_ref));
+					self.line(4, "Method arguments:");
+					for argument in &bootstrap_method.bootstrap_arguments {
+						self.line(6, &format!("#{argument} {}", render::loadable_constant(constant_pool, *argument)?));
+					}
+				}
+			},
+			"Record" => {
+				self.line(0, "Record:");
+				for component in attribute.parse("Record", record_attribute_parser)?.components {
+					let component_type = match component.signature(constant_pool)? {
+						Some(signature) => render::reference_type_signature(&signature),
+						None => render::field_type(&component.descriptor(constant_pool)?),
+					};
+					self.line(2, &format!("{component_type} {};", component.name(constant_pool)?));
+					self.line(4, &format!("descriptor: {}", constant_pool.get_utf8_str(component.descriptor_index)?));
+					for attribute in &component.attributes {
+						let name = attribute.name(constant_pool)?;
+						if !self.common_attribute(4, &name, attribute)? {
+							self.unknown_attribute(4, &name, attribute);
+						}
+					}
+					self.line(0, "");
+				}
+			},
+			"Module" => {
+				let module = class_file.module()?.expect("The attribute is present");
+				self.line(0, "Module:");
+				self.line(2, &format!("name: {}", module.name));
+				self.line(2, &format!("flags: (0x{:04x}) {}", module.flags.bits(), module.flags.names().join(", ")));
+				if let Some(version) = &module.version {
+					self.line(2, &format!("version: {version}"));
+				}
+				self.line(2, &format!("requires: {}", module.requires.len()));
+				for requires in &module.requires {
+					let version = requires.version.as_ref().map(|version| format!("@{version}")).unwrap_or_default();
+					self.line(4, &format!("{}{version} {}", requires.module, requires.flags.names().join(", ")));
+				}
+				for (label, packages) in [("exports", &module.exports), ("opens", &module.opens)] {
+					self.line(2, &format!("{label}: {}", packages.len()));
+					for package in packages {
+						let to = if package.to.is_empty() { String::new() } else { format!(" to {}", package.to.join(", ")) };
+						self.line(4, &format!("{}{to} {}", package.package, package.flags.names().join(", ")));
+					}
+				}
+				self.line(2, &format!("uses: {}", module.uses.len()));
+				self.class_names(4, &module.uses);
+				self.line(2, &format!("provides: {}", module.provides.len()));
+				for provides in &module.provides {
+					self.line(4, &format!("{} with {}", provides.service, provides.with.join(", ")));
+				}
+			},
+			"ModulePackages" => {
+				let packages: Vec<String> = class_file.module_packages()?.unwrap_or_default().iter().map(|package| java_class_name(package)).collect();
+				self.line(0, "ModulePackages:");
+				self.class_names(2, &packages);
+			},
+			"ModuleMainClass" => {
+				let main_class = attribute.parse("ModuleMainClass", module_main_class_attribute_parser)?;
+				let class_name = java_class_name(&constant_pool.get_class_name(main_class.main_class_index)?);
+				self.line(0, &commented(&format!("ModuleMainClass: #{}", main_class.main_class_index), &class_name));
+			},
+			name => {
+				if !self.common_attribute(0, name, attribute)? {
+					self.unknown_attribute(0, name, attribute);
+				}
+			},
+		}
+		Ok(())
+	}
+}
+
+/// Prints `class_file` read from `path` like `javap -v -p -c` does.
+pub fn disassemble(path: &str, size: usize, class_file: &ClassFile) -> Result<String, Error> {
+	let constant_pool = &class_file.constant_pool;
+	let mut printer = Printer {
+		class_file,
+		constant_pool,
+		this_class: constant_pool.get_class_name(class_file.this_class)?.into_owned(),
+		out: String::new(),
+	};
+	printer.header(path, size)?;
+	printer.constant_pool()?;
+	printer.line(0, "{");
+	let mut first = true;
+	for field in &class_file.fields {
+		if !first {
+			printer.line(0, "");
+		}
+		first = false;
+		printer.field(field)?;
+	}
+	for method in &class_file.methods {
+		if !first {
+			printer.line(0, "");
+		}
+		first = false;
+		printer.method(method)?;
+	}
+	printer.line(0, "}");
+	for attribute in &class_file.attributes {
+		printer.class_attribute(attribute)?;
+	}
+	Ok(printer.out)
+}